@@ -22,6 +22,16 @@ CREATE TABLE IF NOT EXISTS feeds (
     updated_at TEXT NOT NULL,
     last_fetched_at TEXT,
     next_fetch_at TEXT,
+    etag TEXT,
+    last_modified TEXT,
+    keep_last_articles INTEGER,
+    auto_download TEXT NOT NULL DEFAULT 'off',
+    description TEXT,
+    language TEXT,
+    author TEXT,
+    generator TEXT,
+    cache_images INTEGER NOT NULL DEFAULT 0,
+    fetch_full_text INTEGER NOT NULL DEFAULT 0,
     FOREIGN KEY(category_id) REFERENCES categories(id)
 );
 
@@ -29,16 +39,20 @@ CREATE TABLE IF NOT EXISTS articles (
     id TEXT PRIMARY KEY,
     feed_id TEXT NOT NULL,
     category_id TEXT,
+    guid TEXT,
     title TEXT NOT NULL,
-    url TEXT NOT NULL UNIQUE,
+    url TEXT NOT NULL,
     author TEXT,
     content TEXT,
     summary TEXT,
+    full_content TEXT,
+    content_version INTEGER NOT NULL DEFAULT 0,
     published_at TEXT,
     read_status TEXT NOT NULL DEFAULT 'Unread',
     is_favorited BOOLEAN NOT NULL DEFAULT 0,
     created_at TEXT NOT NULL,
     updated_at TEXT NOT NULL,
+    UNIQUE(feed_id, guid),
     FOREIGN KEY(feed_id) REFERENCES feeds(id),
     FOREIGN KEY(category_id) REFERENCES categories(id)
 );
@@ -59,4 +73,59 @@ CREATE TABLE IF NOT EXISTS article_tags (
     PRIMARY KEY(article_id, tag_id),
     FOREIGN KEY(article_id) REFERENCES articles(id),
     FOREIGN KEY(tag_id) REFERENCES tags(id)
-);
\ No newline at end of file
+);
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS smart_folders (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    query TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS enclosures (
+    id TEXT PRIMARY KEY,
+    article_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    mime_type TEXT,
+    length INTEGER,
+    duration_secs INTEGER,
+    title TEXT,
+    download_status TEXT NOT NULL DEFAULT 'none',
+    local_path TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE(article_id, url),
+    FOREIGN KEY(article_id) REFERENCES articles(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS feed_icons (
+    feed_id TEXT PRIMARY KEY,
+    url TEXT,
+    data BLOB NOT NULL,
+    mime_type TEXT,
+    fetched_at TEXT NOT NULL,
+    FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS cached_assets (
+    hash TEXT PRIMARY KEY,
+    data BLOB NOT NULL,
+    mime_type TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    last_accessed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cached_assets_accessed ON cached_assets(last_accessed_at);
+
+CREATE TABLE IF NOT EXISTS asset_urls (
+    url TEXT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    FOREIGN KEY(hash) REFERENCES cached_assets(hash) ON DELETE CASCADE
+);
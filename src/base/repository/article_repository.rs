@@ -3,9 +3,13 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 
-use crate::models::article::{Article, ArticleId};
+use crate::models::article::{Article, ArticleId, ReadStatus};
 use crate::models::feed::FeedId;
 use crate::models::category::CategoryId;
+use crate::models::search::{SearchHit, SearchQuery};
+use crate::models::article_query::ArticleQuery;
+use crate::models::article_page::{ArticleCursor, ArticleFilter, ArticlePage};
+use crate::models::settings::ArticleSortOrder;
 
 /// Trait defining the interface for article repository implementations
 #[async_trait]
@@ -19,9 +23,42 @@ pub trait ArticleRepository: Send + Sync {
     /// Retrieves an article by its URL
     async fn get_article_by_url(&self, url: &str) -> Result<Option<Article>>;
     
+    /// Retrieves the article with the given entry GUID within a feed
+    async fn get_article_by_guid(&self, feed_id: &FeedId, guid: &str) -> Result<Option<Article>>;
+    
+    /// Retrieves an article by its URL within a feed
+    ///
+    /// Links are unique among a feed's articles without a GUID of their own,
+    /// and that article is preferred; otherwise the oldest with the link.
+    async fn get_article_by_feed_and_url(&self, feed_id: &FeedId, url: &str) -> Result<Option<Article>>;
+    
     /// Retrieves all articles from the repository
     async fn get_all_articles(&self) -> Result<Vec<Article>>;
     
+    /// Retrieves up to `limit` article summaries in the given order, starting after `cursor`
+    ///
+    /// Pass the previous page's `next_cursor` to continue; `None` starts from the top.
+    async fn get_article_page(
+        &self,
+        filter: &ArticleFilter,
+        order: ArticleSortOrder,
+        cursor: Option<&ArticleCursor>,
+        limit: usize,
+    ) -> Result<ArticlePage>;
+    
+    /// Marks the unread articles selected by `filter` as read in a single transaction
+    ///
+    /// With `published_before`, only articles published before that time are
+    /// marked. Returns the ids of the articles that changed with their feeds.
+    async fn mark_as_read(
+        &self,
+        filter: &ArticleFilter,
+        published_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<(ArticleId, FeedId)>>;
+    
+    /// Sets the read status of the given articles in a single transaction, returning how many changed
+    async fn set_read_status(&self, ids: &[ArticleId], status: ReadStatus) -> Result<usize>;
+    
     /// Retrieves all articles from a specific feed
     async fn get_articles_by_feed(&self, feed_id: &FeedId) -> Result<Vec<Article>>;
     
@@ -37,6 +74,9 @@ pub trait ArticleRepository: Send + Sync {
     /// Updates an existing article
     async fn update_article(&self, article: &Article) -> Result<()>;
     
+    /// Stores the full text extracted from an article's web page
+    async fn set_full_content(&self, id: &ArticleId, full_content: &str) -> Result<()>;
+    
     /// Deletes an article by its ID
     async fn delete_article(&self, id: &ArticleId) -> Result<()>;
     
@@ -55,6 +95,25 @@ pub trait ArticleRepository: Send + Sync {
     /// Retrieves articles published within the given date range
     async fn get_articles_by_date_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Article>>;
     
-    /// Searches for articles matching the given query
+    /// Searches for articles matching the given query, best matches first
     async fn search_articles(&self, query: &str) -> Result<Vec<Article>>;
+    
+    /// Full-text search with filters, returning ranked hits with highlighted snippets
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>>;
+    
+    /// Counts the unread articles matching a structured query
+    async fn count_unread_matching(&self, query: &ArticleQuery) -> Result<usize>;
+    
+    /// Retrieves read articles that retention allows to be deleted
+    ///
+    /// Favorited and tagged articles are never returned. Feeds with
+    /// `keep_last_articles` set give up everything past their newest N
+    /// articles; all other articles expire once older than `older_than`.
+    async fn get_expired_articles(&self, older_than: DateTime<Utc>) -> Result<Vec<Article>>;
+    
+    /// Deletes the given articles and their tag associations, returning how many were removed
+    async fn delete_articles(&self, ids: &[ArticleId]) -> Result<usize>;
+    
+    /// Returns the space freed by deleted rows to the filesystem
+    async fn reclaim_space(&self) -> Result<()>;
 }
\ No newline at end of file
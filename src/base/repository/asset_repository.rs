@@ -0,0 +1,31 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use crate::models::cached_asset::CachedAsset;
+
+/// Trait defining the interface for the offline asset cache
+#[async_trait]
+pub trait AssetRepository: Send + Sync {
+    /// Stores an asset and records that `url` serves it
+    ///
+    /// Storing bytes that are already cached only adds the URL.
+    async fn save_asset(&self, url: &Url, asset: &CachedAsset) -> Result<()>;
+
+    /// Retrieves an asset by the hash of its bytes
+    async fn get_asset(&self, hash: &str) -> Result<Option<CachedAsset>>;
+
+    /// Retrieves the cached assets of those URLs that have one
+    async fn get_assets_by_urls(&self, urls: &[Url]) -> Result<Vec<(Url, CachedAsset)>>;
+
+    /// Marks assets as used now, keeping them from eviction the longest
+    async fn touch_assets(&self, hashes: &[String], accessed_at: DateTime<Utc>) -> Result<()>;
+
+    /// Total size of all cached assets in bytes
+    async fn get_total_size(&self) -> Result<u64>;
+
+    /// Deletes least recently used assets until the total size is at most
+    /// `max_bytes`, returning how many were deleted
+    async fn evict_to_size(&self, max_bytes: u64) -> Result<usize>;
+}
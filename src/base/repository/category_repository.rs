@@ -3,6 +3,8 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 
+use std::collections::HashMap;
+use crate::models::article_counts::ArticleCounts;
 use crate::models::category::{Category, CategoryId};
 
 /// Trait defining the interface for category repository implementations
@@ -43,4 +45,8 @@ pub trait CategoryRepository: Send + Sync {
     
     /// Retrieves categories by their parent ID
     async fn get_categories_by_parent(&self, parent_id: &Option<CategoryId>) -> Result<Vec<Category>>;
+
+    /// Counts the unread and total articles of every category, including
+    /// the feeds of all its subcategories
+    async fn get_article_counts(&self) -> Result<HashMap<CategoryId, ArticleCounts>>;
 }
\ No newline at end of file
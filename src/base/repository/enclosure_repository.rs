@@ -0,0 +1,28 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::article::ArticleId;
+use crate::models::enclosure::{Enclosure, EnclosureId};
+use crate::models::feed::FeedId;
+
+/// Trait defining the interface for enclosure repository implementations
+#[async_trait]
+pub trait EnclosureRepository: Send + Sync {
+    /// Stores the enclosures of an article
+    ///
+    /// An enclosure the article already has (same URL) keeps its id and
+    /// download state; only the metadata from the feed is refreshed.
+    async fn save_enclosures(&self, enclosures: &[Enclosure]) -> Result<()>;
+    
+    /// Retrieves an enclosure by its ID
+    async fn get_enclosure(&self, id: &EnclosureId) -> Result<Option<Enclosure>>;
+    
+    /// Retrieves the enclosures of an article, in the order they were stored
+    async fn get_enclosures_by_article(&self, article_id: &ArticleId) -> Result<Vec<Enclosure>>;
+    
+    /// Retrieves the enclosures of a feed's articles, newest article first
+    async fn get_enclosures_by_feed(&self, feed_id: &FeedId, limit: Option<usize>) -> Result<Vec<Enclosure>>;
+    
+    /// Records the download status and local file of an enclosure
+    async fn update_download(&self, enclosure: &Enclosure) -> Result<()>;
+}
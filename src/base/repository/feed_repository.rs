@@ -3,7 +3,10 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 
+use std::collections::HashMap;
+use crate::models::article_counts::ArticleCounts;
 use crate::models::feed::{Feed, FeedId};
+use crate::models::feed_icon::FeedIcon;
 use crate::models::category::CategoryId;
 
 /// Trait defining the interface for feed repository implementations
@@ -47,4 +50,19 @@ pub trait FeedRepository: Send + Sync {
     
     /// Retrieves the most frequently updated feeds
     async fn get_most_active_feeds(&self, limit: usize) -> Result<Vec<Feed>>;
+
+    /// Counts the unread and total articles of every feed
+    async fn get_article_counts(&self) -> Result<HashMap<FeedId, ArticleCounts>>;
+
+    /// Counts the unread and total articles of the given feeds
+    async fn get_article_counts_for_feeds(&self, feed_ids: &[FeedId]) -> Result<HashMap<FeedId, ArticleCounts>>;
+
+    /// Stores a feed's icon, replacing the one cached before
+    async fn save_feed_icon(&self, icon: &FeedIcon) -> Result<()>;
+
+    /// Retrieves the cached icon of a feed, which may record that it has none
+    async fn get_feed_icon(&self, feed_id: &FeedId) -> Result<Option<FeedIcon>>;
+
+    /// Retrieves every cached icon that has image data
+    async fn get_feed_icons(&self) -> Result<Vec<FeedIcon>>;
 }
\ No newline at end of file
@@ -1,10 +1,18 @@
 // Export repository traits
 mod article_repository;
+mod asset_repository;
 mod category_repository;
+mod enclosure_repository;
 mod feed_repository;
+mod settings_repository;
+mod smart_folder_repository;
 mod tag_repository;
 
 pub use article_repository::ArticleRepository;
+pub use asset_repository::AssetRepository;
 pub use category_repository::CategoryRepository;
+pub use enclosure_repository::EnclosureRepository;
 pub use feed_repository::FeedRepository;
+pub use settings_repository::SettingsRepository;
+pub use smart_folder_repository::SmartFolderRepository;
 pub use tag_repository::TagRepository;
\ No newline at end of file
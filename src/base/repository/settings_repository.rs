@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::settings::Settings;
+
+/// Trait defining the interface for settings repository implementations
+#[async_trait]
+pub trait SettingsRepository: Send + Sync {
+    /// Retrieves the raw value stored under a key
+    async fn get_setting(&self, key: &str) -> Result<Option<String>>;
+
+    /// Stores a raw value under a key, replacing any previous value
+    async fn set_setting(&self, key: &str, value: &str) -> Result<()>;
+
+    /// Retrieves every stored key/value pair
+    async fn get_all_settings(&self) -> Result<HashMap<String, String>>;
+
+    /// Loads the typed settings, using defaults for anything not stored
+    async fn load_settings(&self) -> Result<Settings> {
+        Ok(Settings::from_entries(&self.get_all_settings().await?))
+    }
+
+    /// Stores every field of the typed settings at once
+    async fn save_settings(&self, settings: &Settings) -> Result<()>;
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::smart_folder::{SmartFolder, SmartFolderId};
+
+/// Trait defining the interface for smart folder repository implementations
+#[async_trait]
+pub trait SmartFolderRepository: Send + Sync {
+    /// Saves a smart folder to the repository
+    async fn save_smart_folder(&self, folder: &SmartFolder) -> Result<()>;
+    
+    /// Retrieves a smart folder by its ID
+    async fn get_smart_folder(&self, id: &SmartFolderId) -> Result<Option<SmartFolder>>;
+    
+    /// Retrieves all smart folders, ordered by name
+    async fn get_all_smart_folders(&self) -> Result<Vec<SmartFolder>>;
+    
+    /// Updates an existing smart folder
+    async fn update_smart_folder(&self, folder: &SmartFolder) -> Result<()>;
+    
+    /// Deletes a smart folder by its ID
+    async fn delete_smart_folder(&self, id: &SmartFolderId) -> Result<()>;
+}
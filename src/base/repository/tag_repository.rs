@@ -3,6 +3,8 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 
+use std::collections::HashMap;
+use crate::models::article_counts::ArticleCounts;
 use crate::models::tag::{Tag, TagId};
 use crate::models::article::ArticleId;
 
@@ -47,4 +49,7 @@ pub trait TagRepository: Send + Sync {
     
     /// Retrieves the most frequently used tags, limited by the given count
     async fn get_most_used_tags(&self, limit: usize) -> Result<Vec<Tag>>;
+
+    /// Counts the unread and total articles of every tag, keyed by tag name
+    async fn get_article_counts(&self) -> Result<HashMap<String, ArticleCounts>>;
 }
\ No newline at end of file
@@ -7,8 +7,12 @@ use log::{info, warn, debug};
 // Import the repository traits from the base module
 use crate::base::repository::{
     ArticleRepository,
+    AssetRepository,
     FeedRepository, 
     CategoryRepository,
+    EnclosureRepository,
+    SettingsRepository,
+    SmartFolderRepository,
     TagRepository
 };
 
@@ -16,8 +20,12 @@ use crate::base::repository::{
 // This follows the Dependency Inversion Principle by depending on abstractions
 use crate::data::repositories::{
     SqliteArticleRepository,
+    SqliteAssetRepository,
     SqliteFeedRepository,
     SqliteCategoryRepository,
+    SqliteEnclosureRepository,
+    SqliteSettingsRepository,
+    SqliteSmartFolderRepository,
     SqliteTagRepository
 };
 
@@ -40,6 +48,10 @@ impl ConnectionPool {
         // Create initial connection
         let initial_connection = Self::create_connection(db_path)?;
         
+        // Let freed pages be returned cheaply after cleanups; this only takes
+        // effect on a new database, before any table exists
+        initial_connection.execute_batch("PRAGMA auto_vacuum = INCREMENTAL;")?;
+        
         // Initialize schema on the first connection
         initial_connection.execute_batch(include_str!("../../data/schema.sql"))?;
         
@@ -180,4 +192,24 @@ impl Database {
     pub fn get_tag_repository(&self) -> Arc<dyn TagRepository> {
         Arc::new(SqliteTagRepository::new(self.connection_pool.clone()))
     }
-}
\ No newline at end of file
+
+    /// Get the settings repository implementation
+    pub fn get_settings_repository(&self) -> Arc<dyn SettingsRepository> {
+        Arc::new(SqliteSettingsRepository::new(self.connection_pool.clone()))
+    }
+
+    /// Get the smart folder repository implementation
+    pub fn get_smart_folder_repository(&self) -> Arc<dyn SmartFolderRepository> {
+        Arc::new(SqliteSmartFolderRepository::new(self.connection_pool.clone()))
+    }
+
+    /// Get the enclosure repository implementation
+    pub fn get_enclosure_repository(&self) -> Arc<dyn EnclosureRepository> {
+        Arc::new(SqliteEnclosureRepository::new(self.connection_pool.clone()))
+    }
+
+    /// Get the offline asset cache repository implementation
+    pub fn get_asset_repository(&self) -> Arc<dyn AssetRepository> {
+        Arc::new(SqliteAssetRepository::new(self.connection_pool.clone()))
+    }
+}
@@ -0,0 +1,72 @@
+//! Translation of user search text into FTS5 `MATCH` expressions.
+
+/// Builds an FTS5 query that matches every term of `text`
+///
+/// Bare words and `"quoted phrases"` are passed through as FTS5 strings, so
+/// operators and punctuation typed by the user are never interpreted as query
+/// syntax. A trailing `*` turns a word or phrase into a prefix query. Returns
+/// `None` when there is nothing to search for.
+pub fn match_expression(text: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut term = String::new();
+        if c == '"' {
+            chars.next();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                term.push(c);
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' || (c == '*' && !term.is_empty()) {
+                    break;
+                }
+                term.push(c);
+                chars.next();
+            }
+        }
+
+        let prefix = chars.peek() == Some(&'*');
+        if prefix {
+            chars.next();
+        }
+
+        let term = term.trim().trim_matches('*');
+        if term.is_empty() {
+            continue;
+        }
+        let quoted = format!("\"{}\"", term.replace('"', "\"\""));
+        terms.push(if prefix { format!("{}*", quoted) } else { quoted });
+    }
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_expression() {
+        assert_eq!(match_expression("rust async").as_deref(), Some(r#""rust" "async""#));
+        assert_eq!(match_expression(r#""rust async" tok*"#).as_deref(), Some(r#""rust async" "tok"*"#));
+        assert_eq!(match_expression(r#""hello wor"*"#).as_deref(), Some(r#""hello wor"*"#));
+        // Query syntax typed by the user is searched for literally
+        assert_eq!(match_expression("C++ OR NOT").as_deref(), Some(r#""C++" "OR" "NOT""#));
+        assert_eq!(match_expression(r#"unterminated "phrase"#).as_deref(), Some(r#""unterminated" "phrase""#));
+        assert_eq!(match_expression("  * \"\" "), None);
+    }
+}
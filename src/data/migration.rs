@@ -26,6 +26,22 @@ impl<'a> MigrationManager<'a> {
         self.migrate_add_site_url_to_feeds()?;
         self.migrate_add_last_fetched_at_to_feeds()?;
         self.migrate_add_next_fetch_at_to_feeds()?;
+        self.migrate_add_guid_to_articles()?;
+        self.migrate_add_http_cache_to_feeds()?;
+        self.migrate_create_settings_table()?;
+        self.migrate_add_keep_last_articles_to_feeds()?;
+        self.migrate_create_articles_fts()?;
+        self.migrate_create_smart_folders_table()?;
+        self.migrate_create_article_page_indexes()?;
+        self.migrate_add_auto_download_to_feeds()?;
+        self.migrate_create_enclosures_table()?;
+        self.migrate_add_metadata_to_feeds()?;
+        self.migrate_create_feed_icons_table()?;
+        self.migrate_add_cache_images_to_feeds()?;
+        self.migrate_create_asset_cache_tables()?;
+        self.migrate_add_full_text_columns()?;
+        self.migrate_create_unique_article_url_index()?;
+        self.migrate_add_content_version_to_articles()?;
         
         info!("Database migrations completed successfully");
         Ok(())
@@ -280,6 +296,607 @@ impl<'a> MigrationManager<'a> {
         
         Ok(())
     }
+
+    /// Checks whether a table exists in the database
+    fn table_exists(&self, table: &str) -> Result<bool> {
+        match self.connection.query_row(
+            "SELECT 1 FROM sqlite_master WHERE type='table' AND name=?",
+            [table],
+            |_| Ok(true),
+        ) {
+            Ok(_) => Ok(true),
+            Err(SqliteError::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to check if {} table exists", table)),
+        }
+    }
+
+    /// Checks whether a column exists on a table
+    fn column_exists(&self, table: &str, column: &str) -> Result<bool> {
+        match self.connection.query_row(
+            "SELECT 1 FROM pragma_table_info(?) WHERE name=?",
+            [table, column],
+            |_| Ok(true),
+        ) {
+            Ok(_) => Ok(true),
+            Err(SqliteError::QueryReturnedNoRows) => Ok(false),
+            Err(e) => Err(e).with_context(|| format!("Failed to check if {} column exists", column)),
+        }
+    }
+
+    /// Adds a column unless it is already present, tolerating a concurrent addition
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        if self.column_exists(table, column)? {
+            info!("Column '{}' already exists in {} table", column, table);
+            return Ok(());
+        }
+        
+        debug!("Column '{}' does not exist, attempting to add it", column);
+        match self.connection.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        ) {
+            Ok(_) => info!("Successfully added {} column to {} table", column, table),
+            Err(e) if e.to_string().contains("duplicate column name") => {
+                info!("Column '{}' already exists (concurrent addition detected)", column);
+            },
+            Err(e) => return Err(e).with_context(|| format!("Failed to add {} column to {} table", column, table)),
+        }
+        Ok(())
+    }
+
+    /// Migration: Add guid column to articles and key entries on (feed_id, guid)
+    ///
+    /// The original table made `url` globally UNIQUE, which breaks for feeds that
+    /// reuse URLs. SQLite cannot drop a column constraint in place, so the table
+    /// is rebuilt with the new shape and the existing rows are copied across.
+    fn migrate_add_guid_to_articles(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_guid_to_articles";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("articles")? {
+            debug!("Articles table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        if self.column_exists("articles", "guid")? {
+            info!("Column 'guid' already exists in articles table");
+        } else {
+            debug!("Rebuilding articles table with guid column");
+            // Foreign keys must be off while the referenced table is swapped out
+            let foreign_keys: bool = self.connection.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+            self.connection.execute_batch("PRAGMA foreign_keys = OFF;")?;
+            let result = self.connection.execute_batch(
+                "BEGIN;
+                 CREATE TABLE articles_new (
+                     id TEXT PRIMARY KEY,
+                     feed_id TEXT NOT NULL,
+                     category_id TEXT,
+                     guid TEXT,
+                     title TEXT NOT NULL,
+                     url TEXT NOT NULL,
+                     author TEXT,
+                     content TEXT,
+                     summary TEXT,
+                     published_at TEXT,
+                     read_status TEXT NOT NULL DEFAULT 'Unread',
+                     is_favorited BOOLEAN NOT NULL DEFAULT 0,
+                     created_at TEXT NOT NULL,
+                     updated_at TEXT NOT NULL,
+                     UNIQUE(feed_id, guid),
+                     FOREIGN KEY(feed_id) REFERENCES feeds(id),
+                     FOREIGN KEY(category_id) REFERENCES categories(id)
+                 );
+                 INSERT INTO articles_new (
+                     id, feed_id, category_id, title, url, author, content, summary,
+                     published_at, read_status, is_favorited, created_at, updated_at
+                 )
+                 SELECT id, feed_id, category_id, title, url, author, content, summary,
+                        published_at, read_status, is_favorited, created_at, updated_at
+                 FROM articles;
+                 DROP TABLE articles;
+                 ALTER TABLE articles_new RENAME TO articles;
+                 COMMIT;",
+            );
+            if result.is_err() {
+                let _ = self.connection.execute_batch("ROLLBACK;");
+            }
+            if foreign_keys {
+                self.connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+            }
+            result.context("Failed to rebuild articles table with guid column")?;
+            info!("Successfully added guid column to articles table");
+        }
+        
+        // Lookups by link are now scoped to a feed, so index them that way
+        self.connection.execute(
+            "CREATE INDEX IF NOT EXISTS idx_articles_feed_url ON articles(feed_id, url)",
+            [],
+        ).context("Failed to create articles (feed_id, url) index")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add etag and last_modified columns to feeds table
+    ///
+    /// These hold the validators from the last successful fetch so the next one
+    /// can be sent as a conditional GET.
+    fn migrate_add_http_cache_to_feeds(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_http_cache_to_feeds";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("feeds")? {
+            debug!("Feeds table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.add_column_if_missing("feeds", "etag", "TEXT")?;
+        self.add_column_if_missing("feeds", "last_modified", "TEXT")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the settings key/value table
+    ///
+    /// Databases created before settings were persisted have no such table.
+    fn migrate_create_settings_table(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_settings_table";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        ).context("Failed to create settings table")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add keep_last_articles column to feeds table
+    ///
+    /// Lets individual feeds keep their newest N articles instead of following
+    /// the global retention age.
+    fn migrate_add_keep_last_articles_to_feeds(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_keep_last_articles_to_feeds";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("feeds")? {
+            debug!("Feeds table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.add_column_if_missing("feeds", "keep_last_articles", "INTEGER")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the articles_fts full-text index
+    ///
+    /// An external-content FTS5 table over the searchable article columns,
+    /// kept in step with `articles` by triggers and filled from existing rows.
+    /// It is keyed on the implicit rowid, so anything that may renumber rowids
+    /// (a full `VACUUM`, rebuilding `articles`) must rebuild the index after.
+    fn migrate_create_articles_fts(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_articles_fts";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("articles")? {
+            debug!("Articles table does not exist yet - nothing to index");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.connection.execute_batch("
+            CREATE VIRTUAL TABLE IF NOT EXISTS articles_fts USING fts5(
+                title, summary, content, author,
+                content='articles',
+                content_rowid='rowid',
+                tokenize='unicode61 remove_diacritics 2'
+            );
+            
+            CREATE TRIGGER IF NOT EXISTS articles_fts_insert AFTER INSERT ON articles BEGIN
+                INSERT INTO articles_fts (rowid, title, summary, content, author)
+                VALUES (new.rowid, new.title, new.summary, new.content, new.author);
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS articles_fts_delete AFTER DELETE ON articles BEGIN
+                INSERT INTO articles_fts (articles_fts, rowid, title, summary, content, author)
+                VALUES ('delete', old.rowid, old.title, old.summary, old.content, old.author);
+            END;
+            
+            CREATE TRIGGER IF NOT EXISTS articles_fts_update AFTER UPDATE OF title, summary, content, author ON articles BEGIN
+                INSERT INTO articles_fts (articles_fts, rowid, title, summary, content, author)
+                VALUES ('delete', old.rowid, old.title, old.summary, old.content, old.author);
+                INSERT INTO articles_fts (rowid, title, summary, content, author)
+                VALUES (new.rowid, new.title, new.summary, new.content, new.author);
+            END;
+            
+            INSERT INTO articles_fts (articles_fts) VALUES ('rebuild');
+        ").context("Failed to create articles full-text index")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the smart_folders table for saved article queries
+    fn migrate_create_smart_folders_table(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_smart_folders_table";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS smart_folders (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            [],
+        ).context("Failed to create smart_folders table")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Index articles by their paging order
+    ///
+    /// Article lists are paged by `(published_at, id)`, overall and per feed,
+    /// so each page is a short index range scan instead of a sort.
+    fn migrate_create_article_page_indexes(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_article_page_indexes";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("articles")? {
+            debug!("Articles table does not exist yet - nothing to index");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.connection.execute_batch("
+            CREATE INDEX IF NOT EXISTS idx_articles_published ON articles(published_at, id);
+            CREATE INDEX IF NOT EXISTS idx_articles_feed_published ON articles(feed_id, published_at, id);
+        ").context("Failed to create article paging indexes")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add auto_download column to feeds table
+    ///
+    /// Holds each feed's policy for downloading enclosures as they arrive.
+    fn migrate_add_auto_download_to_feeds(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_auto_download_to_feeds";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("feeds")? {
+            debug!("Feeds table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.add_column_if_missing("feeds", "auto_download", "TEXT NOT NULL DEFAULT 'off'")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the enclosures table for podcast and media attachments
+    ///
+    /// Rows go away with their article, so retention needs no extra step.
+    fn migrate_create_enclosures_table(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_enclosures_table";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS enclosures (
+                id TEXT PRIMARY KEY,
+                article_id TEXT NOT NULL,
+                url TEXT NOT NULL,
+                mime_type TEXT,
+                length INTEGER,
+                duration_secs INTEGER,
+                title TEXT,
+                download_status TEXT NOT NULL DEFAULT 'none',
+                local_path TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                UNIQUE(article_id, url),
+                FOREIGN KEY(article_id) REFERENCES articles(id) ON DELETE CASCADE
+            )",
+            [],
+        ).context("Failed to create enclosures table")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add the description, language, author and generator a feed declares
+    fn migrate_add_metadata_to_feeds(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_metadata_to_feeds";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("feeds")? {
+            debug!("Feeds table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.add_column_if_missing("feeds", "description", "TEXT")?;
+        self.add_column_if_missing("feeds", "language", "TEXT")?;
+        self.add_column_if_missing("feeds", "author", "TEXT")?;
+        self.add_column_if_missing("feeds", "generator", "TEXT")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the table caching each feed's favicon
+    fn migrate_create_feed_icons_table(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_feed_icons_table";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        self.connection.execute(
+            "CREATE TABLE IF NOT EXISTS feed_icons (
+                feed_id TEXT PRIMARY KEY,
+                url TEXT,
+                data BLOB NOT NULL,
+                mime_type TEXT,
+                fetched_at TEXT NOT NULL,
+                FOREIGN KEY(feed_id) REFERENCES feeds(id) ON DELETE CASCADE
+            )",
+            [],
+        ).context("Failed to create feed_icons table")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add the per-feed opt-in for caching article images
+    fn migrate_add_cache_images_to_feeds(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_cache_images_to_feeds";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("feeds")? {
+            debug!("Feeds table does not exist yet - migration will be applied through schema creation");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        self.add_column_if_missing("feeds", "cache_images", "INTEGER NOT NULL DEFAULT 0")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Create the content-addressed store of cached article images
+    ///
+    /// Each image is kept once under the hash of its bytes, however many URLs
+    /// serve it; the URLs go away when eviction removes the image.
+    fn migrate_create_asset_cache_tables(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_asset_cache_tables";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        self.connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cached_assets (
+                hash TEXT PRIMARY KEY,
+                data BLOB NOT NULL,
+                mime_type TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                last_accessed_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_cached_assets_accessed ON cached_assets(last_accessed_at);
+            CREATE TABLE IF NOT EXISTS asset_urls (
+                url TEXT PRIMARY KEY,
+                hash TEXT NOT NULL,
+                FOREIGN KEY(hash) REFERENCES cached_assets(hash) ON DELETE CASCADE
+            );",
+        ).context("Failed to create asset cache tables")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Add the extracted full text of articles and the per-feed
+    /// setting to extract it for every new article
+    fn migrate_add_full_text_columns(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_full_text_columns";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if self.table_exists("feeds")? {
+            self.add_column_if_missing("feeds", "fetch_full_text", "INTEGER NOT NULL DEFAULT 0")?;
+        }
+        if self.table_exists("articles")? {
+            self.add_column_if_missing("articles", "full_content", "TEXT")?;
+        }
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Make links unique among a feed's articles without a GUID
+    ///
+    /// Entries without a GUID of their own are recognised by their link, so two
+    /// such rows with one link would make lookups pick one at random. Earlier
+    /// syncs could store them anyway; the oldest row of each link is kept.
+    /// Articles with a feed GUID may still share links.
+    fn migrate_create_unique_article_url_index(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "create_unique_article_url_index";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if !self.table_exists("articles")? {
+            debug!("Articles table does not exist yet - nothing to index");
+            self.record_migration(MIGRATION_NAME)?;
+            return Ok(());
+        }
+        
+        let duplicates = "SELECT id FROM articles
+             WHERE (guid IS NULL OR guid LIKE 'fingerprint:%')
+               AND EXISTS (
+                   SELECT 1 FROM articles AS kept
+                   WHERE kept.feed_id = articles.feed_id AND kept.url = articles.url
+                     AND (kept.guid IS NULL OR kept.guid LIKE 'fingerprint:%')
+                     AND (kept.created_at < articles.created_at
+                          OR (kept.created_at = articles.created_at AND kept.rowid < articles.rowid))
+               )";
+        let tagged = if self.table_exists("article_tags")? {
+            format!("DELETE FROM article_tags WHERE article_id IN ({});", duplicates)
+        } else {
+            String::new()
+        };
+        self.connection.execute_batch(&format!(
+            "BEGIN;
+             {}
+             DELETE FROM articles WHERE id IN ({});
+             CREATE UNIQUE INDEX IF NOT EXISTS idx_articles_feed_unkeyed_url ON articles(feed_id, url)
+                 WHERE guid IS NULL OR guid LIKE 'fingerprint:%';
+             COMMIT;",
+            tagged, duplicates
+        )).inspect_err(|_| {
+            let _ = self.connection.execute_batch("ROLLBACK;");
+        }).context("Failed to create unique article link index")?;
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
+    
+    /// Migration: Record which version of content processing each article's
+    /// HTML went through
+    ///
+    /// Existing rows get version 0, the HTML as the feed sent it, so the next
+    /// sync stores the processed form without reporting them as updated.
+    fn migrate_add_content_version_to_articles(&self) -> Result<()> {
+        const MIGRATION_NAME: &str = "add_content_version_to_articles";
+        
+        if self.is_migration_applied(MIGRATION_NAME)? {
+            debug!("Migration '{}' already recorded as applied, skipping", MIGRATION_NAME);
+            return Ok(());
+        }
+        
+        info!("Running migration: {}", MIGRATION_NAME);
+        
+        if self.table_exists("articles")? {
+            self.add_column_if_missing("articles", "content_version", "INTEGER NOT NULL DEFAULT 0")?;
+        }
+        
+        self.record_migration(MIGRATION_NAME)?;
+        
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -489,4 +1106,423 @@ mod tests {
         
         Ok(())
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_add_guid_to_articles_migration() -> Result<()> {
+        // Create in-memory database with the original articles table (url UNIQUE, no guid)
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            PRAGMA foreign_keys = ON;
+            CREATE TABLE feeds (id TEXT PRIMARY KEY);
+            CREATE TABLE categories (id TEXT PRIMARY KEY);
+            INSERT INTO feeds (id) VALUES ('f1');
+            CREATE TABLE articles (
+                id TEXT PRIMARY KEY,
+                feed_id TEXT NOT NULL,
+                category_id TEXT,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL UNIQUE,
+                author TEXT,
+                content TEXT,
+                summary TEXT,
+                published_at TEXT,
+                read_status TEXT NOT NULL DEFAULT 'Unread',
+                is_favorited BOOLEAN NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            INSERT INTO articles (id, feed_id, title, url, created_at, updated_at)
+            VALUES ('a1', 'f1', 'Existing', 'http://example.com/1', 'now', 'now');
+        ")?;
+        
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        manager.migrate_add_guid_to_articles()?;
+        
+        assert!(manager.column_exists("articles", "guid")?, "guid column should exist after migration");
+        
+        // Existing rows survive the rebuild
+        let title: String = conn.query_row("SELECT title FROM articles WHERE id = 'a1'", [], |row| row.get(0))?;
+        assert_eq!(title, "Existing");
+        
+        // URLs may now repeat, but (feed_id, guid) must be unique
+        conn.execute(
+            "INSERT INTO articles (id, feed_id, guid, title, url, created_at, updated_at)
+             VALUES ('a2', 'f1', 'g1', 'Reused', 'http://example.com/1', 'now', 'now')",
+            [],
+        )?;
+        let duplicate = conn.execute(
+            "INSERT INTO articles (id, feed_id, guid, title, url, created_at, updated_at)
+             VALUES ('a3', 'f1', 'g1', 'Duplicate', 'http://example.com/2', 'now', 'now')",
+            [],
+        );
+        assert!(duplicate.is_err(), "duplicate (feed_id, guid) should be rejected");
+        
+        // Running the migration again should be a no-op
+        manager.migrate_add_guid_to_articles()?;
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_http_cache_to_feeds_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute(
+            "CREATE TABLE feeds (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL,
+                etag TEXT
+            )",
+            [],
+        )?;
+        
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        // One column already present, the other missing
+        manager.migrate_add_http_cache_to_feeds()?;
+        
+        assert!(manager.column_exists("feeds", "etag")?, "etag column should exist after migration");
+        assert!(manager.column_exists("feeds", "last_modified")?, "last_modified column should exist after migration");
+        
+        // Running the migration again should be a no-op
+        manager.migrate_add_http_cache_to_feeds()?;
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_settings_table_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_create_settings_table()?;
+        assert!(manager.table_exists("settings")?, "settings table should exist after migration");
+        
+        conn.execute(
+            "INSERT INTO settings (key, value, updated_at) VALUES ('ui.theme', 'light', 'now')",
+            [],
+        )?;
+        
+        // Running the migration again should be a no-op and keep stored values
+        manager.migrate_create_settings_table()?;
+        let value: String = conn.query_row("SELECT value FROM settings WHERE key = 'ui.theme'", [], |row| row.get(0))?;
+        assert_eq!(value, "light");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_keep_last_articles_to_feeds_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute(
+            "CREATE TABLE feeds (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL
+            )",
+            [],
+        )?;
+        
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        manager.migrate_add_keep_last_articles_to_feeds()?;
+        
+        assert!(manager.column_exists("feeds", "keep_last_articles")?, "keep_last_articles column should exist after migration");
+        
+        // Running the migration again should be a no-op
+        manager.migrate_add_keep_last_articles_to_feeds()?;
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_articles_fts_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            CREATE TABLE articles (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                author TEXT,
+                content TEXT,
+                summary TEXT
+            );
+            INSERT INTO articles (id, title, summary) VALUES ('a1', 'Existing article', 'About sqlite');
+        ")?;
+        
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        manager.migrate_create_articles_fts()?;
+        
+        let search = |term: &str| -> Result<Vec<String>> {
+            let mut stmt = conn.prepare(
+                "SELECT a.id FROM articles_fts JOIN articles a ON a.rowid = articles_fts.rowid
+                 WHERE articles_fts MATCH ? ORDER BY a.id"
+            )?;
+            let ids = stmt.query_map([term], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+            Ok(ids)
+        };
+        
+        // Rows present before the migration are indexed
+        assert_eq!(search("sqlite")?, vec!["a1"]);
+        
+        // Triggers keep the index in step with inserts, updates and deletes
+        conn.execute("INSERT INTO articles (id, title) VALUES ('a2', 'Fresh sqlite news')", [])?;
+        conn.execute("UPDATE articles SET summary = 'About postgres' WHERE id = 'a1'", [])?;
+        assert_eq!(search("sqlite")?, vec!["a2"]);
+        conn.execute("DELETE FROM articles WHERE id = 'a2'", [])?;
+        assert!(search("sqlite")?.is_empty());
+        assert_eq!(search("postgres")?, vec!["a1"]);
+        
+        // Running the migration again should be a no-op
+        manager.migrate_create_articles_fts()?;
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_smart_folders_table_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_create_smart_folders_table()?;
+        assert!(manager.table_exists("smart_folders")?, "smart_folders table should exist after migration");
+        
+        conn.execute(
+            "INSERT INTO smart_folders (id, name, query, created_at, updated_at)
+             VALUES ('1', 'Rust', 'tag:rust is:unread', 'now', 'now')",
+            [],
+        )?;
+        
+        // Running the migration again should be a no-op and keep saved folders
+        manager.migrate_create_smart_folders_table()?;
+        let query: String = conn.query_row("SELECT query FROM smart_folders WHERE id = '1'", [], |row| row.get(0))?;
+        assert_eq!(query, "tag:rust is:unread");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_article_page_indexes_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        conn.execute_batch(
+            "CREATE TABLE articles (id TEXT PRIMARY KEY, feed_id TEXT NOT NULL, published_at TEXT);"
+        )?;
+        
+        manager.migrate_create_article_page_indexes()?;
+        manager.migrate_create_article_page_indexes()?;
+        
+        let plan: String = conn.query_row(
+            "EXPLAIN QUERY PLAN SELECT id FROM articles WHERE feed_id = 'f'
+             ORDER BY published_at DESC, id DESC LIMIT 50",
+            [],
+            |row| row.get(3),
+        )?;
+        assert!(plan.contains("idx_articles_feed_published"), "unexpected plan: {}", plan);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_auto_download_to_feeds_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute(
+            "CREATE TABLE feeds (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                url TEXT NOT NULL
+            )",
+            [],
+        )?;
+        conn.execute("INSERT INTO feeds (id, title, url) VALUES ('1', 'Feed', 'http://example.com/feed')", [])?;
+        
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        manager.migrate_add_auto_download_to_feeds()?;
+        manager.migrate_add_auto_download_to_feeds()?;
+        
+        let policy: String = conn.query_row("SELECT auto_download FROM feeds WHERE id = '1'", [], |row| row.get(0))?;
+        assert_eq!(policy, "off", "existing feeds should not start downloading");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_enclosures_table_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            PRAGMA foreign_keys = ON;
+            CREATE TABLE articles (id TEXT PRIMARY KEY);
+            INSERT INTO articles (id) VALUES ('a1');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_create_enclosures_table()?;
+        manager.migrate_create_enclosures_table()?;
+        
+        conn.execute(
+            "INSERT INTO enclosures (id, article_id, url, created_at, updated_at)
+             VALUES ('e1', 'a1', 'http://example.com/1.mp3', 'now', 'now')",
+            [],
+        )?;
+        
+        // Enclosures are removed together with their article
+        conn.execute("DELETE FROM articles WHERE id = 'a1'", [])?;
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM enclosures", [], |row| row.get(0))?;
+        assert_eq!(remaining, 0);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_metadata_to_feeds_migration() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            PRAGMA foreign_keys = ON;
+            CREATE TABLE feeds (id TEXT PRIMARY KEY, title TEXT NOT NULL, url TEXT NOT NULL);
+            INSERT INTO feeds (id, title, url) VALUES ('1', 'Feed', 'http://example.com/feed');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_add_metadata_to_feeds()?;
+        manager.migrate_add_metadata_to_feeds()?;
+        manager.migrate_create_feed_icons_table()?;
+        
+        let metadata: (Option<String>, Option<String>, Option<String>, Option<String>) = conn.query_row(
+            "SELECT description, language, author, generator FROM feeds WHERE id = '1'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )?;
+        assert_eq!(metadata, (None, None, None, None));
+        
+        // Cached icons go away with their feed
+        conn.execute(
+            "INSERT INTO feed_icons (feed_id, data, fetched_at) VALUES ('1', x'89504E47', 'now')",
+            [],
+        )?;
+        conn.execute("DELETE FROM feeds WHERE id = '1'", [])?;
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM feed_icons", [], |row| row.get(0))?;
+        assert_eq!(remaining, 0);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_asset_cache_migrations() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            PRAGMA foreign_keys = ON;
+            CREATE TABLE feeds (id TEXT PRIMARY KEY, title TEXT NOT NULL, url TEXT NOT NULL);
+            INSERT INTO feeds (id, title, url) VALUES ('1', 'Feed', 'http://example.com/feed');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_add_cache_images_to_feeds()?;
+        manager.migrate_add_cache_images_to_feeds()?;
+        manager.migrate_create_asset_cache_tables()?;
+        manager.migrate_create_asset_cache_tables()?;
+        
+        let cache_images: bool = conn.query_row("SELECT cache_images FROM feeds WHERE id = '1'", [], |row| row.get(0))?;
+        assert!(!cache_images);
+        
+        // URLs go away with the image they point at
+        conn.execute_batch("
+            INSERT INTO cached_assets (hash, data, mime_type, size, created_at, last_accessed_at)
+                VALUES ('abc', x'89504E47', 'image/png', 4, 'now', 'now');
+            INSERT INTO asset_urls (url, hash) VALUES ('https://example.com/a.png', 'abc');
+            INSERT INTO asset_urls (url, hash) VALUES ('https://cdn.example.com/a.png', 'abc');
+            DELETE FROM cached_assets WHERE hash = 'abc';
+        ")?;
+        let remaining: i64 = conn.query_row("SELECT COUNT(*) FROM asset_urls", [], |row| row.get(0))?;
+        assert_eq!(remaining, 0);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_full_text_columns() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            CREATE TABLE feeds (id TEXT PRIMARY KEY, title TEXT NOT NULL, url TEXT NOT NULL);
+            CREATE TABLE articles (id TEXT PRIMARY KEY, feed_id TEXT NOT NULL, title TEXT NOT NULL);
+            INSERT INTO feeds (id, title, url) VALUES ('1', 'Feed', 'http://example.com/feed');
+            INSERT INTO articles (id, feed_id, title) VALUES ('a1', '1', 'Teaser');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_add_full_text_columns()?;
+        manager.migrate_add_full_text_columns()?;
+        
+        let fetch_full_text: bool = conn.query_row("SELECT fetch_full_text FROM feeds WHERE id = '1'", [], |row| row.get(0))?;
+        assert!(!fetch_full_text);
+        let full_content: Option<String> = conn.query_row("SELECT full_content FROM articles WHERE id = 'a1'", [], |row| row.get(0))?;
+        assert_eq!(full_content, None);
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_create_unique_article_url_index() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            CREATE TABLE articles (
+                id TEXT PRIMARY KEY, feed_id TEXT NOT NULL, guid TEXT,
+                url TEXT NOT NULL, created_at TEXT NOT NULL
+            );
+            CREATE TABLE article_tags (article_id TEXT NOT NULL, tag_id TEXT NOT NULL);
+            INSERT INTO articles (id, feed_id, guid, url, created_at) VALUES
+                ('old', 'f1', NULL, 'http://example.com/1', '2024-01-01'),
+                ('new', 'f1', 'fingerprint:1', 'http://example.com/1', '2024-02-01'),
+                ('keyed1', 'f1', 'g1', 'http://example.com/1', '2024-03-01'),
+                ('keyed2', 'f1', 'g2', 'http://example.com/1', '2024-03-01'),
+                ('other', 'f2', NULL, 'http://example.com/1', '2024-03-01');
+            INSERT INTO article_tags (article_id, tag_id) VALUES ('new', 't1'), ('old', 't1');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_create_unique_article_url_index()?;
+        manager.migrate_create_unique_article_url_index()?;
+        
+        let mut stmt = conn.prepare("SELECT id FROM articles ORDER BY id")?;
+        let ids: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+        assert_eq!(ids, ["keyed1", "keyed2", "old", "other"]);
+        let tags: i64 = conn.query_row("SELECT COUNT(*) FROM article_tags", [], |row| row.get(0))?;
+        assert_eq!(tags, 1);
+        
+        // Links stay free to repeat between articles with their own GUID
+        conn.execute("INSERT INTO articles VALUES ('keyed3', 'f1', 'g3', 'http://example.com/1', 'now')", [])?;
+        let duplicate = conn.execute("INSERT INTO articles VALUES ('dup', 'f1', NULL, 'http://example.com/1', 'now')", []);
+        assert!(duplicate.is_err(), "a second unkeyed row with the same link should be rejected");
+        
+        Ok(())
+    }
+    
+    #[test]
+    fn test_add_content_version_to_articles() -> Result<()> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch("
+            CREATE TABLE articles (id TEXT PRIMARY KEY, feed_id TEXT NOT NULL, title TEXT NOT NULL);
+            INSERT INTO articles (id, feed_id, title) VALUES ('a1', '1', 'Stored');
+        ")?;
+        let manager = MigrationManager::new(&conn);
+        manager.create_migrations_table()?;
+        
+        manager.migrate_add_content_version_to_articles()?;
+        manager.migrate_add_content_version_to_articles()?;
+        
+        let content_version: u32 = conn.query_row("SELECT content_version FROM articles WHERE id = 'a1'", [], |row| row.get(0))?;
+        assert_eq!(content_version, 0);
+        
+        Ok(())
+    }
+}
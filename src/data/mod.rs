@@ -1,4 +1,6 @@
 pub mod database;
+pub mod fts;
+pub mod query;
 pub mod types;
 pub mod repositories;
 pub mod migration;
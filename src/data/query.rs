@@ -0,0 +1,116 @@
+//! Compilation of [`ArticleQuery`] filters into SQL conditions.
+
+use chrono::{NaiveDate, NaiveTime};
+use rusqlite::ToSql;
+
+use crate::data::fts;
+use crate::models::article_query::{ArticleQuery, ArticleState, QueryExpr, QueryTerm};
+
+/// Builds a condition on the `articles` table aliased as `a` that holds for
+/// every article matching `query`
+///
+/// Values are appended to `params` and referenced by number, so the condition
+/// can be combined with other numbered parameters of the same statement.
+pub fn compile(query: &ArticleQuery, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    compile_expr(&query.root, params)
+}
+
+fn compile_expr(expr: &QueryExpr, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    match expr {
+        QueryExpr::And(exprs) => join(exprs, " AND ", params),
+        QueryExpr::Or(exprs) => join(exprs, " OR ", params),
+        QueryExpr::Not(expr) => format!("NOT ({})", compile_expr(expr, params)),
+        QueryExpr::Term(term) => compile_term(term, params),
+    }
+}
+
+fn join(exprs: &[QueryExpr], separator: &str, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    let parts: Vec<String> = exprs.iter()
+        .map(|expr| format!("({})", compile_expr(expr, params)))
+        .collect();
+    parts.join(separator)
+}
+
+fn compile_term(term: &QueryTerm, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    let mut bind = |value: Box<dyn ToSql>| {
+        params.push(value);
+        format!("?{}", params.len())
+    };
+
+    match term {
+        QueryTerm::Text(text) => match fts::match_expression(text) {
+            Some(expression) => format!(
+                "a.rowid IN (SELECT rowid FROM articles_fts WHERE articles_fts MATCH {})",
+                bind(Box::new(expression))
+            ),
+            None => "1".to_string(),
+        },
+        QueryTerm::Feed(feed) => {
+            let param = bind(Box::new(feed.clone()));
+            format!("a.feed_id IN (SELECT id FROM feeds WHERE title = {0} COLLATE NOCASE OR id = {0})", param)
+        }
+        // Named categories and everything nested under them
+        QueryTerm::Category(category) => format!(
+            "a.feed_id IN (SELECT id FROM feeds WHERE category_id IN (
+                WITH RECURSIVE subtree(id) AS (
+                    SELECT id FROM categories WHERE name = {} COLLATE NOCASE
+                    UNION SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+                )
+                SELECT id FROM subtree
+            ))",
+            bind(Box::new(category.clone()))
+        ),
+        QueryTerm::Tag(tag) => format!(
+            "EXISTS (SELECT 1 FROM article_tags at JOIN tags t ON t.id = at.tag_id
+                     WHERE at.article_id = a.id AND t.name = {} COLLATE NOCASE)",
+            bind(Box::new(tag.clone()))
+        ),
+        QueryTerm::Is(ArticleState::Unread) => "a.read_status = 'unread'".to_string(),
+        QueryTerm::Is(ArticleState::Read) => "a.read_status = 'read'".to_string(),
+        QueryTerm::Is(ArticleState::Starred) => "a.is_favorited = 1".to_string(),
+        QueryTerm::After(date) => format!("a.published_at >= {}", bind(Box::new(start_of_day(*date)))),
+        QueryTerm::Before(date) => format!("a.published_at < {}", bind(Box::new(start_of_day(*date)))),
+        QueryTerm::Title(text) => format!(
+            "a.title LIKE {} ESCAPE '\\'",
+            bind(Box::new(like_pattern(text)))
+        ),
+        QueryTerm::Author(text) => format!(
+            "COALESCE(a.author, '') LIKE {} ESCAPE '\\'",
+            bind(Box::new(like_pattern(text)))
+        ),
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> chrono::DateTime<chrono::Utc> {
+    date.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// A case-insensitive `LIKE` pattern matching `text` anywhere, with wildcards escaped
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_compile_numbers_params_after_existing_ones() -> Result<()> {
+        let query = ArticleQuery::parse("is:unread (tag:rust OR -title:50%) rust*")?;
+        let mut params: Vec<Box<dyn ToSql>> = vec![Box::new(1)];
+        let sql = compile(&query, &mut params);
+
+        assert_eq!(params.len(), 4);
+        assert!(sql.starts_with("(a.read_status = 'unread') AND ((EXISTS"), "{}", sql);
+        assert!(sql.contains("t.name = ?2 COLLATE NOCASE"), "{}", sql);
+        assert!(sql.contains("OR (NOT (a.title LIKE ?3 ESCAPE '\\'))"), "{}", sql);
+        assert!(sql.contains("articles_fts MATCH ?4"), "{}", sql);
+        assert_eq!(like_pattern("50%_off"), "%50\\%\\_off%");
+        Ok(())
+    }
+}
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use rusqlite::Connection;
+use rusqlite::{Connection, ToSql};
 use chrono::{DateTime, Utc};
 use anyhow::Result;
 use async_trait::async_trait;
@@ -8,8 +8,14 @@ use crate::models::article::{Article, ArticleId, ReadStatus};
 use crate::models::feed::FeedId;
 use crate::models::category::CategoryId;
 use crate::models::tag::TagId;
+use crate::models::search::{SearchHit, SearchQuery, HIGHLIGHT_END, HIGHLIGHT_START};
+use crate::models::article_query::ArticleQuery;
+use crate::models::article_page::{ArticleCursor, ArticleFilter, ArticlePage, ArticleSummary};
+use crate::models::settings::ArticleSortOrder;
 use crate::base::repository::ArticleRepository;
 use crate::data::database::ConnectionPool;
+use crate::data::fts;
+use crate::data::query;
 
 pub struct SqliteArticleRepository {
     connection_pool: Arc<ConnectionPool>,
@@ -34,10 +40,110 @@ impl SqliteArticleRepository {
             is_favorited: row.get(9)?,
             created_at: row.get(10)?,
             updated_at: row.get(11)?,
+            guid: row.get(12)?,
+            full_content: row.get(13)?,
+            content_version: row.get(14)?,
+        })
+    }
+
+    fn map_summary_row(&self, row: &rusqlite::Row) -> Result<ArticleSummary> {
+        Ok(ArticleSummary {
+            id: row.get::<_, String>(0)?.into(),
+            feed_id: row.get::<_, String>(1)?.into(),
+            title: row.get(2)?,
+            url: row.get(3)?,
+            author: row.get(4)?,
+            summary: row.get(5)?,
+            published_at: row.get(6)?,
+            read_status: ReadStatus::from_str(&row.get::<_, String>(7)?).unwrap_or(ReadStatus::Unread),
+            is_favorited: row.get(8)?,
         })
     }
 }
 
+/// Builds the condition selecting the articles of `filter`, binding values to `params`
+fn filter_condition(filter: &ArticleFilter, params: &mut Vec<Box<dyn ToSql>>) -> String {
+    match filter {
+        ArticleFilter::All => "1".to_string(),
+        ArticleFilter::Unread => "a.read_status = 'unread'".to_string(),
+        ArticleFilter::Favorites => "a.is_favorited = 1".to_string(),
+        ArticleFilter::Feed(feed_id) => {
+            params.push(Box::new(feed_id.to_string()));
+            format!("a.feed_id = ?{}", params.len())
+        }
+        ArticleFilter::Category(category_id) => {
+            params.push(Box::new(category_id.to_string()));
+            format!(
+                "a.feed_id IN (SELECT id FROM feeds WHERE category_id IN (
+                    WITH RECURSIVE subtree(id) AS (
+                        SELECT ?{}
+                        UNION SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+                    )
+                    SELECT id FROM subtree
+                ))",
+                params.len()
+            )
+        }
+        ArticleFilter::Tag(tag) => {
+            params.push(Box::new(tag.clone()));
+            format!(
+                "EXISTS (SELECT 1 FROM article_tags at JOIN tags t ON t.id = at.tag_id
+                         WHERE at.article_id = a.id AND t.name = ?{})",
+                params.len()
+            )
+        }
+        ArticleFilter::Query(article_query) => format!("({})", query::compile(article_query, params)),
+        ArticleFilter::Search(search) => match search_results_clause(search, params) {
+            Some(clause) => format!("a.rowid IN (SELECT a.rowid {})", clause),
+            None => "0".to_string(),
+        },
+    }
+}
+
+/// Ranks full-text matches, weighting title, summary, content and author
+const SEARCH_RANK: &str = "bm25(articles_fts, 10.0, 4.0, 1.0, 2.0)";
+
+/// Builds the `FROM` clause through `LIMIT` of a query for a search's results,
+/// best matches first, or `None` when there is nothing to search for
+///
+/// Shared by [`ArticleRepository::search`] and [`ArticleFilter::Search`], so
+/// bulk actions on a search change exactly the results it lists.
+fn search_results_clause(search: &SearchQuery, params: &mut Vec<Box<dyn ToSql>>) -> Option<String> {
+    params.push(Box::new(fts::match_expression(&search.text)?));
+    let mut conditions = vec![format!("articles_fts MATCH ?{}", params.len())];
+    if let Some(feed_id) = &search.feed_id {
+        conditions.push(filter_condition(&ArticleFilter::Feed(feed_id.clone()), params));
+    }
+    if let Some(category_id) = &search.category_id {
+        conditions.push(filter_condition(&ArticleFilter::Category(category_id.clone()), params));
+    }
+    if let Some(tag) = &search.tag {
+        conditions.push(filter_condition(&ArticleFilter::Tag(tag.clone()), params));
+    }
+    if let Some(read_status) = &search.read_status {
+        params.push(Box::new(read_status.to_string()));
+        conditions.push(format!("a.read_status = ?{}", params.len()));
+    }
+    if let Some(after) = search.published_after {
+        params.push(Box::new(after));
+        conditions.push(format!("a.published_at >= ?{}", params.len()));
+    }
+    if let Some(before) = search.published_before {
+        params.push(Box::new(before));
+        conditions.push(format!("a.published_at < ?{}", params.len()));
+    }
+    params.push(Box::new(search.limit as i64));
+    Some(format!(
+        "FROM articles_fts
+         JOIN articles a ON a.rowid = articles_fts.rowid
+         WHERE {}
+         ORDER BY {}, a.rowid LIMIT ?{}",
+        conditions.join(" AND "),
+        SEARCH_RANK,
+        params.len()
+    ))
+}
+
 #[async_trait]
 impl ArticleRepository for SqliteArticleRepository {
     async fn get_article(&self, id: &ArticleId) -> Result<Option<Article>> {
@@ -45,7 +151,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary, 
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE id = ?"
         )?;
@@ -62,7 +168,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              ORDER BY published_at DESC"
         )?;
@@ -78,7 +184,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE feed_id = ? 
              ORDER BY published_at DESC"
@@ -91,13 +197,118 @@ impl ArticleRepository for SqliteArticleRepository {
         Ok(articles)
     }
 
+    async fn get_article_page(
+        &self,
+        filter: &ArticleFilter,
+        order: ArticleSortOrder,
+        cursor: Option<&ArticleCursor>,
+        limit: usize,
+    ) -> Result<ArticlePage> {
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+        let mut sql = format!(
+            "SELECT a.id, a.feed_id, a.title, a.url, a.author, a.summary,
+                    a.published_at, a.read_status, a.is_favorited
+             FROM articles a
+             WHERE {}",
+            filter_condition(filter, &mut params)
+        );
+
+        // Row values compare in one direction only, so every sort key runs the same way
+        let (keys, comparison, direction) = match order {
+            ArticleSortOrder::NewestFirst => ("a.published_at, a.id", "<", "DESC"),
+            ArticleSortOrder::OldestFirst => ("a.published_at, a.id", ">", "ASC"),
+            ArticleSortOrder::Unread => ("a.read_status = 'unread', a.published_at, a.id", "<", "DESC"),
+        };
+        if let Some(cursor) = cursor {
+            let mut placeholders = Vec::new();
+            if order == ArticleSortOrder::Unread {
+                params.push(Box::new(cursor.unread));
+                placeholders.push(format!("?{}", params.len()));
+            }
+            params.push(Box::new(cursor.published_at));
+            placeholders.push(format!("?{}", params.len()));
+            params.push(Box::new(cursor.id.to_string()));
+            placeholders.push(format!("?{}", params.len()));
+            sql.push_str(&format!(" AND ({}) {} ({})", keys, comparison, placeholders.join(", ")));
+        }
+
+        let order_by: Vec<String> = keys.split(", ").map(|key| format!("{} {}", key, direction)).collect();
+        // One extra row tells whether another page follows
+        params.push(Box::new((limit + 1) as i64));
+        sql.push_str(&format!(" ORDER BY {} LIMIT ?{}", order_by.join(", "), params.len()));
+
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| Ok(self.map_summary_row(row)))?;
+        let mut articles = rows.collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let next_cursor = if articles.len() > limit {
+            articles.truncate(limit);
+            articles.last().map(ArticleCursor::after)
+        } else {
+            None
+        };
+        Ok(ArticlePage { articles, next_cursor })
+    }
+
+    async fn mark_as_read(
+        &self,
+        filter: &ArticleFilter,
+        published_before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<(ArticleId, FeedId)>> {
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+        params.push(Box::new(Utc::now()));
+        let mut sql = format!(
+            "UPDATE articles AS a SET read_status = 'read', updated_at = ?1
+             WHERE a.read_status = 'unread' AND ({})",
+            filter_condition(filter, &mut params)
+        );
+        if let Some(before) = published_before {
+            params.push(Box::new(before));
+            sql.push_str(&format!(" AND a.published_at < ?{}", params.len()));
+        }
+        sql.push_str(" RETURNING id, feed_id");
+
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        let ids = {
+            let mut stmt = tx.prepare(&sql)?;
+            let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+                Ok((ArticleId::from(row.get::<_, String>(0)?), FeedId::from(row.get::<_, String>(1)?)))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    async fn set_read_status(&self, ids: &[ArticleId], status: ReadStatus) -> Result<usize> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        let now = Utc::now();
+        let mut changed = 0;
+        {
+            let mut stmt = tx.prepare(
+                "UPDATE articles SET read_status = ?, updated_at = ? WHERE id = ? AND read_status != ?"
+            )?;
+            let status = status.to_string();
+            for id in ids {
+                changed += stmt.execute(rusqlite::params![status, now, id.to_string(), status])?;
+            }
+        }
+        tx.commit()?;
+        Ok(changed)
+    }
+
     async fn get_articles_by_category(&self, category_id: &CategoryId) -> Result<Vec<Article>> {
         // 锁定连接以延长其生命周期
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT a.id, a.feed_id, a.title, a.url, a.author, a.content, 
                     a.summary, a.published_at, a.read_status, a.is_favorited, 
-                    a.created_at, a.updated_at 
+                    a.created_at, a.updated_at, a.guid, a.full_content, a.content_version
              FROM articles a 
              JOIN feeds f ON a.feed_id = f.id 
              WHERE f.category_id = ? 
@@ -116,7 +327,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary, 
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE url = ?"
         )?;
@@ -129,6 +340,42 @@ impl ArticleRepository for SqliteArticleRepository {
         }
     }
     
+    async fn get_article_by_guid(&self, feed_id: &FeedId, guid: &str) -> Result<Option<Article>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, feed_id, title, url, author, content, summary, 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
+             FROM articles 
+             WHERE feed_id = ? AND guid = ?"
+        )?;
+
+        let mut rows = stmt.query([feed_id.to_string(), guid.to_string()])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(self.map_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn get_article_by_feed_and_url(&self, feed_id: &FeedId, url: &str) -> Result<Option<Article>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, feed_id, title, url, author, content, summary, 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
+             FROM articles 
+             WHERE feed_id = ? AND url = ?
+             ORDER BY (guid IS NULL OR guid LIKE 'fingerprint:%') DESC, created_at
+             LIMIT 1"
+        )?;
+
+        let mut rows = stmt.query([feed_id.to_string(), url.to_string()])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(self.map_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+    
     async fn add_tag(&self, article_id: &ArticleId, tag: &str) -> Result<()> {
         // 锁定连接以延长其生命周期
         let conn = self.connection_pool.get()?;
@@ -210,7 +457,7 @@ impl ArticleRepository for SqliteArticleRepository {
         if let Ok(tag_id) = stmt.query_row([tag], |row| Ok(row.get::<_, String>(0)?)) {
             let mut stmt = conn.prepare(
                 "SELECT a.id, a.feed_id, a.title, a.url, a.author, a.content, a.summary,
-                        a.published_at, a.read_status, a.is_favorited, a.created_at, a.updated_at 
+                        a.published_at, a.read_status, a.is_favorited, a.created_at, a.updated_at, a.guid, a.full_content, a.content_version
                  FROM articles a 
                  JOIN article_tags at ON a.id = at.article_id 
                  WHERE at.tag_id = ? 
@@ -232,7 +479,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE read_status = 'unread' 
              ORDER BY published_at DESC"
@@ -250,7 +497,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE is_favorited = true 
              ORDER BY published_at DESC"
@@ -264,22 +511,57 @@ impl ArticleRepository for SqliteArticleRepository {
     }
 
     async fn search_articles(&self, query: &str) -> Result<Vec<Article>> {
-        // 锁定连接以延长其生命周期
-        let conn = self.connection_pool.get()?;
-        let search_term = format!("%{}%", query);
-        let mut stmt = conn.prepare(
-            "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
-             FROM articles 
-             WHERE title LIKE ? OR content LIKE ? OR summary LIKE ? 
-             ORDER BY published_at DESC"
-        )?;
+        let hits = self.search(&SearchQuery::new(query)).await?;
+        Ok(hits.into_iter().map(|hit| hit.article).collect())
+    }
 
-        let rows = stmt.query_map([&search_term, &search_term, &search_term], |row| Ok(self.map_row(row)))?;
-        let articles = rows.collect::<Result<Vec<_>, _>>()?
+    async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        let mut params: Vec<Box<dyn ToSql>> = vec![
+            Box::new(HIGHLIGHT_START.to_string()),
+            Box::new(HIGHLIGHT_END.to_string()),
+        ];
+        let clause = match search_results_clause(query, &mut params) {
+            Some(clause) => clause,
+            None => return Ok(Vec::new()),
+        };
+        let sql = format!(
+            "SELECT a.id, a.feed_id, a.title, a.url, a.author, a.content, a.summary,
+                    a.published_at, a.read_status, a.is_favorited, a.created_at, a.updated_at, a.guid, a.full_content, a.content_version,
+                    {} AS rank,
+                    highlight(articles_fts, 0, ?1, ?2),
+                    snippet(articles_fts, -1, ?1, ?2, '…', 16)
+             {}",
+            SEARCH_RANK,
+            clause
+        );
+
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(params.iter()), |row| {
+            Ok(self.map_row(row).map(|article| SearchHit {
+                article,
+                rank: row.get(15).unwrap_or_default(),
+                title_highlight: row.get(16).unwrap_or_default(),
+                snippet: row.get(17).unwrap_or_default(),
+            }))
+        })?;
+        let hits = rows.collect::<Result<Vec<_>, _>>()?
             .into_iter()
             .collect::<Result<Vec<_>, _>>()?;
-        Ok(articles)
+        Ok(hits)
+    }
+
+    async fn count_unread_matching(&self, query: &ArticleQuery) -> Result<usize> {
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+        let condition = query::compile(query, &mut params);
+        let sql = format!(
+            "SELECT COUNT(*) FROM articles a WHERE a.read_status = 'unread' AND ({})",
+            condition
+        );
+
+        let conn = self.connection_pool.get()?;
+        let count: i64 = conn.query_row(&sql, rusqlite::params_from_iter(params.iter()), |row| row.get(0))?;
+        Ok(count as usize)
     }
 
     async fn get_articles_by_date_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Article>> {
@@ -287,7 +569,7 @@ impl ArticleRepository for SqliteArticleRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, feed_id, title, url, author, content, summary,
-                    published_at, read_status, is_favorited, created_at, updated_at 
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
              FROM articles 
              WHERE published_at BETWEEN ? AND ? 
              ORDER BY published_at DESC"
@@ -305,8 +587,8 @@ impl ArticleRepository for SqliteArticleRepository {
         conn.execute(
             "INSERT INTO articles (
                 id, feed_id, title, url, author, content, summary, published_at,
-                read_status, is_favorited, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
                 article.id.to_string(),
                 article.feed_id.to_string(),
@@ -320,6 +602,9 @@ impl ArticleRepository for SqliteArticleRepository {
                 article.is_favorited,
                 article.created_at,
                 article.updated_at,
+                article.guid,
+                article.full_content,
+                article.content_version,
             ],
         )?;
         Ok(())
@@ -338,7 +623,10 @@ impl ArticleRepository for SqliteArticleRepository {
                 published_at = ?,
                 read_status = ?,
                 is_favorited = ?,
-                updated_at = ?
+                updated_at = ?,
+                guid = ?,
+                full_content = ?,
+                content_version = ?
             WHERE id = ?",
             rusqlite::params![
                 article.feed_id.to_string(),
@@ -351,15 +639,89 @@ impl ArticleRepository for SqliteArticleRepository {
                 article.read_status.to_string(),
                 article.is_favorited,
                 article.updated_at,
+                article.guid,
+                article.full_content,
+                article.content_version,
                 article.id.to_string(),
             ],
         )?;
         Ok(())
     }
 
+    async fn set_full_content(&self, id: &ArticleId, full_content: &str) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(
+            "UPDATE articles SET full_content = ?, updated_at = ? WHERE id = ?",
+            rusqlite::params![full_content, Utc::now(), id.to_string()],
+        )?;
+        Ok(())
+    }
+
     async fn delete_article(&self, id: &ArticleId) -> Result<()> {
         let conn = self.connection_pool.get()?;
         conn.execute("DELETE FROM articles WHERE id = ?", [id.to_string()])?;
         Ok(())
     }
+
+    async fn get_expired_articles(&self, older_than: DateTime<Utc>) -> Result<Vec<Article>> {
+        let conn = self.connection_pool.get()?;
+        // Position 1 is the newest article of its feed, by publication date where known
+        let mut stmt = conn.prepare(
+            "SELECT id, feed_id, title, url, author, content, summary,
+                    published_at, read_status, is_favorited, created_at, updated_at, guid, full_content, content_version
+             FROM (
+                SELECT a.*, f.keep_last_articles,
+                       ROW_NUMBER() OVER (
+                           PARTITION BY a.feed_id
+                           ORDER BY COALESCE(a.published_at, a.created_at) DESC
+                       ) AS position
+                FROM articles a
+                LEFT JOIN feeds f ON f.id = a.feed_id
+             ) ranked
+             WHERE read_status = 'read'
+               AND is_favorited = 0
+               AND NOT EXISTS (SELECT 1 FROM article_tags at WHERE at.article_id = ranked.id)
+               AND CASE
+                   WHEN keep_last_articles IS NULL THEN COALESCE(published_at, created_at) < ?
+                   ELSE position > keep_last_articles
+               END
+             ORDER BY feed_id, COALESCE(published_at, created_at)"
+        )?;
+
+        let rows = stmt.query_map([older_than], |row| Ok(self.map_row(row)))?;
+        let articles = rows.collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(articles)
+    }
+
+    async fn delete_articles(&self, ids: &[ArticleId]) -> Result<usize> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        let mut deleted = 0;
+        for id in ids {
+            tx.execute("DELETE FROM article_tags WHERE article_id = ?", [id.to_string()])?;
+            deleted += tx.execute("DELETE FROM articles WHERE id = ?", [id.to_string()])?;
+        }
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    async fn reclaim_space(&self) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        // Databases created with incremental auto-vacuum can free pages cheaply;
+        // older ones need a full rebuild, which also switches them over
+        let auto_vacuum: i64 = conn.query_row("PRAGMA auto_vacuum", [], |row| row.get(0))?;
+        if auto_vacuum == 2 {
+            conn.execute_batch("PRAGMA incremental_vacuum;")?;
+        } else {
+            // VACUUM may renumber rowids, which the full-text index is keyed on
+            conn.execute_batch("
+                PRAGMA auto_vacuum = INCREMENTAL;
+                VACUUM;
+                INSERT INTO articles_fts (articles_fts) VALUES ('rebuild');
+            ")?;
+        }
+        Ok(())
+    }
 }
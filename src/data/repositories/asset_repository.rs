@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use url::Url;
+
+use crate::models::cached_asset::CachedAsset;
+use crate::base::repository::AssetRepository;
+use crate::data::database::ConnectionPool;
+
+pub struct SqliteAssetRepository {
+    connection_pool: Arc<ConnectionPool>,
+}
+
+impl SqliteAssetRepository {
+    pub fn new(connection_pool: Arc<ConnectionPool>) -> Self {
+        Self { connection_pool }
+    }
+
+    fn map_row(row: &rusqlite::Row) -> rusqlite::Result<CachedAsset> {
+        Ok(CachedAsset {
+            hash: row.get(0)?,
+            data: row.get(1)?,
+            mime_type: row.get(2)?,
+            created_at: row.get(3)?,
+            last_accessed_at: row.get(4)?,
+        })
+    }
+}
+
+#[async_trait]
+impl AssetRepository for SqliteAssetRepository {
+    async fn save_asset(&self, url: &Url, asset: &CachedAsset) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO cached_assets (hash, data, mime_type, size, created_at, last_accessed_at)
+             VALUES (?, ?, ?, ?, ?, ?)
+             ON CONFLICT(hash) DO UPDATE SET last_accessed_at = excluded.last_accessed_at",
+            rusqlite::params![
+                asset.hash,
+                asset.data,
+                asset.mime_type,
+                asset.size() as i64,
+                asset.created_at,
+                asset.last_accessed_at,
+            ],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO asset_urls (url, hash) VALUES (?, ?)",
+            rusqlite::params![url.to_string(), asset.hash],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn get_asset(&self, hash: &str) -> Result<Option<CachedAsset>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT hash, data, mime_type, created_at, last_accessed_at FROM cached_assets WHERE hash = ?"
+        )?;
+
+        let mut rows = stmt.query([hash])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(Self::map_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_assets_by_urls(&self, urls: &[Url]) -> Result<Vec<(Url, CachedAsset)>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT a.hash, a.data, a.mime_type, a.created_at, a.last_accessed_at
+             FROM asset_urls u
+             JOIN cached_assets a ON a.hash = u.hash
+             WHERE u.url = ?"
+        )?;
+
+        let mut assets = Vec::new();
+        for url in urls {
+            let mut rows = stmt.query([url.to_string()])?;
+            if let Some(row) = rows.next()? {
+                assets.push((url.clone(), Self::map_row(row)?));
+            }
+        }
+        Ok(assets)
+    }
+
+    async fn touch_assets(&self, hashes: &[String], accessed_at: DateTime<Utc>) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        for hash in hashes {
+            tx.execute(
+                "UPDATE cached_assets SET last_accessed_at = ? WHERE hash = ?",
+                rusqlite::params![accessed_at, hash],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn get_total_size(&self) -> Result<u64> {
+        let conn = self.connection_pool.get()?;
+        let total: i64 = conn.query_row("SELECT COALESCE(SUM(size), 0) FROM cached_assets", [], |row| row.get(0))?;
+        Ok(total as u64)
+    }
+
+    async fn evict_to_size(&self, max_bytes: u64) -> Result<usize> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        let mut total: i64 = tx.query_row("SELECT COALESCE(SUM(size), 0) FROM cached_assets", [], |row| row.get(0))?;
+
+        let mut evicted = Vec::new();
+        {
+            let mut stmt = tx.prepare("SELECT hash, size FROM cached_assets ORDER BY last_accessed_at, created_at")?;
+            let mut rows = stmt.query([])?;
+            while total as u64 > max_bytes {
+                let Some(row) = rows.next()? else { break };
+                evicted.push(row.get::<_, String>(0)?);
+                total -= row.get::<_, i64>(1)?;
+            }
+        }
+        for hash in &evicted {
+            tx.execute("DELETE FROM asset_urls WHERE hash = ?", [hash])?;
+            tx.execute("DELETE FROM cached_assets WHERE hash = ?", [hash])?;
+        }
+        tx.commit()?;
+        Ok(evicted.len())
+    }
+}
@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use rusqlite::Connection;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
+use crate::models::article_counts::ArticleCounts;
 use crate::models::category::{Category, CategoryId};
 use crate::base::repository::CategoryRepository;
 use crate::data::database::ConnectionPool;
@@ -237,4 +239,38 @@ impl CategoryRepository for SqliteCategoryRepository {
             .collect::<Result<Vec<_>, _>>()?;
         Ok(categories)
     }
-}
\ No newline at end of file
+
+    async fn get_article_counts(&self) -> Result<HashMap<CategoryId, ArticleCounts>> {
+        let conn = self.connection_pool.get()?;
+        // Pairs every category with itself and each of its ancestors, so the
+        // counts of a category's own feeds are added to all categories above it
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE ancestry(category_id, ancestor_id) AS (
+                SELECT id, id FROM categories
+                UNION
+                SELECT ancestry.category_id, c.parent_id
+                FROM ancestry JOIN categories c ON c.id = ancestry.ancestor_id
+                WHERE c.parent_id IS NOT NULL
+             ),
+             direct_counts(category_id, unread, total) AS (
+                SELECT f.category_id, COALESCE(SUM(a.read_status = 'unread'), 0), COUNT(a.id)
+                FROM feeds f
+                JOIN articles a ON a.feed_id = f.id
+                WHERE f.category_id IS NOT NULL
+                GROUP BY f.category_id
+             )
+             SELECT ancestry.ancestor_id, SUM(direct_counts.unread), SUM(direct_counts.total)
+             FROM ancestry
+             JOIN direct_counts ON direct_counts.category_id = ancestry.category_id
+             GROUP BY ancestry.ancestor_id"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                CategoryId::from(row.get::<_, String>(0)?),
+                ArticleCounts::new(row.get::<_, i64>(1)? as usize, row.get::<_, i64>(2)? as usize),
+            ))
+        })?;
+        Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+    }
+}
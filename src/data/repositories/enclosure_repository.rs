@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::article::ArticleId;
+use crate::models::enclosure::{DownloadStatus, Enclosure, EnclosureId};
+use crate::models::feed::FeedId;
+use crate::base::repository::EnclosureRepository;
+use crate::data::database::ConnectionPool;
+
+const COLUMNS: &str = "e.id, e.article_id, e.url, e.mime_type, e.length, e.duration_secs, e.title,
+                       e.download_status, e.local_path, e.created_at, e.updated_at";
+
+pub struct SqliteEnclosureRepository {
+    connection_pool: Arc<ConnectionPool>,
+}
+
+impl SqliteEnclosureRepository {
+    pub fn new(connection_pool: Arc<ConnectionPool>) -> Self {
+        Self { connection_pool }
+    }
+
+    fn map_row(&self, row: &rusqlite::Row) -> Result<Enclosure> {
+        Ok(Enclosure {
+            id: row.get::<_, String>(0)?.into(),
+            article_id: row.get::<_, String>(1)?.into(),
+            url: row.get(2)?,
+            mime_type: row.get(3)?,
+            length: row.get(4)?,
+            duration_secs: row.get(5)?,
+            title: row.get(6)?,
+            download_status: DownloadStatus::from_str(&row.get::<_, String>(7)?)
+                .unwrap_or(DownloadStatus::NotDownloaded),
+            local_path: row.get(8)?,
+            created_at: row.get(9)?,
+            updated_at: row.get(10)?,
+        })
+    }
+
+    fn query_enclosures(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Enclosure>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(sql)?;
+        let enclosures = stmt.query_map(params, |row| Ok(self.map_row(row)))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(enclosures)
+    }
+}
+
+#[async_trait]
+impl EnclosureRepository for SqliteEnclosureRepository {
+    async fn save_enclosures(&self, enclosures: &[Enclosure]) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        for enclosure in enclosures {
+            tx.execute(
+                "INSERT INTO enclosures (
+                    id, article_id, url, mime_type, length, duration_secs, title,
+                    download_status, local_path, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(article_id, url) DO UPDATE SET
+                    mime_type = excluded.mime_type,
+                    length = excluded.length,
+                    duration_secs = excluded.duration_secs,
+                    title = excluded.title,
+                    updated_at = excluded.updated_at",
+                rusqlite::params![
+                    enclosure.id.to_string(),
+                    enclosure.article_id.to_string(),
+                    enclosure.url.to_string(),
+                    enclosure.mime_type,
+                    enclosure.length,
+                    enclosure.duration_secs,
+                    enclosure.title,
+                    enclosure.download_status.to_string(),
+                    enclosure.local_path,
+                    enclosure.created_at,
+                    enclosure.updated_at,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    async fn get_enclosure(&self, id: &EnclosureId) -> Result<Option<Enclosure>> {
+        let sql = format!("SELECT {} FROM enclosures e WHERE e.id = ?", COLUMNS);
+        Ok(self.query_enclosures(&sql, [id.to_string()])?.into_iter().next())
+    }
+
+    async fn get_enclosures_by_article(&self, article_id: &ArticleId) -> Result<Vec<Enclosure>> {
+        let sql = format!("SELECT {} FROM enclosures e WHERE e.article_id = ? ORDER BY e.rowid", COLUMNS);
+        self.query_enclosures(&sql, [article_id.to_string()])
+    }
+
+    async fn get_enclosures_by_feed(&self, feed_id: &FeedId, limit: Option<usize>) -> Result<Vec<Enclosure>> {
+        // SQLite treats a negative LIMIT as no limit
+        let limit = limit.map_or(-1, |limit| limit as i64);
+        let sql = format!(
+            "SELECT {}
+             FROM enclosures e
+             JOIN articles a ON a.id = e.article_id
+             WHERE a.feed_id = ?
+             ORDER BY a.published_at DESC, a.id DESC, e.rowid
+             LIMIT ?",
+            COLUMNS
+        );
+        self.query_enclosures(&sql, rusqlite::params![feed_id.to_string(), limit])
+    }
+
+    async fn update_download(&self, enclosure: &Enclosure) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(
+            "UPDATE enclosures SET download_status = ?, local_path = ?, updated_at = ? WHERE id = ?",
+            rusqlite::params![
+                enclosure.download_status.to_string(),
+                enclosure.local_path,
+                enclosure.updated_at,
+                enclosure.id.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+}
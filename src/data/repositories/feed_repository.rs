@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use rusqlite::Connection;
 use chrono::{DateTime, Utc};
@@ -5,7 +6,9 @@ use anyhow::Result;
 use async_trait::async_trait;
 use url::Url;
 
-use crate::models::feed::{Feed, FeedId, FeedStatus};
+use crate::models::article_counts::ArticleCounts;
+use crate::models::feed::{AutoDownloadPolicy, Feed, FeedId, FeedStatus};
+use crate::models::feed_icon::FeedIcon;
 use crate::models::category::CategoryId;
 use crate::base::repository::FeedRepository;
 use crate::data::database::ConnectionPool;
@@ -33,8 +36,53 @@ impl SqliteFeedRepository {
             next_fetch_at: row.get(9)?,
             created_at: row.get(10)?,
             updated_at: row.get(11)?,
+            etag: row.get(12)?,
+            last_modified: row.get(13)?,
+            keep_last_articles: row.get(14)?,
+            auto_download: AutoDownloadPolicy::from_str(&row.get::<_, String>(15)?).unwrap_or(AutoDownloadPolicy::Off),
+            description: row.get(16)?,
+            language: row.get(17)?,
+            author: row.get(18)?,
+            generator: row.get(19)?,
+            cache_images: row.get(20)?,
+            fetch_full_text: row.get(21)?,
         })
     }
+
+    fn map_icon_row(row: &rusqlite::Row) -> rusqlite::Result<FeedIcon> {
+        Ok(FeedIcon {
+            feed_id: row.get::<_, String>(0)?.into(),
+            url: row.get(1)?,
+            data: row.get(2)?,
+            mime_type: row.get(3)?,
+            fetched_at: row.get(4)?,
+        })
+    }
+
+    fn query_article_counts(&self, feed_ids: Option<&[FeedId]>) -> Result<HashMap<FeedId, ArticleCounts>> {
+        let filter = match feed_ids {
+            Some(ids) => format!("WHERE f.id IN ({})", vec!["?"; ids.len()].join(", ")),
+            None => String::new(),
+        };
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT f.id, COALESCE(SUM(a.read_status = 'unread'), 0), COUNT(a.id)
+             FROM feeds f
+             LEFT JOIN articles a ON a.feed_id = f.id
+             {}
+             GROUP BY f.id",
+            filter
+        ))?;
+
+        let ids = feed_ids.unwrap_or_default().iter().map(|id| id.to_string());
+        let rows = stmt.query_map(rusqlite::params_from_iter(ids), |row| {
+            Ok((
+                FeedId::from(row.get::<_, String>(0)?),
+                ArticleCounts::new(row.get::<_, i64>(1)? as usize, row.get::<_, i64>(2)? as usize),
+            ))
+        })?;
+        Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+    }
 }
 
 #[async_trait]
@@ -43,7 +91,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE id = ?"
         )?;
@@ -60,7 +109,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE url = ?"
         )?;
@@ -99,7 +149,17 @@ impl FeedRepository for SqliteFeedRepository {
             "last_fetched_at", 
             "next_fetch_at", 
             "created_at", 
-            "updated_at"
+            "updated_at",
+            "etag",
+            "last_modified",
+            "keep_last_articles",
+            "auto_download",
+            "description",
+            "language",
+            "author",
+            "generator",
+            "cache_images",
+            "fetch_full_text"
         ];
         
         for col in &optional_columns {
@@ -162,6 +222,38 @@ impl FeedRepository for SqliteFeedRepository {
             column_index += 1;
             
             let updated_at: DateTime<Utc> = row.get(column_index).unwrap_or(now);
+            column_index += 1;
+            
+            let etag: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let last_modified: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let keep_last_articles: Option<u32> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let auto_download = row.get::<_, Option<String>>(column_index).unwrap_or(None)
+                .and_then(|policy| AutoDownloadPolicy::from_str(&policy))
+                .unwrap_or(AutoDownloadPolicy::Off);
+            column_index += 1;
+            
+            let description: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let language: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let author: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let generator: Option<String> = row.get(column_index).unwrap_or(None);
+            column_index += 1;
+            
+            let cache_images = row.get::<_, Option<bool>>(column_index).unwrap_or(None).unwrap_or(false);
+            column_index += 1;
+            
+            let fetch_full_text = row.get::<_, Option<bool>>(column_index).unwrap_or(None).unwrap_or(false);
             
             Ok(Feed {
                 id: id.into(),
@@ -172,10 +264,20 @@ impl FeedRepository for SqliteFeedRepository {
                 error_message,
                 icon_url,
                 site_url,
+                description,
+                language,
+                author,
+                generator,
                 last_fetched_at,
                 next_fetch_at,
                 created_at,
                 updated_at,
+                etag,
+                last_modified,
+                keep_last_articles,
+                auto_download,
+                cache_images,
+                fetch_full_text,
             })
         })?;
         
@@ -187,7 +289,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE category_id = ? 
              ORDER BY title"
@@ -204,7 +307,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE status = ? 
              ORDER BY title"
@@ -221,7 +325,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE next_fetch_at <= ? OR next_fetch_at IS NULL"
         )?;
@@ -239,7 +344,8 @@ impl FeedRepository for SqliteFeedRepository {
         let search_term = format!("%{}%", query);
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE title LIKE ? OR url LIKE ? 
              ORDER BY title"
@@ -256,7 +362,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              WHERE created_at BETWEEN ? AND ? 
              ORDER BY created_at DESC"
@@ -273,7 +380,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT id, category_id, title, url, status, error_message, icon_url, site_url,
-                    last_fetched_at, next_fetch_at, created_at, updated_at 
+                    last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
              FROM feeds 
              ORDER BY updated_at DESC 
              LIMIT ?"
@@ -290,7 +398,8 @@ impl FeedRepository for SqliteFeedRepository {
         let conn = self.connection_pool.get()?;
         let mut stmt = conn.prepare(
             "SELECT f.id, f.category_id, f.title, f.url, f.status, f.error_message, f.icon_url, f.site_url,
-                    f.last_fetched_at, f.next_fetch_at, f.created_at, f.updated_at 
+                    f.last_fetched_at, f.next_fetch_at, f.created_at, f.updated_at, f.etag, f.last_modified, f.keep_last_articles, f.auto_download,
+                    f.description, f.language, f.author, f.generator, f.cache_images, f.fetch_full_text
              FROM feeds f
              LEFT JOIN (
                 SELECT feed_id, COUNT(*) as article_count
@@ -313,8 +422,9 @@ impl FeedRepository for SqliteFeedRepository {
         conn.execute(
             "INSERT INTO feeds (
                 id, category_id, title, url, status, error_message, icon_url, site_url,
-                last_fetched_at, next_fetch_at, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                last_fetched_at, next_fetch_at, created_at, updated_at, etag, last_modified, keep_last_articles, auto_download,
+                    description, language, author, generator, cache_images, fetch_full_text
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             rusqlite::params![
                 feed.id.to_string(),
                 feed.category_id.as_ref().map(|id| id.to_string()),
@@ -328,6 +438,16 @@ impl FeedRepository for SqliteFeedRepository {
                 feed.next_fetch_at,
                 feed.created_at,
                 feed.updated_at,
+                feed.etag,
+                feed.last_modified,
+                feed.keep_last_articles,
+                feed.auto_download.to_string(),
+                feed.description,
+                feed.language,
+                feed.author,
+                feed.generator,
+                feed.cache_images,
+                feed.fetch_full_text,
             ],
         )?;
         Ok(())
@@ -346,6 +466,16 @@ impl FeedRepository for SqliteFeedRepository {
                 site_url = ?,
                 last_fetched_at = ?,
                 next_fetch_at = ?,
+                etag = ?,
+                last_modified = ?,
+                keep_last_articles = ?,
+                auto_download = ?,
+                description = ?,
+                language = ?,
+                author = ?,
+                generator = ?,
+                cache_images = ?,
+                fetch_full_text = ?,
                 updated_at = ?
             WHERE id = ?",
             rusqlite::params![
@@ -358,6 +488,16 @@ impl FeedRepository for SqliteFeedRepository {
                 feed.site_url.as_ref().map(|u| u.to_string()),
                 feed.last_fetched_at,
                 feed.next_fetch_at,
+                feed.etag,
+                feed.last_modified,
+                feed.keep_last_articles,
+                feed.auto_download.to_string(),
+                feed.description,
+                feed.language,
+                feed.author,
+                feed.generator,
+                feed.cache_images,
+                feed.fetch_full_text,
                 feed.updated_at,
                 feed.id.to_string(),
             ],
@@ -370,4 +510,55 @@ impl FeedRepository for SqliteFeedRepository {
         conn.execute("DELETE FROM feeds WHERE id = ?", [id.to_string()])?;
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn get_article_counts(&self) -> Result<HashMap<FeedId, ArticleCounts>> {
+        self.query_article_counts(None)
+    }
+
+    async fn get_article_counts_for_feeds(&self, feed_ids: &[FeedId]) -> Result<HashMap<FeedId, ArticleCounts>> {
+        if feed_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        self.query_article_counts(Some(feed_ids))
+    }
+
+    async fn save_feed_icon(&self, icon: &FeedIcon) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO feed_icons (feed_id, url, data, mime_type, fetched_at)
+             VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![
+                icon.feed_id.to_string(),
+                icon.url.as_ref().map(|u| u.to_string()),
+                icon.data,
+                icon.mime_type,
+                icon.fetched_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn get_feed_icon(&self, feed_id: &FeedId) -> Result<Option<FeedIcon>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT feed_id, url, data, mime_type, fetched_at FROM feed_icons WHERE feed_id = ?"
+        )?;
+
+        let mut rows = stmt.query([feed_id.to_string()])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(Self::map_icon_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_feed_icons(&self) -> Result<Vec<FeedIcon>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT feed_id, url, data, mime_type, fetched_at FROM feed_icons WHERE length(data) > 0"
+        )?;
+
+        let icons = stmt.query_map([], Self::map_icon_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(icons)
+    }
+}
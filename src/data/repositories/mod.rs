@@ -1,13 +1,21 @@
 // Make each repository module public to enable access to the implementations
 // This follows the Open/Closed Principle by making these modules extensible without modification
 pub mod article_repository;
+pub mod asset_repository;
 pub mod category_repository;
+pub mod enclosure_repository;
 pub mod feed_repository;
+pub mod settings_repository;
+pub mod smart_folder_repository;
 pub mod tag_repository;
 
 // Re-export the concrete implementations to provide a cleaner public API
 // This follows the Interface Segregation Principle by exposing only what clients need
 pub use article_repository::SqliteArticleRepository;
+pub use asset_repository::SqliteAssetRepository;
 pub use category_repository::SqliteCategoryRepository;
+pub use enclosure_repository::SqliteEnclosureRepository;
 pub use feed_repository::SqliteFeedRepository;
+pub use settings_repository::SqliteSettingsRepository;
+pub use smart_folder_repository::SqliteSmartFolderRepository;
 pub use tag_repository::SqliteTagRepository;
\ No newline at end of file
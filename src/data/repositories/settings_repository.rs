@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::models::settings::Settings;
+use crate::base::repository::SettingsRepository;
+use crate::data::database::ConnectionPool;
+
+const UPSERT_SETTING: &str =
+    "INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+     ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at";
+
+pub struct SqliteSettingsRepository {
+    connection_pool: Arc<ConnectionPool>,
+}
+
+impl SqliteSettingsRepository {
+    pub fn new(connection_pool: Arc<ConnectionPool>) -> Self {
+        Self { connection_pool }
+    }
+}
+
+#[async_trait]
+impl SettingsRepository for SqliteSettingsRepository {
+    async fn get_setting(&self, key: &str) -> Result<Option<String>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+
+        let mut rows = stmt.query([key])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(UPSERT_SETTING, rusqlite::params![key, value, Utc::now()])?;
+        Ok(())
+    }
+
+    async fn get_all_settings(&self) -> Result<HashMap<String, String>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+
+        let settings = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<HashMap<String, String>>>()?;
+        Ok(settings)
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> Result<()> {
+        let mut conn = self.connection_pool.get()?;
+        let tx = conn.transaction()?;
+        let now = Utc::now();
+        for (key, value) in settings.to_entries() {
+            tx.execute(UPSERT_SETTING, rusqlite::params![key, value, now])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
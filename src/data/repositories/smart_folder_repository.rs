@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::models::smart_folder::{SmartFolder, SmartFolderId};
+use crate::base::repository::SmartFolderRepository;
+use crate::data::database::ConnectionPool;
+
+pub struct SqliteSmartFolderRepository {
+    connection_pool: Arc<ConnectionPool>,
+}
+
+impl SqliteSmartFolderRepository {
+    pub fn new(connection_pool: Arc<ConnectionPool>) -> Self {
+        Self { connection_pool }
+    }
+
+    fn map_row(&self, row: &rusqlite::Row) -> Result<SmartFolder> {
+        Ok(SmartFolder {
+            id: row.get::<_, String>(0)?.into(),
+            name: row.get(1)?,
+            query: row.get(2)?,
+            created_at: row.get(3)?,
+            updated_at: row.get(4)?,
+        })
+    }
+}
+
+#[async_trait]
+impl SmartFolderRepository for SqliteSmartFolderRepository {
+    async fn save_smart_folder(&self, folder: &SmartFolder) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(
+            "INSERT INTO smart_folders (id, name, query, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?)",
+            rusqlite::params![
+                folder.id.to_string(),
+                folder.name,
+                folder.query,
+                folder.created_at,
+                folder.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn get_smart_folder(&self, id: &SmartFolderId) -> Result<Option<SmartFolder>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, query, created_at, updated_at
+             FROM smart_folders
+             WHERE id = ?"
+        )?;
+
+        let mut rows = stmt.query([id.to_string()])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(self.map_row(row)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn get_all_smart_folders(&self) -> Result<Vec<SmartFolder>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, query, created_at, updated_at
+             FROM smart_folders
+             ORDER BY name COLLATE NOCASE"
+        )?;
+
+        let folders = stmt.query_map([], |row| Ok(self.map_row(row)))?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(folders)
+    }
+
+    async fn update_smart_folder(&self, folder: &SmartFolder) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute(
+            "UPDATE smart_folders SET name = ?, query = ?, updated_at = ? WHERE id = ?",
+            rusqlite::params![
+                folder.name,
+                folder.query,
+                folder.updated_at,
+                folder.id.to_string(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    async fn delete_smart_folder(&self, id: &SmartFolderId) -> Result<()> {
+        let conn = self.connection_pool.get()?;
+        conn.execute("DELETE FROM smart_folders WHERE id = ?", [id.to_string()])?;
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use rusqlite::Connection;
 use anyhow::Result;
@@ -5,6 +6,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
 use crate::models::article::ArticleId;
+use crate::models::article_counts::ArticleCounts;
 use crate::models::tag::{Tag, TagId};
 use crate::base::repository::TagRepository;
 use crate::data::database::ConnectionPool;
@@ -222,4 +224,23 @@ impl TagRepository for SqliteTagRepository {
             .collect::<Result<Vec<_>, _>>()?;
         Ok(tags)
     }
-}
\ No newline at end of file
+
+    async fn get_article_counts(&self) -> Result<HashMap<String, ArticleCounts>> {
+        let conn = self.connection_pool.get()?;
+        let mut stmt = conn.prepare(
+            "SELECT t.name, COALESCE(SUM(a.read_status = 'unread'), 0), COUNT(a.id)
+             FROM tags t
+             LEFT JOIN article_tags at ON at.tag_id = t.id
+             LEFT JOIN articles a ON a.id = at.article_id
+             GROUP BY t.id"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ArticleCounts::new(row.get::<_, i64>(1)? as usize, row.get::<_, i64>(2)? as usize),
+            ))
+        })?;
+        Ok(rows.collect::<Result<HashMap<_, _>, _>>()?)
+    }
+}
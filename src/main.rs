@@ -72,6 +72,7 @@ async fn init_async_services(tx: mpsc::Sender<()>) -> Result<()> {
     let feed_repository = database.get_feed_repository();
     let category_repository = database.get_category_repository();
     let tag_repository = database.get_tag_repository();
+    let enclosure_repository = database.get_enclosure_repository();
     
     // Initialize services - Fix the argument order to match the service constructor signature
     // Following Liskov Substitution Principle by ensuring proper contract adherence
@@ -80,6 +81,7 @@ async fn init_async_services(tx: mpsc::Sender<()>) -> Result<()> {
         feed_repository.clone(),
         category_repository.clone(),
         tag_repository.clone(),
+        enclosure_repository,
     ));
     let webview_service = Arc::new(WebViewService::new());
     let sync_service = Arc::new(SyncService::new(rss_service.clone()));
@@ -112,11 +114,7 @@ async fn init_async_services(tx: mpsc::Sender<()>) -> Result<()> {
         }
     }
     
-    // Fetch feeds
-    info!("Syncing feeds...");
-    if let Err(e) = rss_service.sync_all_feeds().await {
-        warn!("Failed to sync all feeds: {}", e);
-    }
+    // Feeds are fetched by the UI's background scheduler once the window is up
     
     // Signal that initialization is complete
     let _ = tx.send(()).await;
@@ -135,6 +133,10 @@ fn init_ui(rx: mpsc::Receiver<()>) -> Result<MainView> {
     let category_repository = database.get_category_repository();
     let feed_repository = database.get_feed_repository();
     let tag_repository = database.get_tag_repository();
+    let settings_repository = database.get_settings_repository();
+    let smart_folder_repository = database.get_smart_folder_repository();
+    let enclosure_repository = database.get_enclosure_repository();
+    let asset_repository = database.get_asset_repository();
     
     // Create an AppContext instance with the repositories
     // The new constructor only requires repositories
@@ -143,7 +145,11 @@ fn init_ui(rx: mpsc::Receiver<()>) -> Result<MainView> {
         category_repository,
         feed_repository,
         tag_repository,
-    );
+        settings_repository,
+        smart_folder_repository,
+        enclosure_repository,
+        asset_repository,
+    )?;
     
     // Create the main view
     let main_view = MainView::new(app_context);
@@ -167,7 +173,9 @@ fn run_ui(main_view: MainView) -> Result<()> {
         // The window title is now set in the viewport builder, so we pass an empty string here
         "",
         options,
-        Box::new(|_cc| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Box::new(|cc| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            // Decoders for the cached feed favicons
+            egui_extras::install_image_loaders(&cc.egui_ctx);
             // Wrap the main_view in Ok to satisfy the Result return type
             Ok(Box::new(main_view))
         }),
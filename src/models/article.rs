@@ -6,6 +6,12 @@ use rusqlite::types::{FromSql, ToSql, ToSqlOutput, ValueRef, FromSqlResult};
 
 use crate::models::feed::FeedId;
 
+/// Version of the processing entry HTML goes through before it is stored
+///
+/// Rows from an older version hold the HTML as that version left it; 0 is
+/// the feed's HTML as sent, before links were rewritten.
+pub const CONTENT_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ArticleId(pub String);
 
@@ -45,11 +51,19 @@ impl ReadStatus {
 pub struct Article {
     pub id: ArticleId,
     pub feed_id: FeedId,
+    /// Stable identity of the entry within its feed: the feed's own GUID/id
+    /// when it has one, otherwise a fingerprint derived from title and date
+    pub guid: Option<String>,
     pub title: String,
     pub url: Url,
     pub author: Option<String>,
     pub content: Option<String>,
     pub summary: Option<String>,
+    /// Main content extracted from the article's web page, for feeds that
+    /// only publish a summary or teaser
+    pub full_content: Option<String>,
+    /// The [`CONTENT_VERSION`] `content` and `summary` were processed with
+    pub content_version: u32,
     pub published_at: DateTime<Utc>,
     pub read_status: ReadStatus,
     pub is_favorited: bool,
@@ -63,11 +77,14 @@ impl Article {
         Self {
             id: ArticleId::new(),
             feed_id,
+            guid: None,
             title,
             url,
             author: None,
             content: None,
             summary: None,
+            full_content: None,
+            content_version: CONTENT_VERSION,
             published_at: now,
             read_status: ReadStatus::Unread,
             is_favorited: false,
@@ -76,6 +93,11 @@ impl Article {
         }
     }
 
+    pub fn with_guid(mut self, guid: String) -> Self {
+        self.guid = Some(guid);
+        self
+    }
+
     pub fn with_author(mut self, author: String) -> Self {
         self.author = Some(author);
         self
@@ -91,6 +113,11 @@ impl Article {
         self
     }
 
+    pub fn with_full_content(mut self, full_content: String) -> Self {
+        self.full_content = Some(full_content);
+        self
+    }
+
     pub fn with_published_at(mut self, published_at: DateTime<Utc>) -> Self {
         self.published_at = published_at;
         self
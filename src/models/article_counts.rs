@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use crate::models::category::CategoryId;
+use crate::models::feed::FeedId;
+
+/// Number of unread and total articles in a feed, category or tag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArticleCounts {
+    pub unread: usize,
+    pub total: usize,
+}
+
+impl ArticleCounts {
+    pub fn new(unread: usize, total: usize) -> Self {
+        Self { unread, total }
+    }
+
+    fn shift(&mut self, unread: i64, total: i64) {
+        self.unread = (self.unread as i64 + unread).max(0) as usize;
+        self.total = (self.total as i64 + total).max(0) as usize;
+    }
+}
+
+/// Article counts for everything the sidebar lists
+///
+/// Category counts include the feeds of all subcategories. Updating a feed
+/// with [`update_feed`](Self::update_feed) moves the difference up through
+/// its categories, so a sync or read-state change only needs to recount the
+/// feeds it touched.
+#[derive(Debug, Clone, Default)]
+pub struct UnreadCounts {
+    pub all: ArticleCounts,
+    pub feeds: HashMap<FeedId, ArticleCounts>,
+    pub categories: HashMap<CategoryId, ArticleCounts>,
+    pub tags: HashMap<String, ArticleCounts>,
+    feed_categories: HashMap<FeedId, CategoryId>,
+    category_parents: HashMap<CategoryId, CategoryId>,
+}
+
+impl UnreadCounts {
+    /// `feed_categories` and `category_parents` describe the hierarchy the
+    /// category counts were rolled up through
+    pub fn new(
+        feeds: HashMap<FeedId, ArticleCounts>,
+        categories: HashMap<CategoryId, ArticleCounts>,
+        tags: HashMap<String, ArticleCounts>,
+        feed_categories: HashMap<FeedId, CategoryId>,
+        category_parents: HashMap<CategoryId, CategoryId>,
+    ) -> Self {
+        let all = feeds.values().fold(ArticleCounts::default(), |sum, counts| {
+            ArticleCounts::new(sum.unread + counts.unread, sum.total + counts.total)
+        });
+        Self { all, feeds, categories, tags, feed_categories, category_parents }
+    }
+
+    pub fn feed(&self, id: &FeedId) -> ArticleCounts {
+        self.feeds.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn category(&self, id: &CategoryId) -> ArticleCounts {
+        self.categories.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn tag(&self, name: &str) -> ArticleCounts {
+        self.tags.get(name).copied().unwrap_or_default()
+    }
+
+    /// Replaces the counts of a feed and adjusts its categories and the overall total
+    pub fn update_feed(&mut self, id: &FeedId, counts: ArticleCounts) {
+        let previous = self.feeds.insert(id.clone(), counts).unwrap_or_default();
+        let unread = counts.unread as i64 - previous.unread as i64;
+        let total = counts.total as i64 - previous.total as i64;
+        if unread == 0 && total == 0 {
+            return;
+        }
+
+        self.all.shift(unread, total);
+        let mut category = self.feed_categories.get(id).cloned();
+        // Bounded by the number of categories in case the hierarchy has a cycle
+        for _ in 0..=self.category_parents.len() {
+            let id = match category {
+                Some(id) => id,
+                None => break,
+            };
+            self.categories.entry(id.clone()).or_default().shift(unread, total);
+            category = self.category_parents.get(&id).cloned();
+        }
+    }
+
+    /// Replaces the counts of all tags
+    pub fn update_tags(&mut self, tags: HashMap<String, ArticleCounts>) {
+        self.tags = tags;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_feed_rolls_up_through_categories() {
+        let (tech, rust, news) = (CategoryId::new(), CategoryId::new(), CategoryId::new());
+        let (blog, forum, wire) = (FeedId::new(), FeedId::new(), FeedId::new());
+
+        let mut counts = UnreadCounts::new(
+            HashMap::from([
+                (blog.clone(), ArticleCounts::new(2, 5)),
+                (forum.clone(), ArticleCounts::new(1, 1)),
+                (wire.clone(), ArticleCounts::new(4, 4)),
+            ]),
+            HashMap::from([
+                (tech.clone(), ArticleCounts::new(3, 6)),
+                (rust.clone(), ArticleCounts::new(2, 5)),
+                (news.clone(), ArticleCounts::new(4, 4)),
+            ]),
+            HashMap::new(),
+            HashMap::from([
+                (blog.clone(), rust.clone()),
+                (forum.clone(), tech.clone()),
+                (wire.clone(), news.clone()),
+            ]),
+            HashMap::from([(rust.clone(), tech.clone())]),
+        );
+        assert_eq!(counts.all, ArticleCounts::new(7, 10));
+
+        // Two new unread articles in a nested feed, then everything in it read
+        counts.update_feed(&blog, ArticleCounts::new(4, 7));
+        assert_eq!(counts.category(&rust), ArticleCounts::new(4, 7));
+        assert_eq!(counts.category(&tech), ArticleCounts::new(5, 8));
+        counts.update_feed(&blog, ArticleCounts::new(0, 7));
+        assert_eq!(counts.category(&rust), ArticleCounts::new(0, 7));
+        assert_eq!(counts.category(&tech), ArticleCounts::new(1, 8));
+
+        assert_eq!(counts.category(&news), ArticleCounts::new(4, 4));
+        assert_eq!(counts.all, ArticleCounts::new(5, 12));
+
+        // Feeds without a category only change the overall total
+        let loose = FeedId::new();
+        counts.update_feed(&loose, ArticleCounts::new(1, 1));
+        assert_eq!(counts.feed(&loose), ArticleCounts::new(1, 1));
+        assert_eq!(counts.all, ArticleCounts::new(6, 13));
+        assert_eq!(counts.category(&tech), ArticleCounts::new(1, 8));
+    }
+}
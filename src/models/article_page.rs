@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::models::article::{ArticleId, ReadStatus};
+use crate::models::article_query::ArticleQuery;
+use crate::models::category::CategoryId;
+use crate::models::feed::FeedId;
+use crate::models::search::SearchQuery;
+
+/// An article without its content, for listing many articles at once
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArticleSummary {
+    pub id: ArticleId,
+    pub feed_id: FeedId,
+    pub title: String,
+    pub url: Url,
+    pub author: Option<String>,
+    pub summary: Option<String>,
+    pub published_at: DateTime<Utc>,
+    pub read_status: ReadStatus,
+    pub is_favorited: bool,
+}
+
+/// Which articles a page is taken from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArticleFilter {
+    All,
+    Unread,
+    Favorites,
+    Feed(FeedId),
+    /// Articles of feeds in the category or any category below it
+    Category(CategoryId),
+    Tag(String),
+    Query(ArticleQuery),
+    /// The results a full-text search lists: its best `limit` matches, after its filters
+    Search(SearchQuery),
+}
+
+/// Position after the last article of a page, for requesting the next one
+///
+/// Pages are ordered by `(published_at, id)`, preceded by the unread flag
+/// when unread articles come first, so a cursor stays valid while new
+/// articles arrive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArticleCursor {
+    pub unread: bool,
+    pub published_at: DateTime<Utc>,
+    pub id: ArticleId,
+}
+
+impl ArticleCursor {
+    pub fn after(article: &ArticleSummary) -> Self {
+        Self {
+            unread: article.read_status == ReadStatus::Unread,
+            published_at: article.published_at,
+            id: article.id.clone(),
+        }
+    }
+}
+
+/// One page of articles, with the cursor for the next page if there is one
+#[derive(Debug, Clone, Default)]
+pub struct ArticlePage {
+    pub articles: Vec<ArticleSummary>,
+    pub next_cursor: Option<ArticleCursor>,
+}
@@ -0,0 +1,274 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// A parsed article filter, e.g. `feed:"Hacker News" tag:rust is:unread -title:hiring`
+///
+/// Terms separated by spaces must all match; `OR` between terms matches
+/// either side, `-` negates a term and parentheses group. Terms are either
+/// `field:value` filters or plain words and `"phrases"` that are searched for
+/// in the article text (a trailing `*` matches a prefix).
+///
+/// | Field | Matches |
+/// |---|---|
+/// | `feed:` | feed title (case-insensitive) or id |
+/// | `category:` | name of the feed's category or of a category it is nested in |
+/// | `tag:` | a tag on the article |
+/// | `is:` | `unread`, `read` or `starred` |
+/// | `after:` / `before:` | publication date, `YYYY-MM-DD` |
+/// | `title:` / `author:` | substring of the title or author |
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArticleQuery {
+    pub root: QueryExpr,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueryExpr {
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+    Not(Box<QueryExpr>),
+    Term(QueryTerm),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueryTerm {
+    /// Full-text term in search syntax: a word, `"a phrase"`, or either with a trailing `*`
+    Text(String),
+    Feed(String),
+    Category(String),
+    Tag(String),
+    Is(ArticleState),
+    /// Published on or after the start of this day (UTC)
+    After(NaiveDate),
+    /// Published before the start of this day (UTC)
+    Before(NaiveDate),
+    Title(String),
+    Author(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArticleState {
+    Unread,
+    Read,
+    Starred,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Or,
+    Not,
+    Field(String, String),
+    Text(String),
+}
+
+impl ArticleQuery {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            bail!("Query is empty");
+        }
+
+        let mut parser = Parser { tokens, position: 0 };
+        let root = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(Self { root }),
+            Some(Token::RParen) => Err(anyhow!("Unmatched ')'")),
+            Some(token) => Err(anyhow!("Unexpected {:?}", token)),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '-' => {
+                chars.next();
+                if chars.peek().is_some_and(|c| !c.is_whitespace()) {
+                    tokens.push(Token::Not);
+                }
+            }
+            '"' => {
+                chars.next();
+                let phrase = read_quoted(&mut chars)?;
+                let prefix = chars.next_if_eq(&'*').is_some();
+                tokens.push(Token::Text(format!("\"{}\"{}", phrase, if prefix { "*" } else { "" })));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    if c == '"' && word.ends_with(':') {
+                        chars.next();
+                        word.push_str(&read_quoted(&mut chars)?);
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+
+                match word.split_once(':') {
+                    Some((field, value)) if !field.is_empty() && field.chars().all(|c| c.is_ascii_alphabetic()) => {
+                        tokens.push(Token::Field(field.to_ascii_lowercase(), value.to_string()));
+                    }
+                    _ if word == "OR" => tokens.push(Token::Or),
+                    _ => tokens.push(Token::Text(word)),
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String> {
+    let mut value = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            return Ok(value);
+        }
+        value.push(c);
+    }
+    Err(anyhow!("Unterminated quote"))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr> {
+        let mut branches = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            branches.push(self.parse_and()?);
+        }
+        Ok(if branches.len() == 1 { branches.remove(0) } else { QueryExpr::Or(branches) })
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr> {
+        let mut terms = Vec::new();
+        while !matches!(self.peek(), None | Some(Token::RParen) | Some(Token::Or)) {
+            terms.push(self.parse_unary()?);
+        }
+        match terms.len() {
+            0 => Err(anyhow!("Expected a search term")),
+            1 => Ok(terms.remove(0)),
+            _ => Ok(QueryExpr::And(terms)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr> {
+        match self.next() {
+            Some(Token::Not) => Ok(QueryExpr::Not(Box::new(self.parse_unary()?))),
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err(anyhow!("Missing ')'")),
+                }
+            }
+            Some(Token::Field(field, value)) => Ok(QueryExpr::Term(parse_field(&field, value)?)),
+            Some(Token::Text(text)) => Ok(QueryExpr::Term(QueryTerm::Text(text))),
+            Some(token) => Err(anyhow!("Unexpected {:?}", token)),
+            None => Err(anyhow!("Expected a search term")),
+        }
+    }
+}
+
+fn parse_field(field: &str, value: String) -> Result<QueryTerm> {
+    if value.is_empty() {
+        bail!("Missing value for '{}:'", field);
+    }
+
+    let date = |value: &str| NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date '{}', expected YYYY-MM-DD", value));
+
+    Ok(match field {
+        "feed" => QueryTerm::Feed(value),
+        "category" => QueryTerm::Category(value),
+        "tag" => QueryTerm::Tag(value),
+        "is" => QueryTerm::Is(match value.to_ascii_lowercase().as_str() {
+            "unread" => ArticleState::Unread,
+            "read" => ArticleState::Read,
+            "starred" | "favorite" | "favorited" => ArticleState::Starred,
+            _ => bail!("Unknown state 'is:{}'", value),
+        }),
+        "after" => QueryTerm::After(date(&value)?),
+        "before" => QueryTerm::Before(date(&value)?),
+        "title" => QueryTerm::Title(value),
+        "author" => QueryTerm::Author(value),
+        _ => bail!("Unknown field '{}:'", field),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term: QueryTerm) -> QueryExpr {
+        QueryExpr::Term(term)
+    }
+
+    #[test]
+    fn test_parse_fields_and_negation() -> Result<()> {
+        let query = ArticleQuery::parse(r#"feed:"Hacker News" tag:rust is:unread after:2026-01-01 -title:hiring"#)?;
+        assert_eq!(query.root, QueryExpr::And(vec![
+            term(QueryTerm::Feed("Hacker News".to_string())),
+            term(QueryTerm::Tag("rust".to_string())),
+            term(QueryTerm::Is(ArticleState::Unread)),
+            term(QueryTerm::After(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap())),
+            QueryExpr::Not(Box::new(term(QueryTerm::Title("hiring".to_string())))),
+        ]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_text_or_and_groups() -> Result<()> {
+        let query = ArticleQuery::parse(r#"(tag:rust OR tag:go) "async io" tok*"#)?;
+        assert_eq!(query.root, QueryExpr::And(vec![
+            QueryExpr::Or(vec![
+                term(QueryTerm::Tag("rust".to_string())),
+                term(QueryTerm::Tag("go".to_string())),
+            ]),
+            term(QueryTerm::Text("\"async io\"".to_string())),
+            term(QueryTerm::Text("tok*".to_string())),
+        ]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for input in ["", "colour:red", "is:maybe", "after:yesterday", "(tag:rust", "tag:rust)", "feed:\"open", "tag:", "OR tag:rust"] {
+            assert!(ArticleQuery::parse(input).is_err(), "{:?} should not parse", input);
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+
+/// An image from an article, stored for reading offline
+///
+/// Assets are content-addressed: the key is the SHA-256 of the bytes, so an
+/// image served under several URLs is stored once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedAsset {
+    /// Lowercase hex SHA-256 of `data`
+    pub hash: String,
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    pub created_at: DateTime<Utc>,
+    /// When an article showing the asset was last opened; eviction drops the oldest first
+    pub last_accessed_at: DateTime<Utc>,
+}
+
+impl CachedAsset {
+    pub fn new(data: Vec<u8>, mime_type: String) -> Self {
+        let now = Utc::now();
+        Self {
+            hash: format!("{:x}", Sha256::digest(&data)),
+            data,
+            mime_type,
+            created_at: now,
+            last_accessed_at: now,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
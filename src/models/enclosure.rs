@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use uuid::Uuid;
+use rusqlite::types::{FromSql, ToSql, ToSqlOutput, ValueRef, FromSqlResult};
+
+use crate::models::article::ArticleId;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnclosureId(pub String);
+
+impl std::fmt::Display for EnclosureId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DownloadStatus {
+    NotDownloaded,
+    Downloaded,
+    /// The last download failed; a partial file may be left to resume from
+    Failed,
+}
+
+impl DownloadStatus {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::NotDownloaded => "none".to_string(),
+            Self::Downloaded => "downloaded".to_string(),
+            Self::Failed => "failed".to_string(),
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "none" => Some(Self::NotDownloaded),
+            "downloaded" => Some(Self::Downloaded),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A media file attached to an article: a podcast episode, a video, an image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub id: EnclosureId,
+    pub article_id: ArticleId,
+    pub url: Url,
+    pub mime_type: Option<String>,
+    /// Size in bytes as announced by the feed
+    pub length: Option<u64>,
+    pub duration_secs: Option<u64>,
+    pub title: Option<String>,
+    pub download_status: DownloadStatus,
+    /// Where the downloaded file is stored
+    pub local_path: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Enclosure {
+    pub fn new(article_id: ArticleId, url: Url) -> Self {
+        let now = Utc::now();
+        Self {
+            id: EnclosureId::new(),
+            article_id,
+            url,
+            mime_type: None,
+            length: None,
+            duration_secs: None,
+            title: None,
+            download_status: DownloadStatus::NotDownloaded,
+            local_path: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn with_mime_type(mut self, mime_type: String) -> Self {
+        self.mime_type = Some(mime_type);
+        self
+    }
+
+    pub fn with_length(mut self, length: u64) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn with_duration_secs(mut self, duration_secs: u64) -> Self {
+        self.duration_secs = Some(duration_secs);
+        self
+    }
+
+    pub fn with_title(mut self, title: String) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    pub fn is_audio(&self) -> bool {
+        self.mime_type.as_deref().is_some_and(|mime| mime.starts_with("audio/"))
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.mime_type.as_deref().is_some_and(|mime| mime.starts_with("video/"))
+    }
+
+    /// The name shown for the enclosure: its title, else the file name of its URL
+    pub fn display_name(&self) -> String {
+        self.title.clone()
+            .or_else(|| {
+                self.url.path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| self.url.to_string())
+    }
+
+    pub fn mark_downloaded(&mut self, local_path: String) {
+        self.download_status = DownloadStatus::Downloaded;
+        self.local_path = Some(local_path);
+        self.updated_at = Utc::now();
+    }
+
+    pub fn mark_failed(&mut self) {
+        self.download_status = DownloadStatus::Failed;
+        self.updated_at = Utc::now();
+    }
+
+    pub fn clear_download(&mut self) {
+        self.download_status = DownloadStatus::NotDownloaded;
+        self.local_path = None;
+        self.updated_at = Utc::now();
+    }
+}
+
+impl EnclosureId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl FromSql for EnclosureId {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value).map(EnclosureId)
+    }
+}
+
+impl ToSql for EnclosureId {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.0.clone()))
+    }
+}
+
+impl From<String> for EnclosureId {
+    fn from(s: String) -> Self {
+        EnclosureId(s)
+    }
+}
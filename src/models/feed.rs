@@ -44,6 +44,33 @@ impl FeedStatus {
     }
 }
 
+/// Which enclosures of a feed are downloaded without being asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoDownloadPolicy {
+    Off,
+    /// Keep the newest N enclosures downloaded
+    Latest(u32),
+    All,
+}
+
+impl AutoDownloadPolicy {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::Off => "off".to_string(),
+            Self::Latest(count) => format!("latest:{}", count),
+            Self::All => "all".to_string(),
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Self::Off),
+            "all" => Some(Self::All),
+            _ => s.strip_prefix("latest:")?.parse().ok().map(Self::Latest),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Feed {
     pub id: FeedId,
@@ -54,8 +81,24 @@ pub struct Feed {
     pub error_message: Option<String>,
     pub icon_url: Option<Url>,
     pub site_url: Option<Url>,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub author: Option<String>,
+    /// Software that produced the feed, as it names itself
+    pub generator: Option<String>,
     pub last_fetched_at: Option<DateTime<Utc>>,
     pub next_fetch_at: Option<DateTime<Utc>>,
+    /// `ETag` returned by the last successful fetch, sent back as `If-None-Match`
+    pub etag: Option<String>,
+    /// `Last-Modified` returned by the last successful fetch, sent back as `If-Modified-Since`
+    pub last_modified: Option<String>,
+    /// Keep only this many of the newest articles, instead of the global retention age
+    pub keep_last_articles: Option<u32>,
+    pub auto_download: AutoDownloadPolicy,
+    /// Keep copies of the images in new articles for reading offline
+    pub cache_images: bool,
+    /// Extract the full text of new articles from their web pages
+    pub fetch_full_text: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -72,8 +115,18 @@ impl Feed {
             error_message: None,
             icon_url: None,
             site_url: None,
+            description: None,
+            language: None,
+            author: None,
+            generator: None,
             last_fetched_at: None,
             next_fetch_at: None,
+            etag: None,
+            last_modified: None,
+            keep_last_articles: None,
+            auto_download: AutoDownloadPolicy::Off,
+            cache_images: false,
+            fetch_full_text: false,
             created_at: now,
             updated_at: now,
         }
@@ -94,18 +147,74 @@ impl Feed {
         self
     }
     
+    pub fn with_keep_last_articles(mut self, keep_last: u32) -> Self {
+        self.keep_last_articles = Some(keep_last);
+        self
+    }
+    
+    pub fn with_auto_download(mut self, policy: AutoDownloadPolicy) -> Self {
+        self.auto_download = policy;
+        self
+    }
+    
+    pub fn with_cache_images(mut self, cache_images: bool) -> Self {
+        self.cache_images = cache_images;
+        self
+    }
+    
+    pub fn with_fetch_full_text(mut self, fetch_full_text: bool) -> Self {
+        self.fetch_full_text = fetch_full_text;
+        self
+    }
+    
     pub fn with_description(mut self, description: String) -> Self {
-        // Just store the string value, even though we don't have a field for it yet
-        // This maintains compatibility with the code that calls this method
+        self.description = Some(description);
         self
     }
     
     pub fn with_language(mut self, language: String) -> Self {
-        // Just store the string value, even though we don't have a field for it yet
-        // This maintains compatibility with the code that calls this method
+        self.language = Some(language);
         self
     }
 
+    pub fn with_author(mut self, author: String) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    pub fn with_generator(mut self, generator: String) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    /// Takes the metadata a freshly fetched copy of the feed declares
+    ///
+    /// The title is left alone, since the user may have renamed the feed;
+    /// anything the document no longer declares is kept.
+    pub fn update_metadata(&mut self, fetched: &Feed) {
+        let mut changed = false;
+        for (field, value) in [
+            (&mut self.description, &fetched.description),
+            (&mut self.language, &fetched.language),
+            (&mut self.author, &fetched.author),
+            (&mut self.generator, &fetched.generator),
+        ] {
+            if value.is_some() && field != value {
+                field.clone_from(value);
+                changed = true;
+            }
+        }
+        for (field, value) in [(&mut self.icon_url, &fetched.icon_url), (&mut self.site_url, &fetched.site_url)] {
+            if value.is_some() && field != value {
+                field.clone_from(value);
+                changed = true;
+            }
+        }
+        if changed {
+            self.updated_at = Utc::now();
+        }
+    }
+
     pub fn update_status(&mut self, status: FeedStatus) {
         self.status = status;
         self.updated_at = Utc::now();
@@ -121,6 +230,13 @@ impl Feed {
         self.next_fetch_at = Some(next_fetch);
         self.updated_at = Utc::now();
     }
+
+    /// Stores the HTTP cache validators from a fetch that returned a new document
+    pub fn update_cache_validators(&mut self, etag: Option<String>, last_modified: Option<String>) {
+        self.etag = etag;
+        self.last_modified = last_modified;
+        self.updated_at = Utc::now();
+    }
 }
 
 impl FeedId {
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Duration, Utc};
+use url::Url;
+
+use crate::models::feed::FeedId;
+
+/// How long a resolved icon, or the finding that there is none, is kept before looking again
+pub const ICON_REFRESH_INTERVAL_DAYS: i64 = 7;
+
+/// A feed's favicon, cached so it is fetched once rather than on every start
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedIcon {
+    pub feed_id: FeedId,
+    /// Where the image was found
+    pub url: Option<Url>,
+    /// The image file; empty when no icon could be found
+    pub data: Vec<u8>,
+    pub mime_type: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl FeedIcon {
+    pub fn new(feed_id: FeedId, url: Url, data: Vec<u8>, mime_type: String) -> Self {
+        Self {
+            feed_id,
+            url: Some(url),
+            data,
+            mime_type: Some(mime_type),
+            fetched_at: Utc::now(),
+        }
+    }
+
+    /// Records that none of the places a feed icon could be had one
+    pub fn not_found(feed_id: FeedId) -> Self {
+        Self {
+            feed_id,
+            url: None,
+            data: Vec::new(),
+            mime_type: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Whether the icon should be looked up again
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now - self.fetched_at >= Duration::days(ICON_REFRESH_INTERVAL_DAYS)
+    }
+}
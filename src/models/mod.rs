@@ -1,9 +1,27 @@
 pub mod category;
 pub mod feed;
+pub mod feed_icon;
 pub mod article;
 pub mod tag;
+pub mod settings;
+pub mod search;
+pub mod article_query;
+pub mod article_page;
+pub mod smart_folder;
+pub mod article_counts;
+pub mod enclosure;
+pub mod cached_asset;
 
 pub use category::{Category, CategoryId};
-pub use feed::{Feed, FeedId, FeedStatus};
+pub use feed::{AutoDownloadPolicy, Feed, FeedId, FeedStatus};
+pub use feed_icon::FeedIcon;
 pub use article::{Article, ArticleId, ReadStatus};
-pub use tag::{Tag, TagId};
\ No newline at end of file
+pub use tag::{Tag, TagId};
+pub use settings::{AssetCacheSettings, ReaderFont, ReaderSettings, Settings, Theme};
+pub use search::{SearchHit, SearchQuery};
+pub use article_query::ArticleQuery;
+pub use article_page::{ArticleCursor, ArticleFilter, ArticlePage, ArticleSummary};
+pub use smart_folder::{SmartFolder, SmartFolderId};
+pub use article_counts::{ArticleCounts, UnreadCounts};
+pub use enclosure::{DownloadStatus, Enclosure, EnclosureId};
+pub use cached_asset::CachedAsset;
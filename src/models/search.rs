@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::article::{Article, ReadStatus};
+use crate::models::category::CategoryId;
+use crate::models::feed::FeedId;
+
+/// Marks the start of a matched term in [`SearchHit`] highlights
+pub const HIGHLIGHT_START: char = '\u{2}';
+/// Marks the end of a matched term in [`SearchHit`] highlights
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// Default number of hits returned by a search
+pub const DEFAULT_SEARCH_LIMIT: usize = 100;
+
+/// A full-text search with optional filters
+///
+/// `text` matches every word it contains, in any of title, summary, content
+/// and author. Words match whole words only, ignoring case and accents, so
+/// `rust` finds "Rust" but not "Rustacean". `"quoted words"` match as a
+/// phrase and a trailing `*` matches any word starting with the given prefix,
+/// e.g. `"rust async" tok*`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub text: String,
+    pub feed_id: Option<FeedId>,
+    /// Category whose feeds are searched, including those of its subcategories
+    pub category_id: Option<CategoryId>,
+    /// Tag name the article must carry
+    pub tag: Option<String>,
+    pub read_status: Option<ReadStatus>,
+    /// Only articles published at or after this time
+    pub published_after: Option<DateTime<Utc>>,
+    /// Only articles published before this time
+    pub published_before: Option<DateTime<Utc>>,
+    pub limit: usize,
+}
+
+impl SearchQuery {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            feed_id: None,
+            category_id: None,
+            tag: None,
+            read_status: None,
+            published_after: None,
+            published_before: None,
+            limit: DEFAULT_SEARCH_LIMIT,
+        }
+    }
+
+    pub fn with_feed(mut self, feed_id: FeedId) -> Self {
+        self.feed_id = Some(feed_id);
+        self
+    }
+
+    pub fn with_category(mut self, category_id: CategoryId) -> Self {
+        self.category_id = Some(category_id);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn with_read_status(mut self, read_status: ReadStatus) -> Self {
+        self.read_status = Some(read_status);
+        self
+    }
+
+    pub fn with_date_range(mut self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Self {
+        self.published_after = after;
+        self.published_before = before;
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+/// An article matching a search, best matches first
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub article: Article,
+    /// BM25 score; lower is a better match
+    pub rank: f64,
+    /// Title with matched terms wrapped in [`HIGHLIGHT_START`]/[`HIGHLIGHT_END`]
+    pub title_highlight: String,
+    /// Short excerpt around the best match, marked up the same way
+    pub snippet: String,
+}
+
+/// Splits highlighted text into `(text, is_match)` segments for display
+pub fn highlight_segments(highlighted: &str) -> Vec<(String, bool)> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_match = false;
+
+    for c in highlighted.chars() {
+        if c == HIGHLIGHT_START || c == HIGHLIGHT_END {
+            if !current.is_empty() {
+                segments.push((std::mem::take(&mut current), in_match));
+            }
+            in_match = c == HIGHLIGHT_START;
+        } else {
+            current.push(c);
+        }
+    }
+    if !current.is_empty() {
+        segments.push((current, in_match));
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_segments() {
+        let highlighted = format!("Learning {}Rust{} the {}hard{} way", HIGHLIGHT_START, HIGHLIGHT_END, HIGHLIGHT_START, HIGHLIGHT_END);
+        assert_eq!(highlight_segments(&highlighted), vec![
+            ("Learning ".to_string(), false),
+            ("Rust".to_string(), true),
+            (" the ".to_string(), false),
+            ("hard".to_string(), true),
+            (" way".to_string(), false),
+        ]);
+        assert!(highlight_segments("").is_empty());
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fmt;
+use log::warn;
+
+use crate::models::feed::FeedId;
+use serde::{Deserialize, Serialize};
+
+/// Keys under which each setting is stored in the `settings` table
+pub mod keys {
+    pub const SYNC_INTERVAL_MINUTES: &str = "sync.interval_minutes";
+    pub const ARTICLE_RETENTION_DAYS: &str = "articles.retention_days";
+    pub const DEFAULT_SORT_ORDER: &str = "articles.default_sort_order";
+    pub const THEME: &str = "ui.theme";
+    pub const FETCH_TIMEOUT_SECS: &str = "fetch.timeout_secs";
+    pub const FETCH_MAX_BODY_BYTES: &str = "fetch.max_body_bytes";
+    pub const FETCH_USER_AGENT: &str = "fetch.user_agent";
+    pub const FETCH_PROXY_URL: &str = "fetch.proxy_url";
+    pub const FETCH_MAX_REDIRECTS: &str = "fetch.max_redirects";
+    pub const FETCH_MAX_CONCURRENT: &str = "fetch.max_concurrent";
+    pub const FETCH_MAX_PER_HOST: &str = "fetch.max_per_host";
+    pub const FETCH_FEED_HEADERS: &str = "fetch.feed_headers";
+    pub const DOWNLOAD_DIRECTORY: &str = "downloads.directory";
+    pub const ASSET_CACHE_MAX_TOTAL_BYTES: &str = "assets.max_total_bytes";
+    pub const ASSET_CACHE_MAX_ASSET_BYTES: &str = "assets.max_asset_bytes";
+    pub const READER_FONT: &str = "reader.font";
+    pub const READER_FONT_SIZE: &str = "reader.font_size";
+    pub const READER_LINE_HEIGHT_PERCENT: &str = "reader.line_height_percent";
+    pub const READER_COLUMN_WIDTH: &str = "reader.column_width";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    Sepia,
+}
+
+impl Theme {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::Dark => "dark".to_string(),
+            Self::Light => "light".to_string(),
+            Self::Sepia => "sepia".to_string(),
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "dark" => Some(Self::Dark),
+            "light" => Some(Self::Light),
+            "sepia" => Some(Self::Sepia),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArticleSortOrder {
+    NewestFirst,
+    OldestFirst,
+    Unread,
+}
+
+impl ArticleSortOrder {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::NewestFirst => "newest_first".to_string(),
+            Self::OldestFirst => "oldest_first".to_string(),
+            Self::Unread => "unread".to_string(),
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "newest_first" => Some(Self::NewestFirst),
+            "oldest_first" => Some(Self::OldestFirst),
+            "unread" => Some(Self::Unread),
+            _ => None,
+        }
+    }
+}
+
+/// HTTP settings for feed requests; an empty `user_agent` means the built-in one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FetchSettings {
+    pub timeout_secs: u64,
+    pub max_body_bytes: u64,
+    pub user_agent: Option<String>,
+    pub proxy_url: Option<String>,
+    pub max_redirects: u32,
+    pub max_concurrent: u32,
+    pub max_per_host: u32,
+    /// Extra request headers for individual feeds (auth tokens, API keys),
+    /// stored as one JSON object keyed by feed id
+    pub feed_headers: FeedHeaders,
+}
+
+impl Default for FetchSettings {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 30,
+            max_body_bytes: 10 * 1024 * 1024,
+            user_agent: None,
+            proxy_url: None,
+            max_redirects: 5,
+            max_concurrent: 8,
+            max_per_host: 2,
+            feed_headers: FeedHeaders::default(),
+        }
+    }
+}
+
+impl FetchSettings {
+    /// Replaces values no request could succeed with, a zero timeout or body
+    /// limit, by their defaults
+    pub fn validated(mut self) -> Self {
+        let defaults = Self::default();
+        if self.timeout_secs == 0 {
+            warn!("Ignoring a request timeout of 0 seconds");
+            self.timeout_secs = defaults.timeout_secs;
+        }
+        if self.max_body_bytes == 0 {
+            warn!("Ignoring a maximum feed size of 0 bytes");
+            self.max_body_bytes = defaults.max_body_bytes;
+        }
+        self
+    }
+}
+
+/// Extra request headers by feed
+///
+/// Their values are usually credentials, so `Debug` shows header names only
+/// and they can be logged safely.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FeedHeaders(HashMap<FeedId, Vec<(String, String)>>);
+
+impl FeedHeaders {
+    /// Headers configured for a feed
+    pub fn get(&self, feed_id: &FeedId) -> &[(String, String)] {
+        self.0.get(feed_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn push(&mut self, feed_id: FeedId, name: &str, value: &str) {
+        self.0.entry(feed_id).or_default().push((name.to_string(), value.to_string()));
+    }
+}
+
+impl From<HashMap<FeedId, Vec<(String, String)>>> for FeedHeaders {
+    fn from(headers: HashMap<FeedId, Vec<(String, String)>>) -> Self {
+        Self(headers)
+    }
+}
+
+impl fmt::Debug for FeedHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(feed_id, headers)| {
+                let names: Vec<String> = headers.iter().map(|(name, _)| format!("{}: <redacted>", name)).collect();
+                (feed_id, names)
+            }))
+            .finish()
+    }
+}
+
+/// Limits of the offline image cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetCacheSettings {
+    /// Least recently viewed images are evicted once the cache grows past this
+    pub max_total_bytes: u64,
+    /// Larger images are not cached and load from the web as before
+    pub max_asset_bytes: u64,
+}
+
+impl Default for AssetCacheSettings {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: 256 * 1024 * 1024,
+            max_asset_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+/// Typeface family of the article reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReaderFont {
+    SansSerif,
+    Serif,
+    Monospace,
+}
+
+impl ReaderFont {
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::SansSerif => "sans_serif".to_string(),
+            Self::Serif => "serif".to_string(),
+            Self::Monospace => "monospace".to_string(),
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "sans_serif" => Some(Self::SansSerif),
+            "serif" => Some(Self::Serif),
+            "monospace" => Some(Self::Monospace),
+            _ => None,
+        }
+    }
+}
+
+/// Typography of the article reader; its colors follow the app [`Theme`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReaderSettings {
+    pub font: ReaderFont,
+    /// Body text size in CSS pixels
+    pub font_size: u32,
+    /// Line height as a percentage of the font size
+    pub line_height_percent: u32,
+    /// Widest text column, in characters
+    pub column_width: u32,
+}
+
+impl Default for ReaderSettings {
+    fn default() -> Self {
+        Self {
+            font: ReaderFont::SansSerif,
+            font_size: 18,
+            line_height_percent: 160,
+            column_width: 70,
+        }
+    }
+}
+
+/// Application settings, persisted one key per field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// How often feeds without a known posting rhythm are fetched
+    pub sync_interval_minutes: u32,
+    pub article_retention_days: u32,
+    pub theme: Theme,
+    pub default_sort_order: ArticleSortOrder,
+    pub fetch: FetchSettings,
+    /// Where podcast episodes and other enclosures are saved
+    pub download_directory: String,
+    pub asset_cache: AssetCacheSettings,
+    pub reader: ReaderSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            sync_interval_minutes: 60,
+            article_retention_days: 30,
+            theme: Theme::Dark,
+            default_sort_order: ArticleSortOrder::NewestFirst,
+            fetch: FetchSettings::default(),
+            download_directory: "data/downloads".to_string(),
+            asset_cache: AssetCacheSettings::default(),
+            reader: ReaderSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Builds settings from stored key/value pairs
+    ///
+    /// Missing keys take their default; values that fail to parse are logged
+    /// and also fall back, so a bad row never prevents startup.
+    pub fn from_entries(entries: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let text = |key: &str| entries.get(key).map(|v| v.trim()).filter(|v| !v.is_empty());
+
+        Self {
+            sync_interval_minutes: parse_or(entries, keys::SYNC_INTERVAL_MINUTES, defaults.sync_interval_minutes),
+            article_retention_days: parse_or(entries, keys::ARTICLE_RETENTION_DAYS, defaults.article_retention_days),
+            theme: text(keys::THEME).and_then(Theme::from_str).unwrap_or(defaults.theme),
+            default_sort_order: text(keys::DEFAULT_SORT_ORDER)
+                .and_then(ArticleSortOrder::from_str)
+                .unwrap_or(defaults.default_sort_order),
+            fetch: FetchSettings {
+                timeout_secs: parse_or(entries, keys::FETCH_TIMEOUT_SECS, defaults.fetch.timeout_secs),
+                max_body_bytes: parse_or(entries, keys::FETCH_MAX_BODY_BYTES, defaults.fetch.max_body_bytes),
+                user_agent: text(keys::FETCH_USER_AGENT).map(str::to_string),
+                proxy_url: text(keys::FETCH_PROXY_URL).map(str::to_string),
+                max_redirects: parse_or(entries, keys::FETCH_MAX_REDIRECTS, defaults.fetch.max_redirects),
+                max_concurrent: parse_or(entries, keys::FETCH_MAX_CONCURRENT, defaults.fetch.max_concurrent),
+                max_per_host: parse_or(entries, keys::FETCH_MAX_PER_HOST, defaults.fetch.max_per_host),
+                // The value holds credentials, so it is not logged
+                feed_headers: text(keys::FETCH_FEED_HEADERS)
+                    .and_then(|value| serde_json::from_str(value)
+                        .inspect_err(|_| warn!("Ignoring invalid value for setting {}", keys::FETCH_FEED_HEADERS))
+                        .ok())
+                    .unwrap_or_default(),
+            }.validated(),
+            download_directory: text(keys::DOWNLOAD_DIRECTORY)
+                .map(str::to_string)
+                .unwrap_or(defaults.download_directory),
+            asset_cache: AssetCacheSettings {
+                max_total_bytes: parse_or(entries, keys::ASSET_CACHE_MAX_TOTAL_BYTES, defaults.asset_cache.max_total_bytes),
+                max_asset_bytes: parse_or(entries, keys::ASSET_CACHE_MAX_ASSET_BYTES, defaults.asset_cache.max_asset_bytes),
+            },
+            reader: ReaderSettings {
+                font: text(keys::READER_FONT).and_then(ReaderFont::from_str).unwrap_or(defaults.reader.font),
+                font_size: parse_or(entries, keys::READER_FONT_SIZE, defaults.reader.font_size),
+                line_height_percent: parse_or(entries, keys::READER_LINE_HEIGHT_PERCENT, defaults.reader.line_height_percent),
+                column_width: parse_or(entries, keys::READER_COLUMN_WIDTH, defaults.reader.column_width),
+            },
+        }
+    }
+
+    /// Flattens settings into the key/value pairs that are stored
+    pub fn to_entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (keys::SYNC_INTERVAL_MINUTES, self.sync_interval_minutes.to_string()),
+            (keys::ARTICLE_RETENTION_DAYS, self.article_retention_days.to_string()),
+            (keys::THEME, self.theme.to_string()),
+            (keys::DEFAULT_SORT_ORDER, self.default_sort_order.to_string()),
+            (keys::FETCH_TIMEOUT_SECS, self.fetch.timeout_secs.to_string()),
+            (keys::FETCH_MAX_BODY_BYTES, self.fetch.max_body_bytes.to_string()),
+            (keys::FETCH_USER_AGENT, self.fetch.user_agent.clone().unwrap_or_default()),
+            (keys::FETCH_PROXY_URL, self.fetch.proxy_url.clone().unwrap_or_default()),
+            (keys::FETCH_MAX_REDIRECTS, self.fetch.max_redirects.to_string()),
+            (keys::FETCH_MAX_CONCURRENT, self.fetch.max_concurrent.to_string()),
+            (keys::FETCH_MAX_PER_HOST, self.fetch.max_per_host.to_string()),
+            (keys::FETCH_FEED_HEADERS, serde_json::to_string(&self.fetch.feed_headers).unwrap_or_default()),
+            (keys::DOWNLOAD_DIRECTORY, self.download_directory.clone()),
+            (keys::ASSET_CACHE_MAX_TOTAL_BYTES, self.asset_cache.max_total_bytes.to_string()),
+            (keys::ASSET_CACHE_MAX_ASSET_BYTES, self.asset_cache.max_asset_bytes.to_string()),
+            (keys::READER_FONT, self.reader.font.to_string()),
+            (keys::READER_FONT_SIZE, self.reader.font_size.to_string()),
+            (keys::READER_LINE_HEIGHT_PERCENT, self.reader.line_height_percent.to_string()),
+            (keys::READER_COLUMN_WIDTH, self.reader.column_width.to_string()),
+        ]
+    }
+}
+
+fn parse_or<T: std::str::FromStr>(entries: &HashMap<String, String>, key: &str, default: T) -> T {
+    match entries.get(key) {
+        Some(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!("Ignoring invalid value {:?} for setting {}", value, key);
+            default
+        }),
+        None => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_round_trip_through_entries() {
+        let settings = Settings {
+            sync_interval_minutes: 15,
+            theme: Theme::Light,
+            default_sort_order: ArticleSortOrder::Unread,
+            fetch: FetchSettings {
+                proxy_url: Some("http://proxy.corp:3128".to_string()),
+                feed_headers: HashMap::from([(
+                    FeedId("f1".to_string()),
+                    vec![("Authorization".to_string(), "Bearer secret".to_string())],
+                )]).into(),
+                ..FetchSettings::default()
+            },
+            download_directory: "/home/me/Podcasts".to_string(),
+            asset_cache: AssetCacheSettings {
+                max_total_bytes: 64 * 1024 * 1024,
+                ..AssetCacheSettings::default()
+            },
+            reader: ReaderSettings {
+                font: ReaderFont::Serif,
+                font_size: 21,
+                ..ReaderSettings::default()
+            },
+            ..Settings::default()
+        };
+
+        let entries = settings.to_entries()
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        assert_eq!(Settings::from_entries(&entries), settings);
+    }
+
+    #[test]
+    fn test_invalid_entries_fall_back_to_defaults() {
+        let entries = HashMap::from([
+            (keys::SYNC_INTERVAL_MINUTES.to_string(), "soon".to_string()),
+            (keys::THEME.to_string(), "neon".to_string()),
+            (keys::ARTICLE_RETENTION_DAYS.to_string(), "90".to_string()),
+        ]);
+
+        let settings = Settings::from_entries(&entries);
+        assert_eq!(settings.sync_interval_minutes, 60);
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.article_retention_days, 90);
+    }
+
+    #[test]
+    fn test_zero_fetch_limits_fall_back_to_defaults() {
+        let entries = HashMap::from([
+            (keys::FETCH_TIMEOUT_SECS.to_string(), "0".to_string()),
+            (keys::FETCH_MAX_BODY_BYTES.to_string(), "0".to_string()),
+        ]);
+
+        let settings = Settings::from_entries(&entries);
+        assert_eq!(settings.fetch, FetchSettings::default());
+    }
+
+    #[test]
+    fn test_feed_headers_debug_hides_values() {
+        let mut headers = FeedHeaders::default();
+        headers.push(FeedId("f1".to_string()), "Authorization", "Bearer secret");
+        let debug = format!("{:?}", FetchSettings { feed_headers: headers, ..FetchSettings::default() });
+        assert!(debug.contains("Authorization: <redacted>"));
+        assert!(!debug.contains("secret"));
+    }
+}
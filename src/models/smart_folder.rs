@@ -0,0 +1,69 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use rusqlite::types::{FromSql, ToSql, ToSqlOutput, ValueRef, FromSqlResult};
+
+use crate::models::article_query::ArticleQuery;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SmartFolderId(pub String);
+
+impl std::fmt::Display for SmartFolderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A saved article query shown in the sidebar like a folder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmartFolder {
+    pub id: SmartFolderId,
+    pub name: String,
+    /// Query source text, see [`ArticleQuery`] for the syntax
+    pub query: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SmartFolder {
+    pub fn new(name: String, query: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: SmartFolderId::new(),
+            name,
+            query,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Parses the stored query text
+    pub fn parsed_query(&self) -> Result<ArticleQuery> {
+        ArticleQuery::parse(&self.query)
+    }
+}
+
+impl SmartFolderId {
+    pub fn new() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+}
+
+impl FromSql for SmartFolderId {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        String::column_result(value).map(SmartFolderId)
+    }
+}
+
+impl ToSql for SmartFolderId {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.0.clone()))
+    }
+}
+
+impl From<String> for SmartFolderId {
+    fn from(s: String) -> Self {
+        SmartFolderId(s)
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use anyhow::{bail, Result};
+use chrono::Utc;
+use log::{debug, info};
+use reqwest::Client;
+use url::Url;
+
+use crate::base::repository::{ArticleRepository, AssetRepository, FeedRepository};
+use crate::models::article::{Article, ArticleId};
+use crate::models::cached_asset::CachedAsset;
+use crate::models::feed::FeedId;
+use crate::models::settings::AssetCacheSettings;
+use crate::services::rss::{image_mime_type, FetchConfig};
+use crate::services::webview::sanitize_html;
+use crate::utils::html::start_tags;
+
+/// Keeps copies of article images so articles read the same offline
+///
+/// Feeds opt in with [`Feed::cache_images`](crate::models::feed::Feed);
+/// their new articles' images are fetched after each sync, up to a size per
+/// image. The store is capped in total and evicts the images of the least
+/// recently opened articles first.
+pub struct AssetCacheService {
+    asset_repository: Arc<dyn AssetRepository>,
+    article_repository: Arc<dyn ArticleRepository>,
+    feed_repository: Arc<dyn FeedRepository>,
+    client: RwLock<Client>,
+    limits: RwLock<AssetCacheSettings>,
+}
+
+impl AssetCacheService {
+    pub fn new(
+        asset_repository: Arc<dyn AssetRepository>,
+        article_repository: Arc<dyn ArticleRepository>,
+        feed_repository: Arc<dyn FeedRepository>,
+        limits: AssetCacheSettings,
+    ) -> Result<Self> {
+        Ok(Self {
+            asset_repository,
+            article_repository,
+            feed_repository,
+            client: RwLock::new(fetch_client(&FetchConfig::default())?),
+            limits: RwLock::new(limits),
+        })
+    }
+
+    /// Fetches images through the proxy and with the timeouts of `config`
+    pub fn with_fetch_config(self, config: &FetchConfig) -> Result<Self> {
+        self.set_fetch_config(config)?;
+        Ok(self)
+    }
+
+    /// Changes the HTTP settings for images fetched from now on
+    ///
+    /// On error (e.g. an unparseable proxy URL) the current settings stay in use.
+    pub fn set_fetch_config(&self, config: &FetchConfig) -> Result<()> {
+        *self.client.write().unwrap() = fetch_client(config)?;
+        Ok(())
+    }
+
+    /// Changes the size limits; a smaller total takes effect on the next eviction
+    pub fn set_limits(&self, limits: AssetCacheSettings) {
+        *self.limits.write().unwrap() = limits;
+    }
+
+    pub fn limits(&self) -> AssetCacheSettings {
+        *self.limits.read().unwrap()
+    }
+
+    /// Caches the images of the articles a sync just added to a feed, if the
+    /// feed opted in, returning how many images were stored
+    pub async fn cache_new_articles(&self, feed_id: &FeedId, article_ids: &[ArticleId]) -> Result<usize> {
+        let Some(feed) = self.feed_repository.get_feed_by_id(feed_id).await? else {
+            return Ok(0);
+        };
+        if !feed.cache_images || article_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut cached = 0;
+        for id in article_ids {
+            if let Some(article) = self.article_repository.get_article(id).await? {
+                cached += self.cache_article(&article).await?;
+            }
+        }
+        Ok(cached)
+    }
+
+    /// Fetches and stores the article's images that are not cached yet
+    ///
+    /// Images that fail to load, are too large or turn out not to be images
+    /// are skipped; they keep loading from the web when the article is shown.
+    pub async fn cache_article(&self, article: &Article) -> Result<usize> {
+        let urls = article_image_urls(article);
+        if urls.is_empty() {
+            return Ok(0);
+        }
+        let known: HashSet<Url> = self.asset_repository.get_assets_by_urls(&urls).await?
+            .into_iter()
+            .map(|(url, _)| url)
+            .collect();
+
+        let limits = self.limits();
+        let mut cached = 0;
+        for url in urls.iter().filter(|url| !known.contains(url)) {
+            match self.fetch_asset(url, limits.max_asset_bytes).await {
+                Ok(asset) => {
+                    self.asset_repository.save_asset(url, &asset).await?;
+                    cached += 1;
+                }
+                Err(e) => debug!("Not caching {}: {}", url, e),
+            }
+        }
+
+        if cached > 0 {
+            let evicted = self.asset_repository.evict_to_size(limits.max_total_bytes).await?;
+            if evicted > 0 {
+                info!("Evicted {} cached images to stay within {} bytes", evicted, limits.max_total_bytes);
+            }
+        }
+        Ok(cached)
+    }
+
+    /// The article's cached images by URL, marked as used so they are evicted last
+    pub async fn assets_for_article(&self, article: &Article) -> Result<HashMap<Url, CachedAsset>> {
+        let urls = article_image_urls(article);
+        if urls.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let assets = self.asset_repository.get_assets_by_urls(&urls).await?;
+        let hashes: Vec<String> = assets.iter().map(|(_, asset)| asset.hash.clone()).collect();
+        if !hashes.is_empty() {
+            self.asset_repository.touch_assets(&hashes, Utc::now()).await?;
+        }
+        Ok(assets.into_iter().collect())
+    }
+
+    /// Total size of the cache in bytes
+    pub async fn total_size(&self) -> Result<u64> {
+        self.asset_repository.get_total_size().await
+    }
+
+    async fn fetch_asset(&self, url: &Url, max_bytes: u64) -> Result<CachedAsset> {
+        let client = self.client.read().unwrap().clone();
+        let mut response = client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            bail!("HTTP {}", response.status());
+        }
+        if response.content_length().is_some_and(|length| length > max_bytes) {
+            bail!("larger than {} bytes", max_bytes);
+        }
+
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (data.len() + chunk.len()) as u64 > max_bytes {
+                bail!("larger than {} bytes", max_bytes);
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        let Some(mime_type) = image_mime_type(&data) else {
+            bail!("not an image");
+        };
+        Ok(CachedAsset::new(data, mime_type.to_string()))
+    }
+}
+
+fn fetch_client(config: &FetchConfig) -> Result<Client> {
+    Ok(config.client_builder()?.timeout(config.timeout).build()?)
+}
+
+/// The web images an article shows, once each
+///
+/// Read from the sanitized HTML, so tracking pixels and anything else the
+/// reader would not load is not fetched either.
+pub fn article_image_urls(article: &Article) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::new();
+    for html in article.full_content.iter().chain(&article.content).chain(&article.summary) {
+        for (_, attributes) in start_tags(&sanitize_html(html)).into_iter().filter(|(name, _)| name == "img") {
+            let src = attributes.iter()
+                .find(|(key, _)| key == "src")
+                .and_then(|(_, src)| Url::parse(src.trim()).ok())
+                .filter(|url| matches!(url.scheme(), "http" | "https"));
+            if let Some(url) = src.filter(|url| !urls.contains(url)) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::data::Database;
+    use crate::models::feed::Feed;
+    use crate::utils::test_support::{save_article, temp_database, test_article, TestResponse, TestServer};
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x10\0\0\0\x10";
+
+    fn service(database: &Database, limits: AssetCacheSettings) -> AssetCacheService {
+        AssetCacheService::new(
+            database.get_asset_repository(),
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            limits,
+        ).unwrap()
+    }
+
+    /// An image of `size` bytes that hashes differently for each `seed`
+    fn png(size: usize, seed: u8) -> Vec<u8> {
+        let mut data = PNG.to_vec();
+        data.resize(size, seed);
+        data
+    }
+
+    #[tokio::test]
+    async fn test_caches_images_of_opted_in_feeds() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/a.png", TestResponse::ok(png(100, 1)));
+        server.route("/copy-of-a.png", TestResponse::ok(png(100, 1)));
+        server.route("/huge.png", TestResponse::ok(png(5000, 2)));
+        server.route("/page.html", TestResponse::ok("<html>Not an image</html>"));
+
+        let database = temp_database();
+        let feed = Feed::new("Photos".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        database.get_feed_repository().save_feed(&feed).await?;
+        let content = format!(
+            r#"<img src="{a}"><img src="{a}"><img src="{copy}"><img src="{huge}"><img src="{page}"><img src="{missing}"><img src="{pixel}" width="1" height="1">"#,
+            a = server.url("/a.png"),
+            copy = server.url("/copy-of-a.png"),
+            huge = server.url("/huge.png"),
+            page = server.url("/page.html"),
+            missing = server.url("/missing.png"),
+            pixel = server.url("/pixel.gif"),
+        );
+        let article = save_article(&database, test_article(&feed, "photos").with_content(content)).await?;
+        // Stored by an earlier sync
+        server.route("/older.png", TestResponse::ok(png(100, 3)));
+        let older_content = format!(r#"<img src="{}">"#, server.url("/older.png"));
+        let older = save_article(&database, test_article(&feed, "older").with_content(older_content)).await?;
+
+        let limits = AssetCacheSettings { max_total_bytes: 10_000, max_asset_bytes: 1000 };
+        let service = service(&database, limits);
+        // Feeds have to opt in
+        assert_eq!(service.cache_new_articles(&feed.id, std::slice::from_ref(&article.id)).await?, 0);
+
+        let feed = Feed { cache_images: true, ..feed };
+        database.get_feed_repository().update_feed(&feed).await?;
+        assert_eq!(service.cache_new_articles(&feed.id, std::slice::from_ref(&article.id)).await?, 2);
+        // The tracking pixel is never requested, nor are the older article's images
+        assert!(server.requests().iter().all(|request| request.path != "/pixel.gif" && request.path != "/older.png"));
+        assert!(service.assets_for_article(&older).await?.is_empty());
+
+        let assets = service.assets_for_article(&article).await?;
+        assert_eq!(assets.len(), 2);
+        let a = &assets[&Url::parse(&server.url("/a.png"))?];
+        assert_eq!(a.data, png(100, 1));
+        assert_eq!(a.mime_type, "image/png");
+        // Identical bytes are stored once
+        assert_eq!(assets[&Url::parse(&server.url("/copy-of-a.png"))?].hash, a.hash);
+        assert_eq!(service.total_size().await?, 100);
+
+        // Cached images are not fetched again
+        let fetched = server.requests().len();
+        assert_eq!(service.cache_article(&article).await?, 0);
+        assert_eq!(server.requests().len(), fetched + 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() -> Result<()> {
+        let server = TestServer::start().await;
+        for (name, seed) in [("one", 1), ("two", 2), ("three", 3)] {
+            server.route(&format!("/{}.png", name), TestResponse::ok(png(400, seed)));
+        }
+
+        let database = temp_database();
+        let feed = Feed::new("Photos".to_string(), Url::parse(&server.url("/feed.xml"))?).with_cache_images(true);
+        database.get_feed_repository().save_feed(&feed).await?;
+        let service = service(&database, AssetCacheSettings { max_total_bytes: 1000, max_asset_bytes: 1000 });
+
+        let mut articles = Vec::new();
+        for name in ["one", "two", "three"] {
+            let content = format!(r#"<p><img src="{}"></p>"#, server.url(&format!("/{}.png", name)));
+            articles.push(save_article(&database, test_article(&feed, name).with_content(content)).await?);
+        }
+
+        service.cache_article(&articles[0]).await?;
+        service.cache_article(&articles[1]).await?;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        // Opening the first article makes the second the least recently used
+        assert_eq!(service.assets_for_article(&articles[0]).await?.len(), 1);
+        service.cache_article(&articles[2]).await?;
+
+        assert_eq!(service.total_size().await?, 800);
+        assert_eq!(service.assets_for_article(&articles[0]).await?.len(), 1);
+        assert!(service.assets_for_article(&articles[1]).await?.is_empty());
+        assert_eq!(service.assets_for_article(&articles[2]).await?.len(), 1);
+        Ok(())
+    }
+}
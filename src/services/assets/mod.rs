@@ -0,0 +1,3 @@
+mod asset_cache_service;
+
+pub use asset_cache_service::{article_image_urls, AssetCacheService};
@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use anyhow::{bail, Result};
+use log::{info, warn};
+use reqwest::header::{HeaderMap, CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+
+use crate::base::repository::{EnclosureRepository, FeedRepository};
+use crate::models::article::ArticleId;
+use crate::models::enclosure::{DownloadStatus, Enclosure, EnclosureId};
+use crate::models::feed::{AutoDownloadPolicy, FeedId};
+use crate::services::rss::FetchConfig;
+
+/// How far along a running download is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub received: u64,
+    /// Size of the whole file, when the server or the feed announced it
+    pub total: Option<u64>,
+}
+
+impl DownloadProgress {
+    /// Share of the file received so far, if its size is known
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.received as f64 / total as f64).min(1.0) as f32)
+    }
+}
+
+/// Saves enclosures to a local folder, either on request or as a feed's
+/// [`AutoDownloadPolicy`] asks
+///
+/// Bytes go to a `.part` file next to the destination, which a later attempt
+/// resumes with an HTTP range request; the file is renamed into place once
+/// complete.
+pub struct DownloadService {
+    enclosure_repository: Arc<dyn EnclosureRepository>,
+    feed_repository: Arc<dyn FeedRepository>,
+    client: RwLock<Client>,
+    directory: RwLock<PathBuf>,
+    active: Mutex<HashMap<EnclosureId, DownloadProgress>>,
+}
+
+impl DownloadService {
+    pub fn new(
+        enclosure_repository: Arc<dyn EnclosureRepository>,
+        feed_repository: Arc<dyn FeedRepository>,
+        directory: impl Into<PathBuf>,
+    ) -> Result<Self> {
+        Ok(Self {
+            enclosure_repository,
+            feed_repository,
+            client: RwLock::new(download_client(&FetchConfig::default())?),
+            directory: RwLock::new(directory.into()),
+            active: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Downloads through the proxy and with the user agent of `config`
+    pub fn with_fetch_config(self, config: &FetchConfig) -> Result<Self> {
+        self.set_fetch_config(config)?;
+        Ok(self)
+    }
+
+    /// Changes the HTTP settings for downloads started from now on
+    ///
+    /// On error (e.g. an unparseable proxy URL) the current settings stay in use.
+    pub fn set_fetch_config(&self, config: &FetchConfig) -> Result<()> {
+        *self.client.write().unwrap() = download_client(config)?;
+        Ok(())
+    }
+
+    /// Changes the folder later downloads are saved to; finished files stay where they are
+    pub fn set_directory(&self, directory: impl Into<PathBuf>) {
+        *self.directory.write().unwrap() = directory.into();
+    }
+
+    pub fn directory(&self) -> PathBuf {
+        self.directory.read().unwrap().clone()
+    }
+
+    /// Progress of the enclosure's download, while one is running
+    pub fn progress(&self, id: &EnclosureId) -> Option<DownloadProgress> {
+        self.active.lock().unwrap().get(id).copied()
+    }
+
+    /// Downloads an enclosure, resuming an earlier partial download, and
+    /// records the outcome on it
+    pub async fn download(&self, enclosure: &Enclosure) -> Result<Enclosure> {
+        {
+            let mut active = self.active.lock().unwrap();
+            if active.contains_key(&enclosure.id) {
+                bail!("{} is already downloading", enclosure.display_name());
+            }
+            active.insert(enclosure.id.clone(), DownloadProgress { received: 0, total: enclosure.length });
+        }
+
+        let result = self.fetch_to_file(enclosure).await;
+        self.active.lock().unwrap().remove(&enclosure.id);
+
+        let mut enclosure = enclosure.clone();
+        match result {
+            Ok(path) => {
+                info!("Downloaded {} to {}", enclosure.url, path.display());
+                enclosure.mark_downloaded(path.to_string_lossy().into_owned());
+                self.enclosure_repository.update_download(&enclosure).await?;
+                Ok(enclosure)
+            }
+            Err(e) => {
+                enclosure.mark_failed();
+                self.enclosure_repository.update_download(&enclosure).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Deletes the downloaded file of an enclosure, keeping the enclosure itself
+    pub async fn delete_download(&self, enclosure: &Enclosure) -> Result<Enclosure> {
+        self.remove_files(enclosure).await?;
+
+        let mut enclosure = enclosure.clone();
+        enclosure.clear_download();
+        self.enclosure_repository.update_download(&enclosure).await?;
+        Ok(enclosure)
+    }
+
+    /// Deletes the downloaded and partly downloaded files of an enclosure
+    /// without touching the stored enclosure, e.g. because its article is
+    /// being deleted along with it
+    pub async fn remove_files(&self, enclosure: &Enclosure) -> Result<()> {
+        let mut paths: Vec<PathBuf> = enclosure.local_path.iter().map(PathBuf::from).collect();
+        paths.push(partial_path(&self.destination(enclosure)));
+        for path in paths {
+            match fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The enclosures of the given articles, whose files
+    /// [`remove_files`](Self::remove_files) can delete once the articles are gone
+    pub async fn enclosures_of_articles(&self, article_ids: &[ArticleId]) -> Result<Vec<Enclosure>> {
+        let mut enclosures = Vec::new();
+        for article_id in article_ids {
+            enclosures.extend(self.enclosure_repository.get_enclosures_by_article(article_id).await?);
+        }
+        Ok(enclosures)
+    }
+
+    /// Downloads what the feed's auto-download policy asks for and is not
+    /// downloaded yet, e.g. after a sync brought new episodes
+    ///
+    /// Failures are logged and retried, resuming, on the next call.
+    pub async fn auto_download(&self, feed_id: &FeedId) -> Result<Vec<Enclosure>> {
+        let Some(feed) = self.feed_repository.get_feed_by_id(feed_id).await? else {
+            return Ok(Vec::new());
+        };
+        let limit = match feed.auto_download {
+            AutoDownloadPolicy::Off => return Ok(Vec::new()),
+            AutoDownloadPolicy::Latest(count) => Some(count as usize),
+            AutoDownloadPolicy::All => None,
+        };
+
+        let mut downloaded = Vec::new();
+        for enclosure in self.enclosure_repository.get_enclosures_by_feed(feed_id, limit).await? {
+            if enclosure.download_status == DownloadStatus::Downloaded || self.progress(&enclosure.id).is_some() {
+                continue;
+            }
+            match self.download(&enclosure).await {
+                Ok(enclosure) => downloaded.push(enclosure),
+                Err(e) => warn!("Failed to download {} of feed {}: {}", enclosure.url, feed.title, e),
+            }
+        }
+        Ok(downloaded)
+    }
+
+    /// Where the enclosure is saved: its id keeps names from different feeds apart
+    fn destination(&self, enclosure: &Enclosure) -> PathBuf {
+        let name: String = enclosure.url.path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("enclosure")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+            .collect();
+        let prefix: String = enclosure.id.0.chars().take(8).collect();
+        self.directory().join(format!("{}-{}", prefix, name))
+    }
+
+    async fn fetch_to_file(&self, enclosure: &Enclosure) -> Result<PathBuf> {
+        let path = self.destination(enclosure);
+        let part = partial_path(&path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        let mut resume_from = fs::metadata(&part).await.map(|metadata| metadata.len()).unwrap_or(0);
+        let client = self.client.read().unwrap().clone();
+        let mut request = client.get(enclosure.url.clone());
+        if resume_from > 0 {
+            request = request.header(RANGE, format!("bytes={}-", resume_from));
+        }
+        let mut response = request.send().await?;
+        if response.status() == StatusCode::PARTIAL_CONTENT && resume_from > 0
+            && content_range_start(response.headers()) != Some(resume_from)
+        {
+            // Appending a range that starts elsewhere would corrupt the file
+            warn!("{} sent a different range than asked for, downloading it again", enclosure.url);
+            resume_from = 0;
+            response = client.get(enclosure.url.clone()).send().await?;
+        }
+
+        let (mut file, mut received) = match response.status() {
+            StatusCode::PARTIAL_CONTENT if resume_from > 0 => {
+                (OpenOptions::new().append(true).open(&part).await?, resume_from)
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE if resume_from > 0 => {
+                // Nothing left to fetch, unless the file on the server changed
+                if enclosure.length.is_some_and(|length| length != resume_from) {
+                    fs::remove_file(&part).await?;
+                    bail!("{} changed since it was partly downloaded", enclosure.url);
+                }
+                fs::rename(&part, &path).await?;
+                return Ok(path);
+            }
+            // Servers without range support send the whole file again
+            status if status.is_success() => (fs::File::create(&part).await?, 0),
+            status => bail!("HTTP {} downloading {}", status, enclosure.url),
+        };
+
+        // The feed's own length is often stale, so only the server's is held to
+        let expected = response.content_length().map(|length| received + length);
+        let total = expected.or(enclosure.length);
+        self.set_progress(&enclosure.id, received, total);
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            received += chunk.len() as u64;
+            self.set_progress(&enclosure.id, received, total);
+        }
+        file.flush().await?;
+        drop(file);
+
+        if let Some(expected) = expected.filter(|expected| received < *expected) {
+            bail!("Download of {} stopped after {} of {} bytes", enclosure.url, received, expected);
+        }
+        fs::rename(&part, &path).await?;
+        Ok(path)
+    }
+
+    fn set_progress(&self, id: &EnclosureId, received: u64, total: Option<u64>) {
+        if let Some(progress) = self.active.lock().unwrap().get_mut(id) {
+            *progress = DownloadProgress { received, total };
+        }
+    }
+}
+
+fn download_client(config: &FetchConfig) -> Result<Client> {
+    // No overall timeout: an episode can take a long while on a slow line
+    Ok(config.client_builder()?.build()?)
+}
+
+/// Where the body of a `206 Partial Content` response starts in the file,
+/// from a `Content-Range: bytes <start>-<end>/<size>` header
+fn content_range_start(headers: &HeaderMap) -> Option<u64> {
+    headers.get(CONTENT_RANGE)?
+        .to_str().ok()?
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('-')?
+        .0
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn partial_path(path: &std::path::Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+    use uuid::Uuid;
+    use crate::data::Database;
+    use crate::models::article::Article;
+    use crate::models::feed::Feed;
+    use crate::services::rss::RssService;
+    use crate::utils::test_support::{temp_database, TestResponse, TestServer};
+
+    fn service(database: &Database) -> DownloadService {
+        let directory = std::env::temp_dir().join("panda-tests").join(Uuid::new_v4().to_string());
+        DownloadService::new(database.get_enclosure_repository(), database.get_feed_repository(), directory).unwrap()
+    }
+
+    /// A podcast feed whose episodes, newest first, are served by `server`
+    fn podcast(server: &TestServer, episodes: &[&str]) -> String {
+        let items: String = episodes.iter()
+            .enumerate()
+            .map(|(age, name)| format!(
+                r#"<item><title>{name}</title><link>http://example.com/{name}</link><guid>{name}</guid>
+                   <pubDate>Mon, {day:02} Jan 2024 10:00:00 GMT</pubDate>
+                   <itunes:title>Episode {name}</itunes:title><itunes:duration>01:02:03</itunes:duration>
+                   <enclosure url="{url}" length="5" type="audio/mpeg"/></item>"#,
+                name = name,
+                day = 20 - age,
+                url = server.url(&format!("/media/{}.mp3", name)),
+            ))
+            .collect();
+        format!(
+            r#"<?xml version="1.0"?><rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+               <channel><title>Podcast</title><link>http://example.com/</link>{}</channel></rss>"#,
+            items
+        )
+    }
+
+    #[tokio::test]
+    async fn test_auto_download_follows_feed_policy() -> Result<()> {
+        let server = TestServer::start().await;
+        let episodes = ["three", "two", "one"];
+        server.route("/feed.xml", TestResponse::ok(podcast(&server, &episodes)));
+        for name in episodes {
+            server.route(&format!("/media/{}.mp3", name), TestResponse::ok(name.as_bytes()));
+        }
+
+        let database = temp_database();
+        let feed = Feed::new("Podcast".to_string(), Url::parse(&server.url("/feed.xml"))?)
+            .with_auto_download(AutoDownloadPolicy::Latest(2));
+        database.get_feed_repository().save_feed(&feed).await?;
+        let rss_service = RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        );
+        rss_service.sync_feed(&feed.id).await?;
+
+        let enclosure_repository = database.get_enclosure_repository();
+        let enclosures = enclosure_repository.get_enclosures_by_feed(&feed.id, None).await?;
+        assert_eq!(enclosures.len(), 3);
+        assert_eq!(enclosures[0].url.as_str(), server.url("/media/three.mp3"));
+        assert_eq!(enclosures[0].mime_type.as_deref(), Some("audio/mpeg"));
+        assert_eq!(enclosures[0].length, Some(5));
+        assert_eq!(enclosures[0].duration_secs, Some(3723));
+        assert_eq!(enclosures[0].title.as_deref(), Some("Episode three"));
+
+        let service = service(&database);
+        let downloaded = service.auto_download(&feed.id).await?;
+        let names: Vec<String> = downloaded.iter().map(|e| std::fs::read_to_string(e.local_path.as_ref().unwrap()).unwrap()).collect();
+        assert_eq!(names, vec!["three", "two"]);
+        assert!(service.auto_download(&feed.id).await?.is_empty());
+
+        // Syncing the same entries again keeps what was downloaded
+        rss_service.sync_feed(&feed.id).await?;
+        let enclosures = enclosure_repository.get_enclosures_by_feed(&feed.id, None).await?;
+        let statuses: Vec<DownloadStatus> = enclosures.iter().map(|e| e.download_status).collect();
+        assert_eq!(statuses, vec![DownloadStatus::Downloaded, DownloadStatus::Downloaded, DownloadStatus::NotDownloaded]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file() -> Result<()> {
+        let server = TestServer::start().await;
+        let body: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+        let served = body.clone();
+        server.handle("/media/long.mp3", move |request| match request.header("range") {
+            Some(range) => {
+                let start: usize = range.trim_start_matches("bytes=").trim_end_matches('-').parse().unwrap();
+                let content_range = format!("bytes {}-{}/{}", start, served.len() - 1, served.len());
+                TestResponse { status: 206, ..TestResponse::ok(served[start..].to_vec()) }
+                    .with_header("Content-Range", &content_range)
+            }
+            None => TestResponse::ok(served.clone()),
+        });
+
+        let database = temp_database();
+        let feed = Feed::new("Podcast".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        database.get_feed_repository().save_feed(&feed).await?;
+        let article = Article::new(feed.id.clone(), "Long".to_string(), Url::parse("http://example.com/long")?);
+        database.get_article_repository().save_article(&article).await?;
+        let enclosure = Enclosure::new(article.id.clone(), Url::parse(&server.url("/media/long.mp3"))?);
+        let missing = Enclosure::new(article.id.clone(), Url::parse(&server.url("/media/missing.mp3"))?);
+        let enclosure_repository = database.get_enclosure_repository();
+        enclosure_repository.save_enclosures(&[enclosure.clone(), missing.clone()]).await?;
+
+        // An earlier attempt got the first part of the file
+        let service = service(&database);
+        let part = partial_path(&service.destination(&enclosure));
+        std::fs::create_dir_all(service.directory())?;
+        std::fs::write(&part, &body[..4000])?;
+
+        let downloaded = service.download(&enclosure).await?;
+        let path = downloaded.local_path.clone().unwrap();
+        assert_eq!(std::fs::read(&path)?, body);
+        assert!(!part.exists());
+        let requests = server.requests();
+        assert_eq!(requests.last().unwrap().header("range"), Some("bytes=4000-"));
+        assert_eq!(service.progress(&enclosure.id), None);
+
+        assert!(service.download(&missing).await.is_err());
+        let stored = enclosure_repository.get_enclosures_by_article(&article.id).await?;
+        assert_eq!(stored[0].download_status, DownloadStatus::Downloaded);
+        assert_eq!(stored[1].download_status, DownloadStatus::Failed);
+
+        let cleared = service.delete_download(&downloaded).await?;
+        assert_eq!(cleared.download_status, DownloadStatus::NotDownloaded);
+        assert!(!std::path::Path::new(&path).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_server_sends_another_range() -> Result<()> {
+        let server = TestServer::start().await;
+        let body: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+        let served = body.clone();
+        // Answers every range request with the whole file
+        server.handle("/media/long.mp3", move |request| match request.header("range") {
+            Some(_) => {
+                let content_range = format!("bytes 0-{}/{}", served.len() - 1, served.len());
+                TestResponse { status: 206, ..TestResponse::ok(served.clone()) }
+                    .with_header("Content-Range", &content_range)
+            }
+            None => TestResponse::ok(served.clone()),
+        });
+
+        let database = temp_database();
+        let feed = Feed::new("Podcast".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        database.get_feed_repository().save_feed(&feed).await?;
+        let article = Article::new(feed.id.clone(), "Long".to_string(), Url::parse("http://example.com/long")?);
+        database.get_article_repository().save_article(&article).await?;
+        let enclosure = Enclosure::new(article.id.clone(), Url::parse(&server.url("/media/long.mp3"))?);
+        database.get_enclosure_repository().save_enclosures(std::slice::from_ref(&enclosure)).await?;
+
+        let service = service(&database);
+        let part = partial_path(&service.destination(&enclosure));
+        std::fs::create_dir_all(service.directory())?;
+        std::fs::write(&part, &body[..4000])?;
+
+        let downloaded = service.download(&enclosure).await?;
+        assert_eq!(std::fs::read(downloaded.local_path.unwrap())?, body);
+        let ranges: Vec<Option<String>> = server.requests().iter()
+            .map(|request| request.header("range").map(str::to_string))
+            .collect();
+        assert_eq!(ranges, vec![Some("bytes=4000-".to_string()), None]);
+        Ok(())
+    }
+}
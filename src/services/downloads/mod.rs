@@ -0,0 +1,3 @@
+mod download_service;
+
+pub use download_service::{DownloadProgress, DownloadService};
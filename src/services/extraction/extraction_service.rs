@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, RwLock};
+use anyhow::{bail, Result};
+use log::{debug, info};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Client;
+use url::Url;
+
+use crate::base::repository::{ArticleRepository, FeedRepository};
+use crate::models::article::{Article, ArticleId};
+use crate::models::feed::FeedId;
+use crate::services::extraction::extractor::extract_main_content;
+use crate::services::rss::FetchConfig;
+use crate::utils::html::decode_html;
+
+/// Pages larger than this are not articles worth waiting for
+const MAX_PAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Fetches the web pages of articles whose feeds only publish a summary and
+/// keeps their main content as the article's full text
+///
+/// Feeds opt in with [`Feed::fetch_full_text`](crate::models::feed::Feed) to
+/// have it done for every new article after a sync; any article can also be
+/// extracted on demand.
+pub struct ExtractionService {
+    article_repository: Arc<dyn ArticleRepository>,
+    feed_repository: Arc<dyn FeedRepository>,
+    client: RwLock<Client>,
+    /// Articles whose page is being fetched right now
+    extracting: Mutex<HashSet<ArticleId>>,
+}
+
+impl ExtractionService {
+    pub fn new(
+        article_repository: Arc<dyn ArticleRepository>,
+        feed_repository: Arc<dyn FeedRepository>,
+    ) -> Result<Self> {
+        Ok(Self {
+            article_repository,
+            feed_repository,
+            client: RwLock::new(fetch_client(&FetchConfig::default())?),
+            extracting: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Fetches pages through the proxy and with the timeouts of `config`
+    pub fn with_fetch_config(self, config: &FetchConfig) -> Result<Self> {
+        self.set_fetch_config(config)?;
+        Ok(self)
+    }
+
+    /// Changes the HTTP settings for pages fetched from now on
+    ///
+    /// On error (e.g. an unparseable proxy URL) the current settings stay in use.
+    pub fn set_fetch_config(&self, config: &FetchConfig) -> Result<()> {
+        *self.client.write().unwrap() = fetch_client(config)?;
+        Ok(())
+    }
+
+    /// Extracts the full text of the articles a sync just added to a feed, if
+    /// the feed opted in, returning how many were extracted
+    pub async fn extract_new_articles(&self, feed_id: &FeedId, article_ids: &[ArticleId]) -> Result<usize> {
+        let Some(feed) = self.feed_repository.get_feed_by_id(feed_id).await? else {
+            return Ok(0);
+        };
+        if !feed.fetch_full_text || article_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let mut extracted = 0;
+        for id in article_ids {
+            let Some(article) = self.article_repository.get_article(id).await? else { continue };
+            if article.full_content.is_some() {
+                continue;
+            }
+            match self.extract_article(&article).await {
+                Ok(_) => extracted += 1,
+                Err(e) => debug!("No full text for {}: {}", article.url, e),
+            }
+        }
+        if extracted > 0 {
+            info!("Extracted the full text of {} articles of feed {}", extracted, feed_id);
+        }
+        Ok(extracted)
+    }
+
+    /// Fetches the article's page and stores its main content, returning the
+    /// updated article
+    ///
+    /// Fails when the page cannot be loaded or has no recognisable article
+    /// text; whatever full text the article had is kept then.
+    pub async fn extract_article(&self, article: &Article) -> Result<Article> {
+        self.extracting.lock().unwrap().insert(article.id.clone());
+        let result = self.fetch_full_content(&article.url).await;
+        self.extracting.lock().unwrap().remove(&article.id);
+
+        let full_content = result?;
+        self.article_repository.set_full_content(&article.id, &full_content).await?;
+        let mut article = article.clone();
+        article.full_content = Some(full_content);
+        Ok(article)
+    }
+
+    /// Whether the article's page is being fetched right now
+    pub fn is_extracting(&self, id: &ArticleId) -> bool {
+        self.extracting.lock().unwrap().contains(id)
+    }
+
+    async fn fetch_full_content(&self, url: &Url) -> Result<String> {
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("not a web page");
+        }
+        let client = self.client.read().unwrap().clone();
+        let mut response = client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            bail!("HTTP {}", response.status());
+        }
+        let content_type = response.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let is_html = content_type.as_ref()
+            .is_none_or(|value| value.to_ascii_lowercase().contains("html"));
+        if !is_html {
+            bail!("not a web page");
+        }
+        if response.content_length().is_some_and(|length| length > MAX_PAGE_BYTES) {
+            bail!("larger than {} bytes", MAX_PAGE_BYTES);
+        }
+
+        // Relative links resolve against where redirects ended up
+        let page_url = response.url().clone();
+        let mut data = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if (data.len() + chunk.len()) as u64 > MAX_PAGE_BYTES {
+                bail!("larger than {} bytes", MAX_PAGE_BYTES);
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        match extract_main_content(&decode_html(&data, content_type.as_deref()), &page_url) {
+            Some(content) => Ok(content),
+            None => bail!("no article text found"),
+        }
+    }
+}
+
+fn fetch_client(config: &FetchConfig) -> Result<Client> {
+    Ok(config.client_builder()?.timeout(config.timeout).build()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::feed::Feed;
+    use crate::utils::test_support::{temp_database, TestResponse, TestServer};
+
+    const NEWS_ARTICLE: &str = include_str!("fixtures/news_article.html");
+
+    #[tokio::test]
+    async fn test_extracts_new_articles_of_opted_in_feeds() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/bridge", TestResponse::ok(NEWS_ARTICLE).with_header("Content-Type", "text/html; charset=utf-8"));
+        server.route("/cookies", TestResponse::ok("<html><body><p>Please accept cookies.</p></body></html>"));
+        server.route("/feed.pdf", TestResponse::ok(NEWS_ARTICLE).with_header("Content-Type", "application/pdf"));
+
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let feed = Feed::new("News".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        database.get_feed_repository().save_feed(&feed).await?;
+        server.route("/older", TestResponse::ok(NEWS_ARTICLE));
+        let mut new_articles = Vec::new();
+        for path in ["/older", "/bridge", "/cookies", "/feed.pdf", "/missing"] {
+            let article = Article::new(feed.id.clone(), path.to_string(), Url::parse(&server.url(path))?)
+                .with_summary("A teaser.".to_string());
+            article_repository.save_article(&article).await?;
+            new_articles.push(article.id);
+        }
+        // Stored by an earlier sync
+        new_articles.remove(0);
+
+        let service = ExtractionService::new(article_repository.clone(), database.get_feed_repository())?
+            .with_fetch_config(&FetchConfig::default().with_user_agent("Custom/1.0".to_string()))?;
+        // Feeds have to opt in
+        assert_eq!(service.extract_new_articles(&feed.id, &new_articles).await?, 0);
+        assert!(server.requests().is_empty());
+
+        let feed = feed.with_fetch_full_text(true);
+        database.get_feed_repository().update_feed(&feed).await?;
+        assert_eq!(service.extract_new_articles(&feed.id, &new_articles).await?, 1);
+
+        let bridge = article_repository.get_article_by_url(&server.url("/bridge")).await?.unwrap();
+        let full_content = bridge.full_content.as_deref().unwrap();
+        assert!(full_content.contains("Engineers replaced 1,200 bolts"));
+        assert!(full_content.contains(&format!("src=\"{}\"", server.url("/images/bridge.jpg"))));
+        assert!(!full_content.contains("Reader comment"));
+        assert_eq!(bridge.summary.as_deref(), Some("A teaser."));
+        assert!(server.requests().iter().all(|request| request.header("user-agent") == Some("Custom/1.0")));
+        assert!(server.requests().iter().all(|request| request.path != "/older"));
+
+        // Extracted articles are not fetched again after the next sync
+        let fetched = server.requests().len();
+        assert_eq!(service.extract_new_articles(&feed.id, &new_articles).await?, 0);
+        assert_eq!(server.requests().len(), fetched + 3);
+
+        let cookies = article_repository.get_article_by_url(&server.url("/cookies")).await?.unwrap();
+        assert!(service.extract_article(&cookies).await.is_err());
+        assert!(!service.is_extracting(&cookies.id));
+        assert!(article_repository.get_article(&cookies.id).await?.unwrap().full_content.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_extracts_pages_in_their_declared_charset() -> Result<()> {
+        let server = TestServer::start().await;
+        let page = NEWS_ARTICLE.replace("The harbour bridge", "The caf\u{e9} by the harbour bridge");
+        let (latin1, _, _) = encoding_rs::WINDOWS_1252.encode(&page);
+        server.route("/bridge", TestResponse::ok(latin1.into_owned()).with_header("Content-Type", "text/html; charset=ISO-8859-1"));
+
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let feed = Feed::new("News".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        database.get_feed_repository().save_feed(&feed).await?;
+        let article = Article::new(feed.id.clone(), "Bridge".to_string(), Url::parse(&server.url("/bridge"))?);
+        article_repository.save_article(&article).await?;
+
+        let service = ExtractionService::new(article_repository.clone(), database.get_feed_repository())?;
+        service.extract_article(&article).await?;
+        let stored = article_repository.get_article(&article.id).await?.unwrap();
+        assert!(stored.full_content.unwrap().contains("The caf\u{e9} by the harbour bridge"));
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+use url::Url;
+
+use crate::services::rss::rewrite_content;
+use crate::utils::html::{escape_html, tokenize, Attributes, Token, RAW_TEXT_ELEMENTS, VOID_ELEMENTS};
+
+/// Less text than this is a teaser, a cookie wall or an error page, not an article
+const MIN_TEXT_LENGTH: usize = 250;
+
+/// Paragraphs shorter than this say too little to count towards their container
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Elements holding page chrome rather than the article, removed with their content
+const STRIPPED_ELEMENTS: &[&str] = &[
+    "aside", "button", "dialog", "embed", "footer", "form", "head", "header", "input", "menu", "nav",
+    "object", "select", "svg", "template",
+];
+
+/// Elements that end a paragraph left open before them
+const BLOCK_ELEMENTS: &[&str] = &[
+    "address", "article", "aside", "blockquote", "div", "dl", "figure", "footer", "form", "h1", "h2",
+    "h3", "h4", "h5", "h6", "header", "hr", "main", "nav", "ol", "p", "pre", "section", "table", "ul",
+];
+
+/// Class and id fragments of comment sections, sidebars, ads and other page chrome
+const UNLIKELY_NAMES: &[&str] = &[
+    "ad-break", "advert", "banner", "breadcrumb", "combx", "comment", "community", "cookie", "disqus",
+    "footer", "menu", "modal", "newsletter", "pager", "pagination", "popup", "related", "remark",
+    "share", "sidebar", "social", "sponsor", "subscribe",
+];
+
+/// Fragments that keep an element matching [`UNLIKELY_NAMES`] in the running
+const MAYBE_NAMES: &[&str] = &["article", "body", "column", "content", "main", "post", "story"];
+
+/// Class and id fragments that make an element more likely to hold the article
+const POSITIVE_NAMES: &[&str] = &[
+    "article", "blog", "body", "content", "entry", "hentry", "main", "page", "post", "story", "text",
+];
+
+/// Class and id fragments that make an element less likely to hold the article
+const NEGATIVE_NAMES: &[&str] = &[
+    "comment", "footer", "footnote", "masthead", "meta", "outbrain", "promo", "related", "share",
+    "shoutbox", "sidebar", "social", "sponsor", "widget",
+];
+
+/// An element or a run of text in a parsed page
+struct Node {
+    /// Tag name, or `None` for text
+    name: Option<String>,
+    attributes: Attributes,
+    /// Text as it appeared in the page, entities and all
+    text: String,
+    parent: usize,
+    children: Vec<usize>,
+}
+
+impl Node {
+    fn element(name: String, attributes: Attributes, parent: usize) -> Self {
+        Self { name: Some(name), attributes, text: String::new(), parent, children: Vec::new() }
+    }
+
+    fn is(&self, name: &str) -> bool {
+        self.name.as_deref() == Some(name)
+    }
+
+    /// The element's class and id, lowercased, for matching against name fragments
+    fn class_and_id(&self) -> String {
+        self.attributes.iter()
+            .filter(|(key, _)| key == "class" || key == "id")
+            .map(|(_, value)| value.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A parsed page with what scoring needs to know about each node, indexed like `nodes`
+///
+/// Children always come after their parent, so one pass in either direction
+/// sees every parent before or after all of its descendants.
+struct Page {
+    nodes: Vec<Node>,
+    /// Page chrome, and everything inside it
+    removed: Vec<bool>,
+    text_len: Vec<usize>,
+    /// Length of the text inside links
+    link_len: Vec<usize>,
+    commas: Vec<usize>,
+}
+
+impl Page {
+    fn parse(html: &str) -> Self {
+        let nodes = parse_nodes(html);
+        let mut removed = vec![false; nodes.len()];
+        for id in 1..nodes.len() {
+            let node = &nodes[id];
+            removed[id] = removed[node.parent]
+                || node.name.as_deref().is_some_and(|name| STRIPPED_ELEMENTS.contains(&name))
+                || is_unlikely(node);
+        }
+
+        let mut text_len = vec![0; nodes.len()];
+        let mut link_len = vec![0; nodes.len()];
+        let mut commas = vec![0; nodes.len()];
+        for id in (1..nodes.len()).rev() {
+            if removed[id] {
+                continue;
+            }
+            let node = &nodes[id];
+            if node.name.is_none() {
+                text_len[id] = node.text.split_whitespace().map(|word| word.chars().count() + 1).sum();
+                commas[id] = node.text.matches(',').count();
+            }
+            if node.is("a") {
+                link_len[id] = text_len[id];
+            }
+            text_len[node.parent] += text_len[id];
+            link_len[node.parent] += link_len[id];
+            commas[node.parent] += commas[id];
+        }
+
+        Self { nodes, removed, text_len, link_len, commas }
+    }
+
+    fn link_density(&self, id: usize) -> f32 {
+        match self.text_len[id] {
+            0 => 0.0,
+            len => self.link_len[id] as f32 / len as f32,
+        }
+    }
+
+    /// Whether the element is a paragraph of its own: a `p` and alike, or a
+    /// container holding only text and inline elements
+    fn is_paragraph(&self, id: usize) -> bool {
+        let node = &self.nodes[id];
+        match node.name.as_deref() {
+            Some("p" | "pre" | "td" | "blockquote") => true,
+            Some("div" | "section" | "article") => !node.children.iter().any(|&child| {
+                self.nodes[child].name.as_deref().is_some_and(|name| BLOCK_ELEMENTS.contains(&name))
+            }),
+            _ => false,
+        }
+    }
+
+    /// Scores of the elements that contain paragraphs, discounted by how much of
+    /// their text is links
+    ///
+    /// Each paragraph gives its parent a point, one per comma and one per 100
+    /// characters up to three, and its grandparent half of that.
+    fn scores(&self) -> Vec<Option<f32>> {
+        let mut scores: Vec<Option<f32>> = vec![None; self.nodes.len()];
+        for id in 1..self.nodes.len() {
+            if self.removed[id] || self.text_len[id] < MIN_PARAGRAPH_LENGTH || !self.is_paragraph(id) {
+                continue;
+            }
+            let score = 1.0 + self.commas[id] as f32 + (self.text_len[id] / 100).min(3) as f32;
+            let parent = self.nodes[id].parent;
+            for (ancestor, divider) in [(parent, 1.0), (self.nodes[parent].parent, 2.0)] {
+                if ancestor == 0 {
+                    break;
+                }
+                *scores[ancestor].get_or_insert_with(|| initial_score(&self.nodes[ancestor])) += score / divider;
+            }
+        }
+
+        for (id, score) in scores.iter_mut().enumerate() {
+            if let Some(score) = score {
+                *score *= 1.0 - self.link_density(id);
+            }
+        }
+        scores
+    }
+
+    /// Lists, tables and boxes inside the article that are mostly links or
+    /// marked as page chrome
+    fn is_boilerplate(&self, id: usize) -> bool {
+        let node = &self.nodes[id];
+        match node.name.as_deref() {
+            Some("div" | "section" | "ul" | "ol" | "table") => {
+                self.link_density(id) > 0.5 || class_weight(node) < 0.0
+            }
+            Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => class_weight(node) < 0.0,
+            _ => false,
+        }
+    }
+
+    /// Writes an element and what it holds, minus boilerplate and the
+    /// attributes only the page's own styles need
+    ///
+    /// Pages can nest elements thousands deep, so the walk keeps its own
+    /// stack: `Some(id)` writes a node, `None` closes the element it opened.
+    fn write_node(&self, output: &mut String, id: usize) {
+        let mut stack = vec![Some(id)];
+        let mut open: Vec<&str> = Vec::new();
+        while let Some(step) = stack.pop() {
+            let Some(id) = step else {
+                let name = open.pop().unwrap();
+                output.push_str(&format!("</{}>", name));
+                continue;
+            };
+            if self.removed[id] || self.is_boilerplate(id) {
+                continue;
+            }
+            let node = &self.nodes[id];
+            let Some(name) = &node.name else {
+                output.push_str(&node.text);
+                continue;
+            };
+
+            output.push('<');
+            output.push_str(name);
+            for (key, value) in &node.attributes {
+                if !matches!(key.as_str(), "class" | "id" | "style") {
+                    output.push_str(&format!(" {}=\"{}\"", key, escape_html(value)));
+                }
+            }
+            output.push('>');
+            if !VOID_ELEMENTS.contains(&name.as_str()) {
+                open.push(name);
+                stack.push(None);
+            }
+            stack.extend(node.children.iter().rev().map(|&child| Some(child)));
+        }
+    }
+}
+
+/// Finds the main content of a web page the way reader modes do
+///
+/// Navigation, comments, sidebars and alike are dropped by element and by
+/// class or id, then the element whose paragraphs hold the most text, and
+/// the fewest links, is taken together with siblings that score close to
+/// it. Relative URLs are resolved against `base`. Pages without enough text
+/// to be an article give `None`.
+pub fn extract_main_content(html: &str, base: &Url) -> Option<String> {
+    let page = Page::parse(html);
+    let scores = page.scores();
+    let (top, top_score) = scores.iter()
+        .enumerate()
+        .filter_map(|(id, score)| score.map(|score| (id, score)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+    let parent = page.nodes[top].parent;
+    let threshold = (top_score * 0.2).max(10.0);
+    let selected: Vec<usize> = if parent == 0 {
+        vec![top]
+    } else {
+        page.nodes[parent].children.iter()
+            .copied()
+            .filter(|&id| !page.removed[id])
+            .filter(|&id| {
+                id == top
+                    || scores[id].is_some_and(|score| score >= threshold)
+                    || (page.nodes[id].is("p") && page.text_len[id] > 80 && page.link_density(id) < 0.25)
+            })
+            .collect()
+    };
+
+    if selected.iter().map(|&id| page.text_len[id]).sum::<usize>() < MIN_TEXT_LENGTH {
+        return None;
+    }
+
+    let mut content = String::new();
+    for id in selected {
+        page.write_node(&mut content, id);
+    }
+    Some(rewrite_content(content.trim(), base))
+}
+
+/// Builds the element tree, closing what the page leaves open and skipping
+/// comments, doctypes and the bodies of scripts and styles
+fn parse_nodes(html: &str) -> Vec<Node> {
+    let mut nodes = vec![Node::element("#document".to_string(), Vec::new(), 0)];
+    let mut open = vec![0];
+
+    for token in tokenize(html) {
+        let (name, attributes, self_closing) = match token {
+            Token::Text(text) => {
+                push_text(&mut nodes, *open.last().unwrap(), &text.replace('<', "&lt;"));
+                continue;
+            }
+            Token::RawText(_) | Token::Comment(_) => continue,
+            Token::EndTag { name, .. } => {
+                // End tags for elements that are not open are ignored
+                if let Some(position) = open.iter().rposition(|&id| nodes[id].is(&name)).filter(|&position| position > 0) {
+                    open.truncate(position);
+                }
+                continue;
+            }
+            Token::StartTag(tag) => (tag.name, tag.attributes, tag.self_closing),
+        };
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+            continue;
+        }
+
+        let current = &nodes[*open.last().unwrap()];
+        if (current.is("p") && BLOCK_ELEMENTS.contains(&name.as_str())) || (current.is("li") && name == "li") {
+            open.pop();
+        }
+        let parent = *open.last().unwrap();
+        let id = nodes.len();
+        let void = self_closing || VOID_ELEMENTS.contains(&name.as_str());
+        nodes.push(Node::element(name, attributes, parent));
+        nodes[parent].children.push(id);
+        if !void {
+            open.push(id);
+        }
+    }
+    nodes
+}
+
+fn push_text(nodes: &mut Vec<Node>, parent: usize, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let id = nodes.len();
+    nodes.push(Node { name: None, attributes: Vec::new(), text: text.to_string(), parent, children: Vec::new() });
+    nodes[parent].children.push(id);
+}
+
+/// Comment sections, sidebars and alike, unless their names also suggest content
+fn is_unlikely(node: &Node) -> bool {
+    if node.name.is_none() || ["html", "body", "article", "main"].iter().any(|name| node.is(name)) {
+        return false;
+    }
+    let names = node.class_and_id();
+    UNLIKELY_NAMES.iter().any(|name| names.contains(name)) && !MAYBE_NAMES.iter().any(|name| names.contains(name))
+}
+
+/// What an element scores before its paragraphs are counted
+fn initial_score(node: &Node) -> f32 {
+    let tag_score = match node.name.as_deref() {
+        Some("div") => 5.0,
+        Some("pre" | "td" | "blockquote") => 3.0,
+        Some("address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form") => -3.0,
+        Some("h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th") => -5.0,
+        _ => 0.0,
+    };
+    tag_score + class_weight(node)
+}
+
+/// How much the element's class and id suggest content (positive) or chrome (negative)
+fn class_weight(node: &Node) -> f32 {
+    node.attributes.iter()
+        .filter(|(key, _)| key == "class" || key == "id")
+        .map(|(_, value)| {
+            let value = value.to_ascii_lowercase();
+            if NEGATIVE_NAMES.iter().any(|name| value.contains(name)) {
+                -25.0
+            } else if POSITIVE_NAMES.iter().any(|name| value.contains(name)) {
+                25.0
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWS_ARTICLE: &str = include_str!("fixtures/news_article.html");
+
+    #[test]
+    fn test_extracts_article_from_page_chrome() {
+        let base = Url::parse("https://news.example.com/2026/10/harbour-bridge").unwrap();
+        let content = extract_main_content(NEWS_ARTICLE, &base).unwrap();
+
+        for expected in [
+            "The harbour bridge reopened on Monday",
+            "Engineers replaced 1,200 bolts",
+            "<img src=\"https://news.example.com/images/bridge.jpg\" alt=\"The bridge at dawn\">",
+            "<blockquote>",
+            "Traffic is expected to return to normal",
+        ] {
+            assert!(content.contains(expected), "expected {:?} in {}", expected, content);
+        }
+        for unwanted in [
+            "Subscribe to our newsletter", "Top stories", "Reader comment", "Most read",
+            "All rights reserved", "<script", "trackPageView", "class=", "Also in Local news",
+        ] {
+            assert!(!content.contains(unwanted), "unexpected {:?} in {}", unwanted, content);
+        }
+    }
+
+    #[test]
+    fn test_pages_without_an_article_give_nothing() {
+        let base = Url::parse("https://example.com/").unwrap();
+        assert_eq!(extract_main_content("<html><body><p>Please enable cookies to continue.</p></body></html>", &base), None);
+        assert_eq!(extract_main_content("", &base), None);
+        let links = "<ul>".to_string() + &"<li><a href=\"/a\">A link to another page of this site</a></li>".repeat(20) + "</ul>";
+        assert_eq!(extract_main_content(&links, &base), None);
+    }
+
+    #[test]
+    fn test_writes_deeply_nested_elements() {
+        let base = Url::parse("https://example.com/").unwrap();
+        let paragraph = "<p>The council met on Tuesday, and again on Thursday, to settle the budget for the coming year.</p>";
+        let html = format!(
+            "<article>{}<div>{}deep{}</div></article>",
+            paragraph.repeat(5), "<span>".repeat(100_000), "</span>".repeat(100_000)
+        );
+        let content = extract_main_content(&html, &base).unwrap();
+        assert!(content.contains("<span>deep</span>"));
+        assert!(content.ends_with("</span></div></article>"));
+    }
+}
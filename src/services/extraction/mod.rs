@@ -0,0 +1,5 @@
+mod extraction_service;
+mod extractor;
+
+pub use extraction_service::ExtractionService;
+pub use extractor::extract_main_content;
@@ -1,11 +1,25 @@
 pub mod article;
+pub mod assets;
+pub mod downloads;
+pub mod extraction;
 pub mod opml;
+pub mod read_state;
+pub mod retention;
 pub mod rss;
+pub mod smart_folders;
 pub mod sync;
+pub mod unread_counts;
 pub mod webview;
 
 pub use article::*;
+pub use assets::*;
+pub use downloads::*;
+pub use extraction::*;
 pub use opml::*;
+pub use read_state::*;
+pub use retention::*;
 pub use rss::*;
+pub use smart_folders::*;
 pub use sync::*;
+pub use unread_counts::*;
 pub use webview::*;
\ No newline at end of file
@@ -0,0 +1,215 @@
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+use crate::models::category::{Category, CategoryId};
+use crate::models::feed::{Feed, FeedId};
+use crate::services::opml::{OpmlDocument, OpmlOutline};
+
+/// Whether an import only reports what it would do or also does it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    DryRun,
+    Apply,
+    /// Apply, fetching each new feed first and recording the ones that fail
+    ApplyAndVerify,
+}
+
+/// How a feed outline compares to the existing subscriptions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportStatus {
+    New,
+    /// Already subscribed in the same category, or listed outside any folder
+    Existing(FeedId),
+    /// Already subscribed, but in a different category than the outline's folder
+    MovedCategory { feed_id: FeedId, from: Option<CategoryId> },
+    InvalidUrl(String),
+    /// Same feed as an earlier outline in the file
+    Duplicate,
+}
+
+/// A feed outline of the imported file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportEntry {
+    pub title: String,
+    pub xml_url: String,
+    pub html_url: Option<String>,
+    /// Names of the folders the outline is in, outermost first
+    pub folder: Vec<String>,
+    pub status: ImportStatus,
+}
+
+/// What importing an OPML file would change
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub entries: Vec<ImportEntry>,
+    /// Every folder in the file, parents before their subfolders
+    pub folders: Vec<Vec<String>>,
+    /// Folders without a matching category, which the import creates
+    pub new_folders: Vec<Vec<String>>,
+}
+
+impl ImportPlan {
+    /// Compares the feed outlines of `document` to the existing feeds by normalized URL
+    pub fn build(document: &OpmlDocument, feeds: &[Feed], categories: &[Category]) -> Self {
+        let category_ids = category_paths(categories);
+        let subscribed: HashMap<String, &Feed> = feeds.iter()
+            .filter_map(|feed| normalize_feed_url(feed.url.as_str()).ok().map(|url| (url, feed)))
+            .collect();
+
+        let mut plan = Self::default();
+        let mut seen = HashSet::new();
+        let mut pending: Vec<(&OpmlOutline, Vec<String>)> = document.outlines.iter()
+            .rev()
+            .map(|outline| (outline, Vec::new()))
+            .collect();
+
+        while let Some((outline, folder)) = pending.pop() {
+            let children_folder = match &outline.xml_url {
+                Some(xml_url) => {
+                    let status = match normalize_feed_url(xml_url) {
+                        Err(e) => ImportStatus::InvalidUrl(e.to_string()),
+                        Ok(url) if !seen.insert(url.clone()) => ImportStatus::Duplicate,
+                        Ok(url) => match subscribed.get(&url) {
+                            None => ImportStatus::New,
+                            Some(feed) => {
+                                // A feed outside any folder says nothing about where it belongs
+                                let target = category_ids.get(&folder_key(&folder));
+                                if folder.is_empty() || feed.category_id.as_ref() == target {
+                                    ImportStatus::Existing(feed.id.clone())
+                                } else {
+                                    ImportStatus::MovedCategory {
+                                        feed_id: feed.id.clone(),
+                                        from: feed.category_id.clone(),
+                                    }
+                                }
+                            }
+                        },
+                    };
+                    plan.entries.push(ImportEntry {
+                        title: outline.text.clone(),
+                        xml_url: xml_url.clone(),
+                        html_url: outline.html_url.clone(),
+                        folder: folder.clone(),
+                        status,
+                    });
+                    folder
+                }
+                None => {
+                    let mut path = folder;
+                    path.push(outline.text.clone());
+                    if !plan.folders.iter().any(|known| folder_key(known) == folder_key(&path)) {
+                        if !category_ids.contains_key(&folder_key(&path)) {
+                            plan.new_folders.push(path.clone());
+                        }
+                        plan.folders.push(path.clone());
+                    }
+                    path
+                }
+            };
+
+            for child in outline.children.iter().rev() {
+                pending.push((child, children_folder.clone()));
+            }
+        }
+
+        plan
+    }
+
+    pub fn new_feeds(&self) -> impl Iterator<Item = &ImportEntry> {
+        self.entries.iter().filter(|entry| entry.status == ImportStatus::New)
+    }
+
+    pub fn existing_feeds(&self) -> impl Iterator<Item = &ImportEntry> {
+        self.entries.iter().filter(|entry| matches!(entry.status, ImportStatus::Existing(_)))
+    }
+
+    pub fn moved_feeds(&self) -> impl Iterator<Item = &ImportEntry> {
+        self.entries.iter().filter(|entry| matches!(entry.status, ImportStatus::MovedCategory { .. }))
+    }
+
+    pub fn invalid_feeds(&self) -> impl Iterator<Item = &ImportEntry> {
+        self.entries.iter().filter(|entry| matches!(entry.status, ImportStatus::InvalidUrl(_)))
+    }
+}
+
+/// The outcome of [`OpmlService::import`](crate::services::opml::OpmlService::import)
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    pub plan: ImportPlan,
+    pub created_feeds: Vec<Feed>,
+    pub created_categories: Vec<Category>,
+    /// Existing feeds moved into the category of their folder
+    pub moved_feeds: Vec<Feed>,
+    /// New feeds that could not be fetched when verifying, with the error;
+    /// they are still subscribed, marked as failing
+    pub failed_feeds: Vec<(Feed, String)>,
+}
+
+/// Key identifying a feed URL regardless of scheme, `www.`, trailing slash and fragment
+pub fn normalize_feed_url(raw: &str) -> Result<String> {
+    let url = Url::parse(raw.trim())?;
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Unsupported URL scheme '{}'", url.scheme());
+    }
+    let host = match url.host_str() {
+        Some(host) => host.strip_prefix("www.").unwrap_or(host),
+        None => bail!("URL has no host"),
+    };
+
+    let mut key = host.to_string();
+    if let Some(port) = url.port() {
+        key.push_str(&format!(":{}", port));
+    }
+    key.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query() {
+        key.push('?');
+        key.push_str(query);
+    }
+    Ok(key)
+}
+
+/// Case-insensitive key for a folder path
+pub(crate) fn folder_key(path: &[String]) -> Vec<String> {
+    path.iter().map(|name| name.to_lowercase()).collect()
+}
+
+/// Maps the folder path of every category to its id
+pub(crate) fn category_paths(categories: &[Category]) -> HashMap<Vec<String>, CategoryId> {
+    let by_id: HashMap<&CategoryId, &Category> = categories.iter().map(|category| (&category.id, category)).collect();
+
+    categories.iter()
+        .filter_map(|category| {
+            let mut path = vec![category.name.to_lowercase()];
+            let mut parent_id = category.parent_id.as_ref();
+            while let Some(id) = parent_id {
+                // A missing parent or a cycle leaves the category without a usable path
+                let parent = by_id.get(id)?;
+                if path.len() > categories.len() {
+                    return None;
+                }
+                path.push(parent.name.to_lowercase());
+                parent_id = parent.parent_id.as_ref();
+            }
+            path.reverse();
+            Some((path, category.id.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_feed_url() -> Result<()> {
+        let key = normalize_feed_url("https://www.Example.com/feed/#top")?;
+        assert_eq!(key, "example.com/feed");
+        assert_eq!(normalize_feed_url("http://example.com/feed")?, key);
+        assert_ne!(normalize_feed_url("https://example.com/feed?page=2")?, key);
+        assert_ne!(normalize_feed_url("https://example.com:8080/feed")?, key);
+        assert!(normalize_feed_url("feed.xml").is_err());
+        assert!(normalize_feed_url("ftp://example.com/feed").is_err());
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
+mod import_plan;
+mod opml_document;
 mod opml_service;
 
-pub use opml_service::OpmlService;
\ No newline at end of file
+pub use import_plan::{normalize_feed_url, ImportEntry, ImportMode, ImportPlan, ImportReport, ImportStatus};
+pub use opml_document::{OpmlDocument, OpmlOutline};
+pub use opml_service::OpmlService;
@@ -0,0 +1,208 @@
+use anyhow::{anyhow, Result};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use std::io::Cursor;
+
+/// An entry in an OPML body: a feed if it has an `xmlUrl`, otherwise a folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpmlOutline {
+    pub text: String,
+    pub xml_url: Option<String>,
+    pub html_url: Option<String>,
+    pub children: Vec<OpmlOutline>,
+}
+
+impl OpmlOutline {
+    pub fn feed(text: String, xml_url: String, html_url: Option<String>) -> Self {
+        Self { text, xml_url: Some(xml_url), html_url, children: Vec::new() }
+    }
+
+    pub fn folder(text: String, children: Vec<OpmlOutline>) -> Self {
+        Self { text, xml_url: None, html_url: None, children }
+    }
+
+    pub fn is_feed(&self) -> bool {
+        self.xml_url.is_some()
+    }
+}
+
+/// An OPML subscription list as written by feed readers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpmlDocument {
+    pub title: Option<String>,
+    pub outlines: Vec<OpmlOutline>,
+}
+
+impl OpmlDocument {
+    /// Parses an OPML file, keeping the nesting of folder outlines
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut reader = Reader::from_str(content);
+        reader.trim_text(true);
+
+        let mut document = Self::default();
+        // Outlines that are still open, innermost last
+        let mut open: Vec<OpmlOutline> = Vec::new();
+        let mut in_title = false;
+
+        loop {
+            let event = reader.read_event()
+                .map_err(|e| anyhow!("Invalid OPML at position {}: {}", reader.buffer_position(), e))?;
+            match event {
+                Event::Start(e) if e.name().as_ref() == b"outline" => open.push(parse_outline(&e, &reader)?),
+                Event::Empty(e) if e.name().as_ref() == b"outline" => {
+                    let outline = parse_outline(&e, &reader)?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(outline),
+                        None => document.outlines.push(outline),
+                    }
+                }
+                Event::End(e) if e.name().as_ref() == b"outline" => {
+                    let outline = open.pop().ok_or_else(|| anyhow!("Unexpected </outline>"))?;
+                    match open.last_mut() {
+                        Some(parent) => parent.children.push(outline),
+                        None => document.outlines.push(outline),
+                    }
+                }
+                Event::Start(e) if e.name().as_ref() == b"title" && open.is_empty() => in_title = true,
+                Event::End(e) if e.name().as_ref() == b"title" => in_title = false,
+                Event::Text(text) if in_title => document.title = Some(text.unescape()?.into_owned()),
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+
+        if !open.is_empty() {
+            return Err(anyhow!("Unclosed <outline> element"));
+        }
+        Ok(document)
+    }
+
+    /// Writes the document as OPML 2.0
+    pub fn to_xml(&self) -> Result<String> {
+        let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+
+        let mut opml = BytesStart::new("opml");
+        opml.push_attribute(("version", "2.0"));
+        writer.write_event(Event::Start(opml))?;
+
+        writer.write_event(Event::Start(BytesStart::new("head")))?;
+        if let Some(title) = &self.title {
+            writer.write_event(Event::Start(BytesStart::new("title")))?;
+            writer.write_event(Event::Text(BytesText::new(title)))?;
+            writer.write_event(Event::End(BytesEnd::new("title")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("head")))?;
+
+        writer.write_event(Event::Start(BytesStart::new("body")))?;
+        for outline in &self.outlines {
+            write_outline(&mut writer, outline)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("body")))?;
+        writer.write_event(Event::End(BytesEnd::new("opml")))?;
+
+        Ok(String::from_utf8(writer.into_inner().into_inner())?)
+    }
+}
+
+fn parse_outline(element: &BytesStart, reader: &Reader<&[u8]>) -> Result<OpmlOutline> {
+    let mut text = None;
+    let mut title = None;
+    let mut xml_url = None;
+    let mut html_url = None;
+
+    for attr in element.attributes() {
+        let attr = attr?;
+        let value = attr.decode_and_unescape_value(reader)?.trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        match attr.key.as_ref() {
+            b"text" => text = Some(value),
+            b"title" => title = Some(value),
+            b"xmlUrl" | b"xmlurl" => xml_url = Some(value),
+            b"htmlUrl" | b"htmlurl" => html_url = Some(value),
+            _ => (),
+        }
+    }
+
+    // Readers disagree on whether the name goes in `text` or `title`
+    let text = text.or(title)
+        .or_else(|| xml_url.clone())
+        .unwrap_or_default();
+    Ok(OpmlOutline { text, xml_url, html_url, children: Vec::new() })
+}
+
+fn write_outline(writer: &mut Writer<Cursor<Vec<u8>>>, outline: &OpmlOutline) -> Result<()> {
+    let mut element = BytesStart::new("outline");
+    element.push_attribute(("text", outline.text.as_str()));
+    element.push_attribute(("title", outline.text.as_str()));
+    if let Some(xml_url) = &outline.xml_url {
+        element.push_attribute(("type", "rss"));
+        element.push_attribute(("xmlUrl", xml_url.as_str()));
+        if let Some(html_url) = &outline.html_url {
+            element.push_attribute(("htmlUrl", html_url.as_str()));
+        }
+    }
+
+    if outline.children.is_empty() {
+        writer.write_event(Event::Empty(element))?;
+    } else {
+        writer.write_event(Event::Start(element))?;
+        for child in &outline.children {
+            write_outline(writer, child)?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("outline")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested_folders_and_write_them_back() -> Result<()> {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <opml version="1.0">
+              <head><title>My &amp; Your Feeds</title></head>
+              <body>
+                <outline text="Tech">
+                  <outline title="Rust">
+                    <outline text="This Week in Rust" type="rss" xmlUrl="https://this-week-in-rust.org/rss.xml" htmlUrl="https://this-week-in-rust.org/"/>
+                  </outline>
+                  <outline text="LWN" xmlUrl="https://lwn.net/headlines/rss?a=1&amp;b=2"></outline>
+                </outline>
+                <outline text="Empty"/>
+                <outline xmlUrl="https://example.com/untitled.xml"/>
+              </body>
+            </opml>"#;
+
+        let document = OpmlDocument::parse(content)?;
+        assert_eq!(document, OpmlDocument {
+            title: Some("My & Your Feeds".to_string()),
+            outlines: vec![
+                OpmlOutline::folder("Tech".to_string(), vec![
+                    OpmlOutline::folder("Rust".to_string(), vec![
+                        OpmlOutline::feed(
+                            "This Week in Rust".to_string(),
+                            "https://this-week-in-rust.org/rss.xml".to_string(),
+                            Some("https://this-week-in-rust.org/".to_string()),
+                        ),
+                    ]),
+                    OpmlOutline::feed("LWN".to_string(), "https://lwn.net/headlines/rss?a=1&b=2".to_string(), None),
+                ]),
+                OpmlOutline::folder("Empty".to_string(), Vec::new()),
+                OpmlOutline::feed(
+                    "https://example.com/untitled.xml".to_string(),
+                    "https://example.com/untitled.xml".to_string(),
+                    None,
+                ),
+            ],
+        });
+
+        assert_eq!(OpmlDocument::parse(&document.to_xml()?)?, document);
+        assert!(OpmlDocument::parse("<opml><body><outline text=\"open\"></body></opml>").is_err());
+        Ok(())
+    }
+}
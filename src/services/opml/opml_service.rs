@@ -1,19 +1,14 @@
 use anyhow::Result;
-use quick_xml::events::{BytesEnd, BytesStart, Event};
-use quick_xml::Reader;
-use quick_xml::Writer;
+use log::{info, warn};
 use url::Url;
-use std::io::Cursor;
-use crate::models::feed::{Feed, FeedId, FeedStatus};
-use crate::services::rss::RssService;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-#[derive(Debug)]
-struct OpmlOutline {
-    text: String,
-    html_url: Option<String>,
-    xml_url: Option<String>,
-}
+use crate::models::category::{Category, CategoryId};
+use crate::models::feed::{Feed, FeedStatus};
+use crate::services::opml::import_plan::{category_paths, folder_key};
+use crate::services::opml::{ImportEntry, ImportMode, ImportPlan, ImportReport, ImportStatus, OpmlDocument, OpmlOutline};
+use crate::services::rss::RssService;
 
 pub struct OpmlService {
     rss_service: Arc<RssService>,
@@ -24,58 +19,313 @@ impl OpmlService {
         Self { rss_service }
     }
 
-    pub fn import_opml(&self, content: &str) -> Result<Vec<Feed>> {
-        let mut reader = Reader::from_str(content);
-        reader.trim_text(true);
-        
-        let mut feeds = Vec::new();
-        let mut buf = Vec::new();
-        
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Empty(e)) | Ok(Event::Start(e)) => {
-                    if e.name().as_ref() == b"outline" {
-                        if let Some(outline) = self.parse_outline(&e) {
-                            if let Some(xml_url) = outline.xml_url {
-                                let feed = Feed::new(
-                                    outline.text,
-                                    Url::parse(&xml_url)?
-                                );
-                                feeds.push(feed);
-                            }
-                        }
+    /// Compares an OPML file to the existing subscriptions without changing anything
+    pub async fn plan_import(&self, content: &str) -> Result<ImportPlan> {
+        let document = OpmlDocument::parse(content)?;
+        let feeds = self.rss_service.get_all_feeds().await?;
+        let categories = self.rss_service.get_all_categories().await?;
+        Ok(ImportPlan::build(&document, &feeds, &categories))
+    }
+
+    /// Imports the feeds of an OPML file, creating a category for each folder outline
+    ///
+    /// Feeds are matched to existing ones by normalized URL and folders to
+    /// categories by name under the same parent, so importing the same file
+    /// twice changes nothing. Existing feeds found in a different folder are
+    /// moved to its category; those listed outside any folder stay where they
+    /// are. With [`ImportMode::DryRun`] only the plan is returned.
+    pub async fn import(&self, content: &str, mode: ImportMode) -> Result<ImportReport> {
+        let plan = self.plan_import(content).await?;
+        let mut report = ImportReport::default();
+        if mode == ImportMode::DryRun {
+            report.plan = plan;
+            return Ok(report);
+        }
+
+        let mut category_ids = category_paths(&self.rss_service.get_all_categories().await?);
+        for folder in &plan.folders {
+            if category_ids.contains_key(&folder_key(folder)) {
+                continue;
+            }
+            let mut category = Category::new(folder[folder.len() - 1].clone());
+            if let Some(parent_id) = category_ids.get(&folder_key(&folder[..folder.len() - 1])) {
+                category = category.with_parent(parent_id.clone());
+            }
+            self.rss_service.save_category(&category).await?;
+            category_ids.insert(folder_key(folder), category.id.clone());
+            report.created_categories.push(category);
+        }
+
+        for entry in &plan.entries {
+            let category_id = category_ids.get(&folder_key(&entry.folder)).cloned();
+            match &entry.status {
+                ImportStatus::New => {
+                    let (feed, error) = self.import_feed(entry, category_id, mode == ImportMode::ApplyAndVerify).await?;
+                    if let Some(error) = error {
+                        report.failed_feeds.push((feed.clone(), error));
+                    }
+                    report.created_feeds.push(feed);
+                }
+                ImportStatus::MovedCategory { feed_id, .. } => {
+                    if let Some(mut feed) = self.rss_service.get_feed_by_id(feed_id).await? {
+                        feed.category_id = category_id;
+                        self.rss_service.update_feed(&feed).await?;
+                        report.moved_feeds.push(feed);
                     }
                 }
-                Ok(Event::Eof) => break,
-                Err(e) => return Err(anyhow::anyhow!("Error at position {}: {:?}", reader.buffer_position(), e)),
-                _ => (),
+                ImportStatus::InvalidUrl(e) => warn!("Skipping '{}' with invalid feed URL {}: {}", entry.title, entry.xml_url, e),
+                ImportStatus::Existing(_) | ImportStatus::Duplicate => {}
             }
-            buf.clear();
         }
-        
-        Ok(feeds)
-    }
-
-    fn parse_outline(&self, element: &BytesStart) -> Option<OpmlOutline> {
-        let mut text = None;
-        let mut xml_url = None;
-        let mut html_url = None;
-        
-        for attr in element.attributes() {
-            if let Ok(attr) = attr {
-                match attr.key.as_ref() {
-                    b"text" => text = String::from_utf8(attr.value.to_vec()).ok(),
-                    b"xmlUrl" => xml_url = String::from_utf8(attr.value.to_vec()).ok(),
-                    b"htmlUrl" => html_url = String::from_utf8(attr.value.to_vec()).ok(),
-                    _ => (),
-                }
+
+        info!(
+            "Imported OPML: {} feeds and {} categories created, {} feeds moved, {} failed to fetch",
+            report.created_feeds.len(),
+            report.created_categories.len(),
+            report.moved_feeds.len(),
+            report.failed_feeds.len(),
+        );
+        report.plan = plan;
+        Ok(report)
+    }
+
+    /// Subscribes to a new feed from the file, returning it with the fetch error if verifying failed
+    async fn import_feed(
+        &self,
+        entry: &ImportEntry,
+        category_id: Option<CategoryId>,
+        verify: bool,
+    ) -> Result<(Feed, Option<String>)> {
+        let mut feed = Feed::new(entry.title.clone(), Url::parse(entry.xml_url.trim())?);
+        if let Some(category_id) = category_id {
+            feed = feed.with_category(category_id);
+        }
+        if let Some(site_url) = entry.html_url.as_deref().and_then(|url| Url::parse(url).ok()) {
+            feed = feed.with_site_url(site_url);
+        }
+
+        let mut error = None;
+        if verify {
+            if let Err(e) = self.rss_service.fetch_feed(feed.url.as_str()).await {
+                warn!("Imported feed {} could not be fetched: {}", feed.url, e);
+                feed.update_status(FeedStatus::Error);
+                feed.update_error_message(e.to_string());
+                error = Some(e.to_string());
             }
         }
-        
-        text.map(|t| OpmlOutline {
-            text: t,
-            xml_url,
-            html_url,
-        })
-    }
-}
\ No newline at end of file
+
+        self.rss_service.save_feed(&feed).await?;
+        Ok((feed, error))
+    }
+
+    /// Exports all feeds as OPML, with categories as nested folder outlines
+    pub async fn export(&self) -> Result<String> {
+        let categories = self.rss_service.get_all_categories().await?;
+        let feeds = self.rss_service.get_all_feeds().await?;
+        build_document(&categories, &feeds).to_xml()
+    }
+}
+
+fn build_document(categories: &[Category], feeds: &[Feed]) -> OpmlDocument {
+    let known: HashSet<&CategoryId> = categories.iter().map(|category| &category.id).collect();
+    // Anything pointing at a category that no longer exists goes to the top level
+    let existing = |id: &Option<CategoryId>| id.clone().filter(|id| known.contains(id));
+
+    let mut children: HashMap<Option<CategoryId>, Vec<&Category>> = HashMap::new();
+    for category in categories {
+        children.entry(existing(&category.parent_id)).or_default().push(category);
+    }
+    let mut feeds_by_category: HashMap<Option<CategoryId>, Vec<&Feed>> = HashMap::new();
+    for feed in feeds {
+        feeds_by_category.entry(existing(&feed.category_id)).or_default().push(feed);
+    }
+
+    let mut visited = HashSet::new();
+    OpmlDocument {
+        title: Some("Panda subscriptions".to_string()),
+        outlines: folder_outlines(&None, &children, &feeds_by_category, &mut visited),
+    }
+}
+
+/// Outlines for the subcategories and feeds of a category, or of the top level
+fn folder_outlines<'a>(
+    parent_id: &Option<CategoryId>,
+    children: &HashMap<Option<CategoryId>, Vec<&'a Category>>,
+    feeds: &HashMap<Option<CategoryId>, Vec<&Feed>>,
+    visited: &mut HashSet<&'a CategoryId>,
+) -> Vec<OpmlOutline> {
+    let mut outlines = Vec::new();
+    for category in children.get(parent_id).into_iter().flatten() {
+        if !visited.insert(&category.id) {
+            warn!("Category '{}' is its own ancestor, leaving it out of the export", category.name);
+            continue;
+        }
+        let contents = folder_outlines(&Some(category.id.clone()), children, feeds, visited);
+        outlines.push(OpmlOutline::folder(category.name.clone(), contents));
+    }
+    for feed in feeds.get(parent_id).into_iter().flatten() {
+        outlines.push(OpmlOutline::feed(
+            feed.title.clone(),
+            feed.url.to_string(),
+            feed.site_url.as_ref().map(|url| url.to_string()),
+        ));
+    }
+    outlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Database;
+    use crate::utils::test_support::{temp_database, TestResponse, TestServer};
+
+    fn service(database: &Database) -> OpmlService {
+        OpmlService::new(Arc::new(RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        )))
+    }
+
+    /// Outlines in a canonical order with URLs in the form `Url` writes them
+    fn normalized(outlines: &[OpmlOutline]) -> Vec<OpmlOutline> {
+        let url = |value: &Option<String>| value.as_deref().map(|value| Url::parse(value).unwrap().to_string());
+        let mut outlines: Vec<OpmlOutline> = outlines.iter()
+            .map(|outline| OpmlOutline {
+                text: outline.text.clone(),
+                xml_url: url(&outline.xml_url),
+                html_url: url(&outline.html_url),
+                children: normalized(&outline.children),
+            })
+            .collect();
+        outlines.sort_by(|a, b| (a.is_feed(), &a.text).cmp(&(b.is_feed(), &b.text)));
+        outlines
+    }
+
+    fn count_feeds(outlines: &[OpmlOutline]) -> usize {
+        outlines.iter()
+            .map(|outline| usize::from(outline.is_feed()) + count_feeds(&outline.children))
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_round_trip_real_world_files() -> Result<()> {
+        let fixtures = [
+            ("feedly", include_str!("fixtures/feedly.opml")),
+            ("inoreader", include_str!("fixtures/inoreader.opml")),
+            ("netnewswire", include_str!("fixtures/netnewswire.opml")),
+            ("liferea", include_str!("fixtures/liferea.opml")),
+        ];
+
+        for (name, content) in fixtures {
+            let database = temp_database();
+            let service = service(&database);
+            let original = OpmlDocument::parse(content)?;
+
+            let report = service.import(content, ImportMode::Apply).await?;
+            assert_eq!(report.created_feeds.len(), count_feeds(&original.outlines), "{}", name);
+
+            let exported = OpmlDocument::parse(&service.export().await?)?;
+            assert_eq!(normalized(&exported.outlines), normalized(&original.outlines), "{}", name);
+
+            // Importing again finds every folder and feed already there
+            let report = service.import(content, ImportMode::Apply).await?;
+            assert!(report.created_feeds.is_empty() && report.created_categories.is_empty(), "{}", name);
+            assert!(report.moved_feeds.is_empty(), "{}", name);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_links_feeds_to_nested_categories() -> Result<()> {
+        let database = temp_database();
+        let report = service(&database).import(include_str!("fixtures/liferea.opml"), ImportMode::Apply).await?;
+        let (feeds, categories) = (report.created_feeds, report.created_categories);
+
+        let category = |name: &str| categories.iter().find(|category| category.name == name).unwrap();
+        let feed = |title: &str| feeds.iter().find(|feed| feed.title == title).unwrap();
+        assert_eq!(category("Linux").parent_id, None);
+        assert_eq!(category("Desktop").parent_id, Some(category("Linux").id.clone()));
+        assert_eq!(category("GNOME").parent_id, Some(category("Desktop").id.clone()));
+        assert_eq!(feed("Planet GNOME").category_id, Some(category("GNOME").id.clone()));
+        assert_eq!(feed("LWN.net").category_id, Some(category("Linux").id.clone()));
+        assert_eq!(feed("Planet Python").category_id, None);
+
+        let stored = database.get_feed_repository().get_feeds_by_category(&category("Distributions").id).await?;
+        assert_eq!(stored.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_plan_dry_run_and_apply() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/good.xml", TestResponse::ok(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Good</title></channel></rss>"#,
+        ));
+
+        let database = temp_database();
+        let service = service(&database);
+        service.import(include_str!("fixtures/feedly.opml"), ImportMode::Apply).await?;
+
+        // Rust Blog moves to a new folder, The GitHub Blog is written differently,
+        // Julia Evans is missing and is left alone
+        let content = format!(r#"<opml version="2.0"><body>
+            <outline text="Dev">
+              <outline text="The GitHub Blog" xmlUrl="http://www.github.blog/feed"/>
+              <outline text="Good" xmlUrl="{}"/>
+              <outline text="Gone" xmlUrl="{}"/>
+            </outline>
+            <outline text="Languages">
+              <outline text="Rust">
+                <outline text="Rust Blog" xmlUrl="https://blog.rust-lang.org/feed.xml"/>
+              </outline>
+            </outline>
+            <outline text="Broken" xmlUrl="not a url"/>
+            <outline text="Good again" xmlUrl="{}"/>
+          </body></opml>"#,
+            server.url("/good.xml"), server.url("/gone.xml"), server.url("/good.xml"));
+
+        let dry_run = service.import(&content, ImportMode::DryRun).await?;
+        let plan = &dry_run.plan;
+        let titles = |entries: Vec<&ImportEntry>| entries.into_iter().map(|entry| entry.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(plan.new_feeds().collect()), vec!["Good", "Gone"]);
+        assert_eq!(titles(plan.existing_feeds().collect()), vec!["The GitHub Blog"]);
+        assert_eq!(titles(plan.moved_feeds().collect()), vec!["Rust Blog"]);
+        assert_eq!(titles(plan.invalid_feeds().collect()), vec!["Broken"]);
+        assert_eq!(plan.entries.last().map(|entry| &entry.status), Some(&ImportStatus::Duplicate));
+        assert_eq!(plan.new_folders, vec![vec!["Languages".to_string()], vec!["Languages".to_string(), "Rust".to_string()]]);
+        assert!(dry_run.created_feeds.is_empty());
+        assert_eq!(service.rss_service.get_all_feeds().await?.len(), 5);
+        assert_eq!(service.rss_service.get_all_categories().await?.len(), 2);
+
+        let report = service.import(&content, ImportMode::ApplyAndVerify).await?;
+        assert_eq!(report.created_feeds.len(), 2);
+        assert_eq!(report.created_categories.len(), 2);
+        assert_eq!(report.failed_feeds.len(), 1);
+        assert_eq!(report.failed_feeds[0].0.title, "Gone");
+
+        let rust = report.created_categories.iter().find(|category| category.name == "Rust").unwrap();
+        let moved = service.rss_service.get_feed_by_url("https://blog.rust-lang.org/feed.xml").await?.unwrap();
+        assert_eq!(moved.category_id, Some(rust.id.clone()));
+        let gone = service.rss_service.get_feed_by_url(&server.url("/gone.xml")).await?.unwrap();
+        assert_eq!(gone.status, FeedStatus::Error);
+        assert_eq!(service.rss_service.get_all_feeds().await?.len(), 7);
+
+        // Everything is in place now
+        let again = service.plan_import(&content).await?;
+        assert_eq!(again.new_feeds().count() + again.moved_feeds().count() + again.new_folders.len(), 0);
+
+        // A file without folders leaves existing feeds in their categories
+        let flat = r#"<opml version="2.0"><body>
+            <outline text="Rust Blog" xmlUrl="https://blog.rust-lang.org/feed.xml"/>
+          </body></opml>"#;
+        let report = service.import(flat, ImportMode::Apply).await?;
+        assert_eq!(titles(report.plan.existing_feeds().collect()), vec!["Rust Blog"]);
+        assert!(report.moved_feeds.is_empty());
+        let kept = service.rss_service.get_feed_by_url("https://blog.rust-lang.org/feed.xml").await?.unwrap();
+        assert_eq!(kept.category_id, Some(rust.id.clone()));
+        Ok(())
+    }
+}
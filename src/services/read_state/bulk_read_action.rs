@@ -0,0 +1,28 @@
+use std::collections::HashSet;
+
+use crate::models::article::ArticleId;
+use crate::models::feed::FeedId;
+
+/// A bulk mark-as-read that can be undone
+#[derive(Debug, Clone)]
+pub struct BulkReadAction {
+    /// What was marked, e.g. "Hacker News", for status messages and the undo button
+    pub label: String,
+    /// Articles that were unread before the action
+    pub article_ids: Vec<ArticleId>,
+    /// Feeds of those articles, whose unread counts changed
+    pub feed_ids: HashSet<FeedId>,
+}
+
+impl BulkReadAction {
+    pub fn new(label: String, articles: Vec<(ArticleId, FeedId)>) -> Self {
+        let feed_ids = articles.iter().map(|(_, feed_id)| feed_id.clone()).collect();
+        let article_ids = articles.into_iter().map(|(article_id, _)| article_id).collect();
+        Self { label, article_ids, feed_ids }
+    }
+
+    /// Number of articles the action marked as read
+    pub fn count(&self) -> usize {
+        self.article_ids.len()
+    }
+}
@@ -0,0 +1,5 @@
+mod bulk_read_action;
+mod read_state_service;
+
+pub use bulk_read_action::BulkReadAction;
+pub use read_state_service::ReadStateService;
@@ -0,0 +1,154 @@
+use std::sync::{Arc, Mutex};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use log::info;
+
+use crate::base::repository::ArticleRepository;
+use crate::models::article::ReadStatus;
+use crate::models::article_page::ArticleFilter;
+use crate::services::read_state::BulkReadAction;
+
+/// Marks many articles as read at once and remembers the last such action for undo
+pub struct ReadStateService {
+    article_repository: Arc<dyn ArticleRepository>,
+    last_action: Mutex<Option<BulkReadAction>>,
+}
+
+impl ReadStateService {
+    pub fn new(article_repository: Arc<dyn ArticleRepository>) -> Self {
+        Self {
+            article_repository,
+            last_action: Mutex::new(None),
+        }
+    }
+
+    /// Marks the articles selected by `filter` that were published more than `days` ago as read
+    pub async fn mark_older_than_read(&self, filter: &ArticleFilter, days: u32, label: &str) -> Result<BulkReadAction> {
+        let cutoff = Utc::now() - Duration::days(i64::from(days));
+        self.mark_read(filter, Some(cutoff), &format!("{} older than {} days", label, days)).await
+    }
+
+    /// Marks the unread articles selected by `filter` as read, replacing the action undo would revert
+    pub async fn mark_read(
+        &self,
+        filter: &ArticleFilter,
+        published_before: Option<DateTime<Utc>>,
+        label: &str,
+    ) -> Result<BulkReadAction> {
+        let articles = self.article_repository.mark_as_read(filter, published_before).await?;
+        let action = BulkReadAction::new(label.to_string(), articles);
+        info!("Marked {} articles in {} as read", action.count(), action.label);
+
+        if action.count() > 0 {
+            *self.last_action.lock().unwrap() = Some(action.clone());
+        }
+        Ok(action)
+    }
+
+    /// The action [`undo`](Self::undo) would revert
+    pub fn last_action(&self) -> Option<BulkReadAction> {
+        self.last_action.lock().unwrap().clone()
+    }
+
+    /// Marks the articles of the last bulk action unread again
+    ///
+    /// Returns the action that was reverted, or `None` if there was nothing to undo.
+    pub async fn undo(&self) -> Result<Option<BulkReadAction>> {
+        let action = match self.last_action.lock().unwrap().take() {
+            Some(action) => action,
+            None => return Ok(None),
+        };
+
+        match self.article_repository.set_read_status(&action.article_ids, ReadStatus::Unread).await {
+            Ok(restored) => {
+                info!("Restored {} articles in {} to unread", restored, action.label);
+                Ok(Some(action))
+            }
+            Err(e) => {
+                // Keep the action so the undo can be retried
+                *self.last_action.lock().unwrap() = Some(action);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Database;
+    use crate::models::article::Article;
+    use crate::models::category::Category;
+    use crate::models::feed::Feed;
+    use crate::models::search::SearchQuery;
+    use crate::utils::test_support::{save_article, save_feed, temp_database, test_article};
+
+    fn aged_article(feed: &Feed, title: &str, age_days: i64) -> Article {
+        test_article(feed, title).with_published_at(Utc::now() - Duration::days(age_days))
+    }
+
+    async fn unread_titles(database: &Database) -> Result<Vec<String>> {
+        let mut titles: Vec<String> = database.get_article_repository().get_unread_articles().await?
+            .into_iter()
+            .map(|article| article.title)
+            .collect();
+        titles.sort();
+        Ok(titles)
+    }
+
+    #[tokio::test]
+    async fn test_bulk_mark_read_and_undo() -> Result<()> {
+        let database = temp_database();
+        let category_repository = database.get_category_repository();
+        let tech = Category::new("Tech".to_string());
+        let rust = Category::new("Rust".to_string()).with_parent(tech.id.clone());
+        category_repository.save_category(&tech).await?;
+        category_repository.save_category(&rust).await?;
+
+        let news = save_feed(&database, "news", None).await?;
+        let blog = save_feed(&database, "blog", Some(&rust)).await?;
+        save_article(&database, aged_article(&news, "news-new", 1)).await?;
+        save_article(&database, aged_article(&news, "news-old", 30)).await?;
+        save_article(&database, aged_article(&blog, "blog-new", 1)).await?;
+        let tagged = save_article(&database, aged_article(&blog, "blog-tagged", 2)).await?;
+        database.get_article_repository().add_tag(&tagged.id, "later").await?;
+
+        let service = ReadStateService::new(database.get_article_repository());
+
+        // Categories include the feeds of their subcategories
+        let action = service.mark_read(&ArticleFilter::Category(tech.id.clone()), None, "Tech").await?;
+        assert_eq!(action.count(), 2);
+        assert_eq!(unread_titles(&database).await?, vec!["news-new", "news-old"]);
+
+        // Undo restores exactly the articles the last action changed
+        let undone = service.undo().await?.expect("an action to undo");
+        assert_eq!(undone.label, "Tech");
+        assert_eq!(unread_titles(&database).await?, vec!["blog-new", "blog-tagged", "news-new", "news-old"]);
+        assert!(service.undo().await?.is_none());
+
+        // A search marks only the results it lists
+        assert_eq!(service.mark_read(&ArticleFilter::Search(SearchQuery::new("news").with_limit(1)), None, "news").await?.count(), 1);
+        assert_eq!(unread_titles(&database).await?.len(), 3);
+        service.undo().await?;
+
+        let action = service.mark_older_than_read(&ArticleFilter::All, 7, "All").await?;
+        assert_eq!(action.count(), 1);
+        assert_eq!(unread_titles(&database).await?, vec!["blog-new", "blog-tagged", "news-new"]);
+
+        assert_eq!(service.mark_read(&ArticleFilter::Tag("later".to_string()), None, "later").await?.count(), 1);
+        assert_eq!(service.mark_read(&ArticleFilter::Feed(news.id.clone()), None, "news").await?.count(), 1);
+        assert_eq!(unread_titles(&database).await?, vec!["blog-new"]);
+
+        // Nothing left to mark does not replace the action undo would revert
+        assert_eq!(service.mark_read(&ArticleFilter::Feed(news.id.clone()), None, "news").await?.count(), 0);
+        assert_eq!(service.last_action().map(|action| action.label), Some("news".to_string()));
+
+        assert_eq!(service.mark_read(&ArticleFilter::Search(SearchQuery::new("news")), None, "news").await?.count(), 0);
+        assert_eq!(service.mark_read(&ArticleFilter::Search(SearchQuery::new("blog")), None, "blog").await?.count(), 1);
+        assert!(unread_titles(&database).await?.is_empty());
+
+        service.undo().await?;
+        assert_eq!(service.mark_read(&ArticleFilter::All, None, "All").await?.count(), 1);
+        Ok(())
+    }
+}
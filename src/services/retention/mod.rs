@@ -0,0 +1,5 @@
+mod retention_report;
+mod retention_service;
+
+pub use retention_report::RetentionReport;
+pub use retention_service::RetentionService;
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::article::Article;
+
+/// Outcome of one retention pass
+#[derive(Debug, Clone)]
+pub struct RetentionReport {
+    /// Articles were only listed, nothing was deleted
+    pub dry_run: bool,
+    /// Articles older than this expired, unless their feed keeps a fixed number instead
+    pub cutoff: DateTime<Utc>,
+    /// Articles that expired (or would have, in a dry run)
+    pub expired: Vec<Article>,
+    /// Articles actually removed from the repository
+    pub deleted: usize,
+}
+
+impl RetentionReport {
+    pub fn new(cutoff: DateTime<Utc>, expired: Vec<Article>, dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            cutoff,
+            expired,
+            deleted: 0,
+        }
+    }
+}
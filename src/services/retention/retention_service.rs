@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Utc};
+use log::{error, info, warn};
+use tokio::sync::Notify;
+
+use crate::base::repository::ArticleRepository;
+use crate::models::article::ArticleId;
+use crate::services::downloads::DownloadService;
+use crate::services::retention::RetentionReport;
+
+/// How often the background job purges expired articles
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Delay before the first cleanup, so it does not compete with startup syncing
+const FIRST_CLEANUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Deletes read articles once they fall outside the retention window
+///
+/// Favorited and tagged articles are always kept. Feeds with
+/// `keep_last_articles` set keep their newest N articles instead of following
+/// the age limit. With a download service attached, the downloaded enclosures
+/// of deleted articles are deleted from disk too.
+pub struct RetentionService {
+    article_repository: Arc<dyn ArticleRepository>,
+    download_service: Option<Arc<DownloadService>>,
+    retention_days: AtomicU32,
+    wake: Notify,
+    running: AtomicBool,
+    /// Purges that deleted articles so far
+    purges: AtomicU64,
+}
+
+impl RetentionService {
+    pub fn new(article_repository: Arc<dyn ArticleRepository>, retention_days: u32) -> Self {
+        Self {
+            article_repository,
+            download_service: None,
+            retention_days: AtomicU32::new(retention_days),
+            wake: Notify::new(),
+            running: AtomicBool::new(false),
+            purges: AtomicU64::new(0),
+        }
+    }
+
+    /// Deletes the downloaded enclosures of purged articles
+    pub fn with_download_service(mut self, download_service: Arc<DownloadService>) -> Self {
+        self.download_service = Some(download_service);
+        self
+    }
+
+    /// Changes the age limit for every later cleanup
+    pub fn set_retention_days(&self, retention_days: u32) {
+        self.retention_days.store(retention_days, Ordering::SeqCst);
+    }
+
+    pub fn retention_days(&self) -> u32 {
+        self.retention_days.load(Ordering::SeqCst)
+    }
+
+    /// How many purges have deleted articles, so callers can tell when
+    /// counts and lists built from stored articles are out of date
+    pub fn purge_count(&self) -> u64 {
+        self.purges.load(Ordering::SeqCst)
+    }
+
+    /// Purges expired articles under the current retention period, or with
+    /// `dry_run` only reports which would go
+    pub async fn run(&self, dry_run: bool) -> Result<RetentionReport> {
+        self.purge(self.retention_days(), dry_run).await
+    }
+
+    /// Like [`run`](Self::run), with an explicit retention period
+    ///
+    /// After a real purge the database is vacuumed so the space is returned.
+    pub async fn purge(&self, retention_days: u32, dry_run: bool) -> Result<RetentionReport> {
+        let cutoff = Utc::now() - ChronoDuration::days(i64::from(retention_days));
+        let expired = self.article_repository.get_expired_articles(cutoff).await?;
+        let mut report = RetentionReport::new(cutoff, expired, dry_run);
+        if dry_run || report.expired.is_empty() {
+            return Ok(report);
+        }
+
+        let ids: Vec<ArticleId> = report.expired.iter().map(|article| article.id.clone()).collect();
+        // Read before the articles go, since their enclosures are deleted with them
+        let enclosures = match &self.download_service {
+            Some(download_service) => download_service.enclosures_of_articles(&ids).await?,
+            None => Vec::new(),
+        };
+        report.deleted = self.article_repository.delete_articles(&ids).await?;
+        self.article_repository.reclaim_space().await?;
+        self.purges.fetch_add(1, Ordering::SeqCst);
+
+        if let Some(download_service) = &self.download_service {
+            for enclosure in &enclosures {
+                if let Err(e) = download_service.remove_files(enclosure).await {
+                    warn!("Failed to delete the download of {}: {}", enclosure.url, e);
+                }
+            }
+        }
+
+        info!("Retention removed {} articles older than {}", report.deleted, cutoff);
+        Ok(report)
+    }
+
+    /// Runs a cleanup shortly after starting and then once a day, until [`stop`](Self::stop)
+    pub async fn start(&self) -> Result<()> {
+        self.running.store(true, Ordering::SeqCst);
+        let mut wait = FIRST_CLEANUP_DELAY;
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = self.wake.notified() => {}
+            }
+            if !self.running.load(Ordering::SeqCst) {
+                break;
+            }
+
+            if let Err(e) = self.run(false).await {
+                error!("Article cleanup failed: {}", e);
+            }
+            wait = CLEANUP_INTERVAL;
+        }
+
+        info!("Article cleanup stopped");
+        Ok(())
+    }
+
+    /// Asks a running cleanup loop to return
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+    use crate::models::article::Article;
+    use crate::models::enclosure::Enclosure;
+    use crate::models::feed::Feed;
+    use crate::utils::test_support::{save_article, temp_database, test_article};
+
+    fn aged_article(feed: &Feed, title: &str, age_days: i64, read: bool) -> Article {
+        let mut article = test_article(feed, title).with_published_at(Utc::now() - ChronoDuration::days(age_days));
+        if read {
+            article.mark_as_read();
+        }
+        article
+    }
+
+    fn titles(report: &RetentionReport) -> Vec<&str> {
+        report.expired.iter().map(|article| article.title.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_purges_only_old_read_untagged_articles() -> Result<()> {
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let feed = Feed::new("Feed".to_string(), Url::parse("http://example.com/feed.xml")?);
+        database.get_feed_repository().save_feed(&feed).await?;
+
+        save_article(&database, aged_article(&feed, "old-read", 40, true)).await?;
+        save_article(&database, aged_article(&feed, "old-unread", 40, false)).await?;
+        save_article(&database, aged_article(&feed, "recent-read", 5, true)).await?;
+        let mut favorite = save_article(&database, aged_article(&feed, "old-favorite", 40, true)).await?;
+        favorite.is_favorited = true;
+        article_repository.update_article(&favorite).await?;
+        let tagged = save_article(&database, aged_article(&feed, "old-tagged", 40, true)).await?;
+        article_repository.add_tag(&tagged.id, "keep").await?;
+
+        // The purged article's podcast episode was downloaded
+        let old_read = article_repository.get_all_articles().await?
+            .into_iter()
+            .find(|article| article.title == "old-read")
+            .unwrap();
+        let directory = std::env::temp_dir().join("panda-tests").join(uuid::Uuid::new_v4().to_string());
+        let download_service = Arc::new(DownloadService::new(
+            database.get_enclosure_repository(),
+            database.get_feed_repository(),
+            &directory,
+        )?);
+        let mut enclosure = Enclosure::new(old_read.id.clone(), Url::parse("http://example.com/episode.mp3")?);
+        let episode = directory.join("episode.mp3");
+        std::fs::create_dir_all(&directory)?;
+        std::fs::write(&episode, b"audio")?;
+        enclosure.mark_downloaded(episode.to_string_lossy().into_owned());
+        database.get_enclosure_repository().save_enclosures(&[enclosure]).await?;
+
+        let service = RetentionService::new(article_repository.clone(), 30)
+            .with_download_service(download_service);
+
+        let preview = service.run(true).await?;
+        assert_eq!(titles(&preview), vec!["old-read"]);
+        assert_eq!(preview.deleted, 0);
+        assert_eq!(article_repository.get_all_articles().await?.len(), 5);
+        assert_eq!(service.purge_count(), 0);
+        assert!(episode.exists());
+
+        let report = service.run(false).await?;
+        assert_eq!(report.deleted, 1);
+        assert_eq!(service.purge_count(), 1);
+        let mut remaining: Vec<String> = article_repository.get_all_articles().await?
+            .into_iter()
+            .map(|article| article.title)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["old-favorite", "old-tagged", "old-unread", "recent-read"]);
+        assert!(!episode.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_feed_override_keeps_newest_articles() -> Result<()> {
+        let database = temp_database();
+        let feed = Feed::new("Busy".to_string(), Url::parse("http://example.com/busy.xml")?)
+            .with_keep_last_articles(2);
+        database.get_feed_repository().save_feed(&feed).await?;
+
+        // All recent, so only the per-feed limit can expire them
+        for (title, age) in [("first", 4), ("second", 3), ("third", 2), ("fourth", 1)] {
+            save_article(&database, aged_article(&feed, title, age, true)).await?;
+        }
+
+        let service = RetentionService::new(database.get_article_repository(), 30);
+        let report = service.run(false).await?;
+        assert_eq!(titles(&report), vec!["first", "second"]);
+        assert_eq!(report.deleted, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use url::Url;
+
+use crate::utils::html::{escape_html, tokenize, Attributes, Token};
+
+/// Attributes that hold a single URL
+const URL_ATTRIBUTES: &[&str] = &["href", "src", "poster", "cite", "action", "background"];
+
+/// Attributes lazy-loading scripts keep the real image URL in, most common first
+const LAZY_SRC_ATTRIBUTES: &[&str] = &["data-src", "data-lazy-src", "data-original", "data-lazy"];
+
+/// Attributes lazy-loading scripts keep the real `srcset` in
+const LAZY_SRCSET_ATTRIBUTES: &[&str] = &["data-srcset", "data-lazy-srcset"];
+
+/// Query parameters that only identify the campaign or click that led to a link
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc", "_hsmi",
+];
+
+/// Prepares entry HTML to be shown away from the page it was written for
+///
+/// Relative URLs are resolved against `base`, http links to the base's own
+/// host are upgraded when the base is served over https, images left for a
+/// lazy-loading script get their real `src`/`srcset`, and tracking parameters
+/// are removed from links. Tags without anything to change are kept as written.
+pub fn rewrite_content(html: &str, base: &Url) -> String {
+    let mut output = String::with_capacity(html.len());
+    for token in tokenize(html) {
+        match token {
+            Token::StartTag(tag) => match rewrite_attributes(&tag.name, &tag.attributes, base) {
+                Some(rewritten) => {
+                    output.push('<');
+                    output.push_str(&tag.name);
+                    for (key, value) in rewritten {
+                        output.push_str(&format!(" {}=\"{}\"", key, escape_html(&value)));
+                    }
+                    output.push_str(if tag.self_closing { " />" } else { ">" });
+                }
+                None => output.push_str(tag.source),
+            },
+            // Script and style bodies are not markup, so they are kept as well
+            Token::Text(source) | Token::RawText(source) | Token::Comment(source) | Token::EndTag { source, .. } => {
+                output.push_str(source);
+            }
+        }
+    }
+    output
+}
+
+/// Removes known tracking parameters (`utm_*`, `fbclid` and alike) from `url`
+pub fn strip_tracking_params(url: &mut Url) {
+    let Some(query) = url.query() else { return };
+    let is_tracking = |key: &str| {
+        let key = key.to_ascii_lowercase();
+        key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+    };
+    if !url::form_urlencoded::parse(query.as_bytes()).any(|(key, _)| is_tracking(&key)) {
+        return;
+    }
+
+    let kept: Vec<(String, String)> = url.query_pairs()
+        .filter(|(key, _)| !is_tracking(key))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+}
+
+/// The tag's attributes with URLs rewritten, or `None` when nothing changed
+fn rewrite_attributes(name: &str, attributes: &Attributes, base: &Url) -> Option<Attributes> {
+    let mut rewritten = attributes.clone();
+    if matches!(name, "img" | "source" | "video" | "audio" | "iframe") {
+        apply_lazy_loading(name, &mut rewritten);
+    }
+
+    for (key, value) in rewritten.iter_mut() {
+        if key == "srcset" {
+            *value = srcset_candidates(value)
+                .into_iter()
+                .map(|(url, descriptor)| {
+                    let url = resolve(url, base).map_or_else(|| url.to_string(), String::from);
+                    if descriptor.is_empty() { url } else { format!("{} {}", url, descriptor) }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+        } else if URL_ATTRIBUTES.contains(&key.as_str()) {
+            if let Some(mut url) = resolve(value, base) {
+                if key == "href" {
+                    strip_tracking_params(&mut url);
+                }
+                *value = url.into();
+            }
+        }
+    }
+
+    (rewritten != *attributes).then_some(rewritten)
+}
+
+/// Moves the URLs a lazy-loading script would have swapped in into `src` and `srcset`
+///
+/// The lazy value only replaces a missing, empty or inline placeholder `src`.
+/// An image left with nothing but a `srcset` gets its largest candidate as `src`.
+fn apply_lazy_loading(name: &str, attributes: &mut Attributes) {
+    let value = |attributes: &Attributes, key: &str| attributes.iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    let set = |attributes: &mut Attributes, key: &str, value: String| {
+        match attributes.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => attributes.push((key.to_string(), value)),
+        }
+    };
+
+    let has_real_src = value(attributes, "src").is_some_and(|src| !src.to_ascii_lowercase().starts_with("data:"));
+    if !has_real_src {
+        if let Some(lazy) = LAZY_SRC_ATTRIBUTES.iter().find_map(|key| value(attributes, key)) {
+            set(attributes, "src", lazy);
+        }
+    }
+    if value(attributes, "srcset").is_none() {
+        if let Some(lazy) = LAZY_SRCSET_ATTRIBUTES.iter().find_map(|key| value(attributes, key)) {
+            set(attributes, "srcset", lazy);
+        }
+    }
+    attributes.retain(|(key, _)| !LAZY_SRC_ATTRIBUTES.contains(&key.as_str()) && !LAZY_SRCSET_ATTRIBUTES.contains(&key.as_str()));
+
+    let has_src = value(attributes, "src").is_some_and(|src| !src.to_ascii_lowercase().starts_with("data:"));
+    if name == "img" && !has_src {
+        let largest = value(attributes, "srcset").and_then(|srcset| {
+            srcset_candidates(&srcset)
+                .into_iter()
+                .max_by(|a, b| descriptor_size(a.1).total_cmp(&descriptor_size(b.1)))
+                .map(|(url, _)| url.to_string())
+        });
+        if let Some(url) = largest {
+            set(attributes, "src", url);
+        }
+    }
+}
+
+/// Splits a `srcset` into its URLs and their width or density descriptors
+///
+/// URLs may contain commas (image CDNs use them for transformations), so a
+/// candidate only ends at a comma after whitespace or at the end of its URL.
+pub(crate) fn srcset_candidates(srcset: &str) -> Vec<(&str, &str)> {
+    let mut candidates = Vec::new();
+    let mut rest = srcset;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        rest = &rest[url_end..];
+        if let Some(url) = url.strip_suffix(',') {
+            candidates.push((url.trim_end_matches(','), ""));
+            continue;
+        }
+
+        let descriptor_end = rest.find(',').unwrap_or(rest.len());
+        candidates.push((url, rest[..descriptor_end].trim()));
+        rest = &rest[descriptor_end..];
+    }
+}
+
+/// The width or density a candidate is for; one without a descriptor is `1x`
+fn descriptor_size(descriptor: &str) -> f64 {
+    let descriptor = descriptor.trim();
+    descriptor.strip_suffix(['w', 'x'])
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(1.0)
+}
+
+/// `value` as an absolute http(s) URL, or `None` for in-page anchors, other
+/// schemes and anything that is not a URL
+fn resolve(value: &str, base: &Url) -> Option<Url> {
+    let value = value.trim();
+    if value.is_empty() || value.starts_with('#') {
+        return None;
+    }
+    let mut url = base.join(value).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    if url.scheme() == "http" && base.scheme() == "https" && url.host_str() == base.host_str() {
+        url.set_scheme("https").ok()?;
+    }
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> Url {
+        Url::parse("https://blog.example/posts/2024/hello/").unwrap()
+    }
+
+    #[test]
+    fn test_rewrite_content_resolves_relative_urls() {
+        let html = r##"<p><a href="../world/">Next</a> <a href="#notes">Notes</a> <a href='/about'>About</a></p>
+<img src="photo.jpg" alt="A &quot;photo&quot;"><br/>
+<img srcset="small.jpg 480w, //cdn.example/w_800,h_600/large.jpg 800w" src="small.jpg" />
+<a href="mailto:ada@blog.example">Mail</a> <a href="http://blog.example/old">Old</a> <a href="http://other.example/">Other</a>
+<script>var s = "<img src='x.png'>";</script><!-- <img src="comment.png"> -->"##;
+        assert_eq!(
+            rewrite_content(html, &base()),
+            r##"<p><a href="https://blog.example/posts/2024/world/">Next</a> <a href="#notes">Notes</a> <a href="https://blog.example/about">About</a></p>
+<img src="https://blog.example/posts/2024/hello/photo.jpg" alt="A &quot;photo&quot;"><br/>
+<img srcset="https://blog.example/posts/2024/hello/small.jpg 480w, https://cdn.example/w_800,h_600/large.jpg 800w" src="https://blog.example/posts/2024/hello/small.jpg" />
+<a href="mailto:ada@blog.example">Mail</a> <a href="https://blog.example/old">Old</a> <a href="http://other.example/">Other</a>
+<script>var s = "<img src='x.png'>";</script><!-- <img src="comment.png"> -->"##
+        );
+    }
+
+    #[test]
+    fn test_rewrite_content_converts_lazy_images() {
+        let html = concat!(
+            r#"<img src="data:image/gif;base64,R0lGODlhAQABAAAAACw=" data-src="/images/a.jpg" class="lazy">"#,
+            r#"<img data-lazy-src="b.jpg" data-lazy-srcset="b.jpg 1x, b@2x.jpg 2x">"#,
+            r#"<img srcset="c-320.jpg 320w, c-1024.jpg 1024w, c-640.jpg 640w">"#,
+            r#"<img src="d.jpg" data-src="d-large.jpg">"#,
+        );
+        assert_eq!(
+            rewrite_content(html, &base()),
+            concat!(
+                r#"<img src="https://blog.example/images/a.jpg" class="lazy">"#,
+                r#"<img src="https://blog.example/posts/2024/hello/b.jpg" srcset="https://blog.example/posts/2024/hello/b.jpg 1x, https://blog.example/posts/2024/hello/b@2x.jpg 2x">"#,
+                r#"<img srcset="https://blog.example/posts/2024/hello/c-320.jpg 320w, https://blog.example/posts/2024/hello/c-1024.jpg 1024w, https://blog.example/posts/2024/hello/c-640.jpg 640w" src="https://blog.example/posts/2024/hello/c-1024.jpg">"#,
+                r#"<img src="https://blog.example/posts/2024/hello/d.jpg">"#,
+            )
+        );
+    }
+
+    #[test]
+    fn test_strip_tracking_params() {
+        let html = r#"<a href="https://shop.example/item?id=7&utm_source=feed&UTM_Medium=rss&fbclid=abc">Item</a> <a href="/x?utm_campaign=launch">X</a> <img src="https://img.example/a.jpg?utm_source=feed">"#;
+        assert_eq!(
+            rewrite_content(html, &base()),
+            r#"<a href="https://shop.example/item?id=7">Item</a> <a href="https://blog.example/x">X</a> <img src="https://img.example/a.jpg?utm_source=feed">"#
+        );
+
+        let mut url = Url::parse("https://example.com/?q=rust+feeds&page=2").unwrap();
+        strip_tracking_params(&mut url);
+        assert_eq!(url.as_str(), "https://example.com/?q=rust+feeds&page=2");
+    }
+
+    #[test]
+    fn test_srcset_candidates() {
+        assert_eq!(
+            srcset_candidates(" a.jpg, https://cdn.example/w_100,h_100/b.jpg 2x,c.jpg 100w ,"),
+            vec![("a.jpg", ""), ("https://cdn.example/w_100,h_100/b.jpg", "2x"), ("c.jpg", "100w")]
+        );
+    }
+}
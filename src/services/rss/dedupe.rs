@@ -0,0 +1,112 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::article::Article;
+
+/// Prefix marking a `guid` that was derived locally rather than supplied by the feed
+const FINGERPRINT_PREFIX: &str = "fingerprint:";
+
+/// A way of recognising an incoming entry as an article already stored for its feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DedupeKey {
+    /// The entry's own GUID/id as published by the feed
+    Guid(String),
+    /// The entry's link. With `unkeyed_only`, only rows stored without a real
+    /// GUID may match, so aggregators that reuse one URL for several distinct
+    /// entries do not get those entries merged.
+    Link { url: String, unkeyed_only: bool },
+    /// Hash of title and date, for entries that carry no GUID
+    Fingerprint(String),
+}
+
+/// Returns the keys to try for an article, strongest first: GUID → link → fingerprint
+pub fn dedupe_keys(article: &Article) -> Vec<DedupeKey> {
+    let link = |unkeyed_only| DedupeKey::Link { url: article.url.to_string(), unkeyed_only };
+
+    match article.guid.as_deref() {
+        Some(guid) if !is_fingerprint(guid) => vec![DedupeKey::Guid(guid.to_string()), link(true)],
+        Some(fingerprint) => vec![link(false), DedupeKey::Fingerprint(fingerprint.to_string())],
+        None => vec![link(false)],
+    }
+}
+
+/// Whether the article carries a GUID supplied by its feed
+pub fn has_feed_guid(article: &Article) -> bool {
+    article.guid.as_deref().is_some_and(|guid| !is_fingerprint(guid))
+}
+
+/// Whether a stored `guid` value was derived locally by [`fingerprint`]
+pub fn is_fingerprint(guid: &str) -> bool {
+    guid.starts_with(FINGERPRINT_PREFIX)
+}
+
+/// Derives a stable identity for an entry without a GUID
+///
+/// Uses the title and date when the entry is dated; undated entries fall back
+/// to title and link, since a title alone is too weak to tell entries apart.
+pub fn fingerprint(title: &str, date: Option<DateTime<Utc>>, link: &str) -> String {
+    let discriminator = match date {
+        Some(date) => date.to_rfc3339(),
+        None => link.to_string(),
+    };
+    format!("{}{:016x}", FINGERPRINT_PREFIX, fnv1a(&[title.trim(), "\u{1f}", &discriminator]))
+}
+
+/// 64-bit FNV-1a; stable across Rust releases, unlike `DefaultHasher`
+fn fnv1a(parts: &[&str]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in parts.iter().flat_map(|part| part.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use url::Url;
+    use crate::models::feed::FeedId;
+
+    fn article(guid: Option<String>) -> Article {
+        let mut article = Article::new(
+            FeedId("feed".to_string()),
+            "Title".to_string(),
+            Url::parse("http://example.com/post").unwrap(),
+        );
+        article.guid = guid;
+        article
+    }
+
+    #[test]
+    fn test_keys_prefer_feed_guid() {
+        let keys = dedupe_keys(&article(Some("urn:entry:1".to_string())));
+        assert_eq!(keys, vec![
+            DedupeKey::Guid("urn:entry:1".to_string()),
+            DedupeKey::Link { url: "http://example.com/post".to_string(), unkeyed_only: true },
+        ]);
+    }
+
+    #[test]
+    fn test_keys_without_guid_fall_back_to_link_then_fingerprint() {
+        let fp = fingerprint("Title", None, "http://example.com/post");
+        let keys = dedupe_keys(&article(Some(fp.clone())));
+        assert_eq!(keys, vec![
+            DedupeKey::Link { url: "http://example.com/post".to_string(), unkeyed_only: false },
+            DedupeKey::Fingerprint(fp),
+        ]);
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_discriminating() {
+        let date = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let a = fingerprint("Post", Some(date), "http://example.com/a");
+
+        // Same title and date identify the same entry even if the link moved
+        assert_eq!(a, fingerprint("Post", Some(date), "http://example.com/b"));
+        assert_ne!(a, fingerprint("Other post", Some(date), "http://example.com/a"));
+        assert_ne!(a, fingerprint("Post", Some(date + chrono::Duration::days(1)), "http://example.com/a"));
+        assert!(is_fingerprint(&a));
+        assert!(!is_fingerprint("urn:entry:1"));
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::Utc;
+use feed_rs::model::{Content, Entry, Link, Person};
+use url::Url;
+
+use crate::models::article::{Article, ArticleId};
+use crate::models::enclosure::Enclosure;
+use crate::models::feed::FeedId;
+use crate::services::rss::content_rewriter::rewrite_content;
+use crate::services::rss::dedupe::fingerprint;
+use crate::utils::html::{decode_entities, escape_html};
+
+/// Longest title made up from the text of an untitled entry
+const DERIVED_TITLE_CHARS: usize = 80;
+
+/// Maps a parsed feed entry to an `Article` belonging to the given feed
+///
+/// Returns `None` when the entry has no usable link, since the article URL is
+/// what readers open and what existing rows are matched against.
+pub fn map_entry(feed_id: &FeedId, entry: &Entry) -> Option<Article> {
+    let url = entry_link(entry)?;
+    // JSON Feed items and RSS 2.0 items may leave out the title
+    let title = entry.title
+        .as_ref()
+        .map(|t| t.content.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .or_else(|| derived_title(entry))
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let date = entry.published.or(entry.updated);
+    let guid = match entry.id.trim() {
+        "" => fingerprint(&title, date, url.as_str()),
+        id => id.to_string(),
+    };
+
+    let base = content_base(entry, &url);
+    let mut article = Article::new(feed_id.clone(), title, url)
+        .with_guid(guid)
+        .with_published_at(date.unwrap_or_else(Utc::now));
+
+    let mut authors: Vec<String> = Vec::new();
+    for name in entry.authors.iter().filter_map(person_name) {
+        if !authors.contains(&name) {
+            authors.push(name);
+        }
+    }
+    if !authors.is_empty() {
+        article = article.with_author(authors.join(", "));
+    }
+
+    if let Some(content) = entry.content.as_ref().and_then(content_html) {
+        article = article.with_content(rewrite_content(&content, &base));
+    }
+
+    if let Some(summary) = entry.summary.as_ref().map(|s| rewrite_content(&s.content, &base)) {
+        article = article.with_summary(summary);
+    }
+
+    Some(article)
+}
+
+/// Collects the media attached to an entry for the given article
+///
+/// RSS `<enclosure>`, Media RSS and iTunes elements arrive as feed-rs media
+/// objects; Atom `rel="enclosure"` links and JSON Feed attachments as links.
+/// An enclosure listed by more than one of them is kept once.
+pub fn map_enclosures(article_id: &ArticleId, entry: &Entry) -> Vec<Enclosure> {
+    let mut enclosures: Vec<Enclosure> = Vec::new();
+    let mut push = |enclosure: Enclosure| {
+        if !enclosures.iter().any(|known| known.url == enclosure.url) {
+            enclosures.push(enclosure);
+        }
+    };
+
+    for object in &entry.media {
+        let title = object.title.as_ref()
+            .map(|t| t.content.trim().to_string())
+            .filter(|t| !t.is_empty());
+        for content in &object.content {
+            let Some(url) = content.url.clone() else { continue };
+            let mut enclosure = Enclosure::new(article_id.clone(), url);
+            if let Some(mime_type) = &content.content_type {
+                enclosure = enclosure.with_mime_type(mime_type.to_string());
+            }
+            if let Some(length) = content.size.filter(|length| *length > 0) {
+                enclosure = enclosure.with_length(length);
+            }
+            if let Some(duration) = content.duration.or(object.duration) {
+                enclosure = enclosure.with_duration_secs(duration.as_secs());
+            }
+            if let Some(title) = &title {
+                enclosure = enclosure.with_title(title.clone());
+            }
+            push(enclosure);
+        }
+    }
+
+    for link in entry.links.iter().filter(|l| is_attachment(l)) {
+        let Ok(url) = Url::parse(&link.href) else { continue };
+        let mut enclosure = Enclosure::new(article_id.clone(), url);
+        if let Some(mime_type) = &link.media_type {
+            enclosure = enclosure.with_mime_type(mime_type.clone());
+        }
+        if let Some(length) = link.length.filter(|length| *length > 0) {
+            enclosure = enclosure.with_length(length);
+        }
+        if let Some(title) = link.title.as_ref().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            enclosure = enclosure.with_title(title.to_string());
+        }
+        push(enclosure);
+    }
+
+    enclosures
+}
+
+/// Picks the entry's primary link, preferring `rel="alternate"` over the rest
+///
+/// Enclosures and JSON Feed attachments are only used when the entry links
+/// to nothing else.
+fn entry_link(entry: &Entry) -> Option<Url> {
+    let pages: Vec<&Link> = entry.links.iter().filter(|l| !is_attachment(l)).collect();
+    let alternate = pages.iter()
+        .copied()
+        .find(|l| matches!(l.rel.as_deref(), None | Some("alternate")));
+
+    alternate
+        .into_iter()
+        .chain(pages)
+        .chain(entry.links.iter())
+        .find_map(|l| Url::parse(&l.href).ok())
+}
+
+/// What relative URLs in the entry's HTML are resolved against
+///
+/// Atom keeps the content's `xml:base` as the entry base, which may itself be
+/// relative; RSS `content:encoded` carries the `xml:base` in scope as its source.
+/// Without either, the entry's own link is the page the HTML was written for.
+fn content_base(entry: &Entry, url: &Url) -> Url {
+    entry.base.as_deref()
+        .and_then(|base| url.join(base.trim()).ok())
+        .or_else(|| entry.content.as_ref()
+            .filter(|content| content.body.is_some())
+            .and_then(|content| content.src.as_ref())
+            .and_then(|src| Url::parse(&src.href).ok()))
+        .unwrap_or_else(|| url.clone())
+}
+
+/// Whether a link points at media attached to the entry rather than at a page
+fn is_attachment(link: &Link) -> bool {
+    link.rel.as_deref() == Some("enclosure")
+        || link.media_type.as_deref().is_some_and(|media_type| !media_type.contains("html"))
+}
+
+/// The display name of an author
+///
+/// RSS 2.0's `<author>` and `<managingEditor>` hold an email address,
+/// optionally followed by the name in parentheses; feed-rs keeps it as the
+/// email of a person named after the element.
+pub(crate) fn person_name(person: &Person) -> Option<String> {
+    let name = match (person.name.as_str(), person.email.as_deref()) {
+        ("author" | "managingEditor", Some(email)) => match (email.find('('), email.rfind(')')) {
+            (Some(start), Some(end)) if start < end => &email[start + 1..end],
+            _ => email,
+        },
+        (name, _) => name,
+    };
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// The entry's content as HTML; plain text (e.g. JSON Feed's `content_text`) is escaped
+fn content_html(content: &Content) -> Option<String> {
+    let body = content.body.as_ref()?;
+    if content.content_type.essence_str() != "text/plain" {
+        return Some(body.clone());
+    }
+
+    let paragraphs: Vec<String> = body.split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph).replace('\n', "<br>")))
+        .collect();
+    (!paragraphs.is_empty()).then(|| paragraphs.join("\n"))
+}
+
+/// A title for an untitled entry: the start of its summary or content as text
+fn derived_title(entry: &Entry) -> Option<String> {
+    let text = entry.summary.as_ref().map(|s| s.content.as_str())
+        .or_else(|| entry.content.as_ref().and_then(|c| c.body.as_deref()))?;
+
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                plain.push(' ');
+            }
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    let words: Vec<&str> = plain.split_whitespace().collect();
+    let plain = decode_entities(&words.join(" "));
+    if plain.is_empty() {
+        return None;
+    }
+    if plain.chars().count() <= DERIVED_TITLE_CHARS {
+        return Some(plain);
+    }
+
+    let cut: String = plain.chars().take(DERIVED_TITLE_CHARS).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    Some(format!("{}…", cut.trim_end_matches(|c: char| c.is_ascii_punctuation())))
+}
@@ -0,0 +1,41 @@
+/// The image type of `data`, judged by its first bytes
+///
+/// Servers often answer `/favicon.ico` with an HTML error page and a 200, and
+/// label real icons inconsistently, so the bytes are trusted over headers.
+pub fn image_mime_type(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: [(&[u8], &str); 6] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"BM", "image/bmp"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(signature, _)| data.starts_with(signature)) {
+        return Some(mime);
+    }
+    if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(256)]).to_ascii_lowercase();
+    let head = head.trim_start_matches('\u{feff}').trim_start();
+    let is_svg = head.starts_with("<svg") || (head.starts_with("<?xml") && head.contains("<svg"));
+    is_svg.then_some("image/svg+xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_mime_type() {
+        assert_eq!(image_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(image_mime_type(b"\x00\x00\x01\x00\x01\x00\x10\x10"), Some("image/x-icon"));
+        assert_eq!(image_mime_type(b"GIF89a\x01\x00"), Some("image/gif"));
+        assert_eq!(image_mime_type(b"RIFF\x24\x00\x00\x00WEBPVP8 "), Some("image/webp"));
+        assert_eq!(image_mime_type(b"<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), Some("image/svg+xml"));
+        assert_eq!(image_mime_type(b"<!DOCTYPE html><html><body>Not found</body></html>"), None);
+        assert_eq!(image_mime_type(b""), None);
+    }
+}
@@ -0,0 +1,195 @@
+use feed_rs::model::{Feed as FeedRs, FeedType};
+use url::Url;
+
+use crate::utils::html::{decode_entities, start_tags, tokenize, Attributes, Token};
+
+/// Paths tried on a site that does not advertise its feeds
+pub const COMMON_FEED_PATHS: [&str; 5] = ["/feed", "/rss.xml", "/atom.xml", "/index.xml", "/feed.json"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Rss,
+    Atom,
+    Json,
+}
+
+impl FeedFormat {
+    /// The format announced by a `<link type="...">`, if it is a feed type
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime.split(';').next().unwrap_or_default().trim().to_ascii_lowercase().as_str() {
+            "application/rss+xml" | "application/rdf+xml" => Some(Self::Rss),
+            "application/atom+xml" => Some(Self::Atom),
+            "application/feed+json" | "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn to_string(&self) -> String {
+        match self {
+            Self::Rss => "RSS".to_string(),
+            Self::Atom => "Atom".to_string(),
+            Self::Json => "JSON Feed".to_string(),
+        }
+    }
+}
+
+/// A feed found for a site, for the user to choose from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedCandidate {
+    pub url: Url,
+    pub title: String,
+    pub format: FeedFormat,
+}
+
+impl FeedCandidate {
+    /// A candidate for a document that was fetched and parsed as a feed
+    pub fn from_document(url: Url, document: &FeedRs) -> Self {
+        let format = match document.feed_type {
+            FeedType::Atom => FeedFormat::Atom,
+            FeedType::JSON => FeedFormat::Json,
+            FeedType::RSS0 | FeedType::RSS1 | FeedType::RSS2 => FeedFormat::Rss,
+        };
+        let title = document.title.as_ref()
+            .map(|title| title.content.trim().to_string())
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| url.to_string());
+        Self { url, title, format }
+    }
+}
+
+/// Finds the feeds an HTML page advertises with `<link rel="alternate">`
+///
+/// Relative links are resolved against the page's `<base href>` or, without
+/// one, against `page_url`. Links without a title are named after the page.
+pub fn find_feed_links(html: &str, page_url: &Url) -> Vec<FeedCandidate> {
+    let tags = start_tags(html);
+    let base = base_url(&tags, page_url);
+    let page_title = page_title(html);
+
+    let mut candidates: Vec<FeedCandidate> = Vec::new();
+    for (_, attributes) in tags.iter().filter(|(name, _)| name == "link") {
+        let is_alternate = attribute(attributes, "rel")
+            .is_some_and(|rel| rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case("alternate")));
+        let format = attribute(attributes, "type").and_then(FeedFormat::from_mime);
+        let url = attribute(attributes, "href").and_then(|href| base.join(href.trim()).ok());
+
+        if let (true, Some(format), Some(url)) = (is_alternate, format, url) {
+            if candidates.iter().any(|candidate| candidate.url == url) {
+                continue;
+            }
+            let title = attribute(attributes, "title")
+                .map(|title| title.trim().to_string())
+                .filter(|title| !title.is_empty())
+                .or_else(|| page_title.clone())
+                .unwrap_or_else(|| url.to_string());
+            candidates.push(FeedCandidate { url, title, format });
+        }
+    }
+    candidates
+}
+
+/// Finds the icons an HTML page declares, `rel="icon"` ones before Apple touch icons
+pub fn find_icon_links(html: &str, page_url: &Url) -> Vec<Url> {
+    let tags = start_tags(html);
+    let base = base_url(&tags, page_url);
+    let links: Vec<(&str, Url)> = tags.iter()
+        .filter(|(name, _)| name == "link")
+        .filter_map(|(_, attributes)| {
+            let rel = attribute(attributes, "rel")?;
+            let url = base.join(attribute(attributes, "href")?.trim()).ok()?;
+            Some((rel, url))
+        })
+        .collect();
+
+    let mut icons: Vec<Url> = Vec::new();
+    for wanted in ["icon", "apple-touch-icon", "apple-touch-icon-precomposed"] {
+        for (rel, url) in &links {
+            let matches = rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case(wanted));
+            if matches && !icons.contains(url) {
+                icons.push(url.clone());
+            }
+        }
+    }
+    icons
+}
+
+/// What relative links on the page are resolved against: its `<base href>`, else its own URL
+fn base_url(tags: &[(String, Attributes)], page_url: &Url) -> Url {
+    tags.iter()
+        .find(|(name, _)| name == "base")
+        .and_then(|(_, attributes)| attribute(attributes, "href"))
+        .and_then(|href| page_url.join(href).ok())
+        .unwrap_or_else(|| page_url.clone())
+}
+
+fn attribute<'a>(attributes: &'a Attributes, name: &str) -> Option<&'a str> {
+    attributes.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn page_title(html: &str) -> Option<String> {
+    let mut tokens = tokenize(html)
+        .skip_while(|token| !matches!(token, Token::StartTag(tag) if tag.name == "title"))
+        .skip(1);
+    let Some(Token::RawText(content)) = tokens.next() else {
+        return None;
+    };
+    let title = decode_entities(content.trim());
+    (!title.is_empty()).then_some(title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_feed_links() {
+        let html = r#"<!DOCTYPE html>
+            <html><head>
+              <title>Example &amp; Co</title>
+              <!-- <link rel="alternate" type="application/rss+xml" href="/old.xml"> -->
+              <script>document.write('<link rel="alternate" type="application/rss+xml" href="/js.xml">')</script>
+              <link rel="stylesheet" href="/style.css">
+              <LINK REL="Alternate" TYPE="application/rss+xml" TITLE="Posts" HREF="/feed.xml?a=1&amp;b=2">
+              <link rel=alternate type=application/atom+xml href=https://cdn.example.net/atom.xml>
+              <link rel="alternate home" type="application/feed+json" href='feed.json'>
+              <link rel="alternate" type="text/html" hreflang="de" href="/de/">
+              <link rel="alternate" type="application/rss+xml" title="Posts again" href="/feed.xml?a=1&b=2">
+            </head><body></body></html>"#;
+
+        let page = Url::parse("https://example.com/blog/").unwrap();
+        let candidates = find_feed_links(html, &page);
+        let found: Vec<(&str, &str, FeedFormat)> = candidates.iter()
+            .map(|candidate| (candidate.url.as_str(), candidate.title.as_str(), candidate.format))
+            .collect();
+        assert_eq!(found, vec![
+            ("https://example.com/feed.xml?a=1&b=2", "Posts", FeedFormat::Rss),
+            ("https://cdn.example.net/atom.xml", "Example & Co", FeedFormat::Atom),
+            ("https://example.com/blog/feed.json", "Example & Co", FeedFormat::Json),
+        ]);
+
+        let with_base = r#"<head><base href="https://static.example.com/site/"><link rel="alternate" type="application/rss+xml" href="rss"></head>"#;
+        assert_eq!(find_feed_links(with_base, &page)[0].url.as_str(), "https://static.example.com/site/rss");
+    }
+
+    #[test]
+    fn test_find_icon_links() {
+        let html = r#"<head>
+            <link rel="apple-touch-icon" href="/touch.png">
+            <link rel="stylesheet" href="/style.css">
+            <link rel="shortcut icon" href="favicon.ico">
+            <link rel="icon" type="image/png" sizes="32x32" href="/icon-32.png">
+            <link rel="ICON" href="/icon-32.png">
+        </head>"#;
+
+        let page = Url::parse("https://example.com/blog/").unwrap();
+        let icons: Vec<String> = find_icon_links(html, &page).iter().map(Url::to_string).collect();
+        assert_eq!(icons, vec![
+            "https://example.com/blog/favicon.ico",
+            "https://example.com/icon-32.png",
+            "https://example.com/touch.png",
+        ]);
+        assert!(find_icon_links("<head><title>No icons</title></head>", &page).is_empty());
+    }
+}
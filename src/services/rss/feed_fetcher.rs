@@ -0,0 +1,104 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, StatusCode};
+use url::Url;
+
+use crate::models::feed::Feed;
+use crate::services::rss::fetch_config::FetchConfig;
+use crate::services::rss::http_cache::{read_conditional, with_validators, CacheDirectives, ConditionalFetch};
+
+/// Feed formats first, then the HTML pages feeds are discovered from
+const ACCEPT_FEEDS: &str = "application/rss+xml, application/atom+xml, application/feed+json, \
+    application/rdf+xml;q=0.9, application/xml;q=0.8, text/xml;q=0.8, application/json;q=0.8, \
+    text/html;q=0.5, */*;q=0.1";
+
+/// Result of fetching a feed URL
+pub struct FetchedFeed {
+    pub content: ConditionalFetch,
+    /// Where the document was fetched from after following redirects
+    pub url: Url,
+    pub directives: CacheDirectives,
+    /// Where the feed now lives, when every redirect hop was permanent (301/308)
+    pub permanent_redirect: Option<Url>,
+}
+
+/// HTTP client shared by every feed request, configured from a [`FetchConfig`]
+///
+/// Redirects are followed here rather than by reqwest so that permanent moves
+/// can be reported back and written to the stored feed URL.
+pub struct FeedFetcher {
+    client: Client,
+    config: FetchConfig,
+}
+
+impl FeedFetcher {
+    /// Builds the client; fails if the proxy URL is invalid
+    pub fn new(config: FetchConfig) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static(ACCEPT_FEEDS));
+
+        let client = config.client_builder()?
+            .default_headers(headers)
+            .timeout(config.timeout)
+            .redirect(Policy::none())
+            .build()?;
+
+        Ok(Self { client, config })
+    }
+
+    pub fn config(&self) -> &FetchConfig {
+        &self.config
+    }
+
+    /// Fetches `url`, sending the feed's cache validators and custom headers when given
+    ///
+    /// Custom headers usually carry credentials, so they are only sent to the
+    /// origin of `url`; once a redirect leaves it they are dropped for good.
+    /// The configured timeout covers the whole fetch, every redirect hop and
+    /// the body included.
+    pub async fn fetch(&self, url: &str, feed: Option<&Feed>) -> Result<FetchedFeed> {
+        tokio::time::timeout(self.config.timeout, self.follow_redirects(url, feed))
+            .await
+            .map_err(|_| anyhow!("Timed out after {} seconds fetching {}", self.config.timeout.as_secs_f32(), url))?
+    }
+
+    async fn follow_redirects(&self, url: &str, feed: Option<&Feed>) -> Result<FetchedFeed> {
+        let mut current = Url::parse(url)?;
+        let origin = current.origin();
+        let mut same_origin = true;
+        let mut all_permanent = true;
+
+        for hop in 0..=self.config.max_redirects {
+            same_origin &= current.origin() == origin;
+            let mut request = self.client.get(current.clone());
+            if let Some(feed) = feed {
+                request = with_validators(request, feed);
+                if same_origin {
+                    for (name, value) in self.config.headers_for(&feed.id) {
+                        request = request.header(name.as_str(), value.as_str());
+                    }
+                }
+            }
+
+            let response = request.send().await?;
+            let status = response.status();
+            if status.is_redirection() && status != StatusCode::NOT_MODIFIED {
+                let location = response.headers().get(LOCATION)
+                    .and_then(|value| value.to_str().ok())
+                    .ok_or_else(|| anyhow!("Redirect from {} without a Location header", current))?;
+                current = current.join(location)?;
+                all_permanent &= matches!(status, StatusCode::MOVED_PERMANENTLY | StatusCode::PERMANENT_REDIRECT);
+                continue;
+            }
+
+            let directives = CacheDirectives::from_headers(response.headers(), Utc::now());
+            let content = read_conditional(response, self.config.max_body_bytes).await?;
+            let permanent_redirect = (hop > 0 && all_permanent).then(|| current.clone());
+            return Ok(FetchedFeed { content, url: current, directives, permanent_redirect });
+        }
+
+        Err(anyhow!("Too many redirects fetching {}", url))
+    }
+}
@@ -0,0 +1,234 @@
+use feed_rs::model::{Feed as FeedRs, Generator, Link};
+use url::Url;
+
+use crate::models::feed::Feed;
+use crate::services::rss::entry_mapper::person_name;
+
+/// Maps a parsed feed document (RSS, Atom or JSON Feed) to a `Feed` subscribed at `url`
+pub fn map_feed(url: Url, document: &FeedRs) -> Feed {
+    let title = document.title.as_ref()
+        .map(|t| t.content.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "Untitled Feed".to_string());
+    let site_url = site_link(&url, &document.links);
+    // Atom's `<icon>` and JSON Feed's `favicon` are small; RSS only has a logo
+    let icon_url = document.icon.as_ref()
+        .or(document.logo.as_ref())
+        .and_then(|image| url.join(image.uri.trim()).ok());
+
+    let mut feed = Feed::new(title, url);
+
+    if let Some(description) = document.description.as_ref()
+        .map(|d| d.content.trim().to_string())
+        .filter(|d| !d.is_empty())
+    {
+        feed = feed.with_description(description);
+    }
+
+    if let Some(language) = document.language.clone() {
+        feed = feed.with_language(language);
+    }
+
+    if let Some(author) = feed_author(document) {
+        feed = feed.with_author(author);
+    }
+
+    if let Some(generator) = document.generator.as_ref().and_then(generator_name) {
+        feed = feed.with_generator(generator);
+    }
+
+    if let Some(icon_url) = icon_url {
+        feed = feed.with_icon_url(icon_url);
+    }
+
+    if let Some(site_url) = site_url {
+        feed = feed.with_site_url(site_url);
+    }
+
+    feed
+}
+
+/// The feed's authors, or for RSS its managing editor
+fn feed_author(document: &FeedRs) -> Option<String> {
+    let editors = document.contributors.iter().filter(|person| person.name == "managingEditor");
+    let mut names: Vec<String> = Vec::new();
+    for name in document.authors.iter().filter_map(person_name) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        names.extend(editors.filter_map(person_name).take(1));
+    }
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+/// The generator's name with its version, when the feed gives one separately
+fn generator_name(generator: &Generator) -> Option<String> {
+    let name = generator.content.trim();
+    let name = match generator.version.as_deref().map(str::trim) {
+        Some(version) if !version.is_empty() && !name.contains(version) => format!("{} {}", name, version),
+        _ => name.to_string(),
+    };
+    let name = name.trim();
+    match name.is_empty() {
+        true => generator.uri.clone(),
+        false => Some(name.to_string()),
+    }
+}
+
+/// The website a feed belongs to
+///
+/// Atom marks it `rel="alternate"`; RSS and JSON Feed links carry no rel, and
+/// JSON Feed lists its own `feed_url` next to the `home_page_url`.
+fn site_link(feed_url: &Url, links: &[Link]) -> Option<Url> {
+    let alternate = links.iter().find(|l| l.rel.as_deref() == Some("alternate"));
+
+    alternate
+        .into_iter()
+        .chain(links.iter().filter(|l| l.rel.is_none()))
+        .filter_map(|l| Url::parse(&l.href).ok())
+        .find(|url| url != feed_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use chrono::{TimeZone, Utc};
+    use crate::models::article::Article;
+    use crate::services::rss::{map_entry, parse_feed, FeedCandidate, FeedFormat};
+
+    const FIXTURES: [(&str, &str, FeedFormat, &[u8]); 6] = [
+        ("rss091", "https://example.com/rss091.xml", FeedFormat::Rss, include_bytes!("fixtures/rss091.xml")),
+        ("rss2", "https://example.com/feed.xml", FeedFormat::Rss, include_bytes!("fixtures/rss2.xml")),
+        ("rss1", "https://example.com/index.rdf", FeedFormat::Rss, include_bytes!("fixtures/rss1.rdf")),
+        ("atom", "https://example.com/atom.xml", FeedFormat::Atom, include_bytes!("fixtures/atom.xml")),
+        ("jsonfeed10", "https://example.com/feed.json", FeedFormat::Json, include_bytes!("fixtures/jsonfeed10.json")),
+        ("jsonfeed11", "https://example.com/feed.json", FeedFormat::Json, include_bytes!("fixtures/jsonfeed11.json")),
+    ];
+
+    fn load(url: &str, content: &[u8]) -> Result<(Feed, FeedFormat, Vec<Article>)> {
+        let document = parse_feed(content)?;
+        let feed = map_feed(Url::parse(url)?, &document);
+        let format = FeedCandidate::from_document(feed.url.clone(), &document).format;
+        let articles = document.entries.iter().filter_map(|entry| map_entry(&feed.id, entry)).collect();
+        Ok((feed, format, articles))
+    }
+
+    #[test]
+    fn test_formats_map_alike() -> Result<()> {
+        for (name, url, expected_format, content) in FIXTURES {
+            let (feed, format, articles) = load(url, content)?;
+            assert_eq!(format, expected_format, "{}", name);
+            assert_eq!(feed.title, "Parity Blog", "{}", name);
+            assert_eq!(feed.site_url.as_ref().map(Url::as_str), Some("https://example.com/"), "{}", name);
+            assert_eq!(feed.description.as_deref(), Some("Posts about format parity"), "{}", name);
+            if name != "jsonfeed10" {
+                assert_eq!(feed.language.as_deref(), Some("en-us"), "{}", name);
+            }
+
+            let first = &articles[0];
+            let second = &articles[1];
+            assert_eq!((first.title.as_str(), first.url.as_str()), ("First post", "https://example.com/posts/1"), "{}", name);
+            // Enclosures and attachments never replace the link to the post
+            assert_eq!((second.title.as_str(), second.url.as_str()), ("Second post", "https://example.com/posts/2"), "{}", name);
+            let body = first.content.as_ref().or(first.summary.as_ref()).unwrap();
+            assert!(body.contains("Hello <b>world</b>"), "{}: {}", name, body);
+
+            // RSS 0.91 items have nothing but a title, link and description
+            if name == "rss091" {
+                continue;
+            }
+            assert_eq!(first.published_at, Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap(), "{}", name);
+            assert_eq!(second.published_at, Utc.with_ymd_and_hms(2024, 1, 3, 12, 30, 0).unwrap(), "{}", name);
+            assert_eq!(first.author.as_deref(), Some("Ada Lovelace"), "{}", name);
+            assert_eq!(second.author.as_deref(), Some("Grace Hopper"), "{}", name);
+            if name != "rss1" {
+                assert_eq!(first.guid.as_deref(), Some("post-1"), "{}", name);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_feed_metadata() -> Result<()> {
+        let rss = br#"<?xml version="1.0"?><rss version="2.0"><channel>
+            <title>Radio</title><link>https://radio.example/</link>
+            <managingEditor>editor@radio.example (Rita Editor)</managingEditor>
+            <generator>WordPress 6.4</generator>
+            <image><url>/images/logo.png</url><title>Radio</title><link>https://radio.example/</link></image>
+        </channel></rss>"#;
+        let (feed, _, _) = load("https://radio.example/feed/", rss)?;
+        assert_eq!(feed.author.as_deref(), Some("Rita Editor"));
+        assert_eq!(feed.generator.as_deref(), Some("WordPress 6.4"));
+        assert_eq!(feed.icon_url.as_ref().map(Url::as_str), Some("https://radio.example/images/logo.png"));
+
+        let atom = br#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Notes</title><id>urn:notes</id><updated>2024-01-01T00:00:00Z</updated>
+            <author><name>Ada Lovelace</name></author><author><name>Grace Hopper</name></author>
+            <generator uri="https://gohugo.io/" version="0.120">Hugo</generator>
+            <logo>https://notes.example/logo.png</logo><icon>https://notes.example/favicon.ico</icon>
+        </feed>"#;
+        let (feed, _, _) = load("https://notes.example/atom.xml", atom)?;
+        assert_eq!(feed.author.as_deref(), Some("Ada Lovelace, Grace Hopper"));
+        assert_eq!(feed.generator.as_deref(), Some("Hugo 0.120"));
+        assert_eq!(feed.icon_url.as_ref().map(Url::as_str), Some("https://notes.example/favicon.ico"));
+
+        let (feed, _, _) = load("https://example.com/feed.json", include_bytes!("fixtures/jsonfeed11.json"))?;
+        assert_eq!(feed.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(feed.generator, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_content_urls_resolved() -> Result<()> {
+        // The content's xml:base wins over the entry link, which lacks the trailing slash
+        let atom = br#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Notes</title><id>urn:notes</id><updated>2024-01-01T00:00:00Z</updated>
+            <entry><title>Travel</title><id>urn:travel</id><updated>2024-01-01T00:00:00Z</updated>
+              <link href="https://notes.example/post/travel"/>
+              <content type="html" xml:base="https://notes.example/post/travel/"><![CDATA[<p><img src="IMG_1232.jpeg" /></p>]]></content>
+            </entry>
+        </feed>"#;
+        let (_, _, articles) = load("https://notes.example/atom.xml", atom)?;
+        assert_eq!(articles[0].content.as_deref(), Some(r#"<p><img src="https://notes.example/post/travel/IMG_1232.jpeg" /></p>"#));
+
+        let rss = br#"<?xml version="1.0"?><rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>
+            <title>Radio</title><link>http://radio.example/</link>
+            <item><title>Show</title><link>https://radio.example/shows/1</link>
+              <description><![CDATA[<a href="/shows/2?utm_source=rss&amp;utm_medium=feed">Next show</a>]]></description>
+              <content:encoded><![CDATA[<img data-src="cover.jpg" src="data:image/gif;base64,R0lGODlhAQABAAAAACw="><a href="http://radio.example/archive?fbclid=x">Archive</a>]]></content:encoded>
+            </item>
+        </channel></rss>"#;
+        let (_, _, articles) = load("https://radio.example/feed/", rss)?;
+        assert_eq!(
+            articles[0].content.as_deref(),
+            Some(r#"<img src="https://radio.example/shows/cover.jpg"><a href="https://radio.example/archive">Archive</a>"#)
+        );
+        assert_eq!(articles[0].summary.as_deref(), Some(r#"<a href="https://radio.example/shows/2">Next show</a>"#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_feed_items() -> Result<()> {
+        let (_, _, articles) = load("https://example.com/feed.json", include_bytes!("fixtures/jsonfeed10.json"))?;
+        // content_text alone becomes escaped HTML
+        assert_eq!(articles[1].content.as_deref(), Some("<p>Episode two &amp; notes</p>"));
+
+        let (_, _, articles) = load("https://example.com/feed.json", include_bytes!("fixtures/jsonfeed11.json"))?;
+        assert_eq!(articles[0].content.as_deref(), Some("<p>Hello <b>world</b></p>"));
+        assert_eq!(articles[0].summary.as_deref(), Some("Hello world"));
+        assert_eq!(
+            articles[1].content.as_deref(),
+            Some("<p>Episode two &amp; notes</p>\n<p>Second paragraph</p>"),
+        );
+
+        // An untitled note is named after its text and dated by date_modified
+        let note = &articles[2];
+        assert_eq!(note.title, "A short note without a title, as microblogs publish them");
+        assert_eq!(note.author.as_deref(), Some("Ada Lovelace"));
+        assert_eq!(note.published_at, Utc.with_ymd_and_hms(2024, 1, 4, 8, 0, 0).unwrap());
+        Ok(())
+    }
+}
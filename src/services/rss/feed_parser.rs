@@ -0,0 +1,87 @@
+use anyhow::Result;
+use chrono::Duration;
+use feed_rs::model::Feed as FeedRs;
+use feed_rs::parser;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// Parses a raw feed document (RSS, Atom or JSON Feed)
+///
+/// Entries without an id/GUID are left with an empty id rather than feed-rs's
+/// generated link+title hash, which changes whenever a title is edited. The
+/// dedupe step supplies its own fallback keys for those entries.
+pub fn parse_feed(content: &[u8]) -> Result<FeedRs> {
+    let parser = parser::Builder::new()
+        .id_generator(|_links, _title, _uri| String::new())
+        .build();
+    Ok(parser.parse(content)?)
+}
+
+/// Reads the Syndication module's `sy:updatePeriod`/`sy:updateFrequency` pair
+/// as an interval between updates; feed-rs does not expose these elements
+///
+/// Only the channel header is scanned, and a missing frequency counts as one
+/// update per period, as the module specifies.
+pub fn syndication_interval(content: &[u8]) -> Option<Duration> {
+    let mut reader = Reader::from_reader(content);
+    reader.trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut current = None;
+    let mut period = None;
+    let mut frequency = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"item" | b"entry" => break,
+                b"updatePeriod" => current = Some(b"updatePeriod".as_slice()),
+                b"updateFrequency" => current = Some(b"updateFrequency".as_slice()),
+                _ => current = None,
+            },
+            Ok(Event::Text(text)) => {
+                let value = text.unescape().ok()?.trim().to_string();
+                match current.take() {
+                    Some(b"updatePeriod") => period = Some(value),
+                    Some(b"updateFrequency") => frequency = value.parse::<i32>().ok(),
+                    _ => {}
+                }
+            }
+            Ok(Event::End(_)) => current = None,
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    let period = match period?.as_str() {
+        "hourly" => Duration::hours(1),
+        "daily" => Duration::days(1),
+        "weekly" => Duration::weeks(1),
+        "monthly" => Duration::days(30),
+        "yearly" => Duration::days(365),
+        _ => return None,
+    };
+    Some(period / frequency.filter(|f| *f > 0).unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syndication_interval() {
+        let document = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/">
+              <channel>
+                <title>Feed</title>
+                <sy:updatePeriod>daily</sy:updatePeriod>
+                <sy:updateFrequency>4</sy:updateFrequency>
+                <item><title>Post</title></item>
+              </channel>
+            </rss>"#;
+
+        assert_eq!(syndication_interval(document.as_bytes()), Some(Duration::hours(6)));
+        assert_eq!(syndication_interval(b"<rss><channel><title>Feed</title></channel></rss>"), None);
+    }
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+use anyhow::{Context, Result};
+use reqwest::{Client, ClientBuilder, Proxy};
+
+use crate::models::feed::FeedId;
+use crate::models::settings::{FeedHeaders, FetchSettings};
+
+/// Default User-Agent sent with every feed request
+pub const DEFAULT_USER_AGENT: &str = concat!("Panda/", env!("CARGO_PKG_VERSION"), " (RSS reader)");
+
+/// Settings applied to every HTTP request made for feeds
+#[derive(Debug, Clone, PartialEq)]
+pub struct FetchConfig {
+    /// Upper bound on a whole request, including reading the body
+    pub timeout: Duration,
+    /// Responses larger than this are rejected instead of being buffered
+    pub max_body_bytes: usize,
+    pub user_agent: String,
+    /// Proxy used for all requests, e.g. `http://proxy.corp:3128`
+    pub proxy_url: Option<String>,
+    /// Redirect hops followed before a fetch is abandoned
+    pub max_redirects: usize,
+    /// Feeds synced at the same time across all hosts
+    pub max_concurrent_fetches: usize,
+    /// Feeds synced at the same time from any single host
+    pub max_fetches_per_host: usize,
+    /// Extra request headers for individual feeds (auth tokens, API keys)
+    pub feed_headers: FeedHeaders,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_body_bytes: 10 * 1024 * 1024,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy_url: None,
+            max_redirects: 5,
+            max_concurrent_fetches: 8,
+            max_fetches_per_host: 2,
+            feed_headers: FeedHeaders::default(),
+        }
+    }
+}
+
+impl FetchConfig {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = max_body_bytes;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn with_proxy_url(mut self, proxy_url: String) -> Self {
+        self.proxy_url = Some(proxy_url);
+        self
+    }
+
+    pub fn with_max_redirects(mut self, max_redirects: usize) -> Self {
+        self.max_redirects = max_redirects;
+        self
+    }
+
+    pub fn with_max_concurrent_fetches(mut self, max_concurrent_fetches: usize) -> Self {
+        self.max_concurrent_fetches = max_concurrent_fetches;
+        self
+    }
+
+    pub fn with_max_fetches_per_host(mut self, max_fetches_per_host: usize) -> Self {
+        self.max_fetches_per_host = max_fetches_per_host;
+        self
+    }
+
+    /// Takes every user-editable value from `settings`
+    pub fn with_settings(mut self, settings: &FetchSettings) -> Self {
+        self.timeout = Duration::from_secs(settings.timeout_secs);
+        self.max_body_bytes = settings.max_body_bytes as usize;
+        self.user_agent = settings.user_agent.clone().unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+        self.proxy_url = settings.proxy_url.clone();
+        self.max_redirects = settings.max_redirects as usize;
+        self.max_concurrent_fetches = settings.max_concurrent as usize;
+        self.max_fetches_per_host = settings.max_per_host as usize;
+        self.feed_headers = settings.feed_headers.clone();
+        self
+    }
+
+    /// A client builder with the user agent, proxy and connect timeout that
+    /// every request the reader makes shares; fails if the proxy URL is invalid
+    ///
+    /// Callers add the overall timeout, if their requests should have one.
+    pub fn client_builder(&self) -> Result<ClientBuilder> {
+        let mut builder = Client::builder()
+            .connect_timeout(self.timeout)
+            .user_agent(self.user_agent.clone());
+        if let Some(proxy_url) = &self.proxy_url {
+            builder = builder.proxy(Proxy::all(proxy_url).context("Invalid proxy URL")?);
+        }
+        Ok(builder)
+    }
+
+    /// Adds a header sent only when fetching the given feed
+    pub fn with_feed_header(mut self, feed_id: FeedId, name: &str, value: &str) -> Self {
+        self.feed_headers.push(feed_id, name, value);
+        self
+    }
+
+    /// Extra headers configured for a feed
+    pub fn headers_for(&self, feed_id: &FeedId) -> &[(String, String)] {
+        self.feed_headers.get(feed_id)
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header::{
+    HeaderMap, HeaderName, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER,
+};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::models::feed::Feed;
+
+/// Outcome of a conditional request for a feed document
+pub enum ConditionalFetch {
+    /// The server answered `304 Not Modified`; the stored articles are current
+    NotModified,
+    /// A fresh document, with the validators to send on the next request
+    Modified {
+        body: Vec<u8>,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        /// The `Content-Type` header, which may name the body's charset
+        content_type: Option<String>,
+    },
+}
+
+/// Scheduling directives a server attached to its response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CacheDirectives {
+    /// `Cache-Control: max-age`
+    pub max_age: Option<Duration>,
+    /// `Retry-After`, resolved to an absolute time
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl CacheDirectives {
+    /// Reads `Cache-Control` and `Retry-After`, ignoring values that do not parse
+    pub fn from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Self {
+        let max_age = header_value(headers, CACHE_CONTROL).and_then(|value| {
+            value.split(',')
+                .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+                .find_map(|seconds| seconds.trim_matches('"').parse::<i64>().ok())
+                .map(Duration::seconds)
+        });
+
+        // Either a delay in seconds or an HTTP date
+        let retry_after = header_value(headers, RETRY_AFTER).and_then(|value| match value.parse::<i64>() {
+            Ok(seconds) => Some(now + Duration::seconds(seconds)),
+            Err(_) => DateTime::parse_from_rfc2822(&value).ok().map(|date| date.with_timezone(&Utc)),
+        });
+
+        Self { max_age, retry_after }
+    }
+}
+
+/// A feed request that failed with an HTTP error status
+#[derive(Debug)]
+pub struct HttpStatusError {
+    pub status: StatusCode,
+    /// When the server asked us to come back (`429`/`503` responses)
+    pub retry_after: Option<DateTime<Utc>>,
+}
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Server responded with HTTP {}", self.status)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Adds `If-None-Match`/`If-Modified-Since` from the validators stored on the feed
+pub fn with_validators(request: RequestBuilder, feed: &Feed) -> RequestBuilder {
+    let mut request = request;
+    if let Some(etag) = &feed.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &feed.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    request
+}
+
+/// Reads a response to a conditional request, failing with [`HttpStatusError`]
+/// on any error status and on a body larger than `max_body_bytes`
+pub async fn read_conditional(mut response: Response, max_body_bytes: usize) -> Result<ConditionalFetch> {
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(ConditionalFetch::NotModified);
+    }
+    if status.is_client_error() || status.is_server_error() {
+        let retry_after = CacheDirectives::from_headers(response.headers(), Utc::now()).retry_after;
+        return Err(HttpStatusError { status, retry_after }.into());
+    }
+
+    let etag = header_value(response.headers(), ETAG);
+    let last_modified = header_value(response.headers(), LAST_MODIFIED);
+    let content_type = header_value(response.headers(), CONTENT_TYPE);
+
+    if response.content_length().is_some_and(|len| len > max_body_bytes as u64) {
+        bail!("Feed body exceeds the {} byte limit", max_body_bytes);
+    }
+
+    // Content-Length may be absent or wrong, so enforce the limit while reading too
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_body_bytes {
+            bail!("Feed body exceeds the {} byte limit", max_body_bytes);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(ConditionalFetch::Modified { body, etag, last_modified, content_type })
+}
+
+fn header_value(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_cache_directives_from_headers() {
+        let now = Utc::now();
+        let mut headers = HeaderMap::new();
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("public, max-age=1800"));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+
+        let directives = CacheDirectives::from_headers(&headers, now);
+        assert_eq!(directives.max_age, Some(Duration::minutes(30)));
+        assert_eq!(directives.retry_after, Some(now + Duration::minutes(2)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Thu, 01 Jan 2026 00:00:00 GMT"));
+        let directives = CacheDirectives::from_headers(&headers, now);
+        assert_eq!(directives.retry_after.map(|d| d.to_rfc3339()), Some("2026-01-01T00:00:00+00:00".to_string()));
+    }
+}
@@ -1,3 +1,27 @@
+mod content_rewriter;
+mod dedupe;
+mod entry_mapper;
+mod favicon;
+mod feed_discovery;
+mod feed_fetcher;
+mod feed_mapper;
+mod feed_parser;
+mod fetch_config;
+mod http_cache;
 mod rss_service;
+mod sync_progress;
+mod sync_report;
 
-pub use rss_service::RssService;
\ No newline at end of file
+pub(crate) use content_rewriter::srcset_candidates;
+pub use content_rewriter::{rewrite_content, strip_tracking_params};
+pub use dedupe::{dedupe_keys, DedupeKey};
+pub use entry_mapper::{map_enclosures, map_entry};
+pub use favicon::image_mime_type;
+pub use feed_discovery::{find_feed_links, find_icon_links, FeedCandidate, FeedFormat, COMMON_FEED_PATHS};
+pub use feed_fetcher::{FeedFetcher, FetchedFeed};
+pub use feed_mapper::map_feed;
+pub use feed_parser::parse_feed;
+pub use fetch_config::{FetchConfig, DEFAULT_USER_AGENT};
+pub use rss_service::RssService;
+pub use sync_progress::SyncProgress;
+pub use sync_report::FeedSyncReport;
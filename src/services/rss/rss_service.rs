@@ -1,18 +1,81 @@
 use anyhow::{Result, anyhow};
 use chrono::{DateTime, Duration, Utc};
-use feed_rs::parser;
-use log::{error, info};
+use log::{error, info, warn};
 use url::Url;
 use uuid::Uuid;
-use std::sync::Arc;
-use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use futures_util::future::join_all;
+use tokio::sync::{broadcast, watch, Semaphore};
 use feed_rs::model::Feed as FeedRs;
 
 use crate::models::article::{Article, ArticleId, ReadStatus};
+use crate::models::enclosure::Enclosure;
 use crate::models::feed::{Feed, FeedId, FeedStatus};
-use crate::base::repository::{ArticleRepository, FeedRepository, CategoryRepository, TagRepository};
+use crate::models::feed_icon::FeedIcon;
+use crate::base::repository::{ArticleRepository, FeedRepository, CategoryRepository, TagRepository, EnclosureRepository};
 use crate::models::category::{Category, CategoryId};
+use crate::models::search::{SearchHit, SearchQuery};
+use crate::models::article_page::{ArticleCursor, ArticleFilter, ArticlePage};
+use crate::models::settings::ArticleSortOrder;
 use crate::models::tag::{Tag, TagId};
+use crate::services::rss::dedupe::{dedupe_keys, has_feed_guid, DedupeKey};
+use crate::services::rss::entry_mapper::{map_enclosures, map_entry};
+use crate::services::rss::favicon::image_mime_type;
+use crate::services::rss::feed_discovery::{find_feed_links, find_icon_links, FeedCandidate, COMMON_FEED_PATHS};
+use crate::services::rss::feed_parser::parse_feed;
+use crate::services::rss::feed_fetcher::{FeedFetcher, FetchedFeed};
+use crate::services::rss::feed_mapper::map_feed;
+use crate::services::rss::fetch_config::FetchConfig;
+use crate::services::rss::feed_parser::syndication_interval;
+use crate::services::rss::http_cache::{ConditionalFetch, HttpStatusError};
+use crate::utils::html::decode_html;
+use crate::services::sync::{BatchId, ScheduleHints, SchedulePolicy, SyncEvent};
+use crate::services::rss::sync_progress::SyncProgress;
+use crate::services::rss::sync_report::FeedSyncReport;
+
+/// Sync events buffered per subscriber before the oldest are dropped
+const SYNC_EVENT_CAPACITY: usize = 1024;
+
+/// What happened to a single entry when it was written to the repository
+enum UpsertOutcome {
+    Inserted,
+    Updated,
+    Unchanged,
+}
+
+/// Reports a batch as cancelled if its future is dropped before it finishes
+///
+/// It counts the batch's own finished feeds, since the shared progress may
+/// meanwhile belong to another batch.
+struct CancelledBatchGuard<'a> {
+    rss_service: &'a RssService,
+    batch: BatchId,
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+    armed: bool,
+}
+
+impl CancelledBatchGuard<'_> {
+    fn count(&self, result: &Result<FeedSyncReport>) {
+        let counter = if result.is_ok() { &self.succeeded } else { &self.failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for CancelledBatchGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.rss_service.emit_event(SyncEvent::Finished {
+                batch: self.batch,
+                succeeded: self.succeeded.load(Ordering::Relaxed),
+                failed: self.failed.load(Ordering::Relaxed),
+                cancelled: true,
+            });
+        }
+    }
+}
 
 /// Service for managing RSS feeds
 pub struct RssService {
@@ -20,7 +83,13 @@ pub struct RssService {
     feed_repository: Arc<dyn FeedRepository>,
     category_repository: Arc<dyn CategoryRepository>,
     tag_repository: Arc<dyn TagRepository>,
-    client: Client,
+    enclosure_repository: Arc<dyn EnclosureRepository>,
+    /// Swapped out whole when the fetch settings change; in-flight fetches keep the old one
+    fetcher: RwLock<Arc<FeedFetcher>>,
+    schedule_policy: RwLock<SchedulePolicy>,
+    progress: watch::Sender<SyncProgress>,
+    events: broadcast::Sender<SyncEvent>,
+    next_batch: AtomicU64,
 }
 
 impl RssService {
@@ -30,27 +99,89 @@ impl RssService {
         feed_repository: Arc<dyn FeedRepository>,
         category_repository: Arc<dyn CategoryRepository>,
         tag_repository: Arc<dyn TagRepository>,
+        enclosure_repository: Arc<dyn EnclosureRepository>,
     ) -> Self {
         Self {
             article_repository,
             feed_repository,
             category_repository,
             tag_repository,
-            client: Client::new(),
+            enclosure_repository,
+            fetcher: RwLock::new(Arc::new(FeedFetcher::new(FetchConfig::default())
+                .expect("Default fetch configuration is valid"))),
+            schedule_policy: RwLock::new(SchedulePolicy::default()),
+            progress: watch::channel(SyncProgress::default()).0,
+            events: broadcast::channel(SYNC_EVENT_CAPACITY).0,
+            next_batch: AtomicU64::new(1),
         }
     }
 
-    /// Fetches all feeds
+    /// Replaces the bounds used when scheduling each feed's next fetch
+    pub fn with_schedule_policy(self, schedule_policy: SchedulePolicy) -> Self {
+        self.set_schedule_policy(schedule_policy);
+        self
+    }
+
+    /// Replaces the HTTP settings used for every feed request
+    pub fn with_fetch_config(self, config: FetchConfig) -> Result<Self> {
+        self.set_fetch_config(config)?;
+        Ok(self)
+    }
+
+    /// Changes the scheduling bounds for fetches that finish from now on
+    pub fn set_schedule_policy(&self, schedule_policy: SchedulePolicy) {
+        *self.schedule_policy.write().unwrap() = schedule_policy;
+    }
+
+    /// Changes the HTTP settings for requests started from now on
+    ///
+    /// On error (e.g. an unparseable proxy URL) the current settings stay in use.
+    pub fn set_fetch_config(&self, config: FetchConfig) -> Result<()> {
+        let fetcher = Arc::new(FeedFetcher::new(config)?);
+        *self.fetcher.write().unwrap() = fetcher;
+        Ok(())
+    }
+
+    /// Bounds used when scheduling each feed's next fetch
+    pub fn schedule_policy(&self) -> SchedulePolicy {
+        self.schedule_policy.read().unwrap().clone()
+    }
+
+    /// HTTP settings used for every feed request
+    pub fn fetch_config(&self) -> FetchConfig {
+        self.fetcher().config().clone()
+    }
+
+    fn fetcher(&self) -> Arc<FeedFetcher> {
+        self.fetcher.read().unwrap().clone()
+    }
+
+    /// Fetches all feeds, whether or not they are due, and stores their articles
     pub async fn fetch_all_feeds(&self) -> Result<()> {
         let feeds = self.feed_repository.get_all_feeds().await?;
-        for feed in feeds {
-            if let Err(e) = self.fetch_feed(feed.url.as_str()).await {
+        for (feed, result) in feeds.iter().zip(self.sync_feeds(&feeds).await) {
+            if let Err(e) = result {
                 log::error!("Failed to fetch feed {}: {}", feed.title, e);
             }
         }
         Ok(())
     }
 
+    /// Subscribes to progress updates for batch syncs (`fetch_all_feeds`, `sync_all_feeds`)
+    pub fn subscribe_progress(&self) -> watch::Receiver<SyncProgress> {
+        self.progress.subscribe()
+    }
+
+    /// Subscribes to per-feed and per-batch sync events
+    pub fn subscribe_events(&self) -> broadcast::Receiver<SyncEvent> {
+        self.events.subscribe()
+    }
+
+    /// Broadcasts a sync event; having no subscribers is not an error
+    pub fn emit_event(&self, event: SyncEvent) {
+        let _ = self.events.send(event);
+    }
+
     pub async fn fetch_feed_by_id(&self, feed_id: &FeedId) -> Result<Feed> {
         if let Some(feed) = self.feed_repository.get_feed_by_id(feed_id).await? {
             self.fetch_feed(feed.url.as_str()).await
@@ -59,106 +190,127 @@ impl RssService {
         }
     }
 
+    /// Downloads the document at `url`, returning the URL it was served
+    /// from after redirects, its body and its `Content-Type`
+    async fn fetch_body(&self, url: &str) -> Result<(Url, Vec<u8>, Option<String>)> {
+        let fetched = self.fetcher().fetch(url, None).await?;
+        match fetched.content {
+            ConditionalFetch::Modified { body, content_type, .. } => Ok((fetched.url, body, content_type)),
+            ConditionalFetch::NotModified => Err(anyhow!("Unexpected 304 for unconditional request to {}", url)),
+        }
+    }
+
+    /// Downloads and parses the feed document at `url`
+    async fn fetch_document(&self, url: &str) -> Result<FeedRs> {
+        let (_, body, _) = self.fetch_body(url).await?;
+        parse_feed(&body)
+    }
+
     /// Fetches and parses a feed
     pub async fn fetch_feed(&self, url: &str) -> Result<Feed> {
-        let response = reqwest::get(url).await?;
-        let content = response.bytes().await?;
-        let feed_rs = feed_rs::parser::parse(&content[..])?;
-        
-        let mut feed = Feed::new(
-            feed_rs.title.map(|t| t.content).unwrap_or_else(|| "Untitled Feed".to_string()),
-            Url::parse(url)?
-        );
+        let feed_rs = self.fetch_document(url).await?;
+        Ok(map_feed(Url::parse(url)?, &feed_rs))
+    }
 
-        if let Some(desc) = feed_rs.description.map(|d| d.content) {
-            feed = feed.with_description(desc);
-        }
+    /// Fetches a feed and maps its entries to articles without storing them
+    ///
+    /// The request is conditional on the feed's stored validators, so an
+    /// unchanged feed yields no articles.
+    pub async fn fetch_articles(&self, feed: &Feed) -> Result<Vec<Article>> {
+        let fetched = self.fetcher().fetch(feed.url.as_str(), Some(feed)).await?;
+        let ConditionalFetch::Modified { body, .. } = fetched.content else {
+            return Ok(Vec::new());
+        };
+        let feed_rs = parse_feed(&body)?;
 
-        if let Some(lang) = feed_rs.language {
-            feed = feed.with_language(lang);
-        }
+        Ok(feed_rs.entries
+            .iter()
+            .filter_map(|entry| map_entry(&feed.id, entry))
+            .collect())
+    }
 
-        if let Some(link) = feed_rs.links.first() {
-            if let Ok(site_url) = Url::parse(&link.href) {
-                feed = feed.with_site_url(site_url);
-            }
+    /// Finds the feeds of a site: the page's own `<link rel="alternate">`
+    /// feeds and any feed at one of the [`COMMON_FEED_PATHS`]
+    ///
+    /// A URL that is already a feed yields just that feed.
+    pub async fn discover_feeds(&self, url: &str) -> Result<Vec<FeedCandidate>> {
+        let (page_url, body, content_type) = self.fetch_body(url).await?;
+        if let Ok(document) = parse_feed(&body) {
+            return Ok(vec![FeedCandidate::from_document(page_url, &document)]);
         }
 
-        Ok(feed)
+        let candidates = self.feeds_of_page(&page_url, &body, content_type.as_deref()).await;
+        if candidates.is_empty() {
+            return Err(anyhow!("No feeds found at {}", url));
+        }
+        Ok(candidates)
     }
 
-    pub async fn fetch_articles(&self, feed: &Feed) -> Result<Vec<Article>> {
-        let response = reqwest::get(feed.url.as_str()).await?;
-        let content = response.bytes().await?;
-        let feed_rs = feed_rs::parser::parse(&content[..])?;
-        
-        let mut articles = Vec::new();
-        for entry in feed_rs.entries {
-            let content = entry.content.and_then(|c| c.body);
-            let summary = entry.summary.map(|s| s.content);
-            
-            let mut article = Article::new(
-                feed.id.clone(),
-                entry.title.map(|t| t.content).unwrap_or_else(|| "Untitled".to_string()),
-                Url::parse(&entry.links.get(0).map(|l| l.href.clone()).unwrap_or_default())?
-            );
-
-            if let Some(content) = content {
-                article = article.with_content(content);
+    /// The feeds a web page links to, followed by any found at one of the
+    /// [`COMMON_FEED_PATHS`] of its site
+    async fn feeds_of_page(&self, page_url: &Url, body: &[u8], content_type: Option<&str>) -> Vec<FeedCandidate> {
+        let mut candidates = find_feed_links(&decode_html(body, content_type), page_url);
+        let probes = COMMON_FEED_PATHS.iter()
+            .filter_map(|path| page_url.join(path).ok())
+            .filter(|probe| !candidates.iter().any(|candidate| candidate.url == *probe));
+        for candidate in join_all(probes.map(|probe| self.probe_feed(probe))).await.into_iter().flatten() {
+            if !candidates.iter().any(|known| known.url == candidate.url) {
+                candidates.push(candidate);
             }
+        }
+        candidates
+    }
 
-            if let Some(summary) = summary {
-                article = article.with_summary(summary);
-            }
-            
-            articles.push(article);
+    /// The feed at `url`, if there is one
+    async fn probe_feed(&self, url: Url) -> Option<FeedCandidate> {
+        let fetched = self.fetcher().fetch(url.as_str(), None).await.ok()?;
+        match fetched.content {
+            ConditionalFetch::Modified { body, .. } => parse_feed(&body).ok()
+                .map(|document| FeedCandidate::from_document(fetched.url, &document)),
+            ConditionalFetch::NotModified => None,
         }
-        
-        Ok(articles)
     }
 
     /// Adds a new feed
+    ///
+    /// If `url` is a web page rather than a feed, subscribes to the first feed
+    /// [`discover_feeds`](Self::discover_feeds) finds for it.
     pub async fn add_feed(&self, url: &str) -> Result<()> {
-        let url = Url::parse(url)?;
-        let response = reqwest::get(url.as_str()).await?;
-        let content = response.text().await?;
-        let feed_data = parser::parse(content.as_bytes())?;
-
-        let feed = Feed::new(
-            feed_data.title.map(|t| t.content).unwrap_or_default(),
-            url,
-        );
+        let (page_url, body, content_type) = self.fetch_body(url).await?;
+        let (url, feed_data) = match parse_feed(&body) {
+            Ok(document) => (Url::parse(url)?, document),
+            Err(e) => {
+                // The page is already downloaded, so look for its feeds in it
+                // rather than starting discovery over
+                let candidate = self.feeds_of_page(&page_url, &body, content_type.as_deref()).await
+                    .into_iter()
+                    .next()
+                    .ok_or(e)?;
+                info!("Subscribing to {} found at {}", candidate.url, url);
+                let document = self.fetch_document(candidate.url.as_str()).await?;
+                (candidate.url, document)
+            }
+        };
 
+        let feed = map_feed(url, &feed_data);
         self.feed_repository.save_feed(&feed).await?;
         Ok(())
     }
 
+    /// Saves a feed without fetching it, e.g. one imported from OPML
+    pub async fn save_feed(&self, feed: &Feed) -> Result<()> {
+        self.feed_repository.save_feed(feed).await
+    }
+
     /// Updates an existing feed
     pub async fn update_feed(&self, feed: &Feed) -> Result<()> {
-        self.feed_repository.save_feed(feed).await?;
+        self.feed_repository.update_feed(feed).await?;
         Ok(())
     }
 
     /// Fetches new articles for all feeds that need to be updated
     pub async fn sync_all(&self) -> Result<()> {
-        let feeds = self.feed_repository.get_feeds_to_update().await?;
-        
-        for feed in feeds {
-            match self.update_feed(&feed).await {
-                Ok(_) => {
-                    info!("Successfully updated feed: {}", feed.title);
-                }
-                Err(e) => {
-                    error!("Failed to update feed {}: {}", feed.title, e);
-                    let mut failed_feed = feed.clone();
-                    failed_feed.update_status(FeedStatus::Error);
-                    failed_feed.update_error_message(e.to_string());
-                    failed_feed.update_fetch_times(Utc::now(), Utc::now() + chrono::Duration::hours(1));
-                    self.feed_repository.update_feed(&failed_feed).await?;
-                }
-            }
-        }
-        
+        self.sync_all_feeds().await?;
         Ok(())
     }
 
@@ -202,6 +354,11 @@ impl RssService {
         self.article_repository.search_articles(query).await
     }
 
+    /// Full-text search over stored articles, best matches first
+    pub async fn search(&self, query: &SearchQuery) -> Result<Vec<SearchHit>> {
+        self.article_repository.search(query).await
+    }
+
     pub async fn fetch_articles_by_date_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<Article>> {
         self.article_repository.get_articles_by_date_range(start, end).await
     }
@@ -223,6 +380,17 @@ impl RssService {
         self.article_repository.get_all_articles().await
     }
 
+    /// One page of article summaries; see [`ArticleRepository::get_article_page`]
+    pub async fn get_article_page(
+        &self,
+        filter: &ArticleFilter,
+        order: ArticleSortOrder,
+        cursor: Option<&ArticleCursor>,
+        limit: usize,
+    ) -> Result<ArticlePage> {
+        self.article_repository.get_article_page(filter, order, cursor, limit).await
+    }
+
     pub async fn get_category_by_id(&self, category_id: &CategoryId) -> Result<Option<Category>> {
         Ok(self.category_repository.get_category_by_id(category_id).await?)
     }
@@ -291,23 +459,352 @@ impl RssService {
         }
     }
 
-    /// Syncs a feed
-    pub async fn sync_feed(&self, feed_id: &FeedId) -> Result<()> {
-        self.fetch_feed_by_id(feed_id).await?;
-        Ok(())
+    /// Syncs a feed: fetches it, stores new or changed entries and records the
+    /// outcome (status, error and fetch times) on the feed itself
+    pub async fn sync_feed(&self, feed_id: &FeedId) -> Result<FeedSyncReport> {
+        self.sync_feed_in_batch(feed_id, None).await
     }
 
-    /// Syncs all feeds
-    pub async fn sync_all_feeds(&self) -> Result<()> {
+    /// Syncs a feed, tagging its events with the batch it belongs to
+    async fn sync_feed_in_batch(&self, feed_id: &FeedId, batch: Option<BatchId>) -> Result<FeedSyncReport> {
+        let mut feed = self.feed_repository.get_feed_by_id(feed_id).await?
+            .ok_or_else(|| anyhow!("Feed not found"))?;
+
+        self.emit_event(SyncEvent::FeedStarted { batch, feed_id: feed.id.clone() });
+        let result = self.ingest_feed(&mut feed).await;
+        let now = Utc::now();
+
+        match result {
+            Ok((report, hints)) => {
+                let next_fetch = self.schedule_policy().next_fetch_after_success(now, &feed, &hints);
+                feed.update_fetch_times(now, next_fetch);
+                feed.update_status(FeedStatus::Active);
+                feed.error_message = None;
+                self.feed_repository.update_feed(&feed).await?;
+                if report.not_modified {
+                    info!("Synced feed {}: not modified", feed.title);
+                } else {
+                    info!(
+                        "Synced feed {}: {} new, {} updated, {} skipped",
+                        feed.title, report.new_count(), report.updated_count, report.skipped_count
+                    );
+                }
+                self.emit_event(SyncEvent::FeedSucceeded {
+                    batch,
+                    feed_id: feed.id.clone(),
+                    new_articles: report.new_articles.clone(),
+                });
+                Ok(report)
+            }
+            Err(e) => {
+                let retry_after = e.downcast_ref::<HttpStatusError>().and_then(|e| e.retry_after);
+                let next_fetch = self.schedule_policy().next_fetch_after_error(now, &feed, retry_after);
+                feed.update_fetch_times(now, next_fetch);
+                feed.update_status(FeedStatus::Error);
+                feed.update_error_message(e.to_string());
+                self.feed_repository.update_feed(&feed).await?;
+                self.emit_event(SyncEvent::FeedFailed { batch, feed_id: feed.id.clone(), error: e.to_string() });
+                Err(e)
+            }
+        }
+    }
+
+    /// Syncs all feeds that are due, returning a report for each one that succeeded
+    pub async fn sync_all_feeds(&self) -> Result<Vec<FeedSyncReport>> {
         let feeds = self.feed_repository.get_feeds_to_update().await?;
+        let mut reports = Vec::with_capacity(feeds.len());
+        for (feed, result) in feeds.iter().zip(self.sync_feeds(&feeds).await) {
+            match result {
+                Ok(report) => reports.push(report),
+                Err(e) => log::error!("Failed to sync feed {}: {}", feed.id, e),
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Syncs feeds the scheduler found due, returning results in the order given
+    ///
+    /// The same concurrency limits apply as for batches, but the feeds'
+    /// events carry no batch and batch progress is left alone.
+    pub async fn sync_scheduled_feeds(&self, feeds: &[Feed]) -> Vec<Result<FeedSyncReport>> {
+        self.sync_limited(feeds, None, |_, _| {}).await
+    }
+
+    /// Syncs a batch of feeds concurrently, returning results in the order given
+    ///
+    /// Progress is published to [`subscribe_progress`](Self::subscribe_progress)
+    /// as each feed finishes, alongside the batch's [`SyncEvent`]s. A batch
+    /// dropped before it ends still reports `Finished`, marked as cancelled.
+    async fn sync_feeds(&self, feeds: &[Feed]) -> Vec<Result<FeedSyncReport>> {
+        let batch = self.next_batch.fetch_add(1, Ordering::Relaxed);
+        self.progress.send_replace(SyncProgress::new(feeds.len()));
+        self.emit_event(SyncEvent::Started { batch, total: feeds.len() });
+        let mut guard = CancelledBatchGuard {
+            rss_service: self,
+            batch,
+            succeeded: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            armed: true,
+        };
+
+        let results = self.sync_limited(feeds, Some(batch), |feed_id, result| {
+            guard.count(result);
+            self.progress.send_modify(|progress| {
+                progress.done += 1;
+                if let Err(e) = result {
+                    progress.errors.push((feed_id.clone(), e.to_string()));
+                }
+            });
+        }).await;
+
+        guard.armed = false;
+        let failed = results.iter().filter(|result| result.is_err()).count();
+        self.emit_event(SyncEvent::Finished { batch, succeeded: results.len() - failed, failed, cancelled: false });
+        results
+    }
+
+    /// Syncs feeds concurrently, at most `max_concurrent_fetches` in flight
+    /// overall and at most `max_fetches_per_host` from any one host
+    ///
+    /// Everything runs on the calling task, so repository writes never overlap.
+    /// `finished` is called as each feed's sync ends.
+    async fn sync_limited(
+        &self,
+        feeds: &[Feed],
+        batch: Option<BatchId>,
+        finished: impl Fn(&FeedId, &Result<FeedSyncReport>),
+    ) -> Vec<Result<FeedSyncReport>> {
+        let config = self.fetch_config();
+        let global = Semaphore::new(config.max_concurrent_fetches.max(1));
+        let mut hosts: HashMap<&str, Semaphore> = HashMap::new();
         for feed in feeds {
-            if let Err(e) = self.sync_feed(&feed.id).await {
-                log::error!("Failed to sync feed {}: {}", feed.id, e);
+            hosts.entry(feed.url.host_str().unwrap_or_default())
+                .or_insert_with(|| Semaphore::new(config.max_fetches_per_host.max(1)));
+        }
+
+        join_all(feeds.iter().map(|feed| {
+            let host = &hosts[feed.url.host_str().unwrap_or_default()];
+            let global = &global;
+            let finished = &finished;
+            async move {
+                // Semaphores are never closed, so acquiring cannot fail
+                let _host_permit = host.acquire().await.expect("host semaphore closed");
+                let _permit = global.acquire().await.expect("global semaphore closed");
+
+                let result = self.sync_feed_in_batch(&feed.id, batch).await;
+                finished(&feed.id, &result);
+                result
+            }
+        })).await
+    }
+
+    /// Fetches a feed document and writes every entry through to the article repository
+    ///
+    /// The request is conditional on the validators stored on the feed; a
+    /// `304 Not Modified` is a successful sync with nothing to ingest. New
+    /// validators, once every entry is stored, and permanent redirects are
+    /// recorded on `feed` for the caller to persist, and the hints for
+    /// scheduling its next fetch are returned.
+    async fn ingest_feed(&self, feed: &mut Feed) -> Result<(FeedSyncReport, ScheduleHints)> {
+        let fetched = self.fetcher().fetch(feed.url.as_str(), Some(feed)).await?;
+        if let Some(new_url) = fetched.permanent_redirect {
+            self.follow_permanent_redirect(feed, new_url).await?;
+        }
+
+        let mut hints = ScheduleHints {
+            max_age: fetched.directives.max_age,
+            retry_after: fetched.directives.retry_after,
+            ..Default::default()
+        };
+
+        let (body, etag, last_modified) = match fetched.content {
+            ConditionalFetch::NotModified => return Ok((FeedSyncReport::not_modified(feed.id.clone()), hints)),
+            ConditionalFetch::Modified { body, etag, last_modified, .. } => (body, etag, last_modified),
+        };
+
+        let document = parse_feed(&body)?;
+        feed.update_metadata(&map_feed(feed.url.clone(), &document));
+        hints.ttl = document.ttl.map(|minutes| Duration::minutes(minutes as i64));
+        hints.update_interval = syndication_interval(&body);
+        hints.entry_dates = document.entries.iter()
+            .filter_map(|entry| entry.published.or(entry.updated))
+            .collect();
+
+        let mut report = FeedSyncReport::new(feed.id.clone());
+
+        for entry in &document.entries {
+            let Some(article) = map_entry(&feed.id, entry) else {
+                report.skipped_count += 1;
+                continue;
+            };
+
+            let (outcome, article_id) = self.upsert_article(article).await?;
+            match outcome {
+                UpsertOutcome::Inserted => report.new_articles.push(article_id.clone()),
+                UpsertOutcome::Updated => report.updated_count += 1,
+                UpsertOutcome::Unchanged => report.skipped_count += 1,
+            }
+
+            let enclosures = map_enclosures(&article_id, entry);
+            if !enclosures.is_empty() {
+                self.enclosure_repository.save_enclosures(&enclosures).await?;
+            }
+        }
+
+        // Only now that every entry is stored may the next fetch be conditional
+        // on this response; a failed sync keeps the old validators, so the
+        // document is fetched and ingested again in full
+        feed.update_cache_validators(etag, last_modified);
+        Ok((report, hints))
+    }
+
+    /// Points the feed at the URL it permanently moved to, unless another
+    /// subscription already uses that URL
+    async fn follow_permanent_redirect(&self, feed: &mut Feed, new_url: Url) -> Result<()> {
+        match self.feed_repository.get_feed_by_url(new_url.as_str()).await? {
+            Some(other) if other.id != feed.id => {
+                warn!("Feed {} moved to {}, which is already subscribed as {}", feed.url, new_url, other.title);
+            }
+            _ => {
+                info!("Feed {} moved permanently to {}", feed.url, new_url);
+                feed.url = new_url;
             }
         }
         Ok(())
     }
 
+    /// Looks up the stored copy of an entry, trying its dedupe keys in order
+    async fn find_existing(&self, article: &Article) -> Result<Option<Article>> {
+        for key in dedupe_keys(article) {
+            let existing = match key {
+                DedupeKey::Guid(guid) | DedupeKey::Fingerprint(guid) => {
+                    self.article_repository.get_article_by_guid(&article.feed_id, &guid).await?
+                }
+                DedupeKey::Link { url, unkeyed_only } => {
+                    self.article_repository.get_article_by_feed_and_url(&article.feed_id, &url).await?
+                        .filter(|existing| !unkeyed_only || !has_feed_guid(existing))
+                }
+            };
+
+            if existing.is_some() {
+                return Ok(existing);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Inserts an article, or refreshes the stored copy if the entry changed,
+    /// returning the id of the stored row
+    ///
+    /// Read state, favorites and the original publish date of stored articles
+    /// are preserved; only the entry's own fields are overwritten.
+    async fn upsert_article(&self, article: Article) -> Result<(UpsertOutcome, ArticleId)> {
+        let Some(mut existing) = self.find_existing(&article).await? else {
+            self.article_repository.save_article(&article).await?;
+            return Ok((UpsertOutcome::Inserted, article.id));
+        };
+
+        // A row first stored without a GUID takes the feed's once it appears
+        let adopt_guid = has_feed_guid(&article) && !has_feed_guid(&existing);
+        // HTML stored by older content processing can't be compared with the
+        // mapped copy; it takes the current form without showing up as updated
+        let outdated = existing.content_version < article.content_version;
+        let changed = adopt_guid
+            || existing.url != article.url
+            || existing.title != article.title
+            || existing.author != article.author
+            || (!outdated && (existing.content != article.content || existing.summary != article.summary));
+
+        if !changed {
+            if outdated {
+                existing.content = article.content;
+                existing.summary = article.summary;
+                existing.content_version = article.content_version;
+                self.article_repository.update_article(&existing).await?;
+            }
+            return Ok((UpsertOutcome::Unchanged, existing.id));
+        }
+
+        if adopt_guid {
+            existing.guid = article.guid;
+        }
+        existing.url = article.url;
+        existing.title = article.title;
+        existing.author = article.author;
+        existing.content = article.content;
+        existing.summary = article.summary;
+        existing.content_version = article.content_version;
+        existing.updated_at = Utc::now();
+        self.article_repository.update_article(&existing).await?;
+        Ok((UpsertOutcome::Updated, existing.id))
+    }
+
+    /// Looks up the feed's favicon again unless it was looked up recently
+    ///
+    /// The image the feed declares is tried first, then the icons its site's
+    /// page links to, then `/favicon.ico` on the site; the first that really
+    /// is an image is cached. Finding none is cached as well, so the lookup is
+    /// not repeated on every sync.
+    pub async fn refresh_feed_icon(&self, feed_id: &FeedId) -> Result<FeedIcon> {
+        let feed = self.feed_repository.get_feed_by_id(feed_id).await?
+            .ok_or_else(|| anyhow!("Feed not found"))?;
+        if let Some(icon) = self.feed_repository.get_feed_icon(feed_id).await? {
+            if !icon.is_stale(Utc::now()) {
+                return Ok(icon);
+            }
+        }
+
+        let icon = self.resolve_feed_icon(&feed).await;
+        self.feed_repository.save_feed_icon(&icon).await?;
+        if !icon.is_empty() {
+            self.emit_event(SyncEvent::FeedIconUpdated { feed_id: feed.id.clone() });
+        }
+        Ok(icon)
+    }
+
+    async fn resolve_feed_icon(&self, feed: &Feed) -> FeedIcon {
+        if let Some(icon) = self.fetch_icon(feed, feed.icon_url.iter().cloned()).await {
+            return icon;
+        }
+
+        let site = feed.site_url.clone().unwrap_or_else(|| feed.url.clone());
+        let mut candidates = match self.fetcher().fetch(site.as_str(), None).await {
+            Ok(FetchedFeed { content: ConditionalFetch::Modified { body, content_type, .. }, url, .. }) => {
+                find_icon_links(&decode_html(&body, content_type.as_deref()), &url)
+            }
+            _ => Vec::new(),
+        };
+        candidates.extend(site.join("/favicon.ico").ok());
+        candidates.retain(|url| Some(url) != feed.icon_url.as_ref());
+
+        self.fetch_icon(feed, candidates).await
+            .unwrap_or_else(|| FeedIcon::not_found(feed.id.clone()))
+    }
+
+    /// The first of `urls` that serves an image
+    async fn fetch_icon(&self, feed: &Feed, urls: impl IntoIterator<Item = Url>) -> Option<FeedIcon> {
+        for url in urls {
+            let body = match self.fetcher().fetch(url.as_str(), None).await {
+                Ok(FetchedFeed { content: ConditionalFetch::Modified { body, .. }, .. }) => body,
+                _ => continue,
+            };
+            if let Some(mime_type) = image_mime_type(&body) {
+                return Some(FeedIcon::new(feed.id.clone(), url, body, mime_type.to_string()));
+            }
+        }
+        None
+    }
+
+    /// Gets the cached favicon of every feed that has one
+    pub async fn get_feed_icons(&self) -> Result<Vec<FeedIcon>> {
+        self.feed_repository.get_feed_icons().await
+    }
+
+    /// Gets the media attached to an article
+    pub async fn get_enclosures(&self, article_id: &ArticleId) -> Result<Vec<Enclosure>> {
+        self.enclosure_repository.get_enclosures_by_article(article_id).await
+    }
+
     /// Updates an article
     pub async fn update_article(&self, article: &Article) -> Result<()> {
         self.article_repository.update_article(article).await
@@ -317,4 +814,819 @@ impl RssService {
     pub async fn get_feed_by_url(&self, url: &str) -> Result<Option<Feed>> {
         Ok(self.feed_repository.get_feed_by_url(url).await?)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Database;
+    use crate::models::search::{HIGHLIGHT_END, HIGHLIGHT_START};
+    use crate::services::rss::FeedFormat;
+    use crate::models::article::CONTENT_VERSION;
+    use crate::utils::test_support::{temp_database, temp_database_at, TestResponse, TestServer};
+
+    fn rss_document(items: &[(&str, &str, &str)]) -> String {
+        let items: String = items.iter()
+            .map(|(title, link, description)| format!(
+                "<item><title>{}</title><link>{}</link><description>{}</description></item>",
+                title, link, description
+            ))
+            .collect();
+        format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Test Feed</title><link>http://example.com/</link>{}</channel></rss>"#,
+            items
+        )
+    }
+
+    fn rss_document_with_guids(items: &[(&str, &str, &str)]) -> String {
+        let items: String = items.iter()
+            .map(|(guid, title, link)| format!(
+                r#"<item><guid isPermaLink="false">{}</guid><title>{}</title><link>{}</link></item>"#,
+                guid, title, link
+            ))
+            .collect();
+        format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Test Feed</title><link>http://example.com/</link>{}</channel></rss>"#,
+            items
+        )
+    }
+
+    fn service(database: &Database) -> RssService {
+        RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        )
+    }
+
+    async fn saved_feed(database: &Database, url: &str) -> Feed {
+        let feed = Feed::new("Test Feed".to_string(), Url::parse(url).unwrap());
+        database.get_feed_repository().save_feed(&feed).await.unwrap();
+        feed
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_stores_new_articles() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[
+            ("First", "http://example.com/1", "one"),
+            ("Second", "http://example.com/2", "two"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+
+        assert_eq!(report.new_count(), 2);
+        assert_eq!(report.updated_count, 0);
+        assert_eq!(report.skipped_count, 0);
+
+        let articles = rss_service.get_articles_by_feed(&feed.id).await?;
+        assert_eq!(articles.len(), 2);
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.status, FeedStatus::Active);
+        assert!(stored.last_fetched_at.is_some());
+        assert!(stored.next_fetch_at > stored.last_fetched_at);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_rewrites_stored_html_without_marking_it_updated() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[
+            ("First", "http://example.com/posts/1", "&lt;img src=&quot;/a.png&quot;&gt;"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        // A row stored before links were rewritten
+        let mut first = database.get_article_repository()
+            .get_article_by_url("http://example.com/posts/1").await?.unwrap();
+        assert_eq!(first.summary.as_deref(), Some(r#"<img src="http://example.com/a.png">"#));
+        first.summary = Some(r#"<img src="/a.png">"#.to_string());
+        first.content_version = 0;
+        database.get_article_repository().update_article(&first).await?;
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.updated_count, 0);
+        assert_eq!(report.skipped_count, 1);
+
+        let stored = database.get_article_repository()
+            .get_article_by_url("http://example.com/posts/1").await?.unwrap();
+        assert_eq!(stored.summary.as_deref(), Some(r#"<img src="http://example.com/a.png">"#));
+        assert_eq!(stored.content_version, CONTENT_VERSION);
+        assert_eq!(stored.updated_at, first.updated_at);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_rewrites_stored_html_against_xml_base() -> Result<()> {
+        // The content's xml:base points elsewhere than the entry's link
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Test Feed</title><id>urn:test</id><updated>2024-01-01T00:00:00Z</updated>
+            <entry><title>First</title><id>urn:first</id><updated>2024-01-01T00:00:00Z</updated>
+              <link href="http://example.com/posts/1"/>
+              <content type="html" xml:base="http://cdn.example.com/media/"><![CDATA[<img src="a.png">]]></content>
+            </entry>
+        </feed>"#.to_string()));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        // A row stored before links were rewritten
+        let mut first = database.get_article_repository()
+            .get_article_by_url("http://example.com/posts/1").await?.unwrap();
+        assert_eq!(first.content.as_deref(), Some(r#"<img src="http://cdn.example.com/media/a.png">"#));
+        first.content = Some(r#"<img src="a.png">"#.to_string());
+        first.content_version = 0;
+        database.get_article_repository().update_article(&first).await?;
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.updated_count, 0);
+        assert_eq!(report.skipped_count, 1);
+
+        let stored = database.get_article_repository()
+            .get_article_by_url("http://example.com/posts/1").await?.unwrap();
+        assert_eq!(stored.content.as_deref(), Some(r#"<img src="http://cdn.example.com/media/a.png">"#));
+        assert_eq!(stored.content_version, CONTENT_VERSION);
+        assert_eq!(stored.updated_at, first.updated_at);
+
+        // Once current, a change to the content is an update again
+        server.route("/feed.xml", TestResponse::ok(r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom">
+            <title>Test Feed</title><id>urn:test</id><updated>2024-01-01T00:00:00Z</updated>
+            <entry><title>First</title><id>urn:first</id><updated>2024-01-01T00:00:00Z</updated>
+              <link href="http://example.com/posts/1"/>
+              <content type="html" xml:base="http://cdn.example.com/media/"><![CDATA[<img src="b.png">]]></content>
+            </entry>
+        </feed>"#.to_string()));
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.updated_count, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_dedupes_and_updates_existing_articles() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[
+            ("First", "http://example.com/1", "one"),
+            ("Second", "http://example.com/2", "two"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        // Mark one article read so we can check read state survives an update
+        let mut first = database.get_article_repository()
+            .get_article_by_url("http://example.com/1").await?.unwrap();
+        first.mark_as_read();
+        rss_service.update_article(&first).await?;
+
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[
+            ("First (edited)", "http://example.com/1", "one"),
+            ("Second", "http://example.com/2", "two"),
+            ("Third", "http://example.com/3", "three"),
+        ])));
+        let report = rss_service.sync_feed(&feed.id).await?;
+
+        assert_eq!(report.new_count(), 1);
+        assert_eq!(report.updated_count, 1);
+        assert_eq!(report.skipped_count, 1);
+
+        let first = database.get_article_repository()
+            .get_article_by_url("http://example.com/1").await?.unwrap();
+        assert_eq!(first.title, "First (edited)");
+        assert_eq!(first.read_status, ReadStatus::Read);
+        assert_eq!(rss_service.get_articles_by_feed(&feed.id).await?.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_records_errors_on_feed() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::status(500));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        assert!(rss_service.sync_feed(&feed.id).await.is_err());
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.status, FeedStatus::Error);
+        assert!(stored.error_message.is_some());
+        assert!(stored.next_fetch_at.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_keeps_entries_sharing_a_link_apart_by_guid() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document_with_guids(&[
+            ("entry-1", "Morning digest", "http://example.com/digest"),
+            ("entry-2", "Evening digest", "http://example.com/digest"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.new_count(), 2);
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.new_count(), 0);
+        assert_eq!(report.skipped_count, 2);
+        assert_eq!(rss_service.get_articles_by_feed(&feed.id).await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_follows_guid_when_link_changes() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document_with_guids(&[
+            ("entry-1", "Post", "http://example.com/post?utm_source=feed"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        server.route("/feed.xml", TestResponse::ok(rss_document_with_guids(&[
+            ("entry-1", "Post", "http://example.com/post"),
+        ])));
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.updated_count, 1);
+
+        let articles = rss_service.get_articles_by_feed(&feed.id).await?;
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].url.as_str(), "http://example.com/post");
+        assert_eq!(articles[0].guid.as_deref(), Some("entry-1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_adopts_guid_for_articles_stored_without_one() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[
+            ("Post", "http://example.com/post", "body"),
+        ])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        let stored = rss_service.get_articles_by_feed(&feed.id).await?;
+        assert!(stored[0].guid.as_deref().is_some_and(|g| g.starts_with("fingerprint:")));
+
+        server.route("/feed.xml", TestResponse::ok(rss_document_with_guids(&[
+            ("entry-1", "Post", "http://example.com/post"),
+        ])));
+        rss_service.sync_feed(&feed.id).await?;
+
+        let articles = rss_service.get_articles_by_feed(&feed.id).await?;
+        assert_eq!(articles.len(), 1);
+        assert_eq!(articles[0].guid.as_deref(), Some("entry-1"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_sends_etag_and_treats_304_as_success() -> Result<()> {
+        let server = TestServer::start().await;
+        let document = rss_document(&[("First", "http://example.com/1", "one")]);
+        server.handle("/feed.xml", move |request| match request.header("If-None-Match") {
+            Some("\"v1\"") => TestResponse::status(304),
+            _ => TestResponse::ok(document.clone()).with_header("ETag", "\"v1\""),
+        });
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        let first = rss_service.sync_feed(&feed.id).await?;
+        assert!(!first.not_modified);
+        assert_eq!(first.new_count(), 1);
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.etag.as_deref(), Some("\"v1\""));
+
+        let second = rss_service.sync_feed(&feed.id).await?;
+        assert!(second.not_modified);
+        assert_eq!(second.total(), 0);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("If-None-Match"), None);
+        assert_eq!(requests[1].header("If-None-Match"), Some("\"v1\""));
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.status, FeedStatus::Active);
+        assert_eq!(stored.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(rss_service.get_articles_by_feed(&feed.id).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_keeps_old_validators_when_storing_fails() -> Result<()> {
+        let server = TestServer::start().await;
+        let document = rss_document(&[
+            ("First", "http://example.com/1", "one"),
+            ("Broken", "http://example.com/2", "two"),
+        ]);
+        server.handle("/feed.xml", move |request| match request.header("If-None-Match") {
+            Some("\"v1\"") => TestResponse::status(304),
+            _ => TestResponse::ok(document.clone()).with_header("ETag", "\"v1\""),
+        });
+
+        let (database, path) = temp_database_at();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        let connection = rusqlite::Connection::open(&path)?;
+        connection.execute_batch("
+            CREATE TRIGGER reject_broken BEFORE INSERT ON articles WHEN new.title = 'Broken'
+            BEGIN SELECT RAISE(ABORT, 'rejected'); END;
+        ")?;
+
+        assert!(rss_service.sync_feed(&feed.id).await.is_err());
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.status, FeedStatus::Error);
+        assert_eq!(stored.etag, None);
+
+        // The retry fetches the whole document again instead of getting a 304
+        connection.execute_batch("DROP TRIGGER reject_broken;")?;
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert!(!report.not_modified);
+        assert_eq!(report.new_count(), 1);
+        assert_eq!(server.requests()[1].header("If-None-Match"), None);
+        assert_eq!(rss_service.get_articles_by_feed(&feed.id).await?.len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_sends_last_modified() -> Result<()> {
+        const LAST_MODIFIED: &str = "Wed, 01 Jan 2026 00:00:00 GMT";
+
+        let server = TestServer::start().await;
+        let document = rss_document(&[("First", "http://example.com/1", "one")]);
+        server.handle("/feed.xml", move |request| match request.header("If-Modified-Since") {
+            Some(LAST_MODIFIED) => TestResponse::status(304),
+            _ => TestResponse::ok(document.clone()).with_header("Last-Modified", LAST_MODIFIED),
+        });
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        rss_service.sync_feed(&feed.id).await?;
+        let second = rss_service.sync_feed(&feed.id).await?;
+
+        assert!(second.not_modified);
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.last_modified.as_deref(), Some(LAST_MODIFIED));
+        assert_eq!(stored.etag, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_rewrites_url_after_permanent_redirect() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/old.xml", TestResponse::status(301).with_header("Location", "/new.xml"));
+        server.route("/new.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/old.xml")).await;
+
+        let report = rss_service.sync_feed(&feed.id).await?;
+        assert_eq!(report.new_count(), 1);
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.url.as_str(), server.url("/new.xml"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_keeps_url_after_temporary_redirect() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::status(302).with_header("Location", "/mirror.xml"));
+        server.route("/mirror.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+
+        rss_service.sync_feed(&feed.id).await?;
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.url.as_str(), server.url("/feed.xml"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_timeout_covers_every_redirect_hop() -> Result<()> {
+        let server = TestServer::start().await;
+        let delay = std::time::Duration::from_millis(400);
+        server.route("/a.xml", TestResponse::status(302).with_header("Location", "/b.xml").with_delay(delay));
+        server.route("/b.xml", TestResponse::status(302).with_header("Location", "/c.xml").with_delay(delay));
+        server.route("/c.xml", TestResponse::ok(rss_document(&[])).with_delay(delay));
+
+        let database = temp_database();
+        let rss_service = service(&database)
+            .with_fetch_config(FetchConfig::default().with_timeout(std::time::Duration::from_millis(1000)))?;
+        let feed = saved_feed(&database, &server.url("/a.xml")).await;
+
+        let error = rss_service.sync_feed(&feed.id).await.unwrap_err();
+        assert!(error.to_string().contains("Timed out"), "{}", error);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_applies_fetch_config() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+
+        let database = temp_database();
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        let rss_service = service(&database).with_fetch_config(
+            FetchConfig::default()
+                .with_user_agent("PandaTest/1.0".to_string())
+                .with_feed_header(feed.id.clone(), "Authorization", "Bearer secret"),
+        )?;
+
+        rss_service.sync_feed(&feed.id).await?;
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("User-Agent"), Some("PandaTest/1.0"));
+        assert_eq!(request.header("Authorization"), Some("Bearer secret"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_sends_feed_headers_only_to_its_origin() -> Result<()> {
+        let server = TestServer::start().await;
+        let elsewhere = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::status(302).with_header("Location", "/moved.xml"));
+        server.route("/moved.xml", TestResponse::status(302).with_header("Location", &elsewhere.url("/feed.xml")));
+        elsewhere.route("/feed.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+
+        let database = temp_database();
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        let rss_service = service(&database).with_fetch_config(
+            FetchConfig::default().with_feed_header(feed.id.clone(), "Authorization", "Bearer secret"),
+        )?;
+
+        rss_service.sync_feed(&feed.id).await?;
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.header("Authorization") == Some("Bearer secret")));
+        assert_eq!(elsewhere.requests()[0].header("Authorization"), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_feed_rejects_oversized_bodies() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+
+        let database = temp_database();
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        let rss_service = service(&database)
+            .with_fetch_config(FetchConfig::default().with_max_body_bytes(16))?;
+
+        let error = rss_service.sync_feed(&feed.id).await.unwrap_err();
+        assert!(error.to_string().contains("byte limit"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_feeds_respects_per_host_limit() -> Result<()> {
+        let server = TestServer::start().await;
+        let database = temp_database();
+        for i in 0..4 {
+            let path = format!("/feed{}.xml", i);
+            server.route(&path, TestResponse::ok(rss_document(&[]))
+                .with_delay(std::time::Duration::from_millis(100)));
+            saved_feed(&database, &server.url(&path)).await;
+        }
+
+        let rss_service = service(&database).with_fetch_config(
+            FetchConfig::default().with_max_concurrent_fetches(8).with_max_fetches_per_host(2),
+        )?;
+        let reports = rss_service.sync_all_feeds().await?;
+
+        assert_eq!(reports.len(), 4);
+        assert_eq!(server.max_in_flight(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_feeds_respects_global_limit() -> Result<()> {
+        let server = TestServer::start().await;
+        let database = temp_database();
+        for i in 0..3 {
+            let path = format!("/feed{}.xml", i);
+            server.route(&path, TestResponse::ok(rss_document(&[]))
+                .with_delay(std::time::Duration::from_millis(50)));
+            saved_feed(&database, &server.url(&path)).await;
+        }
+
+        let rss_service = service(&database).with_fetch_config(
+            FetchConfig::default().with_max_concurrent_fetches(1).with_max_fetches_per_host(4),
+        )?;
+        rss_service.sync_all_feeds().await?;
+
+        assert_eq!(server.max_in_flight(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_batch_counts_only_its_own_feeds() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/fast.xml", TestResponse::ok(rss_document(&[])));
+        server.route("/slow.xml", TestResponse::ok(rss_document(&[])).with_delay(std::time::Duration::from_secs(10)));
+        server.route("/broken.xml", TestResponse::status(500));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let fast = saved_feed(&database, &server.url("/fast.xml")).await;
+        let slow = saved_feed(&database, &server.url("/slow.xml")).await;
+        let broken = saved_feed(&database, &server.url("/broken.xml")).await;
+        let mut events = rss_service.subscribe_events();
+
+        let feeds = [fast, slow];
+        let mut cancelled = Box::pin(rss_service.sync_feeds(&feeds));
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(500), &mut cancelled).await.is_err());
+        // Another batch takes over the shared progress before the first is dropped
+        rss_service.sync_feeds(std::slice::from_ref(&broken)).await;
+        drop(cancelled);
+
+        let mut finished = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let SyncEvent::Finished { succeeded, failed, cancelled, .. } = event {
+                finished.push((succeeded, failed, cancelled));
+            }
+        }
+        assert_eq!(finished, vec![(0, 1, false), (1, 0, true)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_all_feeds_publishes_progress() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/ok.xml", TestResponse::ok(rss_document(&[("First", "http://example.com/1", "one")])));
+        server.route("/broken.xml", TestResponse::status(500));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        saved_feed(&database, &server.url("/ok.xml")).await;
+        let broken = saved_feed(&database, &server.url("/broken.xml")).await;
+
+        let progress = rss_service.subscribe_progress();
+        let reports = rss_service.sync_all_feeds().await?;
+        assert_eq!(reports.len(), 1);
+
+        let progress = progress.borrow().clone();
+        assert_eq!((progress.done, progress.total), (2, 2));
+        assert!(progress.is_finished());
+        assert_eq!(progress.errors.len(), 1);
+        assert_eq!(progress.errors[0].0, broken.id);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_highlights_and_filters() -> Result<()> {
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let rust_feed = saved_feed(&database, "http://example.com/rust.xml").await;
+        let other_feed = saved_feed(&database, "http://example.com/other.xml").await;
+
+        let article = |feed: &Feed, title: &str, summary: &str| {
+            Article::new(feed.id.clone(), title.to_string(), Url::parse(&format!("http://example.com/{}", Uuid::new_v4())).unwrap())
+                .with_summary(summary.to_string())
+        };
+        let in_title = article(&rust_feed, "Async Rust in practice", "Notes from a talk");
+        let in_summary = article(&rust_feed, "Weekly notes", "A short mention of async rust");
+        let mut other = article(&other_feed, "Rustic furniture", "Asynchronous woodworking");
+        other.mark_as_read();
+        for article in [&in_title, &in_summary, &other] {
+            article_repository.save_article(article).await?;
+        }
+
+        let rss_service = service(&database);
+        let titles = |hits: &[SearchHit]| hits.iter().map(|hit| hit.article.title.clone()).collect::<Vec<_>>();
+
+        // Title matches outrank summary matches, and matches are highlighted
+        let hits = rss_service.search(&SearchQuery::new("async rust")).await?;
+        assert_eq!(titles(&hits), vec!["Async Rust in practice", "Weekly notes"]);
+        assert!(hits[0].title_highlight.contains(&format!("{}Async{}", HIGHLIGHT_START, HIGHLIGHT_END)));
+        assert!(hits[1].snippet.contains("async"));
+
+        // Prefix and phrase queries
+        let hits = rss_service.search(&SearchQuery::new("rust*")).await?;
+        assert_eq!(hits.len(), 3);
+        let hits = rss_service.search(&SearchQuery::new(r#""async rust""#)).await?;
+        assert_eq!(titles(&hits), vec!["Async Rust in practice", "Weekly notes"]);
+
+        // Filters narrow the results
+        let hits = rss_service.search(&SearchQuery::new("rust*").with_feed(other_feed.id.clone())).await?;
+        assert_eq!(titles(&hits), vec!["Rustic furniture"]);
+        let hits = rss_service.search(&SearchQuery::new("rust*").with_read_status(ReadStatus::Unread)).await?;
+        assert_eq!(hits.len(), 2);
+        article_repository.add_tag(&in_summary.id, "newsletter").await?;
+        let hits = rss_service.search(&SearchQuery::new("rust*").with_tag("newsletter")).await?;
+        assert_eq!(titles(&hits), vec!["Weekly notes"]);
+        let future = Utc::now() + Duration::days(1);
+        let hits = rss_service.search(&SearchQuery::new("rust*").with_date_range(Some(future), None)).await?;
+        assert!(hits.is_empty());
+
+        // A category covers the feeds of its subcategories too
+        let parent = Category::new("Hobbies".to_string());
+        let child = Category::new("Crafts".to_string()).with_parent(parent.id.clone());
+        database.get_category_repository().save_category(&parent).await?;
+        database.get_category_repository().save_category(&child).await?;
+        database.get_feed_repository().update_feed(&other_feed.clone().with_category(child.id.clone())).await?;
+        let hits = rss_service.search(&SearchQuery::new("rust*").with_category(parent.id.clone())).await?;
+        assert_eq!(titles(&hits), vec!["Rustic furniture"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_article_pages_follow_cursor_in_each_order() -> Result<()> {
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let feed = saved_feed(&database, "http://example.com/feed.xml").await;
+        let other_feed = saved_feed(&database, "http://example.com/other.xml").await;
+
+        // Two articles share a timestamp so the id has to break the tie
+        let base = Utc::now() - Duration::days(10);
+        let hours = [0, 1, 1, 2, 3, 4, 5];
+        let mut saved = Vec::new();
+        for (i, hour) in hours.iter().enumerate() {
+            let mut article = Article::new(feed.id.clone(), format!("article {}", i), Url::parse(&format!("http://example.com/{}", i))?)
+                .with_content("x".repeat(1000))
+                .with_published_at(base + Duration::hours(*hour));
+            if i % 2 == 0 {
+                article.mark_as_read();
+            }
+            article_repository.save_article(&article).await?;
+            saved.push(article);
+        }
+        let elsewhere = Article::new(other_feed.id.clone(), "elsewhere".to_string(), Url::parse("http://example.com/elsewhere")?);
+        article_repository.save_article(&elsewhere).await?;
+
+        let rss_service = service(&database);
+        let filter = ArticleFilter::Feed(feed.id.clone());
+        let read_all = |order: ArticleSortOrder| {
+            let rss_service = &rss_service;
+            let filter = &filter;
+            async move {
+                let mut ids = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let page = rss_service.get_article_page(filter, order, cursor.as_ref(), 3).await?;
+                    assert!(page.articles.len() <= 3);
+                    ids.extend(page.articles.into_iter().map(|article| article.id));
+                    cursor = page.next_cursor;
+                    if cursor.is_none() {
+                        return Ok::<_, anyhow::Error>(ids);
+                    }
+                }
+            }
+        };
+
+        let key = |article: &Article| (article.published_at, article.id.0.clone());
+        let mut newest_first = saved.clone();
+        newest_first.sort_by_key(|article| std::cmp::Reverse(key(article)));
+        let expected: Vec<ArticleId> = newest_first.iter().map(|article| article.id.clone()).collect();
+        assert_eq!(read_all(ArticleSortOrder::NewestFirst).await?, expected);
+
+        let mut oldest_first = expected.clone();
+        oldest_first.reverse();
+        assert_eq!(read_all(ArticleSortOrder::OldestFirst).await?, oldest_first);
+
+        let mut unread_first = newest_first.clone();
+        unread_first.sort_by_key(|article| article.read_status != ReadStatus::Unread);
+        let expected: Vec<ArticleId> = unread_first.iter().map(|article| article.id.clone()).collect();
+        assert_eq!(read_all(ArticleSortOrder::Unread).await?, expected);
+
+        let page = rss_service.get_article_page(&ArticleFilter::Unread, ArticleSortOrder::NewestFirst, None, 10).await?;
+        assert_eq!(page.articles.len(), 4);
+        assert!(page.next_cursor.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discover_feeds_from_links_and_common_paths() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/", TestResponse::ok(
+            r#"<html><head><title>Example Blog</title>
+               <link rel="alternate" type="application/rss+xml" title="All posts" href="/posts.rss">
+               </head><body>Hello</body></html>"#,
+        ));
+        server.route("/posts.rss", TestResponse::ok(rss_document_with_guids(&[])));
+        server.route("/atom.xml", TestResponse::ok(
+            r#"<?xml version="1.0"?><feed xmlns="http://www.w3.org/2005/Atom"><title>Example Atom</title><id>urn:example</id><updated>2026-01-01T00:00:00Z</updated></feed>"#,
+        ));
+        // Not a feed, so not a candidate
+        server.route("/feed", TestResponse::ok("<html><body>Not here</body></html>"));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let candidates = rss_service.discover_feeds(&server.url("/")).await?;
+        let found: Vec<(String, String, FeedFormat)> = candidates.iter()
+            .map(|candidate| (candidate.url.to_string(), candidate.title.clone(), candidate.format))
+            .collect();
+        assert_eq!(found, vec![
+            (server.url("/posts.rss"), "All posts".to_string(), FeedFormat::Rss),
+            (server.url("/atom.xml"), "Example Atom".to_string(), FeedFormat::Atom),
+        ]);
+
+        // A feed URL is its own only candidate
+        let direct = rss_service.discover_feeds(&server.url("/atom.xml")).await?;
+        assert_eq!(direct.len(), 1);
+        assert_eq!(direct[0].title, "Example Atom");
+
+        // Adding the page subscribes to the feed it advertises, downloading
+        // the page only once
+        let page_requests = |server: &TestServer| server.requests().iter().filter(|request| request.path == "/").count();
+        let before = page_requests(&server);
+        rss_service.add_feed(&server.url("/")).await?;
+        assert_eq!(page_requests(&server), before + 1);
+        let feeds = rss_service.get_all_feeds().await?;
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].url.to_string(), server.url("/posts.rss"));
+
+        // Link titles are read in the charset the page declares
+        let latin1 = TestServer::start().await;
+        latin1.route("/", TestResponse::ok(
+            &b"<html><head><link rel=\"alternate\" type=\"application/rss+xml\" title=\"Caf\xe9\" href=\"/posts.rss\"></head></html>"[..],
+        ).with_header("Content-Type", "text/html; charset=ISO-8859-1"));
+        let candidates = rss_service.discover_feeds(&latin1.url("/")).await?;
+        assert_eq!(candidates[0].title, "Café");
+
+        let empty = TestServer::start().await;
+        empty.route("/", TestResponse::ok("<html></html>"));
+        assert!(rss_service.discover_feeds(&empty.url("/")).await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sync_stores_feed_metadata_and_icon() -> Result<()> {
+        const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        let server = TestServer::start().await;
+        server.route("/feed.xml", TestResponse::ok(format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Site</title><link>{site}</link>
+               <description>About the site</description><language>de</language><generator>Hugo</generator>
+               <image><url>/missing-logo.png</url><title>Site</title><link>{site}</link></image>
+               <item><title>Post</title><link>{site}post</link></item></channel></rss>"#,
+            site = server.url("/"),
+        )));
+        server.route("/", TestResponse::ok(r#"<html><head><link rel="icon" href="/static/icon.png"></head></html>"#));
+        server.route("/static/icon.png", TestResponse::ok(PNG));
+
+        let database = temp_database();
+        let rss_service = service(&database);
+        let feed = saved_feed(&database, &server.url("/feed.xml")).await;
+        rss_service.sync_feed(&feed.id).await?;
+
+        let stored = rss_service.get_feed_by_id(&feed.id).await?.unwrap();
+        assert_eq!(stored.title, "Test Feed", "a renamed feed keeps its name");
+        assert_eq!(stored.description.as_deref(), Some("About the site"));
+        assert_eq!(stored.language.as_deref(), Some("de"));
+        assert_eq!(stored.generator.as_deref(), Some("Hugo"));
+        assert_eq!(stored.icon_url.map(String::from).as_deref(), Some(server.url("/missing-logo.png").as_str()));
+
+        // The declared logo is missing, so the icon the site links to is used
+        let mut events = rss_service.subscribe_events();
+        let icon = rss_service.refresh_feed_icon(&feed.id).await?;
+        assert_eq!(icon.url.map(String::from), Some(server.url("/static/icon.png")));
+        assert_eq!((icon.data.as_slice(), icon.mime_type.as_deref()), (PNG, Some("image/png")));
+        assert_eq!(events.try_recv()?, SyncEvent::FeedIconUpdated { feed_id: feed.id.clone() });
+
+        // A fresh icon is not looked up again
+        let requests = server.requests().len();
+        rss_service.refresh_feed_icon(&feed.id).await?;
+        assert_eq!(server.requests().len(), requests);
+
+        // Finding no icon at all is remembered, but not listed
+        let bare = saved_feed(&database, &server.url("/bare.xml")).await;
+        assert!(rss_service.refresh_feed_icon(&bare.id).await?.is_empty());
+        assert!(database.get_feed_repository().get_feed_icon(&bare.id).await?.is_some());
+        let icons = rss_service.get_feed_icons().await?;
+        assert_eq!(icons.iter().map(|icon| &icon.feed_id).collect::<Vec<_>>(), vec![&feed.id]);
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use crate::models::feed::FeedId;
+
+/// Progress of the current (or last) batch sync, published for the UI
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Feeds in the batch
+    pub total: usize,
+    /// Feeds finished so far, whether they succeeded or failed
+    pub done: usize,
+    /// Feeds that failed in this batch, with their error messages
+    pub errors: Vec<(FeedId, String)>,
+}
+
+impl SyncProgress {
+    pub fn new(total: usize) -> Self {
+        Self { total, ..Self::default() }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done >= self.total
+    }
+
+    /// Fraction of the batch completed, for progress bars
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::models::article::ArticleId;
+use crate::models::feed::FeedId;
+
+/// Outcome of ingesting a single feed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedSyncReport {
+    pub feed_id: FeedId,
+    /// Articles stored for entries that were new
+    pub new_articles: Vec<ArticleId>,
+    /// Entries that matched an existing article whose fields changed
+    pub updated_count: usize,
+    /// Entries that were unchanged or could not be mapped to an article
+    pub skipped_count: usize,
+    /// The server answered `304 Not Modified`, so no entries were read
+    pub not_modified: bool,
+}
+
+impl FeedSyncReport {
+    pub fn new(feed_id: FeedId) -> Self {
+        Self {
+            feed_id,
+            new_articles: Vec::new(),
+            updated_count: 0,
+            skipped_count: 0,
+            not_modified: false,
+        }
+    }
+
+    /// Report for a feed whose document has not changed since the last fetch
+    pub fn not_modified(feed_id: FeedId) -> Self {
+        Self { not_modified: true, ..Self::new(feed_id) }
+    }
+
+    /// Number of entries stored as new articles
+    pub fn new_count(&self) -> usize {
+        self.new_articles.len()
+    }
+
+    /// Total number of entries seen in the fetched document
+    pub fn total(&self) -> usize {
+        self.new_count() + self.updated_count + self.skipped_count
+    }
+}
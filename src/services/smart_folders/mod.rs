@@ -0,0 +1,3 @@
+mod smart_folder_service;
+
+pub use smart_folder_service::SmartFolderService;
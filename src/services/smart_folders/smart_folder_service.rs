@@ -0,0 +1,167 @@
+use std::sync::Arc;
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use log::warn;
+
+use crate::base::repository::{ArticleRepository, SmartFolderRepository};
+use crate::models::smart_folder::{SmartFolder, SmartFolderId};
+
+/// Saved article queries ("smart folders") and how many unread articles they match
+///
+/// The articles themselves are listed like any other selection, through
+/// [`ArticleFilter::Query`](crate::models::article_page::ArticleFilter::Query).
+pub struct SmartFolderService {
+    smart_folder_repository: Arc<dyn SmartFolderRepository>,
+    article_repository: Arc<dyn ArticleRepository>,
+}
+
+impl SmartFolderService {
+    pub fn new(
+        smart_folder_repository: Arc<dyn SmartFolderRepository>,
+        article_repository: Arc<dyn ArticleRepository>,
+    ) -> Self {
+        Self {
+            smart_folder_repository,
+            article_repository,
+        }
+    }
+
+    /// Saves a new smart folder, rejecting queries that do not parse
+    pub async fn create(&self, name: &str, query: &str) -> Result<SmartFolder> {
+        let folder = SmartFolder::new(name.trim().to_string(), query.trim().to_string());
+        validate(&folder)?;
+        self.smart_folder_repository.save_smart_folder(&folder).await?;
+        Ok(folder)
+    }
+
+    /// Renames a smart folder or changes its query
+    pub async fn update(&self, folder: &SmartFolder) -> Result<()> {
+        validate(folder)?;
+        let mut folder = folder.clone();
+        folder.updated_at = Utc::now();
+        self.smart_folder_repository.update_smart_folder(&folder).await
+    }
+
+    pub async fn delete(&self, id: &SmartFolderId) -> Result<()> {
+        self.smart_folder_repository.delete_smart_folder(id).await
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<SmartFolder>> {
+        self.smart_folder_repository.get_all_smart_folders().await
+    }
+
+    /// Every smart folder with the number of unread articles it matches
+    ///
+    /// A folder whose stored query no longer parses is listed with no unread articles.
+    pub async fn get_all_with_unread_counts(&self) -> Result<Vec<(SmartFolder, usize)>> {
+        let mut folders = Vec::new();
+        for folder in self.get_all().await? {
+            let count = match folder.parsed_query() {
+                Ok(query) => self.article_repository.count_unread_matching(&query).await?,
+                Err(e) => {
+                    warn!("Smart folder '{}' has an invalid query: {}", folder.name, e);
+                    0
+                }
+            };
+            folders.push((folder, count));
+        }
+        Ok(folders)
+    }
+}
+
+fn validate(folder: &SmartFolder) -> Result<()> {
+    if folder.name.is_empty() {
+        bail!("Smart folder name cannot be empty");
+    }
+    folder.parsed_query()
+        .with_context(|| format!("Invalid query for smart folder '{}'", folder.name))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone};
+    use url::Url;
+    use crate::data::Database;
+    use crate::models::article::Article;
+    use crate::models::article_page::ArticleFilter;
+    use crate::models::category::Category;
+    use crate::models::feed::Feed;
+    use crate::models::settings::ArticleSortOrder;
+    use crate::utils::test_support::{save_article, temp_database, test_article};
+
+    async fn save_tagged_article(
+        database: &Database,
+        feed: &Feed,
+        title: &str,
+        published_at: DateTime<Utc>,
+        tag: Option<&str>,
+    ) -> Result<Article> {
+        let article = test_article(feed, title)
+            .with_content(format!("{} body text", title))
+            .with_published_at(published_at);
+        let article = save_article(database, article).await?;
+        if let Some(tag) = tag {
+            database.get_article_repository().add_tag(&article.id, tag).await?;
+        }
+        Ok(article)
+    }
+
+    /// Titles of the articles the folder lists, newest first
+    async fn folder_titles(database: &Database, folder: &SmartFolder) -> Result<Vec<String>> {
+        let filter = ArticleFilter::Query(folder.parsed_query()?);
+        let page = database.get_article_repository()
+            .get_article_page(&filter, ArticleSortOrder::NewestFirst, None, 100)
+            .await?;
+        Ok(page.articles.into_iter().map(|article| article.title).collect())
+    }
+
+    #[tokio::test]
+    async fn test_smart_folder_queries_and_unread_counts() -> Result<()> {
+        let database = temp_database();
+        let article_repository = database.get_article_repository();
+        let category = Category::new("Tech".to_string());
+        let subcategory = Category::new("News".to_string()).with_parent(category.id.clone());
+        database.get_category_repository().save_category(&category).await?;
+        database.get_category_repository().save_category(&subcategory).await?;
+        let hacker_news = Feed::new("Hacker News".to_string(), Url::parse("http://example.com/hn.xml")?)
+            .with_category(subcategory.id.clone());
+        let other = Feed::new("Other".to_string(), Url::parse("http://example.com/other.xml")?);
+        database.get_feed_repository().save_feed(&hacker_news).await?;
+        database.get_feed_repository().save_feed(&other).await?;
+
+        let recent = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let old = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+        save_tagged_article(&database, &hacker_news, "Rust 2026 roadmap", recent, Some("rust")).await?;
+        save_tagged_article(&database, &hacker_news, "Hiring Rust developers", recent, Some("Rust")).await?;
+        save_tagged_article(&database, &hacker_news, "Rust in 2025", old, Some("rust")).await?;
+        save_tagged_article(&database, &other, "Rust elsewhere", recent, Some("rust")).await?;
+        let mut read = save_tagged_article(&database, &hacker_news, "Rust already read", recent, Some("rust")).await?;
+        read.mark_as_read();
+        article_repository.update_article(&read).await?;
+
+        let service = SmartFolderService::new(database.get_smart_folder_repository(), article_repository);
+        assert!(service.create("Broken", "colour:red").await.is_err());
+        assert!(service.create(" ", "is:unread").await.is_err());
+
+        let folder = service.create(
+            "HN Rust",
+            r#"feed:"hacker news" tag:rust is:unread after:2026-01-01 -title:hiring"#,
+        ).await?;
+        assert_eq!(folder_titles(&database, &folder).await?, vec!["Rust 2026 roadmap"]);
+
+        // A category covers the feeds of its subcategories
+        let tech = service.create("Tech", "category:tech (roadmap OR title:2025)").await?;
+        assert_eq!(folder_titles(&database, &tech).await?, vec!["Rust 2026 roadmap", "Rust in 2025"]);
+        let counts: Vec<(String, usize)> = service.get_all_with_unread_counts().await?
+            .into_iter()
+            .map(|(folder, count)| (folder.name, count))
+            .collect();
+        assert_eq!(counts, vec![("HN Rust".to_string(), 1), ("Tech".to_string(), 2)]);
+
+        service.delete(&folder.id).await?;
+        assert_eq!(service.get_all().await?.len(), 1);
+        Ok(())
+    }
+}
@@ -1,2 +1,8 @@
+mod schedule;
+mod sync_event;
+mod sync_handle;
 mod sync_service;
+pub use schedule::{ScheduleHints, SchedulePolicy};
+pub use sync_event::{BatchId, SyncEvent};
+pub use sync_handle::SyncHandle;
 pub use sync_service::*;
\ No newline at end of file
@@ -0,0 +1,205 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::models::feed::{Feed, FeedStatus};
+
+/// Signals gathered while fetching a feed that bear on when to fetch it next
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScheduleHints {
+    /// RSS `<ttl>`
+    pub ttl: Option<Duration>,
+    /// `sy:updatePeriod` divided by `sy:updateFrequency`
+    pub update_interval: Option<Duration>,
+    /// `Cache-Control: max-age`
+    pub max_age: Option<Duration>,
+    /// `Retry-After`, resolved to an absolute time
+    pub retry_after: Option<DateTime<Utc>>,
+    /// Publish dates of the entries in the fetched document
+    pub entry_dates: Vec<DateTime<Utc>>,
+}
+
+/// Bounds for adaptive polling and error backoff
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulePolicy {
+    /// Never poll a feed more often than this, whatever it posts
+    pub min_interval: Duration,
+    /// Never leave a healthy feed longer than this
+    pub max_interval: Duration,
+    /// Interval used until a feed's posting rhythm is known
+    pub default_interval: Duration,
+    /// First delay after a failed fetch; doubled on each further failure
+    pub error_base_delay: Duration,
+    pub error_max_delay: Duration,
+}
+
+impl Default for SchedulePolicy {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::minutes(15),
+            max_interval: Duration::hours(24),
+            default_interval: Duration::hours(1),
+            error_base_delay: Duration::minutes(5),
+            error_max_delay: Duration::hours(24),
+        }
+    }
+}
+
+impl SchedulePolicy {
+    /// Next fetch time for a feed that was just fetched successfully
+    ///
+    /// The base interval follows how often the feed posts, falling back to the
+    /// feed's previous interval (for `304` responses, which carry no entries)
+    /// and then to the default. Publisher hints (`<ttl>`, `sy:update*`,
+    /// `max-age`) are treated as "not sooner than" floors.
+    pub fn next_fetch_after_success(&self, now: DateTime<Utc>, feed: &Feed, hints: &ScheduleHints) -> DateTime<Utc> {
+        let base = observed_interval(&hints.entry_dates)
+            .or_else(|| previous_interval(feed).filter(|_| feed.status != FeedStatus::Error))
+            .unwrap_or(self.default_interval);
+
+        let interval = [hints.ttl, hints.update_interval, hints.max_age]
+            .into_iter()
+            .flatten()
+            .fold(base, |interval, floor| interval.max(floor))
+            .clamp(self.min_interval, self.max_interval);
+
+        let next = now + interval;
+        hints.retry_after.map_or(next, |retry_after| next.max(retry_after))
+    }
+
+    /// Next fetch time for a feed whose fetch just failed
+    ///
+    /// Must be called before the feed's status is updated: consecutive failures
+    /// are recognised by the feed already being in `FeedStatus::Error`, in which
+    /// case the previous delay is doubled.
+    pub fn next_fetch_after_error(&self, now: DateTime<Utc>, feed: &Feed, retry_after: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        let delay = match (&feed.status, previous_interval(feed)) {
+            (FeedStatus::Error, Some(previous)) => previous * 2,
+            _ => self.error_base_delay,
+        }
+        .clamp(self.error_base_delay, self.error_max_delay);
+
+        let next = now + delay;
+        retry_after.map_or(next, |retry_after| next.max(retry_after))
+    }
+}
+
+/// Gap between the feed's last fetch and the fetch it had scheduled
+fn previous_interval(feed: &Feed) -> Option<Duration> {
+    match (feed.last_fetched_at, feed.next_fetch_at) {
+        (Some(last), Some(next)) if next > last => Some(next - last),
+        _ => None,
+    }
+}
+
+/// Median gap between consecutive entries, or `None` with fewer than two dates
+fn observed_interval(dates: &[DateTime<Utc>]) -> Option<Duration> {
+    let mut dates = dates.to_vec();
+    dates.sort();
+    dates.dedup();
+
+    let mut gaps: Vec<Duration> = dates.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    gaps.sort();
+    Some(gaps[gaps.len() / 2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use url::Url;
+
+    fn feed() -> Feed {
+        Feed::new("Feed".to_string(), Url::parse("http://example.com/feed.xml").unwrap())
+    }
+
+    fn hourly_posts(now: DateTime<Utc>, hours: i64) -> Vec<DateTime<Utc>> {
+        (0..5).map(|i| now - Duration::hours(i * hours)).collect()
+    }
+
+    #[test]
+    fn test_interval_follows_posting_frequency() {
+        let policy = SchedulePolicy::default();
+        let now = Utc::now();
+        let hints = ScheduleHints { entry_dates: hourly_posts(now, 6), ..Default::default() };
+
+        assert_eq!(policy.next_fetch_after_success(now, &feed(), &hints), now + Duration::hours(6));
+    }
+
+    #[test]
+    fn test_interval_is_clamped() {
+        let policy = SchedulePolicy::default();
+        let now = Utc::now();
+
+        let busy = ScheduleHints {
+            entry_dates: (0..5).map(|i| now - Duration::minutes(i)).collect(),
+            ..Default::default()
+        };
+        assert_eq!(policy.next_fetch_after_success(now, &feed(), &busy), now + policy.min_interval);
+
+        let quiet = ScheduleHints { entry_dates: hourly_posts(now, 24 * 30), ..Default::default() };
+        assert_eq!(policy.next_fetch_after_success(now, &feed(), &quiet), now + policy.max_interval);
+    }
+
+    #[test]
+    fn test_publisher_hints_are_floors() {
+        let policy = SchedulePolicy::default();
+        let now = Utc::now();
+        let hints = ScheduleHints {
+            ttl: Some(Duration::hours(2)),
+            update_interval: Some(Duration::hours(3)),
+            max_age: Some(Duration::minutes(30)),
+            entry_dates: hourly_posts(now, 1),
+            ..Default::default()
+        };
+
+        assert_eq!(policy.next_fetch_after_success(now, &feed(), &hints), now + Duration::hours(3));
+    }
+
+    #[test]
+    fn test_not_modified_keeps_previous_interval() {
+        let policy = SchedulePolicy::default();
+        let now = Utc::now();
+        let mut feed = feed();
+        feed.update_fetch_times(now - Duration::hours(4), now);
+
+        assert_eq!(policy.next_fetch_after_success(now, &feed, &ScheduleHints::default()), now + Duration::hours(4));
+    }
+
+    #[test]
+    fn test_retry_after_postpones_next_fetch() {
+        let policy = SchedulePolicy::default();
+        let now = Utc::now();
+        let retry_after = now + Duration::hours(5);
+        let hints = ScheduleHints { retry_after: Some(retry_after), ..Default::default() };
+
+        assert_eq!(policy.next_fetch_after_success(now, &feed(), &hints), retry_after);
+        assert_eq!(policy.next_fetch_after_error(now, &feed(), Some(retry_after)), retry_after);
+    }
+
+    #[test]
+    fn test_errors_back_off_exponentially() {
+        let policy = SchedulePolicy::default();
+        let mut now = Utc::now();
+        let mut feed = feed();
+        let mut delays = Vec::new();
+
+        for _ in 0..4 {
+            let next = policy.next_fetch_after_error(now, &feed, None);
+            delays.push(next - now);
+            feed.update_fetch_times(now, next);
+            feed.update_status(FeedStatus::Error);
+            now = next;
+        }
+
+        assert_eq!(delays, vec![
+            Duration::minutes(5),
+            Duration::minutes(10),
+            Duration::minutes(20),
+            Duration::minutes(40),
+        ]);
+
+        feed.update_fetch_times(now, now + Duration::hours(20));
+        assert_eq!(policy.next_fetch_after_error(now, &feed, None), now + policy.error_max_delay);
+    }
+}
@@ -0,0 +1,24 @@
+use crate::models::article::ArticleId;
+use crate::models::feed::FeedId;
+
+/// Identifies one batch sync, so its events can be told apart from the
+/// scheduler's and from other batches'
+pub type BatchId = u64;
+
+/// Something that happened during a sync, broadcast to the UI
+///
+/// Per-feed events carry the batch they belong to, or `None` when the
+/// scheduler synced the feed on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncEvent {
+    /// A batch of feeds started syncing
+    Started { batch: BatchId, total: usize },
+    FeedStarted { batch: Option<BatchId>, feed_id: FeedId },
+    /// The feed synced, storing `new_articles` for entries it had not seen before
+    FeedSucceeded { batch: Option<BatchId>, feed_id: FeedId, new_articles: Vec<ArticleId> },
+    FeedFailed { batch: Option<BatchId>, feed_id: FeedId, error: String },
+    /// A new favicon was cached for the feed
+    FeedIconUpdated { feed_id: FeedId },
+    /// A batch ended, either normally or because it was cancelled
+    Finished { batch: BatchId, succeeded: usize, failed: usize, cancelled: bool },
+}
@@ -0,0 +1,513 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use log::{error, info, warn};
+use tokio::runtime::{Builder, Runtime};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::models::article::{Article, ArticleId};
+use crate::models::enclosure::Enclosure;
+use crate::models::feed::FeedId;
+use crate::services::assets::AssetCacheService;
+use crate::services::downloads::DownloadService;
+use crate::services::extraction::ExtractionService;
+use crate::services::retention::RetentionService;
+use crate::services::rss::RssService;
+use crate::services::sync::{SyncEvent, SyncService};
+
+/// How long shutdown waits for cancelled syncs to unwind
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Owns background syncing for a UI thread that has no async runtime of its own
+///
+/// Work runs on a small dedicated runtime. One-off batches (`sync_all`) can be
+/// cancelled on their own (`cancel_batch`) or together with the scheduler
+/// (`cancel`); progress is observed through [`subscribe`](Self::subscribe). The article cleanup
+/// job, when one is attached, runs alongside the scheduler, and so does the
+/// listener that follows up on each synced feed: refreshing its favicon,
+/// applying its auto-download policy, extracting new articles' full text and
+/// caching their images.
+pub struct SyncHandle {
+    runtime: Mutex<Option<Runtime>>,
+    sync_service: Arc<SyncService>,
+    rss_service: Arc<RssService>,
+    retention_service: Option<Arc<RetentionService>>,
+    download_service: Option<Arc<DownloadService>>,
+    extraction_service: Option<Arc<ExtractionService>>,
+    asset_cache: Option<Arc<AssetCacheService>>,
+    /// Parent of the tokens given to running tasks; replaced after each cancel
+    cancel_token: Mutex<CancellationToken>,
+    /// Child of `cancel_token` given to the running batch
+    batch_token: Mutex<CancellationToken>,
+    scheduler: Mutex<Option<JoinHandle<()>>>,
+    cleanup: Mutex<Option<JoinHandle<()>>>,
+    follow_ups: Mutex<Option<JoinHandle<()>>>,
+    batch: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SyncHandle {
+    pub fn new(sync_service: Arc<SyncService>, rss_service: Arc<RssService>) -> Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("panda-sync")
+            .enable_all()
+            .build()?;
+
+        Ok(Self {
+            runtime: Mutex::new(Some(runtime)),
+            sync_service,
+            rss_service,
+            retention_service: None,
+            download_service: None,
+            extraction_service: None,
+            asset_cache: None,
+            cancel_token: Mutex::new(CancellationToken::new()),
+            batch_token: Mutex::new(CancellationToken::new()),
+            scheduler: Mutex::new(None),
+            cleanup: Mutex::new(None),
+            follow_ups: Mutex::new(None),
+            batch: Mutex::new(None),
+        })
+    }
+
+    /// Runs the periodic article cleanup whenever the scheduler runs
+    pub fn with_retention_service(mut self, retention_service: Arc<RetentionService>) -> Self {
+        self.retention_service = Some(retention_service);
+        self
+    }
+
+    /// Downloads new enclosures for feeds with an auto-download policy
+    pub fn with_download_service(mut self, download_service: Arc<DownloadService>) -> Self {
+        self.download_service = Some(download_service);
+        self
+    }
+
+    /// Extracts the full text of new articles in feeds that opted in
+    pub fn with_extraction_service(mut self, extraction_service: Arc<ExtractionService>) -> Self {
+        self.extraction_service = Some(extraction_service);
+        self
+    }
+
+    /// Caches the images of new articles in feeds that opted in
+    pub fn with_asset_cache(mut self, asset_cache: Arc<AssetCacheService>) -> Self {
+        self.asset_cache = Some(asset_cache);
+        self
+    }
+
+    /// Receives every [`SyncEvent`] emitted from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SyncEvent> {
+        self.rss_service.subscribe_events()
+    }
+
+    /// Starts the background scheduler and cleanup job, unless already running
+    pub fn start(&self) {
+        self.start_cleanup();
+        self.start_follow_ups();
+
+        let mut scheduler = self.scheduler.lock().unwrap();
+        if scheduler.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let token = self.cancel_token.lock().unwrap().child_token();
+        let sync_service = self.sync_service.clone();
+        *scheduler = self.spawn(async move {
+            tokio::select! {
+                result = sync_service.start() => {
+                    if let Err(e) = result {
+                        error!("Feed scheduler failed: {}", e);
+                    }
+                }
+                _ = token.cancelled() => {
+                    sync_service.stop();
+                    info!("Feed scheduler cancelled");
+                }
+            }
+        });
+    }
+
+    /// Stops the scheduler once the feed it is syncing has finished
+    pub fn stop(&self) {
+        self.sync_service.stop();
+        if let Some(retention_service) = &self.retention_service {
+            retention_service.stop();
+        }
+    }
+
+    fn start_cleanup(&self) {
+        let retention_service = match &self.retention_service {
+            Some(retention_service) => retention_service.clone(),
+            None => return,
+        };
+        let mut cleanup = self.cleanup.lock().unwrap();
+        if cleanup.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let token = self.cancel_token.lock().unwrap().child_token();
+        *cleanup = self.spawn(async move {
+            tokio::select! {
+                result = retention_service.start() => {
+                    if let Err(e) = result {
+                        error!("Article cleanup failed: {}", e);
+                    }
+                }
+                _ = token.cancelled() => retention_service.stop(),
+            }
+        });
+    }
+
+    /// Follows sync events for the lifetime of the runtime; cancelling a sync
+    /// leaves icon lookups, downloads, extraction and image caching already
+    /// under way alone
+    ///
+    /// Each synced feed is followed up in a task of its own, so a long
+    /// download does not hold up the feeds synced after it.
+    fn start_follow_ups(&self) {
+        let mut follow_ups = self.follow_ups.lock().unwrap();
+        if follow_ups.as_ref().is_some_and(|task| !task.is_finished()) {
+            return;
+        }
+
+        let feed_follow_ups = FollowUps {
+            rss_service: self.rss_service.clone(),
+            download_service: self.download_service.clone(),
+            extraction_service: self.extraction_service.clone(),
+            asset_cache: self.asset_cache.clone(),
+        };
+        let mut events = self.rss_service.subscribe_events();
+        *follow_ups = self.spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(SyncEvent::FeedSucceeded { feed_id, new_articles, .. }) => {
+                        let feed_follow_ups = feed_follow_ups.clone();
+                        tokio::spawn(async move { feed_follow_ups.run(&feed_id, &new_articles).await });
+                    }
+                    Ok(_) => {}
+                    // Which articles were new is lost with the events; the
+                    // missed feeds are followed up on after their next sync
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Missed {} sync events, skipping their follow-ups", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    /// Downloads one enclosure in the background; progress is read from the
+    /// download service
+    pub fn download(&self, enclosure: Enclosure) {
+        let download_service = match &self.download_service {
+            Some(download_service) => download_service.clone(),
+            None => {
+                warn!("Download requested without a download service");
+                return;
+            }
+        };
+        self.spawn(async move {
+            if let Err(e) = download_service.download(&enclosure).await {
+                warn!("Download of {} failed: {}", enclosure.url, e);
+            }
+        });
+    }
+
+    /// Extracts one article's full text in the background; whether it is still
+    /// running is read from the extraction service
+    pub fn extract_full_text(&self, article: Article) {
+        let extraction_service = match &self.extraction_service {
+            Some(extraction_service) => extraction_service.clone(),
+            None => {
+                warn!("Full text requested without an extraction service");
+                return;
+            }
+        };
+        self.spawn(async move {
+            if let Err(e) = extraction_service.extract_article(&article).await {
+                warn!("Full text extraction for {} failed: {}", article.url, e);
+            }
+        });
+    }
+
+    /// Syncs every due feed now, unless a batch is already running
+    pub fn sync_all(&self) {
+        let mut batch = self.batch.lock().unwrap();
+        if self.is_syncing_with(&batch) {
+            return;
+        }
+
+        let token = self.cancel_token.lock().unwrap().child_token();
+        *self.batch_token.lock().unwrap() = token.clone();
+        let sync_service = self.sync_service.clone();
+        // Dropping the batch mid-sync makes it report itself as cancelled
+        *batch = self.spawn(async move {
+            tokio::select! {
+                result = sync_service.sync_all() => {
+                    if let Err(e) = result {
+                        error!("Sync failed: {}", e);
+                    }
+                }
+                _ = token.cancelled() => info!("Sync cancelled"),
+            }
+        });
+    }
+
+    /// Whether a `sync_all` batch is in progress
+    pub fn is_syncing(&self) -> bool {
+        self.is_syncing_with(&self.batch.lock().unwrap())
+    }
+
+    /// Aborts the current `sync_all` batch, leaving the scheduler running
+    ///
+    /// Feeds that were mid-fetch are left due, so they are picked up again by
+    /// the next sync.
+    pub fn cancel_batch(&self) {
+        self.batch_token.lock().unwrap().cancel();
+    }
+
+    /// Aborts in-flight syncs, both the current batch and the scheduler
+    ///
+    /// Feeds that were mid-fetch are left due, so they are picked up again by
+    /// the next sync. `start` and `sync_all` may be called again afterwards.
+    pub fn cancel(&self) {
+        let mut token = self.cancel_token.lock().unwrap();
+        token.cancel();
+        *token = CancellationToken::new();
+    }
+
+    /// Runs `future` to completion on the sync runtime, blocking the calling thread
+    ///
+    /// For UI code that needs an answer before it can go on, such as a page
+    /// of articles or the result of a button. Must not be called from within
+    /// the runtime itself.
+    pub fn block_on<T>(&self, future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        let handle = self.runtime.lock().unwrap().as_ref()
+            .map(|runtime| runtime.handle().clone())
+            .ok_or_else(|| anyhow!("The sync runtime has shut down"))?;
+        handle.block_on(future)
+    }
+
+    /// Cancels everything and stops the runtime, waiting briefly for tasks to unwind
+    pub fn shutdown(&self) {
+        self.cancel();
+        self.stop();
+        if let Some(runtime) = self.runtime.lock().unwrap().take() {
+            runtime.shutdown_timeout(SHUTDOWN_TIMEOUT);
+        }
+    }
+
+    fn is_syncing_with(&self, batch: &Option<JoinHandle<()>>) -> bool {
+        batch.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    fn spawn<F>(&self, task: F) -> Option<JoinHandle<()>>
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        match self.runtime.lock().unwrap().as_ref() {
+            Some(runtime) => Some(runtime.spawn(task)),
+            None => {
+                warn!("Sync requested after shutdown");
+                None
+            }
+        }
+    }
+}
+
+/// The services that follow up on a synced feed
+#[derive(Clone)]
+struct FollowUps {
+    rss_service: Arc<RssService>,
+    download_service: Option<Arc<DownloadService>>,
+    extraction_service: Option<Arc<ExtractionService>>,
+    asset_cache: Option<Arc<AssetCacheService>>,
+}
+
+impl FollowUps {
+    /// Refreshes the feed's icon and, when the sync added `new_articles`,
+    /// downloads, extracts and caches what the feed asks for
+    async fn run(&self, feed_id: &FeedId, new_articles: &[ArticleId]) {
+        let icon = async {
+            if let Err(e) = self.rss_service.refresh_feed_icon(feed_id).await {
+                warn!("Icon lookup for feed {} failed: {}", feed_id, e);
+            }
+        };
+        if new_articles.is_empty() {
+            return icon.await;
+        }
+
+        let downloads = async {
+            if let Some(download_service) = &self.download_service {
+                if let Err(e) = download_service.auto_download(feed_id).await {
+                    warn!("Auto-download for feed {} failed: {}", feed_id, e);
+                }
+            }
+        };
+        let articles = async {
+            // Before caching images, so those of the full text are cached too
+            if let Some(extraction_service) = &self.extraction_service {
+                if let Err(e) = extraction_service.extract_new_articles(feed_id, new_articles).await {
+                    warn!("Full text extraction for feed {} failed: {}", feed_id, e);
+                }
+            }
+            if let Some(asset_cache) = &self.asset_cache {
+                if let Err(e) = asset_cache.cache_new_articles(feed_id, new_articles).await {
+                    warn!("Caching images for feed {} failed: {}", feed_id, e);
+                }
+            }
+        };
+        tokio::join!(icon, downloads, articles);
+    }
+}
+
+impl Drop for SyncHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use url::Url;
+    use crate::data::Database;
+    use crate::models::feed::Feed;
+    use crate::utils::test_support::{temp_database, TestResponse, TestServer};
+
+    const DOCUMENT: &str = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Feed</title>
+        <item><title>Post</title><link>http://example.com/post</link></item></channel></rss>"#;
+
+    fn sync_handle(database: &Database) -> SyncHandle {
+        let rss_service = Arc::new(RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        ));
+        let sync_service = Arc::new(SyncService::new(rss_service.clone()));
+        SyncHandle::new(sync_service, rss_service).unwrap()
+    }
+
+    /// Collects events until a `Finished` arrives or the timeout passes
+    fn events_until_finished(events: &mut broadcast::Receiver<SyncEvent>) -> Vec<SyncEvent> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = Vec::new();
+        while Instant::now() < deadline {
+            match events.try_recv() {
+                Ok(event) => {
+                    let finished = matches!(event, SyncEvent::Finished { .. });
+                    received.push(event);
+                    if finished {
+                        break;
+                    }
+                }
+                Err(_) => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn test_sync_all_emits_events() -> Result<()> {
+        let runtime = Runtime::new()?;
+        let server = runtime.block_on(TestServer::start());
+        server.route("/feed.xml", TestResponse::ok(DOCUMENT));
+
+        let database = temp_database();
+        let feed = Feed::new("Feed".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        runtime.block_on(database.get_feed_repository().save_feed(&feed))?;
+
+        let handle = sync_handle(&database);
+        let mut events = handle.subscribe();
+        handle.sync_all();
+
+        let events = events_until_finished(&mut events);
+        let post = runtime.block_on(database.get_article_repository().get_article_by_url("http://example.com/post"))?.unwrap();
+        assert_eq!(events, vec![
+            SyncEvent::Started { batch: 1, total: 1 },
+            SyncEvent::FeedStarted { batch: Some(1), feed_id: feed.id.clone() },
+            SyncEvent::FeedSucceeded { batch: Some(1), feed_id: feed.id.clone(), new_articles: vec![post.id] },
+            SyncEvent::Finished { batch: 1, succeeded: 1, failed: 0, cancelled: false },
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_aborts_in_flight_sync() -> Result<()> {
+        let runtime = Runtime::new()?;
+        let server = runtime.block_on(TestServer::start());
+        server.route("/feed.xml", TestResponse::ok(DOCUMENT).with_delay(Duration::from_secs(10)));
+
+        let database = temp_database();
+        let feed = Feed::new("Feed".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        runtime.block_on(database.get_feed_repository().save_feed(&feed))?;
+
+        let handle = sync_handle(&database);
+        let mut events = handle.subscribe();
+        handle.sync_all();
+        while server.requests().is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        let cancelled_at = Instant::now();
+        handle.cancel();
+
+        let events = events_until_finished(&mut events);
+        assert_eq!(events.last(), Some(&SyncEvent::Finished { batch: 1, succeeded: 0, failed: 0, cancelled: true }));
+        assert!(cancelled_at.elapsed() < Duration::from_secs(5));
+
+        // The feed was not marked as fetched, so it stays due
+        let stored = runtime.block_on(database.get_feed_repository().get_feed_by_id(&feed.id))?.unwrap();
+        assert!(stored.last_fetched_at.is_none());
+
+        handle.shutdown();
+        assert!(!handle.is_syncing());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_batch_leaves_scheduler_running() -> Result<()> {
+        let runtime = Runtime::new()?;
+        let server = runtime.block_on(TestServer::start());
+        server.route("/feed.xml", TestResponse::ok(DOCUMENT).with_delay(Duration::from_secs(10)));
+
+        let database = temp_database();
+        let feed = Feed::new("Feed".to_string(), Url::parse(&server.url("/feed.xml"))?);
+        runtime.block_on(database.get_feed_repository().save_feed(&feed))?;
+
+        let handle = sync_handle(&database);
+        let mut events = handle.subscribe();
+        handle.start();
+        handle.sync_all();
+        while server.requests().len() < 2 {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        handle.cancel_batch();
+        let events = events_until_finished(&mut events);
+        assert!(matches!(events.last(), Some(SyncEvent::Finished { cancelled: true, .. })));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(handle.scheduler.lock().unwrap().as_ref().is_some_and(|task| !task.is_finished()));
+        assert!(!handle.is_syncing());
+
+        handle.shutdown();
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_on_waits_on_sync_runtime_until_shutdown() -> Result<()> {
+        let database = temp_database();
+        let handle = sync_handle(&database);
+
+        let feeds = handle.block_on(database.get_feed_repository().get_all_feeds())?;
+        assert!(feeds.is_empty());
+
+        handle.shutdown();
+        assert!(handle.block_on(database.get_feed_repository().get_all_feeds()).is_err());
+        Ok(())
+    }
+}
@@ -1,25 +1,335 @@
-use std::sync::Arc;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use anyhow::Result;
-use crate::services::rss::RssService;
+use chrono::{DateTime, Utc};
+use log::{debug, error, info};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+
+use crate::models::feed::{FeedId, FeedStatus};
+use crate::models::settings::Settings;
+use crate::services::rss::{FeedSyncReport, RssService};
+
+/// How often the scheduler reloads the queue from the repository, picking up
+/// feeds that were added, removed or re-timed outside the scheduler
+const RESCAN_INTERVAL: Duration = Duration::from_secs(300);
+
+/// A feed waiting in the scheduler queue, ordered by when it is due
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScheduledFeed {
+    due: DateTime<Utc>,
+    feed_id: FeedId,
+}
+
+impl Ord for ScheduledFeed {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.due.cmp(&other.due).then_with(|| self.feed_id.0.cmp(&other.feed_id.0))
+    }
+}
+
+impl PartialOrd for ScheduledFeed {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
 pub struct SyncService {
     rss_service: Arc<RssService>,
+    /// Min-heap of feeds keyed on their `next_fetch_at`
+    queue: Mutex<BinaryHeap<Reverse<ScheduledFeed>>>,
+    wake: Notify,
+    running: AtomicBool,
 }
 
 impl SyncService {
     pub fn new(rss_service: Arc<RssService>) -> Self {
         Self {
             rss_service,
+            queue: Mutex::new(BinaryHeap::new()),
+            wake: Notify::new(),
+            running: AtomicBool::new(false),
         }
     }
 
-    pub async fn sync_all(&self) -> Result<()> {
+    pub async fn sync_all(&self) -> Result<Vec<FeedSyncReport>> {
         self.rss_service.sync_all_feeds().await
     }
 
-    pub async fn sync_feed(&self, feed_id: &str) -> Result<()> {
+    pub async fn sync_feed(&self, feed_id: &str) -> Result<FeedSyncReport> {
         // Create a proper FeedId from the string
         let feed_id = crate::models::feed::FeedId(feed_id.to_string());
         self.rss_service.sync_feed(&feed_id).await
     }
-}
\ No newline at end of file
+
+    /// Runs the polling scheduler until [`stop`](Self::stop) is called
+    ///
+    /// Feeds are fetched as their `next_fetch_at` comes due, all that are due
+    /// at once within the fetch concurrency limits. Each sync records the feed's next fetch time (adapted to its posting
+    /// rhythm, or backed off after errors), and the feed is queued again for it.
+    pub async fn start(&self) -> Result<()> {
+        info!("Starting feed scheduler");
+        self.running.store(true, AtomicOrdering::SeqCst);
+        self.reload_queue().await?;
+        let mut last_scan = Instant::now();
+
+        while self.running.load(AtomicOrdering::SeqCst) {
+            if last_scan.elapsed() >= RESCAN_INTERVAL {
+                if let Err(e) = self.reload_queue().await {
+                    error!("Failed to reload feed schedule: {}", e);
+                }
+                last_scan = Instant::now();
+            }
+
+            let now = Utc::now();
+            let due = self.pop_due(now);
+            if !due.is_empty() {
+                self.sync_scheduled(&due).await;
+                continue;
+            }
+
+            let until_rescan = RESCAN_INTERVAL.saturating_sub(last_scan.elapsed());
+            let wait = self.next_due()
+                .map(|due| (due - now).to_std().unwrap_or_default())
+                .map_or(until_rescan, |until_due| until_due.min(until_rescan));
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = self.wake.notified() => {}
+            }
+        }
+
+        info!("Feed scheduler stopped");
+        Ok(())
+    }
+
+    /// Asks a running scheduler to return after the feeds it is currently syncing
+    pub fn stop(&self) {
+        self.running.store(false, AtomicOrdering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    /// Applies the sync and fetch parts of `settings` without a restart
+    ///
+    /// The sync interval becomes the default polling interval for feeds with
+    /// no known rhythm, used from each feed's next fetch onwards. Fetches
+    /// already in flight finish with the old HTTP settings.
+    pub fn apply_settings(&self, settings: &Settings) -> Result<()> {
+        let fetch_config = self.rss_service.fetch_config().with_settings(&settings.fetch);
+        self.rss_service.set_fetch_config(fetch_config)?;
+
+        let mut policy = self.rss_service.schedule_policy();
+        let interval = chrono::Duration::minutes(i64::from(settings.sync_interval_minutes));
+        policy.default_interval = interval.max(policy.min_interval);
+        policy.max_interval = policy.max_interval.max(policy.default_interval);
+        self.rss_service.set_schedule_policy(policy);
+        Ok(())
+    }
+
+    /// Rebuilds the queue from the stored `next_fetch_at` of every enabled feed
+    async fn reload_queue(&self) -> Result<()> {
+        let now = Utc::now();
+        let queue: BinaryHeap<_> = self.rss_service.get_all_feeds().await?
+            .into_iter()
+            .filter(|feed| feed.status != FeedStatus::Disabled)
+            .map(|feed| Reverse(ScheduledFeed { due: feed.next_fetch_at.unwrap_or(now), feed_id: feed.id }))
+            .collect();
+
+        debug!("Scheduled {} feeds", queue.len());
+        *self.queue.lock().unwrap() = queue;
+        Ok(())
+    }
+
+    /// Takes every feed that is due by `now` off the queue
+    fn pop_due(&self, now: DateTime<Utc>) -> Vec<FeedId> {
+        let mut queue = self.queue.lock().unwrap();
+        let mut due = Vec::new();
+        while queue.peek().is_some_and(|Reverse(next)| next.due <= now) {
+            let Reverse(next) = queue.pop().unwrap();
+            due.push(next.feed_id);
+        }
+        due
+    }
+
+    fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.queue.lock().unwrap().peek().map(|Reverse(next)| next.due)
+    }
+
+    /// Syncs the due feeds together and queues each again for the time its
+    /// sync recorded
+    async fn sync_scheduled(&self, feed_ids: &[FeedId]) {
+        let mut feeds = Vec::with_capacity(feed_ids.len());
+        for feed_id in feed_ids {
+            match self.rss_service.get_feed_by_id(feed_id).await {
+                Ok(Some(feed)) if feed.status != FeedStatus::Disabled => feeds.push(feed),
+                // Deleted or disabled since it was queued
+                Ok(_) => debug!("Feed {} dropped from the schedule", feed_id),
+                Err(e) => error!("Failed to load scheduled feed {}: {}", feed_id, e),
+            }
+        }
+
+        let results = self.rss_service.sync_scheduled_feeds(&feeds).await;
+        for (feed, result) in feeds.iter().zip(results) {
+            if let Err(e) = result {
+                error!("Scheduled sync of feed {} failed: {}", feed.id, e);
+            }
+
+            match self.rss_service.get_feed_by_id(&feed.id).await {
+                Ok(Some(feed)) if feed.status != FeedStatus::Disabled => {
+                    if let Some(due) = feed.next_fetch_at {
+                        self.queue.lock().unwrap().push(Reverse(ScheduledFeed { due, feed_id: feed.id }));
+                    }
+                }
+                // Deleted or disabled while it was being fetched
+                Ok(_) => debug!("Feed {} dropped from the schedule", feed.id),
+                Err(e) => error!("Failed to reschedule feed {}: {}", feed.id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::Duration as ChronoDuration;
+    use url::Url;
+    use crate::models::feed::Feed;
+    use crate::models::settings::FetchSettings;
+    use crate::services::rss::FetchConfig;
+    use crate::utils::test_support::{temp_database, TestResponse, TestServer};
+
+    const DOCUMENT: &str = r#"<?xml version="1.0"?><rss version="2.0"><channel><title>Feed</title>
+        <item><title>Post</title><link>http://example.com/post</link></item></channel></rss>"#;
+
+    #[tokio::test]
+    async fn test_scheduler_fetches_only_due_feeds() -> Result<()> {
+        let server = TestServer::start().await;
+        server.route("/due.xml", TestResponse::ok(DOCUMENT));
+        server.route("/later.xml", TestResponse::ok(DOCUMENT));
+
+        let database = temp_database();
+        let feed_repository = database.get_feed_repository();
+        let due = Feed::new("Due".to_string(), Url::parse(&server.url("/due.xml"))?);
+        let mut later = Feed::new("Later".to_string(), Url::parse(&server.url("/later.xml"))?);
+        later.update_fetch_times(Utc::now(), Utc::now() + ChronoDuration::hours(1));
+        feed_repository.save_feed(&due).await?;
+        feed_repository.save_feed(&later).await?;
+
+        let rss_service = Arc::new(RssService::new(
+            database.get_article_repository(),
+            feed_repository.clone(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        ));
+        let sync_service = Arc::new(SyncService::new(rss_service.clone()));
+        let scheduler = tokio::spawn({
+            let sync_service = sync_service.clone();
+            async move { sync_service.start().await }
+        });
+
+        for _ in 0..100 {
+            if rss_service.get_feed_by_id(&due.id).await?.unwrap().last_fetched_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        sync_service.stop();
+        scheduler.await??;
+
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, vec!["/due.xml".to_string()]);
+
+        // The fetched feed is queued again for later, alongside the untouched one
+        let stored = rss_service.get_feed_by_id(&due.id).await?.unwrap();
+        assert!(stored.next_fetch_at.unwrap() > Utc::now());
+        assert_eq!(sync_service.queue.lock().unwrap().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_syncs_due_feeds_within_concurrency_limits() -> Result<()> {
+        let server = TestServer::start().await;
+        let database = temp_database();
+        let mut feeds = Vec::new();
+        for i in 0..4 {
+            let path = format!("/{}.xml", i);
+            server.route(&path, TestResponse::ok(DOCUMENT).with_delay(Duration::from_millis(200)));
+            let feed = Feed::new(format!("Feed {}", i), Url::parse(&server.url(&path))?);
+            database.get_feed_repository().save_feed(&feed).await?;
+            feeds.push(feed);
+        }
+
+        let rss_service = Arc::new(RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        ).with_fetch_config(FetchConfig::default().with_max_fetches_per_host(2))?);
+        let sync_service = Arc::new(SyncService::new(rss_service.clone()));
+        let scheduler = tokio::spawn({
+            let sync_service = sync_service.clone();
+            async move { sync_service.start().await }
+        });
+
+        for _ in 0..100 {
+            if server.requests().len() == feeds.len() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        sync_service.stop();
+        scheduler.await??;
+
+        assert_eq!(server.requests().len(), 4);
+        assert_eq!(server.max_in_flight(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_settings_updates_fetch_config_and_schedule() -> Result<()> {
+        let database = temp_database();
+        let rss_service = Arc::new(RssService::new(
+            database.get_article_repository(),
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+            database.get_enclosure_repository(),
+        ));
+        let sync_service = SyncService::new(rss_service.clone());
+
+        let mut settings = Settings {
+            sync_interval_minutes: 48 * 60,
+            fetch: FetchSettings {
+                timeout_secs: 5,
+                user_agent: Some("Custom/1.0".to_string()),
+                feed_headers: HashMap::from([(
+                    FeedId("f1".to_string()),
+                    vec![("X-Token".to_string(), "secret".to_string())],
+                )]).into(),
+                ..FetchSettings::default()
+            },
+            ..Settings::default()
+        };
+        sync_service.apply_settings(&settings)?;
+
+        let config = rss_service.fetch_config();
+        assert_eq!(config.timeout, Duration::from_secs(5));
+        assert_eq!(config.user_agent, "Custom/1.0");
+        assert_eq!(config.headers_for(&FeedId("f1".to_string())).len(), 1);
+
+        let policy = rss_service.schedule_policy();
+        assert_eq!(policy.default_interval, ChronoDuration::hours(48));
+        assert!(policy.max_interval >= policy.default_interval);
+
+        // An invalid proxy is rejected and the previous settings stay in place
+        settings.fetch.proxy_url = Some("not a proxy".to_string());
+        assert!(sync_service.apply_settings(&settings).is_err());
+        assert_eq!(rss_service.fetch_config().proxy_url, None);
+        Ok(())
+    }
+}
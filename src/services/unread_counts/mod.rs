@@ -0,0 +1,3 @@
+mod unread_count_service;
+
+pub use unread_count_service::UnreadCountService;
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use anyhow::Result;
+
+use crate::base::repository::{CategoryRepository, FeedRepository, TagRepository};
+use crate::models::article_counts::UnreadCounts;
+use crate::models::feed::FeedId;
+
+/// Loads the unread badges of the sidebar and keeps them current
+///
+/// [`load`](Self::load) counts everything, including which category each
+/// feed is in; it is run again when feeds, categories or stored articles are
+/// added or removed. Otherwise a sync or a read-state change recounts only
+/// the feeds it touched.
+pub struct UnreadCountService {
+    feed_repository: Arc<dyn FeedRepository>,
+    category_repository: Arc<dyn CategoryRepository>,
+    tag_repository: Arc<dyn TagRepository>,
+}
+
+impl UnreadCountService {
+    pub fn new(
+        feed_repository: Arc<dyn FeedRepository>,
+        category_repository: Arc<dyn CategoryRepository>,
+        tag_repository: Arc<dyn TagRepository>,
+    ) -> Self {
+        Self {
+            feed_repository,
+            category_repository,
+            tag_repository,
+        }
+    }
+
+    /// Counts the articles of every feed, category and tag
+    pub async fn load(&self) -> Result<UnreadCounts> {
+        let feed_categories = self.feed_repository.get_all_feeds().await?
+            .into_iter()
+            .filter_map(|feed| feed.category_id.map(|category_id| (feed.id, category_id)))
+            .collect();
+        let category_parents = self.category_repository.get_all_categories().await?
+            .into_iter()
+            .filter_map(|category| category.parent_id.map(|parent_id| (category.id, parent_id)))
+            .collect();
+
+        Ok(UnreadCounts::new(
+            self.feed_repository.get_article_counts().await?,
+            self.category_repository.get_article_counts().await?,
+            self.tag_repository.get_article_counts().await?,
+            feed_categories,
+            category_parents,
+        ))
+    }
+
+    /// Recounts the given feeds, e.g. after a sync stored new articles in them
+    pub async fn refresh_feeds<'a>(
+        &self,
+        counts: &mut UnreadCounts,
+        feed_ids: impl IntoIterator<Item = &'a FeedId>,
+    ) -> Result<()> {
+        let feed_ids: Vec<FeedId> = feed_ids.into_iter().cloned().collect();
+        for (feed_id, feed_counts) in self.feed_repository.get_article_counts_for_feeds(&feed_ids).await? {
+            counts.update_feed(&feed_id, feed_counts);
+        }
+        Ok(())
+    }
+
+    /// Recounts the tags, whose unread counts change with the read state of their articles
+    pub async fn refresh_tags(&self, counts: &mut UnreadCounts) -> Result<()> {
+        counts.update_tags(self.tag_repository.get_article_counts().await?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Database;
+    use crate::models::article::Article;
+    use crate::models::article_page::ArticleFilter;
+    use crate::models::article_counts::ArticleCounts;
+    use crate::models::category::Category;
+    use crate::models::feed::Feed;
+    use crate::services::read_state::ReadStateService;
+    use crate::utils::test_support::{save_article, save_feed, temp_database, test_article};
+
+    fn service(database: &Database) -> UnreadCountService {
+        UnreadCountService::new(
+            database.get_feed_repository(),
+            database.get_category_repository(),
+            database.get_tag_repository(),
+        )
+    }
+
+    async fn save_articles(database: &Database, feed: &Feed, titles: &[&str]) -> Result<Vec<Article>> {
+        let mut articles = Vec::new();
+        for title in titles {
+            articles.push(save_article(database, test_article(feed, title)).await?);
+        }
+        Ok(articles)
+    }
+
+    #[tokio::test]
+    async fn test_counts_roll_up_and_follow_read_changes() -> Result<()> {
+        let database = temp_database();
+        let category_repository = database.get_category_repository();
+        let tech = Category::new("Tech".to_string());
+        let rust = Category::new("Rust".to_string()).with_parent(tech.id.clone());
+        let empty = Category::new("Empty".to_string());
+        for category in [&tech, &rust, &empty] {
+            category_repository.save_category(category).await?;
+        }
+
+        let news = save_feed(&database, "news", None).await?;
+        let hardware = save_feed(&database, "hardware", Some(&tech)).await?;
+        let blog = save_feed(&database, "blog", Some(&rust)).await?;
+        let quiet = save_feed(&database, "quiet", Some(&rust)).await?;
+        save_articles(&database, &news, &["n1", "n2"]).await?;
+        save_articles(&database, &hardware, &["h1"]).await?;
+        let posts = save_articles(&database, &blog, &["b1", "b2", "b3"]).await?;
+
+        let article_repository = database.get_article_repository();
+        article_repository.add_tag(&posts[0].id, "later").await?;
+        article_repository.add_tag(&posts[1].id, "later").await?;
+        article_repository.add_tag(&posts[0].id, "rust").await?;
+        let mut read = posts[2].clone();
+        read.mark_as_read();
+        article_repository.update_article(&read).await?;
+
+        let service = service(&database);
+        let mut counts = service.load().await?;
+        assert_eq!(counts.all, ArticleCounts::new(5, 6));
+        assert_eq!(counts.feed(&blog.id), ArticleCounts::new(2, 3));
+        assert_eq!(counts.feed(&quiet.id), ArticleCounts::new(0, 0));
+        assert_eq!(counts.category(&rust.id), ArticleCounts::new(2, 3));
+        assert_eq!(counts.category(&tech.id), ArticleCounts::new(3, 4));
+        assert_eq!(counts.category(&empty.id), ArticleCounts::new(0, 0));
+        assert_eq!(counts.tag("later"), ArticleCounts::new(2, 2));
+        assert_eq!(counts.tag("rust"), ArticleCounts::new(1, 1));
+
+        // Marking a category read only recounts the feeds it changed
+        let action = ReadStateService::new(article_repository.clone())
+            .mark_read(&ArticleFilter::Category(rust.id.clone()), None, "Rust")
+            .await?;
+        assert_eq!(action.feed_ids.len(), 1);
+        service.refresh_feeds(&mut counts, &action.feed_ids).await?;
+        service.refresh_tags(&mut counts).await?;
+        assert_eq!(counts.category(&rust.id), ArticleCounts::new(0, 3));
+        assert_eq!(counts.category(&tech.id), ArticleCounts::new(1, 4));
+        assert_eq!(counts.tag("later"), ArticleCounts::new(0, 2));
+
+        // New articles from a sync
+        save_articles(&database, &quiet, &["q1"]).await?;
+        service.refresh_feeds(&mut counts, [&quiet.id]).await?;
+
+        let reloaded = service.load().await?;
+        assert_eq!(counts.all, reloaded.all);
+        assert_eq!(counts.feeds, reloaded.feeds);
+        assert_eq!(counts.tags, reloaded.tags);
+        for category in [&tech, &rust, &empty] {
+            assert_eq!(counts.category(&category.id), reloaded.category(&category.id));
+        }
+        assert_eq!(counts.category(&tech.id), ArticleCounts::new(2, 5));
+        Ok(())
+    }
+}
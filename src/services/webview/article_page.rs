@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use crate::models::article::Article;
+use crate::services::webview::highlight::highlight_code_blocks;
+use crate::services::webview::reader_style::ReaderStyle;
+use crate::services::webview::sanitizer::sanitize_html_with_images;
+use crate::utils::html::escape_html;
+
+/// Policy for the article page: no scripts, frames, plugins or form posts,
+/// only the page's own styles, and images and media from the web or the
+/// offline image cache
+pub const CONTENT_SECURITY_POLICY: &str = "default-src 'none'; img-src http: https: data: panda-asset:; media-src http: https:; \
+    style-src 'unsafe-inline'; font-src data:; base-uri 'none'; form-action 'none'";
+
+/// The web view's page around `body`, styled by `style`
+pub fn page_html(body: &str, style: &ReaderStyle) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta http-equiv="Content-Security-Policy" content="{}">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <style>
+{}
+    </style>
+</head>
+<body>
+<main>
+{}
+</main>
+</body>
+</html>"#,
+        CONTENT_SECURITY_POLICY, style.css(), body
+    )
+}
+
+/// The article's title, byline, sanitized content and a link to the original
+///
+/// Extracted full text is shown in place of the feed's content when there is any.
+///
+/// Everything here comes from the feed, so metadata is escaped and the
+/// content goes through [`sanitize_html_with_images`], which points images
+/// in `local_images` at their cached copies, before its code blocks are
+/// highlighted.
+pub fn article_body(article: &Article, local_images: &HashMap<String, String>) -> String {
+    let content = article.full_content.as_deref()
+        .or(article.content.as_deref())
+        .or(article.summary.as_deref())
+        .map(|html| highlight_code_blocks(&sanitize_html_with_images(html, local_images)))
+        .unwrap_or_else(|| "<p>No content available.</p>".to_string());
+
+    let mut body = format!(
+        r#"<h1>{}</h1>
+<div class="meta">
+    <span>By {}</span>
+    <span> • </span>
+    <span>{}</span>
+</div>
+<div class="content">
+{}
+</div>"#,
+        escape_html(&article.title),
+        escape_html(article.author.as_deref().unwrap_or("Unknown")),
+        article.published_at.format("%Y-%m-%d %H:%M"),
+        content,
+    );
+
+    if matches!(article.url.scheme(), "http" | "https") {
+        body.push_str(&format!(
+            r#"
+<div class="meta">
+    <a href="{}" rel="noopener noreferrer">Read original article</a>
+</div>"#,
+            escape_html(article.url.as_str())
+        ));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::feed::FeedId;
+    use url::Url;
+
+    #[test]
+    fn test_article_body_escapes_feed_fields() {
+        let article = Article::new(
+            FeedId::new(),
+            "<img src=x onerror=alert(1)>Title".to_string(),
+            Url::parse("https://example.com/post?a=1&b=');alert(2);//").unwrap(),
+        )
+        .with_author("\"><script>alert(3)</script>".to_string())
+        .with_content("<p>Body</p><script>alert(4)</script>".to_string());
+
+        let body = article_body(&article, &HashMap::new());
+        assert!(body.contains("<h1>&lt;img src=x onerror=alert(1)&gt;Title</h1>"));
+        assert!(body.contains("By &quot;&gt;&lt;script&gt;alert(3)&lt;/script&gt;"));
+        assert!(body.contains("<p>Body</p>"));
+        assert!(body.contains(r#"<a href="https://example.com/post?a=1&amp;b=%27);alert(2);//" rel="noopener noreferrer">"#));
+        assert!(!body.contains("<script"));
+        assert!(!body.contains("onclick"));
+
+        let javascript = Article::new(FeedId::new(), "Title".to_string(), Url::parse("javascript:alert(5)").unwrap());
+        assert!(!article_body(&javascript, &HashMap::new()).contains("Read original article"));
+    }
+
+    #[test]
+    fn test_page_html_applies_content_security_policy() {
+        let page = page_html("<p>Body</p>", &ReaderStyle::default());
+        assert!(page.contains(&format!(r#"<meta http-equiv="Content-Security-Policy" content="{}">"#, CONTENT_SECURITY_POLICY)));
+        assert!(CONTENT_SECURITY_POLICY.starts_with("default-src 'none'"));
+        assert!(!CONTENT_SECURITY_POLICY.contains("script-src"));
+        assert!(page.contains("<main>\n<p>Body</p>\n</main>"));
+        assert!(page.contains(&ReaderStyle::default().css()));
+    }
+}
@@ -0,0 +1,171 @@
+use crate::utils::html::decode_entities;
+
+/// Words that are keywords or literals in most mainstream languages
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "break", "case", "catch", "class", "const", "continue", "def",
+    "default", "defer", "del", "do", "elif", "else", "enum", "except", "export", "extends", "false",
+    "False", "final", "finally", "fn", "for", "from", "func", "function", "go", "if", "impl",
+    "implements", "import", "in", "interface", "is", "lambda", "let", "loop", "match", "mod", "mut",
+    "new", "nil", "None", "not", "null", "package", "pass", "private", "protected", "pub", "public",
+    "raise", "return", "self", "Self", "static", "struct", "super", "switch", "this", "throw", "trait",
+    "true", "True", "try", "type", "typeof", "undefined", "use", "val", "var", "void", "where",
+    "while", "with", "yield",
+];
+
+/// Colors the code in `<pre><code>` blocks of sanitized HTML
+///
+/// The page may not run scripts, so highlighting happens here: keywords,
+/// strings, comments and numbers are wrapped in `tok-*` spans the reader
+/// style sheet colors. It is language-agnostic, in the spirit of a light
+/// theme for any snippet rather than a parser for each language. Text is
+/// copied as it was, character references and all.
+pub fn highlight_code_blocks(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(open_end) = find_code_block(rest) {
+        output.push_str(&rest[..open_end]);
+        rest = &rest[open_end..];
+
+        let end = rest.find("</pre>").unwrap_or(rest.len());
+        highlight_markup(&mut output, &rest[..end]);
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Where the start tag of the next `<pre>` holding a `<code>` element ends
+///
+/// The sanitizer writes tags lowercase with quoted attributes, so a plain
+/// search is enough.
+fn find_code_block(html: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(start) = html[offset..].find("<pre") {
+        let start = offset + start;
+        let name_end = start + "<pre".len();
+        if html[name_end..].starts_with(['>', ' ']) {
+            let open_end = name_end + html[name_end..].find('>')? + 1;
+            if html[open_end..].trim_start().starts_with("<code") {
+                return Some(open_end);
+            }
+        }
+        offset = start + 1;
+    }
+    None
+}
+
+/// Highlights the text between the tags of a code block, leaving the tags alone
+fn highlight_markup(output: &mut String, markup: &str) {
+    let mut rest = markup;
+    while let Some(start) = rest.find('<') {
+        highlight_text(output, &rest[..start]);
+        let end = rest[start..].find('>').map_or(rest.len(), |end| start + end + 1);
+        output.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    highlight_text(output, rest);
+}
+
+fn highlight_text(output: &mut String, text: &str) {
+    let mut rest = text;
+    // Only whitespace since the last line break, where `#` starts a comment
+    let mut line_start = true;
+    while let Some((c, char_len)) = next_char(rest) {
+        let hash_comment = c == '#' && line_start && starts_with_space(&rest[char_len..]);
+        let (class, len) = if rest.starts_with("//") || hash_comment {
+            (Some("comment"), rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            (Some("comment"), comment.find("*/").map_or(rest.len(), |end| end + 4))
+        } else if matches!(c, '"' | '\'' | '`') {
+            match string_len(rest, c, char_len) {
+                Some(len) => (Some("string"), len),
+                None => (None, char_len),
+            }
+        } else if c.is_ascii_digit() {
+            let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.')).unwrap_or(rest.len());
+            (Some("number"), len)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len());
+            (KEYWORDS.contains(&&rest[..len]).then_some("keyword"), len)
+        } else {
+            (None, char_len)
+        };
+
+        let token = &rest[..len];
+        match class {
+            Some(class) => output.push_str(&format!("<span class=\"tok-{}\">{}</span>", class, token)),
+            None => output.push_str(token),
+        }
+        line_start = class.is_none() && len == char_len && (c == '\n' || (line_start && c.is_whitespace()));
+        rest = &rest[len..];
+    }
+}
+
+/// The next character and how many bytes it takes, reading a character
+/// reference like `&quot;` as the character it stands for
+fn next_char(text: &str) -> Option<(char, usize)> {
+    let c = text.chars().next()?;
+    if c == '&' {
+        let reference = text.find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let decoded = decode_entities(&text[..=end]);
+                let mut chars = decoded.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(decoded), None) => Some((decoded, end + 1)),
+                    _ => None,
+                }
+            });
+        if reference.is_some() {
+            return reference;
+        }
+    }
+    Some((c, c.len_utf8()))
+}
+
+fn starts_with_space(text: &str) -> bool {
+    next_char(text).is_none_or(|(c, _)| c.is_whitespace())
+}
+
+/// Length of the string literal opened by `quote`, if it closes on the same line
+fn string_len(text: &str, quote: char, quote_len: usize) -> Option<usize> {
+    let mut position = quote_len;
+    while let Some((c, len)) = next_char(&text[position..]) {
+        position += len;
+        match c {
+            '\n' => return None,
+            '\\' => position += next_char(&text[position..]).map_or(0, |(_, len)| len),
+            c if c == quote => return Some(position),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlights_code_blocks_only() {
+        let html = "<p>if you return</p><pre><code>fn main() {\n    // Greet\n    let name = &quot;Panda \\&quot;RSS\\&quot;&quot;;\n    println!(&quot;{} {}&quot;, name, 42);\n}</code></pre><pre>if it rhymes</pre>";
+        assert_eq!(
+            highlight_code_blocks(html),
+            "<p>if you return</p><pre><code><span class=\"tok-keyword\">fn</span> main() {\n    \
+             <span class=\"tok-comment\">// Greet</span>\n    \
+             <span class=\"tok-keyword\">let</span> name = <span class=\"tok-string\">&quot;Panda \\&quot;RSS\\&quot;&quot;</span>;\n    \
+             println!(<span class=\"tok-string\">&quot;{} {}&quot;</span>, name, <span class=\"tok-number\">42</span>);\n}</code></pre>\
+             <pre>if it rhymes</pre>"
+        );
+    }
+
+    #[test]
+    fn test_highlight_leaves_markup_and_unclosed_quotes_alone() {
+        let html = "<pre lang=\"py\"><code># A comment\nx = 'it's' <b>don't</b> /* a\nb */ #tag 0x1F\n</code></pre>";
+        assert_eq!(
+            highlight_code_blocks(html),
+            "<pre lang=\"py\"><code><span class=\"tok-comment\"># A comment</span>\nx = <span class=\"tok-string\">'it'</span>s' <b>don't</b> \
+             <span class=\"tok-comment\">/* a\nb */</span> #tag <span class=\"tok-number\">0x1F</span>\n</code></pre>"
+        );
+    }
+}
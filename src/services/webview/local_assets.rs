@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use url::Url;
+use wry::http::{header::CONTENT_TYPE, Request, Response, StatusCode};
+
+use crate::models::cached_asset::CachedAsset;
+
+/// Custom protocol the web view loads cached images from
+pub const ASSET_SCHEME: &str = "panda-asset";
+
+/// Local URL of a cached asset
+///
+/// WebView2 only serves custom protocols under an http host, the other
+/// platforms use the scheme directly.
+pub fn asset_url(hash: &str) -> String {
+    if cfg!(windows) {
+        format!("http://{}.localhost/{}", ASSET_SCHEME, hash)
+    } else {
+        format!("{}://localhost/{}", ASSET_SCHEME, hash)
+    }
+}
+
+/// Web URL to local URL for each cached image, as the sanitizer takes them
+pub fn local_image_urls(assets: &HashMap<Url, CachedAsset>) -> HashMap<String, String> {
+    assets.iter()
+        .map(|(url, asset)| (url.to_string(), asset_url(&asset.hash)))
+        .collect()
+}
+
+/// Answers a request to [`ASSET_SCHEME`] from `assets`, keyed by hash
+pub fn asset_response(assets: &HashMap<String, CachedAsset>, request: &Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let hash = request.uri().path().trim_start_matches('/');
+    let response = match assets.get(hash) {
+        Some(asset) => Response::builder()
+            .header(CONTENT_TYPE, asset.mime_type.as_str())
+            .body(Cow::Owned(asset.data.clone())),
+        None => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Cow::Borrowed(&[][..])),
+    };
+    response.unwrap_or_else(|_| Response::new(Cow::Borrowed(&[][..])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serves_cached_assets() {
+        let asset = CachedAsset::new(b"\x89PNG\r\n\x1a\n".to_vec(), "image/png".to_string());
+        let url = Url::parse("https://example.com/photo.png").unwrap();
+        let local = local_image_urls(&HashMap::from([(url.clone(), asset.clone())]));
+        assert_eq!(local[url.as_str()], asset_url(&asset.hash));
+
+        let assets = HashMap::from([(asset.hash.clone(), asset.clone())]);
+        let request = Request::builder().uri(asset_url(&asset.hash)).body(Vec::new()).unwrap();
+        let response = asset_response(&assets, &request);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "image/png");
+        assert_eq!(response.body().as_ref(), asset.data.as_slice());
+
+        let request = Request::builder().uri(asset_url("unknown")).body(Vec::new()).unwrap();
+        assert_eq!(asset_response(&assets, &request).status(), StatusCode::NOT_FOUND);
+    }
+}
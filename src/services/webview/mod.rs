@@ -1,3 +1,13 @@
+mod article_page;
+mod highlight;
+mod local_assets;
+mod reader_style;
+mod sanitizer;
 mod webview_service;
 
+pub use article_page::{article_body, page_html, CONTENT_SECURITY_POLICY};
+pub use highlight::highlight_code_blocks;
+pub use local_assets::{asset_response, asset_url, local_image_urls, ASSET_SCHEME};
+pub use reader_style::{ReaderPalette, ReaderStyle};
+pub use sanitizer::{sanitize_html, sanitize_html_with_images};
 pub use webview_service::WebViewService;
\ No newline at end of file
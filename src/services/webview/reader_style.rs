@@ -0,0 +1,187 @@
+use crate::models::settings::{ReaderFont, ReaderSettings};
+
+/// Colors of the reader page, taken from the app's colors so both panes match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderPalette {
+    pub background: [u8; 3],
+    pub text: [u8; 3],
+    pub text_dim: [u8; 3],
+    pub accent: [u8; 3],
+    /// Background of code blocks and quotes
+    pub surface: [u8; 3],
+    /// Whether the background is dark, which picks the code highlighting colors
+    pub dark: bool,
+}
+
+/// Colors of highlighted code: keywords, strings, comments and numbers
+struct SyntaxColors {
+    keyword: &'static str,
+    string: &'static str,
+    comment: &'static str,
+    number: &'static str,
+}
+
+const DARK_SYNTAX: SyntaxColors = SyntaxColors {
+    keyword: "#c792ea",
+    string: "#c3e88d",
+    comment: "#7f8c98",
+    number: "#f78c6c",
+};
+
+const LIGHT_SYNTAX: SyntaxColors = SyntaxColors {
+    keyword: "#8839a8",
+    string: "#2f7d32",
+    comment: "#7a7468",
+    number: "#b35c00",
+};
+
+/// How the reader page looks: the theme's colors and the user's typography
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderStyle {
+    pub palette: ReaderPalette,
+    pub settings: ReaderSettings,
+}
+
+impl ReaderStyle {
+    pub fn new(palette: ReaderPalette, settings: ReaderSettings) -> Self {
+        Self { palette, settings }
+    }
+
+    /// The page's style sheet
+    ///
+    /// Sizes are clamped so a hand-edited setting cannot make the page unreadable.
+    pub fn css(&self) -> String {
+        let palette = &self.palette;
+        let settings = &self.settings;
+        let syntax = if palette.dark { DARK_SYNTAX } else { LIGHT_SYNTAX };
+        format!(
+            r#":root {{
+    color-scheme: {scheme};
+}}
+body {{
+    font-family: {font};
+    font-size: {font_size}px;
+    line-height: {line_height:.2};
+    margin: 0;
+    padding: 24px 20px;
+    color: {text};
+    background: {background};
+}}
+main {{
+    max-width: {column_width}ch;
+    margin: 0 auto;
+}}
+h1 {{
+    font-size: 1.8em;
+    line-height: 1.25;
+    margin: 0 0 0.4em;
+}}
+.meta {{
+    color: {text_dim};
+    font-size: 0.85em;
+    margin-bottom: 1.5em;
+}}
+a {{
+    color: {accent};
+}}
+img, video {{
+    max-width: 100%;
+    height: auto;
+}}
+blockquote {{
+    margin: 1em 0;
+    padding: 0.2em 1em;
+    border-left: 3px solid {text_dim};
+    background: {surface};
+}}
+pre, code {{
+    font-family: ui-monospace, "SF Mono", Menlo, Consolas, monospace;
+    font-size: 0.9em;
+}}
+pre {{
+    background: {surface};
+    padding: 12px 16px;
+    border-radius: 6px;
+    line-height: 1.45;
+    overflow-x: auto;
+}}
+.tok-keyword {{ color: {keyword}; }}
+.tok-string {{ color: {string}; }}
+.tok-comment {{ color: {comment}; font-style: italic; }}
+.tok-number {{ color: {number}; }}"#,
+            scheme = if palette.dark { "dark" } else { "light" },
+            font = font_stack(settings.font),
+            font_size = settings.font_size.clamp(12, 32),
+            line_height = settings.line_height_percent.clamp(100, 250) as f32 / 100.0,
+            column_width = settings.column_width.clamp(40, 120),
+            text = css_color(palette.text),
+            background = css_color(palette.background),
+            text_dim = css_color(palette.text_dim),
+            accent = css_color(palette.accent),
+            surface = css_color(palette.surface),
+            keyword = syntax.keyword,
+            string = syntax.string,
+            comment = syntax.comment,
+            number = syntax.number,
+        )
+    }
+}
+
+impl Default for ReaderStyle {
+    /// Dark text on white, as the reader looked before it had themes
+    fn default() -> Self {
+        Self::new(
+            ReaderPalette {
+                background: [255, 255, 255],
+                text: [51, 51, 51],
+                text_dim: [102, 102, 102],
+                accent: [0, 102, 204],
+                surface: [245, 245, 245],
+                dark: false,
+            },
+            ReaderSettings::default(),
+        )
+    }
+}
+
+/// Fonts installed on the system; the page's policy allows no downloaded fonts
+fn font_stack(font: ReaderFont) -> &'static str {
+    match font {
+        ReaderFont::SansSerif => r#"system-ui, -apple-system, "Segoe UI", Roboto, sans-serif"#,
+        ReaderFont::Serif => r#"Charter, "Iowan Old Style", Georgia, "Times New Roman", serif"#,
+        ReaderFont::Monospace => r#"ui-monospace, "SF Mono", Menlo, Consolas, monospace"#,
+    }
+}
+
+fn css_color([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_follows_palette_and_typography() {
+        let mut style = ReaderStyle::default();
+        style.palette.background = [244, 236, 216];
+        style.settings = ReaderSettings {
+            font: ReaderFont::Serif,
+            font_size: 20,
+            line_height_percent: 175,
+            column_width: 500,
+        };
+
+        let css = style.css();
+        assert!(css.contains("background: #f4ecd8;"));
+        assert!(css.contains("font-family: Charter,"));
+        assert!(css.contains("font-size: 20px;"));
+        assert!(css.contains("line-height: 1.75;"));
+        assert!(css.contains("max-width: 120ch;"));
+        assert!(css.contains("color-scheme: light;"));
+        assert!(css.contains(&format!(".tok-keyword {{ color: {}; }}", LIGHT_SYNTAX.keyword)));
+
+        style.palette.dark = true;
+        assert!(style.css().contains(&format!(".tok-keyword {{ color: {}; }}", DARK_SYNTAX.keyword)));
+    }
+}
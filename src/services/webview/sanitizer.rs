@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use url::Url;
+
+use crate::services::rss::srcset_candidates;
+use crate::utils::html::{escape_html, tokenize, Attributes, Token, RAW_TEXT_ELEMENTS, VOID_ELEMENTS};
+
+/// Attributes any allowed element may keep
+const GLOBAL_ATTRIBUTES: &[&str] = &["title", "lang", "dir"];
+
+/// Elements dropped together with everything inside them
+const DROPPED_ELEMENTS: &[&str] = &[
+    "applet", "frameset", "head", "math", "object", "select", "svg", "template",
+];
+
+/// Hosts that only serve tracking pixels and counters, never article images
+const TRACKER_HOSTS: &[&str] = &[
+    "feeds.feedburner.com",
+    "pixel.wp.com",
+    "stats.wordpress.com",
+    "www.google-analytics.com",
+    "pixel.quantserve.com",
+    "sb.scorecardresearch.com",
+    "ad.doubleclick.net",
+];
+
+/// Attributes `tag` may keep besides the global ones, or `None` when the tag itself is not allowed
+fn allowed_attributes(tag: &str) -> Option<&'static [&'static str]> {
+    let attributes: &[&str] = match tag {
+        "a" => &["href"],
+        "img" => &["src", "srcset", "alt", "width", "height"],
+        "audio" => &["src", "controls", "loop", "muted", "preload"],
+        "video" => &["src", "poster", "controls", "loop", "muted", "preload", "width", "height"],
+        "source" => &["src", "srcset", "type", "media", "sizes"],
+        "track" => &["src", "kind", "label", "srclang", "default"],
+        "blockquote" | "q" | "del" | "ins" => &["cite"],
+        "ol" => &["start", "reversed", "type"],
+        "li" => &["value"],
+        "td" | "th" => &["colspan", "rowspan", "headers", "scope"],
+        "col" | "colgroup" => &["span"],
+        "time" => &["datetime"],
+        "details" => &["open"],
+        "abbr" | "acronym" | "address" | "article" | "aside" | "b" | "bdi" | "bdo" | "big" | "br"
+        | "caption" | "center" | "cite" | "code" | "dd" | "dfn" | "div" | "dl" | "dt" | "em"
+        | "figcaption" | "figure" | "footer" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "header"
+        | "hr" | "i" | "kbd" | "mark" | "p" | "picture" | "pre" | "rp" | "rt" | "ruby" | "s"
+        | "samp" | "section" | "small" | "span" | "strike" | "strong" | "sub" | "summary" | "sup"
+        | "table" | "tbody" | "tfoot" | "thead" | "tr" | "tt" | "u" | "ul" | "var" | "wbr" => &[],
+        _ => return None,
+    };
+    Some(attributes)
+}
+
+/// Cleans feed-supplied HTML down to an allowlist of tags and attributes
+///
+/// Scripts, styles, frames, plugins and forms are removed with their content,
+/// other unknown tags are unwrapped to their text. Event handlers, inline
+/// styles and any URL that is not http(s), mailto or an inline raster image
+/// are dropped, and so are tracking pixels. Every kept attribute is written
+/// back escaped and unclosed elements are closed, so the result cannot break
+/// out of the element it is placed in.
+pub fn sanitize_html(html: &str) -> String {
+    sanitize_html_with_images(html, &HashMap::new())
+}
+
+/// [`sanitize_html`], pointing images found in `local_images` (web URL to
+/// local URL) at their local copy instead
+///
+/// The local URL replaces `src` after the checks, and any `srcset` is dropped
+/// so the browser does not pick a web candidate over the copy.
+pub fn sanitize_html_with_images(html: &str, local_images: &HashMap<String, String>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+    // The element being dropped with its content, and how deeply it is nested in itself
+    let mut dropping: Option<(String, usize)> = None;
+    // Everything after a `<plaintext>` tag is text, markup included
+    let mut plaintext = false;
+
+    for token in tokenize(html) {
+        match token {
+            Token::Text(text) if dropping.is_none() => output.push_str(&text.replace('<', "&lt;")),
+            Token::RawText(text) if dropping.is_none() && plaintext => output.push_str(&escape_html(text)),
+            // Comments, the bodies of scripts, styles and alike, and whatever is being dropped
+            Token::Text(_) | Token::RawText(_) | Token::Comment(_) => {}
+            Token::EndTag { name, .. } => match &mut dropping {
+                Some((dropped, depth)) if *dropped == name => {
+                    *depth -= 1;
+                    if *depth == 0 {
+                        dropping = None;
+                    }
+                }
+                Some(_) => {}
+                None => close_element(&mut output, &mut open, &name),
+            },
+            Token::StartTag(tag) => {
+                if RAW_TEXT_ELEMENTS.contains(&tag.name.as_str()) {
+                    continue;
+                }
+                if tag.name == "plaintext" {
+                    plaintext = true;
+                    continue;
+                }
+                match &mut dropping {
+                    Some((dropped, depth)) => {
+                        if *dropped == tag.name && !tag.self_closing {
+                            *depth += 1;
+                        }
+                    }
+                    None if DROPPED_ELEMENTS.contains(&tag.name.as_str()) => {
+                        if !tag.self_closing {
+                            dropping = Some((tag.name, 1));
+                        }
+                    }
+                    None => open_element(&mut output, &mut open, tag.name, &tag.attributes, local_images),
+                }
+            }
+        }
+    }
+
+    while let Some(name) = open.pop() {
+        output.push_str(&format!("</{}>", name));
+    }
+    output
+}
+
+fn open_element(
+    output: &mut String,
+    open: &mut Vec<String>,
+    name: String,
+    attributes: &Attributes,
+    local_images: &HashMap<String, String>,
+) {
+    let Some(allowed) = allowed_attributes(&name) else {
+        return;
+    };
+
+    let mut kept = Vec::new();
+    for (key, value) in attributes {
+        if !allowed.contains(&key.as_str()) && !GLOBAL_ATTRIBUTES.contains(&key.as_str()) {
+            continue;
+        }
+        let safe = match key.as_str() {
+            "href" => is_safe_url(value, &["http", "https", "mailto"]),
+            "src" if name == "img" => is_safe_url(value, &["http", "https"]) || is_inline_image(value),
+            "src" | "cite" | "poster" => is_safe_url(value, &["http", "https"]),
+            "srcset" => srcset_candidates(value).iter().all(|(url, _)| is_safe_url(url, &["http", "https"])),
+            _ => true,
+        };
+        if safe && !kept.iter().any(|(kept_key, _)| kept_key == key) {
+            kept.push((key.as_str(), value.as_str()));
+        }
+    }
+
+    if name == "img" && (is_tracking_pixel(&kept) || !kept.iter().any(|(key, _)| matches!(*key, "src" | "srcset"))) {
+        return;
+    }
+    if name == "img" {
+        let local = kept.iter()
+            .find(|(key, _)| *key == "src")
+            .and_then(|(_, src)| Url::parse(src.trim()).ok())
+            .and_then(|url| local_images.get(url.as_str()));
+        if let Some(local) = local {
+            kept.retain(|(key, _)| *key != "srcset");
+            if let Some(src) = kept.iter_mut().find(|(key, _)| *key == "src") {
+                src.1 = local.as_str();
+            }
+        }
+    }
+
+    output.push('<');
+    output.push_str(&name);
+    for (key, value) in &kept {
+        output.push_str(&format!(" {}=\"{}\"", key, escape_html(value)));
+    }
+    if name == "a" {
+        output.push_str(" rel=\"noopener noreferrer\"");
+    }
+    output.push('>');
+
+    if !VOID_ELEMENTS.contains(&name.as_str()) {
+        open.push(name);
+    }
+}
+
+/// Closes `name` and anything left open inside it; closing tags for elements
+/// that are not open are dropped so they cannot close the surrounding page
+fn close_element(output: &mut String, open: &mut Vec<String>, name: &str) {
+    let Some(position) = open.iter().rposition(|open_name| open_name == name) else {
+        return;
+    };
+    for name in open.drain(position..).rev() {
+        output.push_str(&format!("</{}>", name));
+    }
+}
+
+/// Whether `value` is a relative URL or uses one of `schemes`
+///
+/// Browsers ignore whitespace and control characters inside a scheme, so
+/// they are removed before looking, which catches `java&#9;script:` and alike.
+fn is_safe_url(value: &str, schemes: &[&str]) -> bool {
+    let normalized: String = value.chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match normalized.find([':', '/', '?', '#']) {
+        Some(end) if normalized[end..].starts_with(':') => schemes.contains(&&normalized[..end]),
+        _ => true,
+    }
+}
+
+/// Whether `value` is a `data:` URL holding a raster image; SVG can carry script
+fn is_inline_image(value: &str) -> bool {
+    let normalized = value.trim().to_ascii_lowercase();
+    ["data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp"]
+        .iter()
+        .any(|prefix| normalized.starts_with(prefix))
+}
+
+/// Images sized to a pixel or less, or served by a known counter, exist only to track reads
+fn is_tracking_pixel(attributes: &[(&str, &str)]) -> bool {
+    let value = |name: &str| attributes.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+    let is_tiny = |name: &str| value(name)
+        .and_then(|size| size.trim().trim_end_matches("px").parse::<f32>().ok())
+        .is_some_and(|size| size <= 1.0);
+    let from_tracker = value("src")
+        .and_then(|src| Url::parse(src.trim()).ok())
+        .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+        .is_some_and(|host| TRACKER_HOSTS.iter().any(|tracker| host == *tracker || host.ends_with(&format!(".{}", tracker))));
+    is_tiny("width") || is_tiny("height") || from_tracker
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds seen in the wild or written to get script into a reader, each with
+    /// text that must survive sanitizing and markers that must not
+    const MALICIOUS_FIXTURES: [(&str, &str, &[&str], &[&str]); 9] = [
+        ("script", include_str!("fixtures/script.html"), &["Before the script", "After the script"], &["<script", "alert", "evil.example"]),
+        ("event_handlers", include_str!("fixtures/event_handlers.html"), &["<img src=\"https://example.com/photo.jpg\" alt=\"Photo\">", "Hover me"], &["onerror", "onload", "onmouseover", "onclick", "style="]),
+        ("javascript_urls", include_str!("fixtures/javascript_urls.html"), &["<a href=\"https://example.com/safe\" rel=\"noopener noreferrer\">Safe</a>", "Encoded"], &["javascript", "vbscript", "data:text/html", "&#"]),
+        ("iframes_and_plugins", include_str!("fixtures/iframes_and_plugins.html"), &["Video below", "Caption"], &["<iframe", "<object", "<embed", "<form", "<input", "<svg", "fallback", "evil.example"]),
+        ("tracking_pixels", include_str!("fixtures/tracking_pixels.html"), &["<img src=\"https://example.com/chart.png\" alt=\"Chart\">", "Tracked post"], &["feedburner", "pixel.wp.com", "beacon.example"]),
+        ("breakout", include_str!("fixtures/breakout.html"), &["Trapped text"], &["</div>", "<meta", "<base", "<link", "<style"]),
+        ("comments_and_cdata", include_str!("fixtures/comments_and_cdata.html"), &["Visible"], &["<!--", "[CDATA[", "<script"]),
+        ("malformed", include_str!("fixtures/malformed.html"), &["1 &lt; 2", "Unclosed"], &["<script", "onerror", "<scr"]),
+        ("plaintext", include_str!("fixtures/plaintext.html"), &["Read the rest as plain text", "&lt;script&gt;alert(document.cookie)&lt;/script&gt;", "&lt;/a&gt;\n</div>"], &["<script", "<img", "<a "]),
+    ];
+
+    #[test]
+    fn test_malicious_fixtures() {
+        for (name, html, kept, removed) in MALICIOUS_FIXTURES {
+            let sanitized = sanitize_html(html);
+            for expected in kept {
+                assert!(sanitized.contains(expected), "{}: expected {:?} in {}", name, expected, sanitized);
+            }
+            for unwanted in removed {
+                assert!(
+                    !sanitized.to_ascii_lowercase().contains(&unwanted.to_ascii_lowercase()),
+                    "{}: unexpected {:?} in {}", name, unwanted, sanitized
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sanitize_keeps_article_markup() {
+        let html = r#"<h2 id="x" class="title">Heading</h2><p>Text with <a href="/relative" target="_blank">a link</a>, <em>emphasis</em> &amp; <code>code</code>.</p><ul><li>One<li>Two</ul><img src="data:image/png;base64,iVBORw0KGgo=" alt="Inline"><br/>"#;
+        assert_eq!(
+            sanitize_html(html),
+            r#"<h2>Heading</h2><p>Text with <a href="/relative" rel="noopener noreferrer">a link</a>, <em>emphasis</em> &amp; <code>code</code>.</p><ul><li>One<li>Two</li></li></ul><img src="data:image/png;base64,iVBORw0KGgo=" alt="Inline"><br>"#
+        );
+    }
+
+    #[test]
+    fn test_sanitize_points_images_at_local_copies() {
+        let local_images = HashMap::from([
+            ("https://example.com/photo.jpg".to_string(), "panda-asset://localhost/abc".to_string()),
+        ]);
+        let html = r#"<img src=" https://example.com/photo.jpg" srcset="https://example.com/photo-2x.jpg 2x" alt="Photo"><img src="https://example.com/other.jpg">"#;
+        assert_eq!(
+            sanitize_html_with_images(html, &local_images),
+            r#"<img src="panda-asset://localhost/abc" alt="Photo"><img src="https://example.com/other.jpg">"#
+        );
+    }
+}
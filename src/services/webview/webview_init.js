@@ -14,26 +14,6 @@ document.addEventListener('DOMContentLoaded', () => {
         if (e.key === 'Escape') {
             window.close();
         }
-        
-        // Toggle dark mode with Ctrl/Cmd + D
-        if ((e.ctrlKey || e.metaKey) && e.key === 'd') {
-            e.preventDefault();
-            document.documentElement.classList.toggle('dark-mode');
-        }
-    });
-
-    // Apply system theme preference
-    if (window.matchMedia('(prefers-color-scheme: dark)').matches) {
-        document.documentElement.classList.add('dark-mode');
-    }
-
-    // Listen for system theme changes
-    window.matchMedia('(prefers-color-scheme: dark)').addListener(e => {
-        if (e.matches) {
-            document.documentElement.classList.add('dark-mode');
-        } else {
-            document.documentElement.classList.remove('dark-mode');
-        }
     });
 
     // Set up image handling
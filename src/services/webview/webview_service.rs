@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use anyhow::Result;
+use url::Url;
 use wry::{WebView, WebViewBuilder};
 use tao::{
     window::{Window, WindowBuilder},
@@ -7,6 +9,13 @@ use tao::{
     event_loop::{EventLoop, ControlFlow},
 };
 
+use crate::models::article::Article;
+use crate::models::cached_asset::CachedAsset;
+use crate::services::webview::article_page::{article_body, page_html};
+use crate::services::webview::local_assets::{asset_response, local_image_urls, ASSET_SCHEME};
+use crate::services::webview::reader_style::ReaderStyle;
+use crate::services::webview::sanitizer::sanitize_html;
+
 /// Container for WebView and its associated Window
 struct WebViewData {
     webview: WebView,
@@ -18,6 +27,8 @@ struct WebViewData {
 pub struct WebViewService {
     // Store the webview data
     webview_data: Arc<Mutex<Option<WebViewData>>>,
+    // Cached images of the shown article by hash, served on the asset scheme
+    assets: Arc<Mutex<HashMap<String, CachedAsset>>>,
 }
 
 impl WebViewService {
@@ -25,46 +36,33 @@ impl WebViewService {
     pub fn new() -> Self {
         Self {
             webview_data: Arc::new(Mutex::new(None)),
+            assets: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Displays HTML content in a webview
-    pub fn show_content(&mut self, content: &str) -> Result<(), anyhow::Error> {
-        let html = format!(
-            r#"
-            <!DOCTYPE html>
-            <html>
-            <head>
-                <meta charset=\"UTF-8\">
-                <meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\">
-                <style>
-                    body {{
-                        font-family: system-ui, -apple-system, sans-serif;
-                        line-height: 1.6;
-                        padding: 20px;
-                        margin: 0;
-                        color: #333;
-                        background: #fff;
-                    }}
-                    img {{
-                        max-width: 100%;
-                        height: auto;
-                    }}
-                    pre {{
-                        background: #f5f5f5;
-                        padding: 15px;
-                        border-radius: 5px;
-                        overflow-x: auto;
-                    }}
-                </style>
-            </head>
-            <body>
-                {}
-            </body>
-            </html>
-            "#,
-            content
-        );
+    /// Displays an article, sanitized and styled by `style`, in a webview
+    ///
+    /// Images in `assets` are shown from the cache rather than the web.
+    pub fn show_article(
+        &self,
+        article: &Article,
+        assets: &HashMap<Url, CachedAsset>,
+        style: &ReaderStyle,
+    ) -> Result<(), anyhow::Error> {
+        *self.assets.lock().unwrap() = assets.values()
+            .map(|asset| (asset.hash.clone(), asset.clone()))
+            .collect();
+        self.show_body(&article_body(article, &local_image_urls(assets)), style)
+    }
+
+    /// Displays feed-supplied HTML content, sanitized, in a webview
+    pub fn show_content(&self, content: &str) -> Result<(), anyhow::Error> {
+        self.show_body(&sanitize_html(content), &ReaderStyle::default())
+    }
+
+    /// Displays an already safe page body, creating the webview on first use
+    fn show_body(&self, body: &str, style: &ReaderStyle) -> Result<(), anyhow::Error> {
+        let html = page_html(body, style);
 
         let mut webview_data = self.webview_data.lock().unwrap();
         
@@ -80,10 +78,14 @@ impl WebViewService {
                 .map_err(|e| anyhow::anyhow!("Failed to build window: {}", e))?;
                 
             // Now create the webview with the window reference
+            let assets = self.assets.clone();
             let webview = WebViewBuilder::new()
-                .with_url("about:blank")
                 .with_html(&html)
                 .with_initialization_script("document.title = 'Article Viewer';")
+                .with_custom_protocol(ASSET_SCHEME.to_string(), move |_, request| {
+                    asset_response(&assets.lock().unwrap(), &request)
+                })
+                .with_navigation_handler(open_links_externally)
                 // Pass window reference to satisfy HasWindowHandle trait
                 .build(&window)
                 .map_err(|e| anyhow::anyhow!("Failed to build WebView: {}", e))?;
@@ -101,8 +103,9 @@ impl WebViewService {
             // Instead we'll keep the event loop alive and assume the application
             // has its own event handling mechanism
         } else if let Some(data) = webview_data.as_mut() {
-            // Update content in existing webview
-            data.webview.evaluate_script(&format!("document.body.innerHTML = `{}`", html))
+            // Reload the whole page rather than swapping the body, so a changed
+            // theme or typography applies along with the content
+            data.webview.load_html(&html)
                 .map_err(|e| anyhow::anyhow!("Failed to update content: {}", e))?;
         }
 
@@ -110,7 +113,7 @@ impl WebViewService {
     }
 
     /// Hides the webview by destroying the window and webview
-    pub fn hide(&mut self) {
+    pub fn hide(&self) {
         let mut webview_data = self.webview_data.lock().unwrap();
         *webview_data = None;
     }
@@ -119,4 +122,17 @@ impl WebViewService {
     pub fn is_visible(&self) -> bool {
         self.webview_data.lock().unwrap().is_some()
     }
-}
\ No newline at end of file
+}
+
+/// Keeps the reader on the article: links the reader follows open in the
+/// default browser or mail client instead of replacing the page
+fn open_links_externally(url: String) -> bool {
+    let external = url.starts_with("http://") || url.starts_with("https://") || url.starts_with("mailto:");
+    if external {
+        if let Err(e) = open::that(&url) {
+            log::error!("Failed to open link {}: {}", url, e);
+        }
+    }
+    // Loading the page itself, from HTML or the asset scheme, stays allowed
+    !external
+}
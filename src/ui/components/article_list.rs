@@ -1,103 +1,335 @@
-use egui::{Ui, RichText};
+use egui::{Button, DragValue, Ui, RichText, ScrollArea, TextFormat};
+use egui::text::LayoutJob;
+use std::collections::HashSet;
 use std::sync::Arc;
 use anyhow::Result;
 use log::error;
 
-use crate::models::article::{Article, ArticleId, ReadStatus};
+use crate::models::article::{ArticleId, ReadStatus};
+use crate::models::article_page::{ArticleCursor, ArticleFilter, ArticleSummary};
+use crate::models::feed::FeedId;
+use crate::models::search::{highlight_segments, SearchHit, SearchQuery};
 use crate::base::repository::ArticleRepository;
+use crate::services::read_state::ReadStateService;
 use crate::services::rss::RssService;
+use crate::services::sync::SyncHandle;
+use crate::ui::components::feed_icons::FeedIcons;
 use crate::ui::styles::AppColors;
 
-pub enum ArticleSortOrder {
-    NewestFirst,
-    OldestFirst,
-    Unread,
-}
+pub use crate::models::settings::ArticleSortOrder;
+
+/// Articles loaded per page while scrolling
+const PAGE_SIZE: usize = 50;
 
 pub struct ArticleList {
     article_repository: Arc<dyn ArticleRepository>,
     rss_service: Arc<RssService>,
+    read_state_service: Arc<ReadStateService>,
+    sync_handle: Arc<SyncHandle>,
     colors: AppColors,
-    articles: Vec<Article>,
+    /// Which articles are listed
+    filter: ArticleFilter,
+    /// Name of what is listed, e.g. the feed title, used to describe bulk actions
+    title: String,
+    /// Pages loaded so far
+    articles: Vec<ArticleSummary>,
+    /// Where the next page starts, if one has been loaded before
+    next_cursor: Option<ArticleCursor>,
+    /// False once the last page has been loaded
+    has_more: bool,
     sort_order: ArticleSortOrder,
     selected_article: Option<ArticleId>,
+    /// Results of the active search, shown instead of `articles` in rank order
+    search_hits: Option<Vec<SearchHit>>,
+    search_query: Option<SearchQuery>,
+    /// Age used by "mark older than" in the toolbar
+    older_than_days: u32,
+    /// Description of read state changed from the toolbar since the owner last
+    /// asked, with the feeds whose articles changed
+    read_state_change: Option<(String, HashSet<FeedId>)>,
+    feed_icons: FeedIcons,
+}
+
+/// Toolbar buttons that need the list to act
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToolbarAction {
+    MarkAllRead,
+    MarkOlderThanRead,
+    Undo,
 }
 
 impl ArticleList {
     pub fn new(
         article_repository: Arc<dyn ArticleRepository>,
         rss_service: Arc<RssService>,
+        read_state_service: Arc<ReadStateService>,
+        sync_handle: Arc<SyncHandle>,
         colors: AppColors,
     ) -> Self {
         Self {
             article_repository,
             rss_service,
+            read_state_service,
+            sync_handle,
             colors,
+            filter: ArticleFilter::All,
+            title: "All Articles".to_string(),
             articles: Vec::new(),
+            next_cursor: None,
+            has_more: true,
             sort_order: ArticleSortOrder::NewestFirst,
             selected_article: None,
+            search_hits: None,
+            search_query: None,
+            older_than_days: 7,
+            read_state_change: None,
+            feed_icons: FeedIcons::default(),
         }
     }
 
     pub fn ui(&mut self, ui: &mut Ui) -> Result<Option<ArticleId>> {
+        if let Some(action) = self.toolbar_ui(ui) {
+            match self.run_toolbar_action(action) {
+                Ok(change) => self.read_state_change = Some(change),
+                Err(e) => {
+                    error!("Bulk read state change failed: {}", e);
+                    self.read_state_change = Some((format!("Could not update read state: {}", e), HashSet::new()));
+                }
+            }
+        }
+        ui.separator();
+
+        if self.search_hits.is_some() {
+            return Ok(self.search_results_ui(ui));
+        }
+
         let mut selected = None;
+        let mut wants_more = false;
 
-        for article in &self.articles {
-            let text = format!(
-                "{}\n{}",
-                article.title,
-                article.summary.as_deref().unwrap_or("")
-            );
+        ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+            for article in &self.articles {
+                let text = format!(
+                    "{}\n{}",
+                    article.title,
+                    article.summary.as_deref().unwrap_or("")
+                );
 
-            let mut color = self.colors.text;
-            if article.read_status == ReadStatus::Unread {
-                color = self.colors.text_highlight;
+                let mut color = self.colors.text;
+                if article.read_status == ReadStatus::Unread {
+                    color = self.colors.text_highlight;
+                }
+
+                ui.horizontal(|ui| {
+                    self.feed_icons.show(ui, &article.feed_id, "📰", &self.colors);
+                    if ui.add(
+                        egui::Button::new(RichText::new(&text).color(color))
+                            .wrap()
+                            .min_size(egui::vec2(0.0, 60.0))
+                    ).clicked() {
+                        selected = Some(article.id.clone());
+                    }
+                });
+            }
+
+            if self.has_more {
+                // Scrolling the placeholder into view loads the next page
+                let placeholder = ui.label(RichText::new("Loading…").color(self.colors.text_dim));
+                wants_more = ui.is_rect_visible(placeholder.rect);
+            } else if self.articles.is_empty() {
+                ui.label(RichText::new("No articles").color(self.colors.text_dim));
             }
+        });
+
+        if selected.is_some() {
+            self.selected_article = selected.clone();
+        }
+        if wants_more {
+            let sync_handle = self.sync_handle.clone();
+            if let Err(e) = sync_handle.block_on(self.load_next_page()) {
+                error!("Failed to load articles: {}", e);
+                // Stop retrying every frame; selecting the list again starts over
+                self.has_more = false;
+            }
+        }
+
+        Ok(selected)
+    }
+
+    fn search_results_ui(&mut self, ui: &mut Ui) -> Option<ArticleId> {
+        let mut selected = None;
+        let hits = self.search_hits.as_deref().unwrap_or_default();
+
+        if hits.is_empty() {
+            ui.label(RichText::new("No matching articles").color(self.colors.text_dim));
+        } else if self.search_query.as_ref().is_some_and(|query| hits.len() >= query.limit) {
+            ui.label(RichText::new(format!("Showing the best {} matches; refine the search to see others", hits.len()))
+                .color(self.colors.text_dim));
+        }
+
+        for hit in hits {
+            let mut job = LayoutJob::default();
+            let title_color = if hit.article.read_status == ReadStatus::Unread {
+                self.colors.text_highlight
+            } else {
+                self.colors.text
+            };
+            self.append_highlighted(&mut job, &hit.title_highlight, title_color);
+            job.append("\n", 0.0, TextFormat::default());
+            self.append_highlighted(&mut job, &hit.snippet, self.colors.text_dim);
 
             if ui.add(
-                egui::Button::new(RichText::new(&text).color(color))
+                egui::Button::new(job)
                     .wrap()
                     .min_size(egui::vec2(0.0, 60.0))
             ).clicked() {
-                selected = Some(article.id.clone());
-                self.selected_article = Some(article.id.clone());
+                selected = Some(hit.article.id.clone());
             }
         }
 
-        Ok(selected)
+        if selected.is_some() {
+            self.selected_article = selected.clone();
+        }
+        selected
+    }
+
+    /// Appends search-highlighted text, drawing matched terms in the accent color
+    fn append_highlighted(&self, job: &mut LayoutJob, highlighted: &str, color: egui::Color32) {
+        for (text, is_match) in highlight_segments(highlighted) {
+            let format = TextFormat {
+                color: if is_match { self.colors.accent } else { color },
+                ..Default::default()
+            };
+            job.append(&text, 0.0, format);
+        }
     }
 
-    pub async fn load_articles(&mut self, feed_id: Option<String>) -> Result<()> {
-        self.articles = match feed_id {
-            Some(id) => self.rss_service.get_articles_by_feed(&id.into()).await?,
-            None => self.rss_service.get_all_articles().await?,
+    fn toolbar_ui(&mut self, ui: &mut Ui) -> Option<ToolbarAction> {
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            if ui.button("✔ Mark All Read").clicked() {
+                action = Some(ToolbarAction::MarkAllRead);
+            }
+            if ui.button("Mark Older Than").clicked() {
+                action = Some(ToolbarAction::MarkOlderThanRead);
+            }
+            ui.add(DragValue::new(&mut self.older_than_days).range(1..=365).suffix(" days"));
+
+            if let Some(last) = self.read_state_service.last_action() {
+                let label = format!("↶ Undo ({} in {})", last.count(), last.label);
+                if ui.add(Button::new(RichText::new(label).color(self.colors.text_dim))).clicked() {
+                    action = Some(ToolbarAction::Undo);
+                }
+            }
+        });
+
+        action
+    }
+
+    /// Carries out a toolbar action on what is listed, returning a description
+    /// of the change and the feeds it touched
+    fn run_toolbar_action(&mut self, action: ToolbarAction) -> Result<(String, HashSet<FeedId>)> {
+        let (filter, title) = match &self.search_query {
+            Some(query) => (ArticleFilter::Search(query.clone()), format!("\"{}\"", query.text)),
+            None => (self.filter.clone(), self.title.clone()),
         };
 
-        self.sort_articles();
+        let change = match action {
+            ToolbarAction::MarkAllRead => {
+                let done = self.sync_handle.block_on(self.read_state_service.mark_read(&filter, None, &title))?;
+                (format!("Marked {} articles in {} as read", done.count(), done.label), done.feed_ids)
+            }
+            ToolbarAction::MarkOlderThanRead => {
+                let done = self.sync_handle.block_on(
+                    self.read_state_service.mark_older_than_read(&filter, self.older_than_days, &title)
+                )?;
+                (format!("Marked {} articles in {} as read", done.count(), done.label), done.feed_ids)
+            }
+            ToolbarAction::Undo => match self.sync_handle.block_on(self.read_state_service.undo())? {
+                Some(undone) => (
+                    format!("Marked {} articles in {} as unread again", undone.count(), undone.label),
+                    undone.feed_ids,
+                ),
+                None => ("Nothing to undo".to_string(), HashSet::new()),
+            },
+        };
+
+        self.refresh()?;
+        Ok(change)
+    }
+
+    /// Description of read state changed from the toolbar since the last call,
+    /// with the feeds whose unread counts changed
+    pub fn take_read_state_change(&mut self) -> Option<(String, HashSet<FeedId>)> {
+        self.read_state_change.take()
+    }
+
+    /// Replaces the list with the results of a full-text search
+    pub async fn search(&mut self, query: &SearchQuery) -> Result<()> {
+        self.search_hits = Some(self.rss_service.search(query).await?);
+        self.search_query = Some(query.clone());
         Ok(())
     }
 
+    /// Leaves search results and shows the loaded articles again
+    pub fn clear_search(&mut self) {
+        self.search_hits = None;
+        self.search_query = None;
+    }
+
+    /// Lists the articles selected by `filter`, loading pages as they are scrolled into view
+    pub fn show(&mut self, filter: ArticleFilter, title: &str) {
+        self.filter = filter;
+        self.title = title.to_string();
+        self.reset();
+    }
+
+    /// Reloads what is listed after articles changed elsewhere, e.g. a bulk mark as read
+    pub fn refresh(&mut self) -> Result<()> {
+        self.reset();
+        if let Some(query) = self.search_query.clone() {
+            self.search_hits = Some(self.sync_handle.block_on(self.rss_service.search(&query))?);
+        }
+        Ok(())
+    }
+
+    /// Replaces the favicons shown next to each article
+    pub fn set_feed_icons(&mut self, feed_icons: FeedIcons) {
+        self.feed_icons = feed_icons;
+    }
+
+    /// Switches to the colors of a newly chosen theme
+    pub fn set_colors(&mut self, colors: AppColors) {
+        self.colors = colors;
+    }
+
     pub fn set_sort_order(&mut self, order: ArticleSortOrder) {
-        self.sort_order = order;
-        self.sort_articles();
+        if self.sort_order != order {
+            self.sort_order = order;
+            self.reset();
+        }
     }
 
-    fn sort_articles(&mut self) {
-        match self.sort_order {
-            ArticleSortOrder::NewestFirst => {
-                self.articles.sort_by(|a, b| b.published_at.cmp(&a.published_at));
-            }
-            ArticleSortOrder::OldestFirst => {
-                self.articles.sort_by(|a, b| a.published_at.cmp(&b.published_at));
-            }
-            ArticleSortOrder::Unread => {
-                self.articles.sort_by(|a, b| {
-                    (b.read_status == ReadStatus::Unread)
-                        .cmp(&(a.read_status == ReadStatus::Unread))
-                        .then_with(|| b.published_at.cmp(&a.published_at))
-                });
-            }
+    /// Drops the loaded pages so the list starts again from the top
+    pub fn reset(&mut self) {
+        self.articles.clear();
+        self.next_cursor = None;
+        self.has_more = true;
+    }
+
+    /// Appends the next page of articles, if any are left
+    pub async fn load_next_page(&mut self) -> Result<()> {
+        if !self.has_more {
+            return Ok(());
         }
+
+        let page = self.rss_service
+            .get_article_page(&self.filter, self.sort_order, self.next_cursor.as_ref(), PAGE_SIZE)
+            .await?;
+        self.articles.extend(page.articles);
+        self.has_more = page.next_cursor.is_some();
+        self.next_cursor = page.next_cursor;
+        Ok(())
     }
 
     pub fn get_selected_article(&self) -> Option<ArticleId> {
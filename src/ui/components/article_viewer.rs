@@ -1,12 +1,22 @@
-use egui::{RichText, Context};
+use egui::{Button, RichText, Context, ProgressBar};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use std::sync::Arc;
 use anyhow::Result;
 use url::Url;
 use log::error;
 
-use crate::models::article::Article;
+use crate::models::article::{Article, ReadStatus};
+use crate::models::cached_asset::CachedAsset;
+use crate::models::enclosure::{DownloadStatus, Enclosure, EnclosureId};
+use crate::models::feed::FeedId;
+use crate::models::settings::ReaderSettings;
 use crate::base::repository::ArticleRepository;
-use crate::services::webview::WebViewService;
+use crate::services::assets::AssetCacheService;
+use crate::services::downloads::DownloadService;
+use crate::services::extraction::ExtractionService;
+use crate::services::sync::SyncHandle;
+use crate::services::webview::{ReaderStyle, WebViewService};
 use crate::services::rss::RssService;
 use crate::ui::styles::AppColors;
 
@@ -14,8 +24,44 @@ pub struct ArticleViewer {
     article_repository: Arc<dyn ArticleRepository>,
     webview_service: Arc<WebViewService>,
     rss_service: Arc<RssService>,
+    download_service: Arc<DownloadService>,
+    asset_cache_service: Arc<AssetCacheService>,
+    extraction_service: Arc<ExtractionService>,
+    sync_handle: Arc<SyncHandle>,
     colors: AppColors,
+    /// Typography of the article page
+    reader: ReaderSettings,
     current_article: Option<Article>,
+    enclosures: Vec<Enclosure>,
+    /// Cached copies of the current article's images
+    assets: HashMap<Url, CachedAsset>,
+    /// Downloads started from this view that have not finished yet, by start time
+    downloading: HashMap<EnclosureId, Instant>,
+    /// When full text was requested for the current article, until it is in
+    extracting_since: Option<Instant>,
+    /// Shown when the last full text request came back empty
+    extraction_failed: bool,
+    /// Set when the reader asked to go back to the list, until the owner takes it
+    closed: bool,
+    /// Description of read state changed from the reader since the owner last
+    /// asked, with the feeds whose articles changed
+    read_state_change: Option<(String, HashSet<FeedId>)>,
+    /// Whether the web view shows something other than the current article,
+    /// its images and the current theme
+    page_stale: bool,
+}
+
+/// How long a requested download may take to show up in the download service
+const DOWNLOAD_START_GRACE: Duration = Duration::from_secs(1);
+
+/// How long a full text request may take to show up in the extraction service
+const EXTRACTION_START_GRACE: Duration = Duration::from_secs(1);
+
+/// What a click in the enclosure list asked for
+enum EnclosureAction {
+    Download(Enclosure),
+    Open(Enclosure),
+    Delete(Enclosure),
 }
 
 impl ArticleViewer {
@@ -23,18 +69,43 @@ impl ArticleViewer {
         article_repository: Arc<dyn ArticleRepository>,
         webview_service: Arc<WebViewService>,
         rss_service: Arc<RssService>,
+        download_service: Arc<DownloadService>,
+        asset_cache_service: Arc<AssetCacheService>,
+        extraction_service: Arc<ExtractionService>,
+        sync_handle: Arc<SyncHandle>,
         colors: AppColors,
+        reader: ReaderSettings,
     ) -> Self {
         Self {
             article_repository,
             webview_service,
             rss_service,
+            download_service,
+            asset_cache_service,
+            extraction_service,
+            sync_handle,
             colors,
+            reader,
             current_article: None,
+            enclosures: Vec::new(),
+            assets: HashMap::new(),
+            downloading: HashMap::new(),
+            extracting_since: None,
+            extraction_failed: false,
+            closed: false,
+            read_state_change: None,
+            page_stale: false,
         }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) -> Result<()> {
+        self.poll_downloads(ui.ctx());
+        self.poll_extraction(ui.ctx());
+
+        let mut enclosure_action = None;
+        let mut fetch_full_text = false;
+        let mut mark_read = false;
+        let mut closed = false;
         if let Some(article) = &self.current_article {
             ui.vertical(|ui| {
                 // Title
@@ -43,61 +114,36 @@ impl ArticleViewer {
 
                 // Actions
                 ui.horizontal(|ui| {
+                    closed = ui.button("Back to List").clicked();
+
                     if ui.button("Open in Browser").clicked() {
                         if let Err(e) = open::that(&article.url.to_string()) {
                             error!("Failed to open article URL: {}", e);
                         }
                     }
 
-                    if ui.button("Mark as Read").clicked() {
-                        if let Some(mut article) = self.current_article.clone() {
-                            article.mark_as_read();
-                            
-                            // Use the RssService instead of directly calling the repository
-                            // as the service handles awaiting the async operations properly
-                            tokio::spawn({
-                                let article = article.clone();
-                                let rss_service = self.rss_service.clone();
-                                async move {
-                                    if let Err(e) = rss_service.update_article(&article).await {
-                                        error!("Failed to mark article as read: {}", e);
-                                    }
-                                }
-                            });
+                    mark_read = ui.add_enabled(article.read_status == ReadStatus::Unread, Button::new("Mark as Read"))
+                        .clicked();
+
+                    if self.extracting_since.is_some() {
+                        ui.spinner();
+                        ui.label(RichText::new("Fetching full text…").color(self.colors.text_dim));
+                    } else {
+                        let label = if article.full_content.is_some() { "Refetch Full Text" } else { "Fetch Full Text" };
+                        fetch_full_text = ui.button(label)
+                            .on_hover_text("Load the article from its web page")
+                            .clicked();
+                        if self.extraction_failed {
+                            ui.label(RichText::new("No article text found on the page").color(self.colors.error));
                         }
                     }
                 });
                 ui.add_space(16.0);
 
-                // Content
-                if let Some(content) = &article.content {
-                    // Clone the Arc but get a mutable reference to the inner WebViewService through the Mutex
-                    let webview_service = self.webview_service.clone();
-                    // Create a mutable reference to the WebViewService through a new variable
-                    let mut webview = WebViewService::new(); // Create a new instance
-                    
-                    // Copy settings from the shared instance if needed
-                    // (assuming WebViewService can be used this way)
-                    
-                    if let Err(e) = webview.show_content(content) {
-                        error!("Failed to show article content: {}", e);
-                    }
-                } else if let Some(summary) = &article.summary {
-                    // Same approach for summary
-                    let webview_service = self.webview_service.clone();
-                    let mut webview = WebViewService::new(); 
-                    
-                    if let Err(e) = webview.show_content(summary) {
-                        error!("Failed to show article summary: {}", e);
-                    }
-                } else {
-                    // Same approach for empty content
-                    let webview_service = self.webview_service.clone();
-                    let mut webview = WebViewService::new();
-                    
-                    if let Err(e) = webview.show_content("No content available.") {
-                        error!("Failed to show empty content message: {}", e);
-                    }
+                // Attachments
+                if !self.enclosures.is_empty() {
+                    enclosure_action = self.enclosures_ui(ui);
+                    ui.add_space(16.0);
                 }
             });
         } else {
@@ -106,14 +152,269 @@ impl ArticleViewer {
             });
         }
 
+        if let Some(action) = enclosure_action {
+            self.handle_enclosure_action(action);
+        }
+        if mark_read {
+            self.mark_read();
+        }
+        if fetch_full_text {
+            if let Some(article) = self.current_article.clone() {
+                self.extracting_since = Some(Instant::now());
+                self.extraction_failed = false;
+                self.sync_handle.extract_full_text(article);
+            }
+        }
+        self.closed |= closed;
+        self.show_page();
+
         Ok(())
     }
 
+    /// Loads the article into the web view when it changed since it was last shown
+    ///
+    /// Content, summary or a placeholder, sanitized for the web view with
+    /// cached images shown from the cache, in the app's theme.
+    fn show_page(&mut self) {
+        if !self.page_stale {
+            return;
+        }
+        self.page_stale = false;
+        if let Some(article) = &self.current_article {
+            let style = ReaderStyle::new(self.colors.reader_palette(), self.reader);
+            if let Err(e) = self.webview_service.show_article(article, &self.assets, &style) {
+                error!("Failed to show article content: {}", e);
+            }
+        }
+    }
+
+    fn enclosures_ui(&self, ui: &mut egui::Ui) -> Option<EnclosureAction> {
+        let mut action = None;
+        ui.label(RichText::new("Attachments").strong().color(self.colors.text_highlight));
+        for enclosure in &self.enclosures {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(enclosure.display_name()).color(self.colors.text));
+                let details = enclosure_details(enclosure);
+                if !details.is_empty() {
+                    ui.label(RichText::new(details).small().color(self.colors.text_dim));
+                }
+
+                if let Some(progress) = self.download_service.progress(&enclosure.id) {
+                    let bar = match progress.fraction() {
+                        Some(fraction) => ProgressBar::new(fraction).show_percentage(),
+                        None => ProgressBar::new(0.0).animate(true).text(format_size(progress.received)),
+                    };
+                    ui.add(bar.desired_width(160.0));
+                    return;
+                }
+
+                match enclosure.download_status {
+                    DownloadStatus::Downloaded => {
+                        if ui.button("Open").clicked() {
+                            action = Some(EnclosureAction::Open(enclosure.clone()));
+                        }
+                        if ui.button("Delete Download").clicked() {
+                            action = Some(EnclosureAction::Delete(enclosure.clone()));
+                        }
+                    }
+                    DownloadStatus::NotDownloaded | DownloadStatus::Failed => {
+                        let label = if enclosure.download_status == DownloadStatus::Failed {
+                            "Retry Download"
+                        } else {
+                            "Download"
+                        };
+                        if ui.button(label).clicked() {
+                            action = Some(EnclosureAction::Download(enclosure.clone()));
+                        }
+                        let open_label = if enclosure.is_audio() || enclosure.is_video() { "Stream" } else { "Open Link" };
+                        if ui.button(open_label).on_hover_text(enclosure.url.as_str()).clicked() {
+                            if let Err(e) = open::that(enclosure.url.as_str()) {
+                                error!("Failed to open enclosure URL: {}", e);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+        action
+    }
+
+    fn handle_enclosure_action(&mut self, action: EnclosureAction) {
+        match action {
+            EnclosureAction::Download(enclosure) => {
+                self.downloading.insert(enclosure.id.clone(), Instant::now());
+                self.sync_handle.download(enclosure);
+            }
+            EnclosureAction::Open(enclosure) => {
+                if let Some(path) = &enclosure.local_path {
+                    if let Err(e) = open::that(path) {
+                        error!("Failed to open downloaded file: {}", e);
+                    }
+                }
+            }
+            EnclosureAction::Delete(enclosure) => {
+                let result = self.sync_handle.block_on(self.download_service.delete_download(&enclosure));
+                if let Err(e) = result {
+                    error!("Failed to delete download: {}", e);
+                }
+                self.load_enclosures();
+            }
+        }
+    }
+
+    /// Keeps repainting while downloads run and picks up their outcome once they end
+    fn poll_downloads(&mut self, ctx: &Context) {
+        if self.downloading.is_empty() {
+            return;
+        }
+        let download_service = self.download_service.clone();
+        let before = self.downloading.len();
+        // A download reports no progress until its task has started
+        self.downloading.retain(|id, started| {
+            download_service.progress(id).is_some() || started.elapsed() < DOWNLOAD_START_GRACE
+        });
+        if self.downloading.len() != before {
+            self.load_enclosures();
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    /// Keeps repainting while the full text is fetched and shows it once it is in
+    fn poll_extraction(&mut self, ctx: &Context) {
+        let (Some(started), Some(article)) = (self.extracting_since, &self.current_article) else {
+            return;
+        };
+        // Extraction reports nothing until its task has started
+        if self.extraction_service.is_extracting(&article.id) || started.elapsed() < EXTRACTION_START_GRACE {
+            ctx.request_repaint_after(Duration::from_millis(250));
+            return;
+        }
+
+        self.extracting_since = None;
+        let result = self.sync_handle.block_on(self.article_repository.get_article(&article.id));
+        match result {
+            Ok(Some(updated)) => {
+                // Storing the full text touches the article; nothing changed means nothing was found
+                self.extraction_failed = updated.updated_at == article.updated_at;
+                self.current_article = Some(updated);
+                self.load_assets();
+            }
+            Ok(None) => {}
+            Err(e) => error!("Failed to reload article: {}", e),
+        }
+    }
+
+    fn load_enclosures(&mut self) {
+        self.enclosures = match &self.current_article {
+            Some(article) => self.sync_handle.block_on(self.rss_service.get_enclosures(&article.id))
+                .unwrap_or_else(|e| {
+                    error!("Failed to load attachments: {}", e);
+                    Vec::new()
+                }),
+            None => Vec::new(),
+        };
+    }
+
+    fn load_assets(&mut self) {
+        self.assets = match &self.current_article {
+            Some(article) => self.sync_handle.block_on(self.asset_cache_service.assets_for_article(article))
+                .unwrap_or_else(|e| {
+                    error!("Failed to load cached images: {}", e);
+                    HashMap::new()
+                }),
+            None => HashMap::new(),
+        };
+        self.page_stale = true;
+    }
+
+    /// Switches to the colors of a newly chosen theme
+    pub fn set_colors(&mut self, colors: AppColors) {
+        self.colors = colors;
+        self.page_stale = true;
+    }
+
+    pub fn set_reader_settings(&mut self, reader: ReaderSettings) {
+        self.reader = reader;
+        self.page_stale = true;
+    }
+
     pub fn set_article(&mut self, article: Article) {
         self.current_article = Some(article);
+        self.extracting_since = None;
+        self.extraction_failed = false;
+        self.load_enclosures();
+        self.load_assets();
+    }
+
+    /// Marks the current article as read, for the owner to pick up through
+    /// [`take_read_state_change`](Self::take_read_state_change)
+    fn mark_read(&mut self) {
+        let Some(mut article) = self.current_article.clone() else {
+            return;
+        };
+        article.mark_as_read();
+        self.read_state_change = Some(match self.sync_handle.block_on(self.rss_service.update_article(&article)) {
+            Ok(()) => {
+                let change = (format!("Marked \"{}\" as read", article.title), HashSet::from([article.feed_id.clone()]));
+                self.current_article = Some(article);
+                change
+            }
+            Err(e) => {
+                error!("Failed to mark article as read: {}", e);
+                (format!("Could not update read state: {}", e), HashSet::new())
+            }
+        });
+    }
+
+    /// Description of read state changed from the reader since the last call,
+    /// with the feeds whose unread counts changed
+    pub fn take_read_state_change(&mut self) -> Option<(String, HashSet<FeedId>)> {
+        self.read_state_change.take()
+    }
+
+    /// Whether the reader asked to go back to the list since the last call
+    pub fn take_closed(&mut self) -> bool {
+        std::mem::take(&mut self.closed)
     }
 
     pub fn clear_article(&mut self) {
         self.current_article = None;
+        self.page_stale = false;
+        self.webview_service.hide();
+        self.extracting_since = None;
+        self.extraction_failed = false;
+        self.enclosures.clear();
+        self.assets.clear();
+    }
+}
+
+/// Type, length and size of an enclosure, as far as the feed told us
+fn enclosure_details(enclosure: &Enclosure) -> String {
+    let mut details = Vec::new();
+    if let Some(mime_type) = &enclosure.mime_type {
+        details.push(mime_type.clone());
+    }
+    if let Some(secs) = enclosure.duration_secs {
+        details.push(format_duration(secs));
+    }
+    if let Some(length) = enclosure.length.filter(|&length| length > 0) {
+        details.push(format_size(length));
+    }
+    details.join(" · ")
+}
+
+fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, minutes, seconds) => format!("{}:{:02}", minutes, seconds),
+        (hours, minutes, seconds) => format!("{}:{:02}:{:02}", hours, minutes, seconds),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: u64 = 1024 * 1024;
+    if bytes >= MB {
+        format!("{:.1} MB", bytes as f64 / MB as f64)
+    } else {
+        format!("{} KB", bytes.div_ceil(1024))
     }
 }
\ No newline at end of file
@@ -15,6 +15,8 @@ pub struct CategoryManager {
     name_input: String,
     description_input: String,
     error_message: Option<String>,
+    /// Set when a category was added, until the owner takes it
+    categories_changed: bool,
 }
 
 impl CategoryManager {
@@ -27,8 +29,14 @@ impl CategoryManager {
             name_input: String::new(),
             description_input: String::new(),
             error_message: None,
+            categories_changed: false,
         }
     }
+
+    /// Switches to the colors of a newly chosen theme
+    pub fn set_colors(&mut self, colors: AppColors) {
+        self.colors = colors;
+    }
     
     /// Shows the category manager dialog
     pub fn show(&mut self, ui: &mut Ui) -> Result<()> {
@@ -58,6 +66,7 @@ impl CategoryManager {
                                     error!("Failed to add category: {}", e);
                                     self.error_message = Some(e.to_string());
                                 } else {
+                                    self.categories_changed = true;
                                     self.visible = false;
                                     self.name_input.clear();
                                     self.description_input.clear();
@@ -78,6 +87,11 @@ impl CategoryManager {
         Ok(())
     }
     
+    /// Whether categories were added since the last call
+    pub fn take_categories_changed(&mut self) -> bool {
+        std::mem::take(&mut self.categories_changed)
+    }
+
     /// Adds a new category
     fn add_category(&self) -> Result<()> {
         if self.name_input.is_empty() {
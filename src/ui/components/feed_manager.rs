@@ -1,4 +1,4 @@
-use egui::{Ui, Window, TextEdit, ComboBox, Button, RichText, Color32, ScrollArea, Checkbox};
+use egui::{Ui, Window, TextEdit, ComboBox, Button, RichText, Color32, ScrollArea, Checkbox, DragValue};
 use log::{info, error};
 use anyhow::Result;
 use chrono::{DateTime, Duration, Utc};
@@ -6,12 +6,15 @@ use url::Url;
 use std::sync::Arc;
 use std::collections::HashSet;
 
-use crate::models::feed::{Feed, FeedId, FeedStatus};
+use crate::models::feed::{AutoDownloadPolicy, Feed, FeedId, FeedStatus};
 use crate::models::category::{Category, CategoryId};
-use crate::services::rss::RssService;
+use crate::services::rss::{FeedCandidate, RssService};
 use crate::ui::styles::{AppColors, DEFAULT_PADDING};
 use crate::base::repository::FeedRepository;
 
+/// Limit offered when the user first opts a feed out of the retention age
+const DEFAULT_KEEP_LAST_ARTICLES: u32 = 100;
+
 /// Feed management dialog component
 pub struct FeedManager {
     pub visible: bool,
@@ -21,6 +24,12 @@ pub struct FeedManager {
     pub selected_category: Option<CategoryId>,
     pub selected_categories: HashSet<CategoryId>, // Add support for multiple categories
     pub categories: Vec<Category>,
+    pub auto_download: AutoDownloadPolicy,
+    pub cache_images: bool,
+    pub fetch_full_text: bool,
+    /// Keep only this many of the newest articles instead of following the
+    /// global retention age
+    pub keep_last_articles: Option<u32>,
     pub feeds: Vec<Feed>,
     pub colors: AppColors,
     pub rss_service: Arc<RssService>,
@@ -28,8 +37,11 @@ pub struct FeedManager {
     error_message: Option<String>,
     show_preview: bool,
     is_fetching: bool,
-    feed_preview: Option<Feed>,
+    /// Feeds found at the entered address, for the user to pick from
+    candidates: Vec<FeedCandidate>,
     is_saving: bool,
+    /// Set when a feed was added or removed, until the owner takes it
+    feeds_changed: bool,
 }
 
 impl FeedManager {
@@ -43,6 +55,10 @@ impl FeedManager {
             selected_category: None,
             selected_categories: HashSet::new(),
             categories: Vec::new(),
+            auto_download: AutoDownloadPolicy::Off,
+            cache_images: false,
+            fetch_full_text: false,
+            keep_last_articles: None,
             feeds: Vec::new(),
             colors,
             rss_service,
@@ -50,8 +66,9 @@ impl FeedManager {
             error_message: None,
             show_preview: false,
             is_fetching: false,
-            feed_preview: None,
+            candidates: Vec::new(),
             is_saving: false,
+            feeds_changed: false,
         }
     }
     
@@ -87,10 +104,10 @@ impl FeedManager {
     
     /// Renders the URL input section
     fn render_url_input_section(&mut self, ui: &mut Ui) {
-        ui.label(RichText::new("Enter Feed URL").color(self.colors.text));
+        ui.label(RichText::new("Enter a feed or website URL").color(self.colors.text));
         ui.horizontal(|ui| {
             ui.add(TextEdit::singleline(&mut self.url_input)
-                .hint_text("https://example.com/feed.xml")
+                .hint_text("https://example.com/")
                 .desired_width(300.0));
             
             // Fetch button to get feed info
@@ -115,6 +132,8 @@ impl FeedManager {
         ui.add_space(10.0);
         
         ui.label(RichText::new("Feed Information").color(self.colors.text).size(16.0));
+
+        self.render_candidates(ui);
         
         // Feed title
         ui.horizontal(|ui| {
@@ -125,6 +144,12 @@ impl FeedManager {
         
         // Category selection
         self.render_category_selection(ui);
+
+        self.render_auto_download_selection(ui);
+        ui.checkbox(&mut self.cache_images, "Cache images for offline reading");
+        ui.checkbox(&mut self.fetch_full_text, "Always fetch full text")
+            .on_hover_text("Load each new article from its web page, for feeds that only publish summaries");
+        self.render_retention_selection(ui);
         
         ui.add_space(10.0);
         ui.separator();
@@ -134,6 +159,32 @@ impl FeedManager {
         self.render_action_buttons(ui);
     }
     
+    /// Lets the user pick one of several feeds found for a site
+    fn render_candidates(&mut self, ui: &mut Ui) {
+        if self.candidates.len() < 2 {
+            return;
+        }
+
+        ui.label(RichText::new(format!("{} feeds found:", self.candidates.len())).color(self.colors.text_dim));
+        let mut picked = None;
+        for candidate in &self.candidates {
+            let selected = self.url == candidate.url.as_str();
+            let label = format!("{} ({})", candidate.title, candidate.format.to_string());
+            if ui.radio(selected, label).on_hover_text(candidate.url.as_str()).clicked() {
+                picked = Some(candidate.clone());
+            }
+        }
+        if let Some(candidate) = picked {
+            self.select_candidate(&candidate);
+        }
+        ui.add_space(DEFAULT_PADDING);
+    }
+
+    fn select_candidate(&mut self, candidate: &FeedCandidate) {
+        self.url = candidate.url.to_string();
+        self.title = candidate.title.clone();
+    }
+
     /// Renders the category selection UI
     fn render_category_selection(&mut self, ui: &mut Ui) {
         ui.label("Categories:");
@@ -151,6 +202,40 @@ impl FeedManager {
         });
     }
     
+    /// Renders the choice of which attachments to download after each sync
+    fn render_auto_download_selection(&mut self, ui: &mut Ui) {
+        let policies = [
+            AutoDownloadPolicy::Off,
+            AutoDownloadPolicy::Latest(1),
+            AutoDownloadPolicy::Latest(3),
+            AutoDownloadPolicy::Latest(5),
+            AutoDownloadPolicy::All,
+        ];
+        ui.horizontal(|ui| {
+            ui.label("Auto-download:");
+            ComboBox::from_id_salt("feed_auto_download")
+                .selected_text(auto_download_label(self.auto_download))
+                .show_ui(ui, |ui| {
+                    for policy in policies {
+                        ui.selectable_value(&mut self.auto_download, policy, auto_download_label(policy));
+                    }
+                });
+        });
+    }
+
+    /// Renders the per-feed limit that replaces the global retention age
+    fn render_retention_selection(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            let mut keep_last = self.keep_last_articles.is_some();
+            if ui.checkbox(&mut keep_last, "Keep only the newest").changed() {
+                self.keep_last_articles = keep_last.then_some(DEFAULT_KEEP_LAST_ARTICLES);
+            }
+            if let Some(count) = &mut self.keep_last_articles {
+                ui.add(DragValue::new(count).range(1..=10_000).suffix(" articles"));
+            }
+        });
+    }
+
     /// Renders the action buttons (Save/Cancel)
     fn render_action_buttons(&mut self, ui: &mut Ui) {
         let save_clicked = ui.button("Save").clicked();
@@ -170,7 +255,7 @@ impl FeedManager {
         self.url = self.url_input.clone();
         self.is_fetching = true;
         self.show_preview = false;
-        self.feed_preview = None;
+        self.candidates.clear();
         self.error_message = None;
     }
     
@@ -180,8 +265,8 @@ impl FeedManager {
             // Use a runtime to execute the async operation
             let rt = tokio::runtime::Runtime::new().unwrap();
             match rt.block_on(self.fetch_feed_info()) {
-                Ok(feed) => {
-                    self.handle_fetch_success(feed);
+                Ok(candidates) => {
+                    self.handle_fetch_success(candidates);
                 },
                 Err(e) => {
                     self.handle_fetch_error(e);
@@ -193,9 +278,11 @@ impl FeedManager {
     }
     
     /// Handles a successful fetch operation
-    fn handle_fetch_success(&mut self, feed: Feed) {
-        self.title = feed.title.clone();
-        self.feed_preview = Some(feed);
+    fn handle_fetch_success(&mut self, candidates: Vec<FeedCandidate>) {
+        if let Some(first) = candidates.first() {
+            self.select_candidate(first);
+        }
+        self.candidates = candidates;
         self.show_preview = true;
         self.error_message = None;
     }
@@ -231,6 +318,7 @@ impl FeedManager {
     
     /// Handles a successful save operation
     fn handle_save_success(&mut self) {
+        self.feeds_changed = true;
         self.reset_form();
     }
     
@@ -251,7 +339,12 @@ impl FeedManager {
         self.url_input.clear();
         self.error_message = None;
         self.show_preview = false;
+        self.candidates.clear();
         self.selected_categories.clear();
+        self.auto_download = AutoDownloadPolicy::Off;
+        self.cache_images = false;
+        self.fetch_full_text = false;
+        self.keep_last_articles = None;
     }
     
     /// Opens the dialog in add mode
@@ -262,6 +355,10 @@ impl FeedManager {
         self.description.clear();
         self.selected_category = None;
         self.selected_categories.clear();
+        self.auto_download = AutoDownloadPolicy::Off;
+        self.cache_images = false;
+        self.fetch_full_text = false;
+        self.keep_last_articles = None;
     }
     
     /// Opens the dialog in edit mode
@@ -269,9 +366,12 @@ impl FeedManager {
         self.visible = true;
         self.url = feed.url.to_string();
         self.title = feed.title.clone();
-        // Feed doesn't have a description field, so we'll just use an empty string
-        self.description = String::new();
+        self.description = feed.description.clone().unwrap_or_default();
         self.selected_category = feed.category_id.clone();
+        self.auto_download = feed.auto_download;
+        self.cache_images = feed.cache_images;
+        self.fetch_full_text = feed.fetch_full_text;
+        self.keep_last_articles = feed.keep_last_articles;
     }
     
     /// Closes the dialog
@@ -284,27 +384,27 @@ impl FeedManager {
         self.selected_categories.clear();
     }
     
+    /// Whether feeds were added or removed since the last call
+    pub fn take_feeds_changed(&mut self) -> bool {
+        std::mem::take(&mut self.feeds_changed)
+    }
+
     /// Returns whether the dialog is currently open
     pub fn is_open(&self) -> bool {
         self.visible
     }
     
-    /// Fetches feed information from the URL
-    async fn fetch_feed_info(&mut self) -> Result<Feed> {
+    /// Finds the feeds at the entered URL, which may be a feed or any page of a site
+    async fn fetch_feed_info(&mut self) -> Result<Vec<FeedCandidate>> {
         if self.url_input.is_empty() {
             return Err(anyhow::anyhow!("URL cannot be empty"));
         }
 
-        let url = Url::parse(&self.url_input)?;
-        // Pass the URL as a string since fetch_feed expects &str
-        let feed = self.rss_service.fetch_feed(url.as_str()).await?;
-        
-        // self.title = feed.title;
-        // Feed doesn't have a description field
+        let url = Url::parse(self.url_input.trim())?;
+        let candidates = self.rss_service.discover_feeds(url.as_str()).await?;
         self.description = String::new();
-        self.url = url.to_string();
         
-        Ok(feed)
+        Ok(candidates)
     }
     
     /// Saves the current feed
@@ -320,6 +420,17 @@ impl FeedManager {
             // No categories selected, save with default settings
             let feed = Feed::new(self.title.clone(), url.clone());
             self.rss_service.add_feed(&feed.url.to_string()).await?;
+            if self.auto_download != AutoDownloadPolicy::Off || self.cache_images || self.fetch_full_text
+                || self.keep_last_articles.is_some()
+            {
+                if let Some(mut saved_feed) = self.rss_service.get_feed_by_url(&feed.url.to_string()).await? {
+                    saved_feed.auto_download = self.auto_download;
+                    saved_feed.cache_images = self.cache_images;
+                    saved_feed.fetch_full_text = self.fetch_full_text;
+                    saved_feed.keep_last_articles = self.keep_last_articles;
+                    self.rss_service.update_feed(&saved_feed).await?;
+                }
+            }
         } else {
             // Process each selected category
             for category_id in &self.selected_categories {
@@ -339,6 +450,10 @@ impl FeedManager {
                             if let Ok(Some(saved_feed)) = self.rss_service.get_feed_by_url(&feed.url.to_string()).await {
                                 let mut updated_feed = saved_feed.clone();
                                 updated_feed.category_id = Some(category_id.clone());
+                                updated_feed.auto_download = self.auto_download;
+                                updated_feed.cache_images = self.cache_images;
+                                updated_feed.fetch_full_text = self.fetch_full_text;
+                                updated_feed.keep_last_articles = self.keep_last_articles;
                                 self.rss_service.update_feed(&updated_feed).await?;
                             }
                         },
@@ -371,6 +486,7 @@ impl FeedManager {
             return Err(e);
         }
         self.feeds.retain(|f| f.id != *feed_id);
+        self.feeds_changed = true;
         Ok(())
     }
 
@@ -379,8 +495,11 @@ impl FeedManager {
             return Err(anyhow::anyhow!("URL cannot be empty"));
         }
 
-        // First fetch feed info to validate URL and get feed details
-        self.fetch_feed_info().await?;
+        // First find the feed to validate URL and get feed details
+        let candidates = self.fetch_feed_info().await?;
+        if let Some(first) = candidates.first() {
+            self.select_candidate(first);
+        }
         
         // Then save the feed
         self.save_feed().await?;
@@ -401,4 +520,13 @@ impl FeedManager {
         self.refresh().await?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+fn auto_download_label(policy: AutoDownloadPolicy) -> String {
+    match policy {
+        AutoDownloadPolicy::Off => "Off".to_string(),
+        AutoDownloadPolicy::Latest(1) => "Latest episode".to_string(),
+        AutoDownloadPolicy::Latest(count) => format!("Latest {} episodes", count),
+        AutoDownloadPolicy::All => "All episodes".to_string(),
+    }
+}
@@ -1,6 +1,7 @@
 mod article_list;
 mod article_viewer;
 mod category_manager;
+mod feed_icons;
 mod feed_manager;
 mod settings;
 pub mod sidebar;
@@ -8,6 +9,7 @@ pub mod sidebar;
 pub use article_list::*;
 pub use article_viewer::*;
 pub use category_manager::*;
+pub use feed_icons::*;
 pub use feed_manager::*;
 pub use settings::*;
 pub use sidebar::*;
\ No newline at end of file
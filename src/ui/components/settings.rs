@@ -1,29 +1,55 @@
-use egui::{Ui, Window, RichText, Context, DragValue, ViewportCommand};
+use egui::{Ui, Window, RichText, DragValue, TextEdit, ComboBox};
 use std::sync::Arc;
 use anyhow::Result;
+use log::{error, info};
 
-use crate::services::sync::SyncService;
+use crate::base::repository::SettingsRepository;
+use crate::models::settings::{ArticleSortOrder, ReaderFont, Settings, Theme};
+use crate::services::retention::RetentionService;
+use crate::services::sync::{SyncHandle, SyncService};
 use crate::ui::styles::AppColors;
 
+const MIB: u64 = 1024 * 1024;
+
 pub struct SettingsDialog {
+    settings_repository: Arc<dyn SettingsRepository>,
     sync_service: Arc<SyncService>,
+    retention_service: Arc<RetentionService>,
+    sync_handle: Arc<SyncHandle>,
     colors: AppColors,
     show: bool,
-    sync_interval: i32,
-    article_retention_days: i32,
+    /// Settings currently in effect
+    settings: Settings,
+    /// Copy being edited; discarded if the dialog is closed without saving
+    draft: Settings,
+    /// Settings saved since the owner last asked, for the parts it applies itself
+    saved: Option<Settings>,
+    /// Result of the last cleanup preview, shown under the retention setting
+    cleanup_preview: Option<String>,
+    last_error: Option<String>,
 }
 
 impl SettingsDialog {
     pub fn new(
+        settings_repository: Arc<dyn SettingsRepository>,
         sync_service: Arc<SyncService>,
+        retention_service: Arc<RetentionService>,
+        sync_handle: Arc<SyncHandle>,
+        settings: Settings,
         colors: AppColors,
     ) -> Self {
         Self {
+            settings_repository,
             sync_service,
+            retention_service,
+            sync_handle,
             colors,
             show: false,
-            sync_interval: 60,
-            article_retention_days: 30,
+            draft: settings.clone(),
+            settings,
+            saved: None,
+            cleanup_preview: None,
+            last_error: None,
         }
     }
 
@@ -32,49 +58,72 @@ impl SettingsDialog {
             return Ok(());
         }
 
-        // Create local mutable copies of the state we need to modify
-        let mut sync_interval = self.sync_interval;
-        let mut article_retention_days = self.article_retention_days;
-        let colors = &self.colors; // Reference instead of clone
-        
-        // Step 1: Create the window but capture its response instead of chaining
-        let window = Window::new("Settings")
-            .open(&mut self.show)  // Use self.show directly here
-            .resizable(false);
-            
-        // Step 2: Show the window and handle UI content
-        let response = window.show(ctx, |ui| {
-            // Pass individual fields instead of self (no show reference here)
-            ui_content(ui, colors, &mut sync_interval, &mut article_retention_days)
-        });
-
-        // Step 3: Update struct fields with any changes from the UI
-        let changed = self.sync_interval != sync_interval || 
-                      self.article_retention_days != article_retention_days;
-        
-        self.sync_interval = sync_interval;
-        self.article_retention_days = article_retention_days;
-        
-        // If the dialog was closed and values changed, save settings
-        if !self.show && changed {
-            if let Err(e) = self.save_settings() {
-                eprintln!("Error saving settings: {}", e);
+        let mut open = true;
+        let colors = &self.colors;
+        let draft = &mut self.draft;
+        let cleanup_preview = self.cleanup_preview.as_deref();
+        let last_error = self.last_error.as_deref();
+
+        let response = Window::new("Settings")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| ui_content(ui, colors, draft, cleanup_preview, last_error));
+
+        let action = response.and_then(|r| r.inner).flatten();
+        if action == Some(DialogAction::PreviewCleanup) {
+            self.cleanup_preview = Some(match self.preview_cleanup() {
+                Ok(count) => format!("{} read articles would be deleted now", count),
+                Err(e) => format!("Could not preview cleanup: {}", e),
+            });
+        } else if action == Some(DialogAction::Save) {
+            match self.save_settings() {
+                Ok(()) => self.close(),
+                Err(e) => {
+                    error!("Error saving settings: {}", e);
+                    self.last_error = Some(e.to_string());
+                }
             }
+        } else if !open {
+            self.close();
         }
 
         Ok(())
     }
 
-    fn save_settings(&self) -> Result<()> {
-        // Here we would save the settings to persistent storage
-        // For now, we just print them
-        println!("Saving settings:");
-        println!("Sync interval: {} minutes", self.sync_interval);
-        println!("Article retention: {} days", self.article_retention_days);
+    /// Persists the draft and applies it to syncing straight away
+    fn save_settings(&mut self) -> Result<()> {
+        self.draft.fetch = self.draft.fetch.clone().validated();
+        // Apply first so settings the fetcher rejects (e.g. a bad proxy) are never stored
+        self.sync_service.apply_settings(&self.draft)?;
+        self.retention_service.set_retention_days(self.draft.article_retention_days);
+        self.sync_handle.block_on(self.settings_repository.save_settings(&self.draft))?;
+
+        info!("Saved settings");
+        self.settings = self.draft.clone();
+        self.saved = Some(self.settings.clone());
         Ok(())
     }
 
+    /// Counts the articles a cleanup would delete with the retention period being edited
+    fn preview_cleanup(&self) -> Result<usize> {
+        let report = self.sync_handle.block_on(self.retention_service.purge(self.draft.article_retention_days, true))?;
+        Ok(report.expired.len())
+    }
+
+    /// Switches to the colors of a newly chosen theme
+    pub fn set_colors(&mut self, colors: AppColors) {
+        self.colors = colors;
+    }
+
+    /// Settings saved since the last call, which the caller should apply to the UI
+    pub fn take_saved(&mut self) -> Option<Settings> {
+        self.saved.take()
+    }
+
     pub fn open(&mut self) {
+        self.draft = self.settings.clone();
+        self.cleanup_preview = None;
+        self.last_error = None;
         self.show = true;
     }
 
@@ -83,21 +132,31 @@ impl SettingsDialog {
     }
 }
 
-// Define the UI rendering function as a free function instead of a method
+/// Buttons in the dialog body that need the dialog to act
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialogAction {
+    PreviewCleanup,
+    Save,
+}
+
+/// Renders the dialog body, returning the button clicked, if any
 fn ui_content(
-    ui: &mut Ui, 
-    colors: &AppColors, 
-    sync_interval: &mut i32, 
-    article_retention_days: &mut i32,
-) -> Result<()> {
+    ui: &mut Ui,
+    colors: &AppColors,
+    draft: &mut Settings,
+    cleanup_preview: Option<&str>,
+    last_error: Option<&str>,
+) -> Option<DialogAction> {
+    let mut action = None;
+
     ui.vertical(|ui| {
         ui.heading(RichText::new("Sync Settings").color(colors.text_highlight));
         ui.add_space(8.0);
 
         ui.horizontal(|ui| {
             ui.label("Sync Interval (minutes):");
-            ui.add(DragValue::new(sync_interval)
-                .clamp_range(15..=1440));
+            ui.add(DragValue::new(&mut draft.sync_interval_minutes)
+                .range(15..=1440));
         });
 
         ui.add_space(16.0);
@@ -106,18 +165,161 @@ fn ui_content(
 
         ui.horizontal(|ui| {
             ui.label("Article Retention (days):");
-            ui.add(DragValue::new(article_retention_days)
-                .clamp_range(1..=365));
+            ui.add(DragValue::new(&mut draft.article_retention_days)
+                .range(1..=365));
+            if ui.button("Preview Cleanup").clicked() {
+                action = Some(DialogAction::PreviewCleanup);
+            }
+        });
+
+        ui.label(RichText::new("Favorited and tagged articles are always kept. Feeds set to keep only \
+            their newest articles follow that limit instead.").color(colors.text_dim));
+        if let Some(preview) = cleanup_preview {
+            ui.label(RichText::new(preview).color(colors.text_dim));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Default Sort Order:");
+            ui.radio_value(&mut draft.default_sort_order, ArticleSortOrder::NewestFirst, "Newest first");
+            ui.radio_value(&mut draft.default_sort_order, ArticleSortOrder::OldestFirst, "Oldest first");
+            ui.radio_value(&mut draft.default_sort_order, ArticleSortOrder::Unread, "Unread first");
         });
 
         ui.add_space(16.0);
+        ui.heading(RichText::new("Appearance").color(colors.text_highlight));
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Theme:");
+            ui.radio_value(&mut draft.theme, Theme::Dark, "Dark");
+            ui.radio_value(&mut draft.theme, Theme::Light, "Light");
+            ui.radio_value(&mut draft.theme, Theme::Sepia, "Sepia");
+        });
+
+        let reader = &mut draft.reader;
+        ui.horizontal(|ui| {
+            ui.label("Article Font:");
+            ComboBox::from_id_salt("reader_font")
+                .selected_text(font_label(reader.font))
+                .show_ui(ui, |ui| {
+                    for font in [ReaderFont::SansSerif, ReaderFont::Serif, ReaderFont::Monospace] {
+                        ui.selectable_value(&mut reader.font, font, font_label(font));
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Font Size (px):");
+            ui.add(DragValue::new(&mut reader.font_size).range(12..=32));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Line Height (%):");
+            ui.add(DragValue::new(&mut reader.line_height_percent).range(100..=250).speed(5));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Column Width (characters):");
+            ui.add(DragValue::new(&mut reader.column_width).range(40..=120));
+        });
+
+        ui.add_space(16.0);
+        ui.heading(RichText::new("Network Settings").color(colors.text_highlight));
+        ui.add_space(8.0);
+
+        let fetch = &mut draft.fetch;
+        ui.horizontal(|ui| {
+            ui.label("Request Timeout (seconds):");
+            ui.add(DragValue::new(&mut fetch.timeout_secs).range(5..=300));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Maximum Feed Size (MiB):");
+            let mut max_body_mib = (fetch.max_body_bytes / MIB).max(1);
+            if ui.add(DragValue::new(&mut max_body_mib).range(1..=100)).changed() {
+                fetch.max_body_bytes = max_body_mib * MIB;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Maximum Redirects:");
+            ui.add(DragValue::new(&mut fetch.max_redirects).range(0..=20));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Concurrent Fetches:");
+            ui.add(DragValue::new(&mut fetch.max_concurrent).range(1..=32));
+            ui.label("per host:");
+            ui.add(DragValue::new(&mut fetch.max_per_host).range(1..=8));
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("User Agent:");
+            optional_text_edit(ui, &mut fetch.user_agent, "Default");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Proxy URL:");
+            optional_text_edit(ui, &mut fetch.proxy_url, "None");
+        });
+
+        ui.add_space(16.0);
+        ui.heading(RichText::new("Downloads").color(colors.text_highlight));
+        ui.add_space(8.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Download Folder:");
+            ui.add(TextEdit::singleline(&mut draft.download_directory).hint_text("data/downloads"));
+        });
+
+        ui.add_space(16.0);
+        ui.heading(RichText::new("Offline Images").color(colors.text_highlight));
+        ui.add_space(8.0);
+
+        let asset_cache = &mut draft.asset_cache;
+        ui.horizontal(|ui| {
+            ui.label("Image Cache Size (MiB):");
+            let mut max_total_mib = (asset_cache.max_total_bytes / MIB).max(16);
+            if ui.add(DragValue::new(&mut max_total_mib).range(16..=4096)).changed() {
+                asset_cache.max_total_bytes = max_total_mib * MIB;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Maximum Image Size (MiB):");
+            let mut max_asset_mib = (asset_cache.max_asset_bytes / MIB).max(1);
+            if ui.add(DragValue::new(&mut max_asset_mib).range(1..=50)).changed() {
+                asset_cache.max_asset_bytes = max_asset_mib * MIB;
+            }
+        });
+        ui.label(RichText::new("Feeds opt in to image caching in their settings.").color(colors.text_dim));
+
+        ui.add_space(16.0);
+
+        if let Some(error) = last_error {
+            ui.label(RichText::new(error).color(colors.error));
+        }
 
-        // The save button sets a close flag in egui that will be handled by the window
         if ui.button("Save Changes").clicked() {
-            // Return true to indicate we want to close the dialog
-            ui.ctx().send_viewport_cmd(ViewportCommand::Close);
+            action = Some(DialogAction::Save);
         }
     });
 
-    Ok(())
-}
\ No newline at end of file
+    action
+}
+
+/// Edits an optional string, treating an empty field as `None`
+fn optional_text_edit(ui: &mut Ui, value: &mut Option<String>, hint: &str) {
+    let mut text = value.clone().unwrap_or_default();
+    if ui.add(TextEdit::singleline(&mut text).hint_text(hint)).changed() {
+        *value = (!text.is_empty()).then_some(text);
+    }
+}
+
+fn font_label(font: ReaderFont) -> &'static str {
+    match font {
+        ReaderFont::SansSerif => "Sans-serif",
+        ReaderFont::Serif => "Serif",
+        ReaderFont::Monospace => "Monospace",
+    }
+}
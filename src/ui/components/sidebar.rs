@@ -1,20 +1,40 @@
-use egui::{Button, Context as EguiContext, Label, RichText, ScrollArea, TextEdit, Ui};
+use egui::{Button, Context as EguiContext, Label, Response, RichText, ScrollArea, Spinner, TextEdit, Ui};
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 use log::{error, warn};
 use anyhow::Result;
 
 use crate::base::repository::{FeedRepository, CategoryRepository};
 use crate::models::category::{Category, CategoryId};
-use crate::models::feed::Feed;
+use crate::models::feed::{Feed, FeedId};
+use crate::models::article::ReadStatus;
+use crate::models::article_counts::{ArticleCounts, UnreadCounts};
+use crate::models::article_page::ArticleFilter;
+use crate::models::article_query::ArticleQuery;
+use crate::models::smart_folder::{SmartFolder, SmartFolderId};
+use crate::models::search::SearchQuery;
+use crate::ui::components::feed_icons::FeedIcons;
 use crate::ui::styles::{AppColors, DEFAULT_PADDING};
 
+/// How long typing must pause before the search box runs its search
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Explains the search syntax, which matches whole words unless asked otherwise
+const SEARCH_HELP: &str = "Finds articles containing every word, ignoring case and accents.\n\
+    Words match whole words: end one with * to match words starting with it (rust*).\n\
+    Put words in quotes to match them as a phrase (\"async rust\").";
+
 /// Data model specifically for the Sidebar component
 /// This implements SRP by separating the UI state from the data model
 #[derive(Debug, Clone, Default)]
 pub struct SidebarData {
     pub categories_by_parent: HashMap<Option<CategoryId>, Vec<Category>>,
     pub feeds_by_category: HashMap<Option<CategoryId>, Vec<Feed>>,
+    /// Saved queries with the number of unread articles each matches
+    pub smart_folders: Vec<(SmartFolder, usize)>,
+    /// Unread and total articles per feed, category and tag
+    pub counts: UnreadCounts,
     pub is_loading: bool,
     pub last_error: Option<String>,
 }
@@ -24,6 +44,8 @@ impl SidebarData {
         Self {
             categories_by_parent: HashMap::new(),
             feeds_by_category: HashMap::new(),
+            smart_folders: Vec::new(),
+            counts: UnreadCounts::default(),
             is_loading: false,
             last_error: None,
         }
@@ -63,6 +85,12 @@ impl SidebarData {
 pub struct SidebarState {
     pub selection: Option<SidebarSelection>,
     pub search_query: String,
+    /// Restrict search results to unread articles
+    pub search_unread_only: bool,
+    /// Whether the form for saving a new smart folder is open
+    pub show_new_smart_folder: bool,
+    pub new_smart_folder_name: String,
+    pub new_smart_folder_query: String,
     pub expanded_categories: Vec<CategoryId>,
     pub data_load_requested: Vec<Option<CategoryId>>, // Track IDs that need data loading
 }
@@ -72,6 +100,10 @@ impl Default for SidebarState {
         Self {
             selection: None,
             search_query: String::new(),
+            search_unread_only: false,
+            show_new_smart_folder: false,
+            new_smart_folder_name: String::new(),
+            new_smart_folder_query: String::new(),
             expanded_categories: Vec::new(),
             data_load_requested: Vec::new(),
         }
@@ -84,6 +116,19 @@ pub enum SidebarSelection {
     Favorites,
     Feed(Feed),
     Category(Category),
+    /// Full-text search, scoped to the feed or category selected before it
+    Search(SearchQuery),
+    SmartFolder(SmartFolder),
+    Tag(String),
+}
+
+/// Changes asked for in the sidebar, carried out by its owner
+#[derive(Debug, Clone, PartialEq)]
+pub enum SidebarRequest {
+    CreateSmartFolder { name: String, query: String },
+    DeleteSmartFolder(SmartFolderId),
+    /// Mark every unread article selected by the filter as read
+    MarkRead { filter: ArticleFilter, label: String },
 }
 
 pub struct Sidebar {
@@ -92,6 +137,12 @@ pub struct Sidebar {
     state: SidebarState,
     data: SidebarData,
     colors: AppColors,
+    /// Feeds being fetched right now, shown with a spinner
+    syncing_feeds: HashSet<FeedId>,
+    feed_icons: FeedIcons,
+    request: Option<SidebarRequest>,
+    /// When the search text last changed, while waiting for typing to pause
+    search_edited_at: Option<Instant>,
 }
 
 impl Sidebar {
@@ -105,30 +156,62 @@ impl Sidebar {
             state: SidebarState::default(),
             data: SidebarData::new(),
             colors: AppColors::default(),
+            syncing_feeds: HashSet::new(),
+            feed_icons: FeedIcons::default(),
+            request: None,
+            search_edited_at: None,
         }
     }
 
+    /// Switches to the colors of a newly chosen theme
+    pub fn set_colors(&mut self, colors: AppColors) {
+        self.colors = colors;
+    }
+
     pub fn ui(&mut self, ui: &mut Ui) -> Result<Option<SidebarSelection>> {
         let mut new_selection = None;
 
         // Search box
         ui.horizontal(|ui| {
             ui.label("Search");
-            if ui.text_edit_singleline(&mut self.state.search_query).changed() {
-                // Search logic will be handled by the filtering below
+            let response = ui.text_edit_singleline(&mut self.state.search_query).on_hover_text(SEARCH_HELP);
+            if response.changed() {
+                self.search_edited_at = Some(Instant::now());
+            }
+            let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            let filter_changed = ui.checkbox(&mut self.state.search_unread_only, "Unread").changed();
+            let typing_paused = self.search_edited_at.is_some_and(|edited| edited.elapsed() >= SEARCH_DEBOUNCE);
+            if typing_paused || submitted || filter_changed {
+                self.search_edited_at = None;
+                new_selection = Some(match self.search_query() {
+                    Some(query) => SidebarSelection::Search(query),
+                    None => self.state.selection.clone().unwrap_or(SidebarSelection::AllFeeds),
+                });
+            } else if self.search_edited_at.is_some() {
+                // Come back once typing has paused, even if nothing else repaints
+                ui.ctx().request_repaint_after(SEARCH_DEBOUNCE);
             }
         });
 
         ui.add_space(DEFAULT_PADDING);
 
         // Special sections
-        if ui.add(Button::new(RichText::new("📚 All Articles").color(self.colors.text))).clicked() {
-            new_selection = Some(SidebarSelection::AllFeeds);
-        }
+        ui.horizontal(|ui| {
+            let response = ui.add(Button::new(RichText::new("📚 All Articles").color(self.colors.text)));
+            if response.clicked() {
+                new_selection = Some(SidebarSelection::AllFeeds);
+            }
+            mark_read_menu(&response, &mut self.request, ArticleFilter::All, "All Articles");
+            unread_badge(ui, self.data.counts.all, &self.colors);
+        });
 
-        if ui.add(Button::new(RichText::new("⭐ Favorites").color(self.colors.text))).clicked() {
+        let response = ui.add(Button::new(RichText::new("⭐ Favorites").color(self.colors.text)));
+        if response.clicked() {
             new_selection = Some(SidebarSelection::Favorites);
         }
+        mark_read_menu(&response, &mut self.request, ArticleFilter::Favorites, "Favorites");
+
+        self.render_smart_folders(ui, &mut new_selection);
 
         ui.add_space(DEFAULT_PADDING);
 
@@ -152,15 +235,101 @@ impl Sidebar {
         // Categories and feeds
         ScrollArea::vertical().show(ui, |ui| {
             self.render_categories(ui, root_parent_id, 0, &mut new_selection);
+            self.render_tags(ui, &mut new_selection);
         });
 
+        // A search is layered over the selection it is scoped to, not a selection itself
         if let Some(selection) = new_selection.clone() {
-            self.state.selection = Some(selection);
+            if !matches!(selection, SidebarSelection::Search(_)) {
+                self.state.selection = Some(selection);
+            }
         }
 
         Ok(new_selection)
     }
 
+    /// Search for the text in the search box, within the selected feed or category
+    fn search_query(&self) -> Option<SearchQuery> {
+        if self.state.search_query.trim().is_empty() {
+            return None;
+        }
+
+        let mut query = SearchQuery::new(&self.state.search_query);
+        match &self.state.selection {
+            Some(SidebarSelection::Feed(feed)) => query = query.with_feed(feed.id.clone()),
+            Some(SidebarSelection::Category(category)) => query = query.with_category(category.id.clone()),
+            _ => {}
+        }
+        if self.state.search_unread_only {
+            query = query.with_read_status(ReadStatus::Unread);
+        }
+        Some(query)
+    }
+
+    fn render_smart_folders(&mut self, ui: &mut Ui, selection: &mut Option<SidebarSelection>) {
+        for (folder, unread) in &self.data.smart_folders {
+            let text = if *unread > 0 {
+                format!("🔎 {} ({})", folder.name, unread)
+            } else {
+                format!("🔎 {}", folder.name)
+            };
+
+            let response = ui.add(Button::new(RichText::new(text).color(self.colors.text)))
+                .on_hover_text(&folder.query);
+            if response.clicked() {
+                *selection = Some(SidebarSelection::SmartFolder(folder.clone()));
+            }
+            response.context_menu(|ui| {
+                if let Ok(query) = folder.parsed_query() {
+                    if ui.button("Mark All as Read").clicked() {
+                        self.request = Some(SidebarRequest::MarkRead {
+                            filter: ArticleFilter::Query(query),
+                            label: folder.name.clone(),
+                        });
+                        ui.close_menu();
+                    }
+                }
+                if ui.button("Delete").clicked() {
+                    self.request = Some(SidebarRequest::DeleteSmartFolder(folder.id.clone()));
+                    ui.close_menu();
+                }
+            });
+        }
+
+        if !self.state.show_new_smart_folder {
+            if ui.add(Button::new(RichText::new("＋ Smart Folder").color(self.colors.text_dim))).clicked() {
+                self.state.show_new_smart_folder = true;
+            }
+            return;
+        }
+
+        ui.add(TextEdit::singleline(&mut self.state.new_smart_folder_name).hint_text("Name"));
+        ui.add(TextEdit::singleline(&mut self.state.new_smart_folder_query)
+            .hint_text("tag:rust is:unread -title:hiring"));
+
+        let query = self.state.new_smart_folder_query.trim().to_string();
+        let parsed = (!query.is_empty()).then(|| ArticleQuery::parse(&query));
+        if let Some(Err(e)) = &parsed {
+            ui.label(RichText::new(e.to_string()).color(self.colors.error));
+        }
+
+        ui.horizontal(|ui| {
+            let valid = matches!(parsed, Some(Ok(_))) && !self.state.new_smart_folder_name.trim().is_empty();
+            if ui.add_enabled(valid, Button::new("Save")).clicked() {
+                self.request = Some(SidebarRequest::CreateSmartFolder {
+                    name: self.state.new_smart_folder_name.trim().to_string(),
+                    query: query.clone(),
+                });
+                self.state.show_new_smart_folder = false;
+                self.state.new_smart_folder_name.clear();
+                self.state.new_smart_folder_query.clear();
+            }
+            if ui.button("Cancel").clicked() {
+                self.state.show_new_smart_folder = false;
+            }
+        });
+    }
+
     /// Synchronous method to render categories from the cached data
     fn render_categories(
         &mut self,
@@ -175,9 +344,14 @@ impl Sidebar {
             let indent = "  ".repeat(depth as usize);
             let text = format!("{}{} {}", indent, "📁", category.name);
             
-            if ui.add(Button::new(RichText::new(&text).color(self.colors.text))).clicked() {
-                *selection = Some(SidebarSelection::Category(category.clone()));
-            }
+            ui.horizontal(|ui| {
+                let response = ui.add(Button::new(RichText::new(&text).color(self.colors.text)));
+                if response.clicked() {
+                    *selection = Some(SidebarSelection::Category(category.clone()));
+                }
+                mark_read_menu(&response, &mut self.request, ArticleFilter::Category(category.id.clone()), &category.name);
+                unread_badge(ui, self.data.counts.category(&category.id), &self.colors);
+            });
 
             // Only show feeds for expanded categories
             if self.state.expanded_categories.contains(&category.id) {
@@ -204,18 +378,80 @@ impl Sidebar {
     }
 
     fn render_feed(
-        &self,
+        &mut self,
         ui: &mut Ui,
         feed: &Feed,
         depth: i32,
         selection: &mut Option<SidebarSelection>
     ) {
         let indent = "  ".repeat(depth as usize);
-        let text = format!("{}{} {}", indent, "📰", feed.title);
         
-        if ui.add(Button::new(RichText::new(&text).color(self.colors.text))).clicked() {
-            *selection = Some(SidebarSelection::Feed(feed.clone()));
+        ui.horizontal(|ui| {
+            ui.label(&indent);
+            self.feed_icons.show(ui, &feed.id, "📰", &self.colors);
+            let response = ui.add(Button::new(RichText::new(&feed.title).color(self.colors.text)));
+            if response.clicked() {
+                *selection = Some(SidebarSelection::Feed(feed.clone()));
+            }
+            mark_read_menu(&response, &mut self.request, ArticleFilter::Feed(feed.id.clone()), &feed.title);
+            unread_badge(ui, self.data.counts.feed(&feed.id), &self.colors);
+            if self.syncing_feeds.contains(&feed.id) {
+                ui.add(Spinner::new().size(12.0));
+            }
+        });
+    }
+
+    fn render_tags(&mut self, ui: &mut Ui, selection: &mut Option<SidebarSelection>) {
+        if self.data.counts.tags.is_empty() {
+            return;
         }
+
+        let mut tags: Vec<String> = self.data.counts.tags.keys().cloned().collect();
+        tags.sort_by_key(|name| name.to_lowercase());
+
+        ui.add_space(DEFAULT_PADDING);
+        ui.label(RichText::new("Tags").color(self.colors.text_dim));
+        for name in tags {
+            let counts = self.data.counts.tag(&name);
+            ui.horizontal(|ui| {
+                let response = ui.add(Button::new(RichText::new(format!("🏷 {}", name)).color(self.colors.text)));
+                if response.clicked() {
+                    *selection = Some(SidebarSelection::Tag(name.clone()));
+                }
+                mark_read_menu(&response, &mut self.request, ArticleFilter::Tag(name.clone()), &name);
+                unread_badge(ui, counts, &self.colors);
+            });
+        }
+    }
+
+    /// Replaces the smart folders and their unread counts
+    pub fn set_smart_folders(&mut self, smart_folders: Vec<(SmartFolder, usize)>) {
+        self.data.smart_folders = smart_folders;
+    }
+
+    /// Replaces all unread counts, e.g. after the first load
+    pub fn set_unread_counts(&mut self, counts: UnreadCounts) {
+        self.data.counts = counts;
+    }
+
+    /// The unread counts, for updating the feeds a sync or read-state change touched
+    pub fn unread_counts_mut(&mut self) -> &mut UnreadCounts {
+        &mut self.data.counts
+    }
+
+    /// Change asked for since the last call, if any
+    pub fn take_request(&mut self) -> Option<SidebarRequest> {
+        self.request.take()
+    }
+
+    /// Replaces the favicons shown next to feed titles
+    pub fn set_feed_icons(&mut self, feed_icons: FeedIcons) {
+        self.feed_icons = feed_icons;
+    }
+
+    /// Replaces the set of feeds shown as currently syncing
+    pub fn set_syncing_feeds(&mut self, feed_ids: HashSet<FeedId>) {
+        self.syncing_feeds = feed_ids;
     }
     
     /// Method to update the cached data with new information from the async thread
@@ -318,4 +554,23 @@ impl Sidebar {
     pub fn needs_data_loading(&self) -> bool {
         !self.state.data_load_requested.is_empty()
     }
-}
\ No newline at end of file
+}
+
+/// Adds a "Mark All as Read" context menu to a sidebar entry
+fn mark_read_menu(response: &Response, request: &mut Option<SidebarRequest>, filter: ArticleFilter, label: &str) {
+    response.context_menu(|ui| {
+        if ui.button("Mark All as Read").clicked() {
+            *request = Some(SidebarRequest::MarkRead { filter, label: label.to_string() });
+            ui.close_menu();
+        }
+    });
+}
+
+/// Shows the number of unread articles next to a sidebar entry, if there are any
+fn unread_badge(ui: &mut Ui, counts: ArticleCounts, colors: &AppColors) {
+    if counts.unread == 0 {
+        return;
+    }
+    ui.label(RichText::new(counts.unread.to_string()).small().strong().color(colors.accent))
+        .on_hover_text(format!("{} unread of {}", counts.unread, counts.total));
+}
@@ -6,9 +6,17 @@ pub mod views;
 use anyhow::Result;
 use std::sync::Arc;
 
-use crate::base::repository::{ArticleRepository, CategoryRepository, FeedRepository, TagRepository};
+use crate::base::repository::{ArticleRepository, AssetRepository, CategoryRepository, EnclosureRepository, FeedRepository, SettingsRepository, SmartFolderRepository, TagRepository};
+use crate::models::settings::Settings;
+use crate::services::assets::AssetCacheService;
+use crate::services::downloads::DownloadService;
+use crate::services::extraction::ExtractionService;
+use crate::services::read_state::ReadStateService;
+use crate::services::retention::RetentionService;
 use crate::services::rss::RssService;
-use crate::services::sync::SyncService;
+use crate::services::smart_folders::SmartFolderService;
+use crate::services::sync::{SyncHandle, SyncService};
+use crate::services::unread_counts::UnreadCountService;
 use crate::services::webview::WebViewService;
 use crate::ui::components::sidebar::Sidebar;
 
@@ -17,8 +25,19 @@ pub struct AppContext {
     pub category_repository: Arc<dyn CategoryRepository>,
     pub feed_repository: Arc<dyn FeedRepository>,
     pub tag_repository: Arc<dyn TagRepository>,
+    pub settings_repository: Arc<dyn SettingsRepository>,
+    /// Settings as loaded at startup; the settings dialog owns later changes
+    pub settings: Settings,
     pub rss_service: Arc<RssService>,
     pub sync_service: Arc<SyncService>,
+    pub retention_service: Arc<RetentionService>,
+    pub read_state_service: Arc<ReadStateService>,
+    pub smart_folder_service: Arc<SmartFolderService>,
+    pub unread_count_service: Arc<UnreadCountService>,
+    pub download_service: Arc<DownloadService>,
+    pub asset_cache_service: Arc<AssetCacheService>,
+    pub extraction_service: Arc<ExtractionService>,
+    pub sync_handle: Arc<SyncHandle>,
     pub webview_service: Arc<WebViewService>,
 }
 
@@ -28,26 +47,91 @@ impl AppContext {
         category_repository: Arc<dyn CategoryRepository>,
         feed_repository: Arc<dyn FeedRepository>,
         tag_repository: Arc<dyn TagRepository>,
-    ) -> Self {
+        settings_repository: Arc<dyn SettingsRepository>,
+        smart_folder_repository: Arc<dyn SmartFolderRepository>,
+        enclosure_repository: Arc<dyn EnclosureRepository>,
+        asset_repository: Arc<dyn AssetRepository>,
+    ) -> Result<Self> {
         let rss_service = Arc::new(RssService::new(
             article_repository.clone(),
             feed_repository.clone(),
             category_repository.clone(),
             tag_repository.clone(),
+            enclosure_repository.clone(),
         ));
 
         let sync_service = Arc::new(SyncService::new(rss_service.clone()));
+        let sync_handle = SyncHandle::new(sync_service.clone(), rss_service.clone())?;
+
+        // A broken settings row should not keep the reader from starting
+        let settings = sync_handle.block_on(settings_repository.load_settings())
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to load settings, using defaults: {}", e);
+                Settings::default()
+            });
+        if let Err(e) = sync_service.apply_settings(&settings) {
+            log::warn!("Failed to apply stored settings: {}", e);
+        }
+
+        // Downloads, images and article pages go through the feeds' proxy
+        let fetch_config = rss_service.fetch_config();
+        let download_service = Arc::new(DownloadService::new(
+            enclosure_repository,
+            feed_repository.clone(),
+            &settings.download_directory,
+        )?.with_fetch_config(&fetch_config)?);
+        let retention_service = Arc::new(
+            RetentionService::new(article_repository.clone(), settings.article_retention_days)
+                .with_download_service(download_service.clone()),
+        );
+        let asset_cache_service = Arc::new(AssetCacheService::new(
+            asset_repository,
+            article_repository.clone(),
+            feed_repository.clone(),
+            settings.asset_cache,
+        )?.with_fetch_config(&fetch_config)?);
+        let extraction_service = Arc::new(ExtractionService::new(
+            article_repository.clone(),
+            feed_repository.clone(),
+        )?.with_fetch_config(&fetch_config)?);
+        let sync_handle = Arc::new(
+            sync_handle
+                .with_retention_service(retention_service.clone())
+                .with_download_service(download_service.clone())
+                .with_extraction_service(extraction_service.clone())
+                .with_asset_cache(asset_cache_service.clone()),
+        );
+        let read_state_service = Arc::new(ReadStateService::new(article_repository.clone()));
+        let smart_folder_service = Arc::new(SmartFolderService::new(
+            smart_folder_repository,
+            article_repository.clone(),
+        ));
+        let unread_count_service = Arc::new(UnreadCountService::new(
+            feed_repository.clone(),
+            category_repository.clone(),
+            tag_repository.clone(),
+        ));
         let webview_service = Arc::new(WebViewService::new());
 
-        Self {
+        Ok(Self {
             article_repository,
             category_repository,
             feed_repository,
             tag_repository,
+            settings_repository,
+            settings,
             rss_service,
             sync_service,
+            retention_service,
+            read_state_service,
+            smart_folder_service,
+            unread_count_service,
+            download_service,
+            asset_cache_service,
+            extraction_service,
+            sync_handle,
             webview_service,
-        }
+        })
     }
     
     /// Initialize a new Sidebar component using the repositories from this context
@@ -80,16 +164,13 @@ impl AppContext {
         
         // First, UI-related services
         log::debug!("Shutting down WebView service");
-        // Use the public is_visible() method to check if webview is visible
-        if self.webview_service.is_visible() {
-            log::warn!("WebView is still visible during shutdown");
-            // Ideally, we would call hide() here if we had mutable access
-        }
+        // Close the article window along with the app
+        self.webview_service.hide();
         
         // Then shutdown any background tasks in application services
         log::debug!("Shutting down Sync service");
-        // Note: We need to make sure SyncService has a way to stop any background tasks
-        // This would typically be implemented in the SyncService
+        // Cancels the scheduler and any in-flight batch, then stops the sync runtime
+        self.sync_handle.shutdown();
         
         // Finally any core services that might need cleanup
         log::debug!("Shutting down RSS service");
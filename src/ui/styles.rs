@@ -1,4 +1,7 @@
-use egui::Color32;
+use egui::{Color32, Visuals};
+
+use crate::models::settings::Theme;
+use crate::services::webview::ReaderPalette;
 
 #[derive(Clone)]
 pub struct AppColors {
@@ -25,5 +28,62 @@ impl Default for AppColors {
     }
 }
 
+impl AppColors {
+    /// Colors of `theme`, shared by the egui panes and the reader page
+    pub fn for_theme(theme: Theme) -> Self {
+        match theme {
+            Theme::Dark => Self::default(),
+            Theme::Light => Self {
+                text: Color32::from_rgb(51, 51, 51),
+                text_dim: Color32::from_rgb(102, 102, 102),
+                text_highlight: Color32::from_rgb(0, 0, 0),
+                background: Color32::from_rgb(255, 255, 255),
+                background_highlight: Color32::from_rgb(240, 240, 240),
+                accent: Color32::from_rgb(0, 102, 204),
+                error: Color32::from_rgb(200, 40, 40),
+            },
+            Theme::Sepia => Self {
+                text: Color32::from_rgb(91, 70, 54),
+                text_dim: Color32::from_rgb(140, 118, 98),
+                text_highlight: Color32::from_rgb(60, 42, 28),
+                background: Color32::from_rgb(244, 236, 216),
+                background_highlight: Color32::from_rgb(233, 221, 194),
+                accent: Color32::from_rgb(160, 82, 45),
+                error: Color32::from_rgb(180, 40, 40),
+            },
+        }
+    }
+
+    /// Whether text goes light on dark
+    pub fn is_dark(&self) -> bool {
+        let [r, g, b, _] = self.background.to_array();
+        u32::from(r) + u32::from(g) + u32::from(b) < 3 * 128
+    }
+
+    /// egui visuals with panels and windows in these colors
+    pub fn visuals(&self) -> Visuals {
+        let mut visuals = if self.is_dark() { Visuals::dark() } else { Visuals::light() };
+        visuals.panel_fill = self.background;
+        visuals.window_fill = self.background;
+        visuals.faint_bg_color = self.background_highlight;
+        visuals.hyperlink_color = self.accent;
+        visuals.error_fg_color = self.error;
+        visuals
+    }
+
+    /// The same colors for the reader page
+    pub fn reader_palette(&self) -> ReaderPalette {
+        let rgb = |color: Color32| [color.r(), color.g(), color.b()];
+        ReaderPalette {
+            background: rgb(self.background),
+            text: rgb(self.text),
+            text_dim: rgb(self.text_dim),
+            accent: rgb(self.accent),
+            surface: rgb(self.background_highlight),
+            dark: self.is_dark(),
+        }
+    }
+}
+
 pub const DEFAULT_PADDING: f32 = 8.0;
 pub const DEFAULT_SPACING: f32 = 4.0;
\ No newline at end of file
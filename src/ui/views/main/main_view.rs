@@ -1,8 +1,15 @@
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use egui::{Button, Context, TopBottomPanel, RichText, Color32, CentralPanel, SidePanel, Window};
+use egui::{Button, Context, TopBottomPanel, RichText, Color32, CentralPanel, SidePanel, Window, ProgressBar};
+use tokio::sync::broadcast::{self, error::TryRecvError};
 use crate::ui::AppContext;
 use crate::models::category::CategoryId;
 use crate::models::article::ArticleId;
+use crate::models::feed::FeedId;
+use crate::models::search::SearchQuery;
+use crate::models::article_page::ArticleFilter;
+use crate::models::settings::{Settings, Theme};
+use crate::services::sync::{BatchId, SyncEvent};
 use crate::ui::components::*;
 use crate::ui::styles::AppColors;
 use std::time::{Duration, Instant};
@@ -10,8 +17,31 @@ use anyhow::Result;
 use eframe::App;
 use log::{info, warn, error};
 
+/// Progress of background syncing, rebuilt from the `SyncEvent` stream
+#[derive(Debug, Default)]
+struct SyncProgressState {
+    /// The running batch, once it has started
+    batch: Option<BatchId>,
+    /// Feeds in the running batch, if a batch is running
+    total: usize,
+    done: usize,
+    failed: usize,
+    /// Feeds currently being fetched, with the batch fetching them or `None`
+    /// for the scheduler
+    active: HashMap<FeedId, Option<BatchId>>,
+}
+
+impl SyncProgressState {
+    /// Whether an event tagged with `batch` belongs to the running batch
+    fn counts(&self, batch: Option<BatchId>) -> bool {
+        batch.is_some() && batch == self.batch
+    }
+}
+
 pub struct MainView {
     app_context: AppContext,
+    sync_events: broadcast::Receiver<SyncEvent>,
+    sync_progress: SyncProgressState,
     
     // UI Components
     sidebar: Sidebar,
@@ -23,33 +53,57 @@ pub struct MainView {
     
     // UI State
     colors: AppColors,
+    theme: Theme,
+    /// Theme last pushed to egui, so visuals are only replaced when it changes
+    applied_theme: Option<Theme>,
     show_sync_indicator: bool,
+    /// Set when syncing may have changed smart folder unread counts
+    smart_folders_stale: bool,
+    /// Feeds whose unread counts changed since they were last counted
+    stale_feed_counts: HashSet<FeedId>,
+    /// Set when read state changed, which changes the unread counts of tags
+    tag_counts_stale: bool,
+    /// Set when a feed got a new favicon
+    feed_icons_stale: bool,
+    /// Set when feeds or categories were added or removed, or a cleanup deleted
+    /// articles, so every count and which category each feed is in are reloaded
+    all_counts_stale: bool,
+    /// Cleanups seen so far, see [`RetentionService::purge_count`](crate::services::retention::RetentionService::purge_count)
+    seen_purges: u64,
     status_message: Option<(String, Instant)>,
     selected_article: Option<ArticleId>,
     show_categories: bool,
-    show_settings: bool,
     show_feed_manager: bool,
 }
 
 impl MainView {
     pub fn new(app_context: AppContext) -> Self {
-        let colors = AppColors::default();
+        let colors = AppColors::for_theme(app_context.settings.theme);
         
         // Initialize components using AppContext, which now manages async operations safely
-        let sidebar = app_context.init_sidebar();
+        let mut sidebar = app_context.init_sidebar();
+        sidebar.set_colors(colors.clone());
         
         // Initialize other components (these don't have the same Tokio runtime issue)
-        let article_list = ArticleList::new(
+        let mut article_list = ArticleList::new(
             app_context.article_repository.clone(),
             app_context.rss_service.clone(), // Properly pass the RssService as required by ArticleList
+            app_context.read_state_service.clone(),
+            app_context.sync_handle.clone(),
             colors.clone(),
         );
+        article_list.set_sort_order(app_context.settings.default_sort_order);
         
         let article_viewer = ArticleViewer::new(
             app_context.article_repository.clone(),
             app_context.webview_service.clone(),
             app_context.rss_service.clone(),
+            app_context.download_service.clone(),
+            app_context.asset_cache_service.clone(),
+            app_context.extraction_service.clone(),
+            app_context.sync_handle.clone(),
             colors.clone(),
+            app_context.settings.reader,
         );
         
         let feed_manager = FeedManager::new(
@@ -63,33 +117,74 @@ impl MainView {
         );
         
         let settings_dialog = SettingsDialog::new(
+            app_context.settings_repository.clone(),
             app_context.sync_service.clone(),
+            app_context.retention_service.clone(),
+            app_context.sync_handle.clone(),
+            app_context.settings.clone(),
             colors.clone(),
         );
         
+        let sync_events = app_context.sync_handle.subscribe();
+        app_context.sync_handle.start();
+        
         Self {
+            sync_events,
+            sync_progress: SyncProgressState::default(),
             sidebar,
             article_list,
             article_viewer,
             feed_manager,
             category_manager,
             settings_dialog,
+            theme: app_context.settings.theme,
+            applied_theme: None,
             app_context,
             colors,
             show_sync_indicator: false,
+            smart_folders_stale: true,
+            stale_feed_counts: HashSet::new(),
+            feed_icons_stale: true,
+            all_counts_stale: true,
+            seen_purges: 0,
+            tag_counts_stale: false,
             status_message: None,
             selected_article: None,
             show_categories: false,
-            show_settings: false,
             show_feed_manager: false
         }
     }
 
     pub fn update(&mut self, ctx: &Context) -> Result<()> {
-        if let Some(article_id) = self.selected_article.clone() {
-            // TODO: This needs to be handled through the background thread mechanism
-            // For now, we'll just display any cached article data
-            self.set_status_message("Loading article...".to_string());
+        self.process_sync_events();
+        // Either way, the feeds and categories counted no longer match the database
+        if self.feed_manager.take_feeds_changed() | self.category_manager.take_categories_changed() {
+            self.all_counts_stale = true;
+        }
+        let purges = self.app_context.retention_service.purge_count();
+        if purges != self.seen_purges {
+            self.seen_purges = purges;
+            self.all_counts_stale = true;
+        }
+        if self.all_counts_stale {
+            self.reload_unread_counts();
+        }
+        if self.smart_folders_stale {
+            self.refresh_smart_folders();
+        }
+        if !self.stale_feed_counts.is_empty() || self.tag_counts_stale {
+            self.refresh_unread_counts();
+        }
+        if self.feed_icons_stale {
+            self.refresh_feed_icons();
+        }
+        if self.applied_theme != Some(self.theme) {
+            self.apply_theme(ctx);
+        }
+
+        if self.show_sync_indicator || !self.sync_progress.active.is_empty() {
+            // Keep spinners and the progress bar moving while nothing else repaints
+            ctx.request_repaint_after(Duration::from_millis(100));
         }
 
         TopBottomPanel::top("toolbar").show(ctx, |ui| {
@@ -102,10 +197,19 @@ impl MainView {
                 }
 
                 if ui.add(Button::new("Settings")).clicked() {
-                    self.show_settings = !self.show_settings;
+                    self.settings_dialog.open();
                 }
 
-                if ui.add(Button::new("Sync All")).clicked() {
+                if self.show_sync_indicator {
+                    let progress = &self.sync_progress;
+                    let fraction = if progress.total == 0 { 0.0 } else { progress.done as f32 / progress.total as f32 };
+                    ui.add(ProgressBar::new(fraction)
+                        .desired_width(200.0)
+                        .text(format!("Syncing {}/{}", progress.done, progress.total)));
+                    if ui.add(Button::new("Cancel")).clicked() {
+                        self.cancel_sync();
+                    }
+                } else if ui.add(Button::new("Sync All")).clicked() {
                     self.sync_all();
                 }
             });
@@ -145,13 +249,252 @@ impl MainView {
 
     fn sync_all(&mut self) {
         self.show_sync_indicator = true;
+        self.sync_progress = SyncProgressState::default();
         self.set_status_message("Starting sync...".to_string());
-        
-        // The actual sync should now be handled through a background thread or queue
-        // For now, just indicate success as we've moved away from direct runtime usage
-        // TODO: Implement a proper background sync mechanism
-        self.set_status_message("Sync request submitted".to_string());
-        self.show_sync_indicator = false;
+        self.app_context.sync_handle.sync_all();
+    }
+
+    /// Cancels the running batch; the scheduler keeps going
+    fn cancel_sync(&mut self) {
+        self.app_context.sync_handle.cancel_batch();
+        if self.sync_progress.batch.is_none() {
+            // Cancelled before it started, so no `Finished` will arrive
+            self.show_sync_indicator = false;
+            self.set_status_message("Sync cancelled".to_string());
+        }
+    }
+
+    /// Applies sync events received since the last frame
+    fn process_sync_events(&mut self) {
+        loop {
+            let event = match self.sync_events.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Lagged(skipped)) => {
+                    warn!("Missed {} sync events", skipped);
+                    continue;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            };
+
+            match event {
+                SyncEvent::Started { batch, total } => {
+                    self.show_sync_indicator = true;
+                    self.sync_progress.batch = Some(batch);
+                    self.sync_progress.total = total;
+                    self.sync_progress.done = 0;
+                    self.sync_progress.failed = 0;
+                }
+                SyncEvent::FeedStarted { batch, feed_id } => {
+                    self.sync_progress.active.insert(feed_id, batch);
+                }
+                SyncEvent::FeedSucceeded { batch, feed_id, new_articles } => {
+                    if !new_articles.is_empty() {
+                        self.smart_folders_stale = true;
+                        self.stale_feed_counts.insert(feed_id.clone());
+                    }
+                    self.sync_progress.active.remove(&feed_id);
+                    if self.sync_progress.counts(batch) {
+                        self.sync_progress.done += 1;
+                    }
+                }
+                SyncEvent::FeedFailed { batch, feed_id, error } => {
+                    warn!("Sync of feed {} failed: {}", feed_id, error);
+                    self.sync_progress.active.remove(&feed_id);
+                    if self.sync_progress.counts(batch) {
+                        self.sync_progress.done += 1;
+                        self.sync_progress.failed += 1;
+                    }
+                }
+                SyncEvent::FeedIconUpdated { .. } => {
+                    self.feed_icons_stale = true;
+                }
+                SyncEvent::Finished { batch, succeeded, failed, cancelled } => {
+                    // Feeds the batch left mid-fetch report nothing more
+                    self.sync_progress.active.retain(|_, active_batch| *active_batch != Some(batch));
+                    if self.sync_progress.batch != Some(batch) {
+                        continue;
+                    }
+                    self.show_sync_indicator = false;
+                    self.sync_progress.batch = None;
+                    let message = if cancelled {
+                        "Sync cancelled".to_string()
+                    } else if failed > 0 {
+                        format!("Synced {} feeds, {} failed", succeeded, failed)
+                    } else {
+                        format!("Synced {} feeds", succeeded)
+                    };
+                    self.set_status_message(message);
+                }
+            }
+        }
+
+        self.sidebar.set_syncing_feeds(self.sync_progress.active.keys().cloned().collect());
+    }
+
+    /// Shows full-text search results in place of the article list
+    fn search_articles(&mut self, query: &SearchQuery) {
+        let result = self.app_context.sync_handle.block_on(self.article_list.search(query));
+        if let Err(e) = result {
+            error!("Search failed: {}", e);
+            self.set_status_message(format!("Search failed: {}", e));
+        }
+        self.close_article();
+    }
+
+    /// Reloads smart folders and their unread counts into the sidebar
+    fn refresh_smart_folders(&mut self) {
+        self.smart_folders_stale = false;
+        let result = self.app_context.sync_handle.block_on(self.app_context.smart_folder_service.get_all_with_unread_counts());
+        match result {
+            Ok(folders) => self.sidebar.set_smart_folders(folders),
+            Err(e) => error!("Failed to load smart folders: {}", e),
+        }
+    }
+
+    /// Reloads the cached favicons into the sidebar and article list
+    fn refresh_feed_icons(&mut self) {
+        self.feed_icons_stale = false;
+        let result = self.app_context.sync_handle.block_on(self.app_context.rss_service.get_feed_icons());
+        match result {
+            Ok(icons) => {
+                let feed_icons = FeedIcons::new(icons);
+                self.sidebar.set_feed_icons(feed_icons.clone());
+                self.article_list.set_feed_icons(feed_icons);
+            }
+            Err(e) => error!("Failed to load feed icons: {}", e),
+        }
+    }
+
+    /// Counts every feed, category and tag again, replacing the sidebar badges
+    fn reload_unread_counts(&mut self) {
+        self.all_counts_stale = false;
+        // A full count covers whatever was waiting to be recounted
+        self.stale_feed_counts.clear();
+        self.tag_counts_stale = false;
+        self.smart_folders_stale = true;
+        match self.app_context.sync_handle.block_on(self.app_context.unread_count_service.load()) {
+            Ok(counts) => self.sidebar.set_unread_counts(counts),
+            Err(e) => error!("Failed to count unread articles: {}", e),
+        }
+    }
+
+    /// Recounts the feeds and tags whose unread counts changed, updating the sidebar badges in place
+    fn refresh_unread_counts(&mut self) {
+        let feed_ids = std::mem::take(&mut self.stale_feed_counts);
+        let refresh_tags = std::mem::take(&mut self.tag_counts_stale);
+        let service = self.app_context.unread_count_service.clone();
+        let counts = self.sidebar.unread_counts_mut();
+        let result = self.app_context.sync_handle.block_on(async {
+            service.refresh_feeds(counts, &feed_ids).await?;
+            if refresh_tags {
+                service.refresh_tags(counts).await?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            error!("Failed to update unread counts: {}", e);
+        }
+    }
+
+    /// Lists the articles behind a sidebar entry
+    fn open_selection(&mut self, selection: SidebarSelection) {
+        let (filter, title) = match selection {
+            SidebarSelection::AllFeeds => (ArticleFilter::All, "All Articles".to_string()),
+            SidebarSelection::Favorites => (ArticleFilter::Favorites, "Favorites".to_string()),
+            SidebarSelection::Feed(feed) => (ArticleFilter::Feed(feed.id), feed.title),
+            SidebarSelection::Category(category) => (ArticleFilter::Category(category.id), category.name),
+            SidebarSelection::Tag(tag) => (ArticleFilter::Tag(tag.clone()), tag),
+            SidebarSelection::SmartFolder(folder) => match folder.parsed_query() {
+                Ok(query) => (ArticleFilter::Query(query), folder.name),
+                Err(e) => {
+                    self.set_status_message(format!("Could not open '{}': {}", folder.name, e));
+                    return;
+                }
+            },
+            SidebarSelection::Search(query) => {
+                self.search_articles(&query);
+                return;
+            }
+        };
+
+        self.article_list.clear_search();
+        self.article_list.show(filter, &title);
+        self.close_article();
+    }
+
+    /// Carries out a change asked for in the sidebar
+    fn handle_sidebar_request(&mut self, request: SidebarRequest) {
+        let smart_folders = self.app_context.smart_folder_service.clone();
+        let read_state = self.app_context.read_state_service.clone();
+        let result = self.app_context.sync_handle.block_on(async {
+            match &request {
+                SidebarRequest::CreateSmartFolder { name, query } => {
+                    smart_folders.create(name, query).await.map(|_| None)
+                }
+                SidebarRequest::DeleteSmartFolder(id) => smart_folders.delete(id).await.map(|_| None),
+                SidebarRequest::MarkRead { filter, label } => {
+                    let done = read_state.mark_read(filter, None, label).await?;
+                    Ok(Some((format!("Marked {} articles in {} as read", done.count(), done.label), done.feed_ids)))
+                }
+            }
+        });
+        match result {
+            Ok(message) => {
+                self.smart_folders_stale = true;
+                if let Some((message, feed_ids)) = message {
+                    self.read_state_changed(message, feed_ids);
+                }
+            }
+            Err(e) => {
+                error!("Sidebar action failed: {}", e);
+                self.set_status_message(format!("Action failed: {}", e));
+            }
+        }
+    }
+
+    /// Reloads what depends on read state after articles were marked from the sidebar or the reader
+    fn read_state_changed(&mut self, message: String, feed_ids: HashSet<FeedId>) {
+        self.smart_folders_stale = true;
+        self.tag_counts_stale = true;
+        self.stale_feed_counts.extend(feed_ids);
+        if let Err(e) = self.article_list.refresh() {
+            error!("Failed to reload articles: {}", e);
+        }
+        self.set_status_message(message);
+    }
+
+    /// Applies the UI parts of newly saved settings; syncing was updated by the dialog
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.theme = settings.theme;
+        self.article_viewer.set_reader_settings(settings.reader);
+        self.article_list.set_sort_order(settings.default_sort_order);
+        self.app_context.download_service.set_directory(&settings.download_directory);
+        self.app_context.asset_cache_service.set_limits(settings.asset_cache);
+
+        // The dialog already checked these on the feed fetcher
+        let fetch_config = self.app_context.rss_service.fetch_config();
+        let results = [
+            self.app_context.download_service.set_fetch_config(&fetch_config),
+            self.app_context.asset_cache_service.set_fetch_config(&fetch_config),
+            self.app_context.extraction_service.set_fetch_config(&fetch_config),
+        ];
+        for e in results.into_iter().filter_map(Result::err) {
+            error!("Failed to apply HTTP settings: {}", e);
+        }
+        self.set_status_message("Settings saved".to_string());
+    }
+
+    /// Recolors egui and every pane, the article page included, for the current theme
+    fn apply_theme(&mut self, ctx: &Context) {
+        self.colors = AppColors::for_theme(self.theme);
+        ctx.set_visuals(self.colors.visuals());
+        self.sidebar.set_colors(self.colors.clone());
+        self.article_list.set_colors(self.colors.clone());
+        self.article_viewer.set_colors(self.colors.clone());
+        self.feed_manager.colors = self.colors.clone();
+        self.category_manager.set_colors(self.colors.clone());
+        self.settings_dialog.set_colors(self.colors.clone());
+        self.applied_theme = Some(self.theme);
     }
 
     fn set_status_message(&mut self, message: String) {
@@ -159,8 +502,26 @@ impl MainView {
         self.status_message = Some((message, Instant::now()));
     }
 
+    /// Shows an article in the reader in place of the list
     pub fn select_article(&mut self, article_id: ArticleId) {
-        self.selected_article = Some(article_id);
+        let result = self.app_context.sync_handle.block_on(self.app_context.rss_service.get_article(&article_id));
+        match result {
+            Ok(Some(article)) => {
+                self.article_viewer.set_article(article);
+                self.selected_article = Some(article_id);
+            }
+            Ok(None) => self.set_status_message("The article no longer exists".to_string()),
+            Err(e) => {
+                error!("Failed to load article {}: {}", article_id, e);
+                self.set_status_message(format!("Could not open the article: {}", e));
+            }
+        }
+    }
+
+    /// Goes back from the reader to the article list
+    fn close_article(&mut self) {
+        self.selected_article = None;
+        self.article_viewer.clear_article();
     }
 
     pub fn select_category(&mut self, category_id: CategoryId) {
@@ -178,31 +539,63 @@ impl App for MainView {
         }
         
         // Set up main UI layout
-        SidePanel::left("sidebar_panel").show(ctx, |ui| {
-            if let Err(e) = self.sidebar.ui(ui) {
-                error!("Error rendering sidebar: {}", e);
+        let selection = SidePanel::left("sidebar_panel").show(ctx, |ui| {
+            match self.sidebar.ui(ui) {
+                Ok(selection) => selection,
+                Err(e) => {
+                    error!("Error rendering sidebar: {}", e);
+                    None
+                }
             }
-        });
+        }).inner;
+        if let Some(selection) = selection {
+            self.open_selection(selection);
+        }
+        if let Some(request) = self.sidebar.take_request() {
+            self.handle_sidebar_request(request);
+        }
 
         // Main content area - only one CentralPanel should exist
-        CentralPanel::default().show(ctx, |ui| {
+        let clicked = CentralPanel::default().show(ctx, |ui| {
             // Depending on what's selected in the sidebar, show either article list or article
             if self.selected_article.is_some() {
                 if let Err(e) = self.article_viewer.ui(ui) {
                     error!("Error rendering article viewer: {}", e);
                 }
+                None
             } else {
-                if let Err(e) = self.article_list.ui(ui) {
-                    error!("Error rendering article list: {}", e);
+                match self.article_list.ui(ui) {
+                    Ok(clicked) => clicked,
+                    Err(e) => {
+                        error!("Error rendering article list: {}", e);
+                        None
+                    }
                 }
             }
-        });
+        }).inner;
+        if let Some(article_id) = clicked {
+            self.select_article(article_id);
+        }
+        if self.article_viewer.take_closed() {
+            self.close_article();
+        }
+        if let Some((message, feed_ids)) = self.article_viewer.take_read_state_change() {
+            self.read_state_changed(message, feed_ids);
+        }
+        if let Some((message, feed_ids)) = self.article_list.take_read_state_change() {
+            // The list has already reloaded itself
+            self.smart_folders_stale = true;
+            self.tag_counts_stale = true;
+            self.stale_feed_counts.extend(feed_ids);
+            self.set_status_message(message);
+        }
         
         // Render settings dialog if visible
-        if self.show_settings {
-            if let Err(e) = self.settings_dialog.show(ctx) {
-                error!("Error rendering settings dialog: {}", e);
-            }
+        if let Err(e) = self.settings_dialog.show(ctx) {
+            error!("Error rendering settings dialog: {}", e);
+        }
+        if let Some(settings) = self.settings_dialog.take_saved() {
+            self.apply_settings(&settings);
         }
 
         // Wrap the category_manager.show call in a Window to provide a UI context
@@ -0,0 +1,351 @@
+//! Helpers for reading and writing HTML text
+
+use encoding_rs::{Encoding, UTF_8};
+
+/// How far into a page a `<meta charset>` declaration is looked for
+const META_PRESCAN_BYTES: usize = 1024;
+
+/// Decodes an HTML page to text in the character encoding it declares
+///
+/// A byte order mark wins, then the `charset` of the `Content-Type` header,
+/// then a `<meta charset>` or `<meta http-equiv="Content-Type">` near the
+/// start of the page. Pages that declare nothing are read as UTF-8, with
+/// invalid bytes replaced.
+pub fn decode_html(body: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(charset_parameter)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .or_else(|| meta_charset(body))
+        .unwrap_or(UTF_8);
+    // `decode` lets a byte order mark override the declared encoding
+    encoding.decode(body).0.into_owned()
+}
+
+/// The `charset` parameter of a `Content-Type` value
+fn charset_parameter(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(['"', '\'']))
+    })
+}
+
+/// The encoding a `<meta>` tag near the start of the page declares
+fn meta_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let head = &body[..body.len().min(META_PRESCAN_BYTES)];
+    // Every encoding a page can declare this way is ASCII-compatible here
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+    let mut rest = head.as_str();
+    while let Some(start) = rest.find("<meta") {
+        let tag = &rest[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        if let Some(position) = tag.find("charset") {
+            let value = tag[position + "charset".len()..].trim_start();
+            if let Some(value) = value.strip_prefix('=') {
+                let value = value.trim_start().trim_start_matches(['"', '\'']);
+                let end = value.find(|c: char| c.is_ascii_whitespace() || "\"';/>".contains(c))
+                    .unwrap_or(value.len());
+                // A page that could be read this far is not UTF-16, which
+                // `output_encoding` maps to UTF-8
+                return Encoding::for_label(&value.as_bytes()[..end]).map(Encoding::output_encoding);
+            }
+        }
+        rest = &rest[start + "<meta".len()..];
+    }
+    None
+}
+
+/// Escapes text for use in HTML content or a quoted attribute value
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Decodes the character references that appear in attribute values and titles
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest.find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| {
+                let name = &rest[1..end];
+                let c = match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some('\u{a0}'),
+                    "colon" => Some(':'),
+                    "Tab" => Some('\t'),
+                    "NewLine" => Some('\n'),
+                    _ => match name.strip_prefix('#') {
+                        Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32),
+                        Some(decimal) => decimal.parse().ok().and_then(char::from_u32),
+                        None => None,
+                    },
+                };
+                c.map(|c| (c, end))
+            });
+
+        match reference {
+            Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Attributes of a tag in source order, with lowercased keys and decoded values
+pub type Attributes = Vec<(String, String)>;
+
+/// Elements whose content is raw text rather than markup, up to their end tag
+pub const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "iframe", "noembed", "noframes", "noscript", "script", "style", "textarea", "title", "xmp",
+];
+
+/// Elements that never have content or an end tag
+pub const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// A piece of an HTML document, as [`tokenize`] reads it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
+    /// Text as written, character references and all; a `<` that starts
+    /// no tag is part of it
+    Text(&'a str),
+    /// The content of a [raw text element](RAW_TEXT_ELEMENTS), or everything
+    /// after a `<plaintext>` tag
+    RawText(&'a str),
+    /// A comment, doctype or processing instruction, as written
+    Comment(&'a str),
+    StartTag(StartTag<'a>),
+    EndTag {
+        /// Lowercased name
+        name: String,
+        source: &'a str,
+    },
+}
+
+/// A start tag, see [`Token::StartTag`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartTag<'a> {
+    /// Lowercased name
+    pub name: String,
+    pub attributes: Attributes,
+    /// Whether the tag ends in `/>`
+    pub self_closing: bool,
+    /// The tag as written
+    pub source: &'a str,
+}
+
+/// Splits HTML into text, tags and comments, the one reading of markup that
+/// the sanitizer, the content rewriter, the extractor and feed discovery share
+///
+/// It is a tokenizer rather than a parser: tags are reported as written,
+/// without closing what is left open. The contents of scripts, styles and
+/// the other [`RAW_TEXT_ELEMENTS`] come as [`Token::RawText`], never as tags.
+pub fn tokenize(html: &str) -> Tokenizer<'_> {
+    Tokenizer { rest: html, raw_text_of: None }
+}
+
+/// Iterator over the [`Token`]s of a document, see [`tokenize`]
+pub struct Tokenizer<'a> {
+    rest: &'a str,
+    /// The raw text element whose content comes next
+    raw_text_of: Option<String>,
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if let Some(name) = self.raw_text_of.take() {
+            let end = if name == "plaintext" {
+                self.rest.len()
+            } else {
+                find_ignore_case(self.rest, &format!("</{}", name)).unwrap_or(self.rest.len())
+            };
+            if end > 0 {
+                return Some(Token::RawText(self.take(end)));
+            }
+        }
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let text_end = markup_start(self.rest).unwrap_or(self.rest.len());
+        if text_end > 0 {
+            return Some(Token::Text(self.take(text_end)));
+        }
+
+        let rest = self.rest;
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").map_or(rest.len(), |end| end + "<!---->".len());
+            return Some(Token::Comment(self.take(end)));
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            return Some(Token::Comment(self.take(end)));
+        }
+
+        let closing = rest.starts_with("</");
+        let after_lt = &rest[if closing { 2 } else { 1 }..];
+        let name_len = after_lt.find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(after_lt.len());
+        let name = after_lt[..name_len].to_ascii_lowercase();
+        let (attributes, remaining) = parse_attributes(&after_lt[name_len..]);
+        let source = self.take(rest.len() - remaining.len());
+
+        if closing {
+            return Some(Token::EndTag { name, source });
+        }
+        if RAW_TEXT_ELEMENTS.contains(&name.as_str()) || name == "plaintext" {
+            self.raw_text_of = Some(name.clone());
+        }
+        Some(Token::StartTag(StartTag { name, attributes, self_closing: source.ends_with("/>"), source }))
+    }
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Consumes the next `len` bytes
+    fn take(&mut self, len: usize) -> &'a str {
+        let (taken, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        taken
+    }
+}
+
+/// The start tags of a document in order, with their names and attributes,
+/// e.g. to read `<head>` metadata
+pub fn start_tags(html: &str) -> Vec<(String, Attributes)> {
+    tokenize(html)
+        .filter_map(|token| match token {
+            Token::StartTag(tag) => Some((tag.name, tag.attributes)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Where the first `<` that opens a tag, comment or doctype is
+fn markup_start(html: &str) -> Option<usize> {
+    html.match_indices('<').map(|(start, _)| start).find(|&start| {
+        let after_lt = &html[start + 1..];
+        let name = after_lt.strip_prefix('/').unwrap_or(after_lt);
+        name.starts_with(|c: char| c.is_ascii_alphabetic()) || after_lt.starts_with(['!', '?'])
+    })
+}
+
+/// Reads attributes up to the end of the tag, returning them and the text after the tag
+fn parse_attributes(mut rest: &str) -> (Attributes, &str) {
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        match rest.chars().next() {
+            None => return (attributes, rest),
+            Some('>') => return (attributes, &rest[1..]),
+            _ => {}
+        }
+
+        let key_len = rest.find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len())
+            .max(1);
+        let key = rest[..key_len].to_ascii_lowercase();
+        rest = rest[key_len..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            rest = after_equals.trim_start();
+            let (raw, remaining) = match rest.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &rest[1..];
+                    match inner.find(quote) {
+                        Some(end) => (&inner[..end], &inner[end + 1..]),
+                        None => (inner, ""),
+                    }
+                }
+                _ => {
+                    let end = rest.find(|c: char| c.is_whitespace() || c == '>').unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        attributes.push((key, value));
+    }
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_html_follows_declared_charset() {
+        let latin1 = b"<html><head><title>Caf\xe9</title></head></html>";
+        assert!(decode_html(latin1, Some("text/html; charset=ISO-8859-1")).contains("Café"));
+        assert!(decode_html(latin1, Some("text/html")).contains("Caf\u{FFFD}"));
+
+        let meta = b"<html><head><meta charset=\"windows-1252\"><title>Caf\xe9</title>";
+        assert!(decode_html(meta, None).contains("Café"));
+        let http_equiv = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=koi8-r\">\xf0\xd2\xc9";
+        assert!(decode_html(http_equiv, None).ends_with("При"));
+
+        // The header wins over the page, and a byte order mark over both
+        assert!(decode_html("<meta charset=latin1>Café".as_bytes(), Some("text/html; charset=utf-8")).ends_with("Café"));
+        assert_eq!(decode_html(b"\xef\xbb\xbfCaf\xc3\xa9", Some("text/html; charset=latin1")), "Café");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<script>alert("x")</script> & 'quotes'"#),
+            "&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; &#39;quotes&#39;"
+        );
+    }
+
+    #[test]
+    fn test_tokenize_reads_raw_text_and_stray_brackets() {
+        let html = "<!-- x --><P Class=\"a&amp;b\">1 < 2<br/></p><script>if (a<b) \"</p>\"</SCRIPT>";
+        let tokens: Vec<Token> = tokenize(html).collect();
+        assert_eq!(tokens, vec![
+            Token::Comment("<!-- x -->"),
+            Token::StartTag(StartTag {
+                name: "p".to_string(),
+                attributes: vec![("class".to_string(), "a&b".to_string())],
+                self_closing: false,
+                source: "<P Class=\"a&amp;b\">",
+            }),
+            Token::Text("1 < 2"),
+            Token::StartTag(StartTag { name: "br".to_string(), attributes: Vec::new(), self_closing: true, source: "<br/>" }),
+            Token::EndTag { name: "p".to_string(), source: "</p>" },
+            Token::StartTag(StartTag { name: "script".to_string(), attributes: Vec::new(), self_closing: false, source: "<script>" }),
+            Token::RawText("if (a<b) \"</p>\""),
+            Token::EndTag { name: "script".to_string(), source: "</SCRIPT>" },
+        ]);
+
+        let plaintext: Vec<Token> = tokenize("<plaintext></plaintext><b>").collect();
+        assert_eq!(plaintext[1], Token::RawText("</plaintext><b>"));
+    }
+}
@@ -4,6 +4,9 @@ use std::path::Path;
 use anyhow::{Result, Context};
 
 mod db_inspector;
+pub mod html;
+#[cfg(test)]
+pub mod test_support;
 
 pub use db_inspector::*;
 
@@ -0,0 +1,217 @@
+//! Helpers shared by unit tests: throwaway databases and a minimal HTTP stand-in
+//! that serves canned responses from a local port.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+use uuid::Uuid;
+
+use crate::data::Database;
+use crate::models::article::Article;
+use crate::models::category::Category;
+use crate::models::feed::Feed;
+
+/// Creates a fresh database in the system temp directory
+pub fn temp_database() -> Database {
+    temp_database_at().0
+}
+
+/// Creates a fresh database and returns its path too, for tests that need to
+/// reach into it with a connection of their own
+pub fn temp_database_at() -> (Database, PathBuf) {
+    let path = std::env::temp_dir()
+        .join("panda-tests")
+        .join(format!("{}.db", Uuid::new_v4()));
+    let database = Database::new(path.to_str().expect("temp path is valid UTF-8"))
+        .expect("Failed to create test database");
+    (database, path)
+}
+
+/// Saves a feed served at `http://example.com/<name>.xml`, in `category` if given
+pub async fn save_feed(database: &Database, name: &str, category: Option<&Category>) -> Result<Feed> {
+    let mut feed = Feed::new(name.to_string(), Url::parse(&format!("http://example.com/{}.xml", name))?);
+    if let Some(category) = category {
+        feed = feed.with_category(category.id.clone());
+    }
+    database.get_feed_repository().save_feed(&feed).await?;
+    Ok(feed)
+}
+
+/// An article of `feed` linking to `http://example.com/<title>`, with spaces
+/// in the title made dashes
+pub fn test_article(feed: &Feed, title: &str) -> Article {
+    let url = Url::parse(&format!("http://example.com/{}", title.replace(' ', "-")))
+        .expect("title makes a valid URL path");
+    Article::new(feed.id.clone(), title.to_string(), url)
+}
+
+/// Saves an article and hands it back
+pub async fn save_article(database: &Database, article: Article) -> Result<Article> {
+    database.get_article_repository().save_article(&article).await?;
+    Ok(article)
+}
+
+/// A request as seen by the test server
+#[derive(Debug, Clone)]
+pub struct TestRequest {
+    pub path: String,
+    pub headers: HashMap<String, String>,
+}
+
+impl TestRequest {
+    /// Returns a header value, matching the name case-insensitively
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(|v| v.as_str())
+    }
+}
+
+/// A canned response returned by the test server
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Delay before the response is written, to keep requests in flight
+    pub delay: Option<Duration>,
+}
+
+impl TestResponse {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self { status: 200, headers: Vec::new(), body: body.into(), delay: None }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self { status, headers: Vec::new(), body: Vec::new(), delay: None }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+type Handler = Box<dyn Fn(&TestRequest) -> TestResponse + Send + Sync>;
+
+/// Minimal HTTP/1.1 server answering each request from a path → handler table
+pub struct TestServer {
+    port: u16,
+    handlers: Arc<Mutex<HashMap<String, Handler>>>,
+    requests: Arc<Mutex<Vec<TestRequest>>>,
+    max_in_flight: Arc<AtomicUsize>,
+}
+
+impl TestServer {
+    /// Binds to an ephemeral local port and starts serving in the background
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("Failed to bind test server");
+        let port = listener.local_addr().unwrap().port();
+        let handlers: Arc<Mutex<HashMap<String, Handler>>> = Arc::new(Mutex::new(HashMap::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let served_handlers = handlers.clone();
+        let served_requests = requests.clone();
+        let served_max_in_flight = max_in_flight.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let handlers = served_handlers.clone();
+                let requests = served_requests.clone();
+                let in_flight = in_flight.clone();
+                let max_in_flight = served_max_in_flight.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut socket).await else { return };
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    requests.lock().unwrap().push(request.clone());
+                    let response = match handlers.lock().unwrap().get(&request.path) {
+                        Some(handler) => handler(&request),
+                        None => TestResponse::status(404),
+                    };
+                    if let Some(delay) = response.delay {
+                        tokio::time::sleep(delay).await;
+                    }
+                    let _ = socket.write_all(&encode_response(&response)).await;
+                    let _ = socket.shutdown().await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        Self { port, handlers, requests, max_in_flight }
+    }
+
+    /// Serves the same response for every request to `path`
+    pub fn route(&self, path: &str, response: TestResponse) {
+        self.handle(path, move |_| response.clone());
+    }
+
+    /// Serves `path` with a handler that can inspect the request
+    pub fn handle<F>(&self, path: &str, handler: F)
+    where
+        F: Fn(&TestRequest) -> TestResponse + Send + Sync + 'static,
+    {
+        self.handlers.lock().unwrap().insert(path.to_string(), Box::new(handler));
+    }
+
+    /// Absolute URL for a path on this server
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", self.port, path)
+    }
+
+    /// All requests received so far
+    pub fn requests(&self) -> Vec<TestRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Highest number of requests that were being served at the same time
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<TestRequest> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    while !data.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = socket.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+
+    let head = String::from_utf8_lossy(&data);
+    let mut lines = head.split("\r\n");
+    let path = lines.next()?.split_whitespace().nth(1)?.to_string();
+    let headers = lines
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    Some(TestRequest { path, headers })
+}
+
+fn encode_response(response: &TestResponse) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {} Test\r\n", response.status);
+    for (name, value) in &response.headers {
+        out.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    out.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", response.body.len()));
+    let mut bytes = out.into_bytes();
+    bytes.extend_from_slice(&response.body);
+    bytes
+}